@@ -0,0 +1,131 @@
+//! Compares the byte-level [`SseDecoder`] with a line-based parser in the
+//! style of the one it replaced, which decoded every network chunk as text
+//! and copied out each line.
+//!
+//! Run with `cargo bench --bench sse_decoder`. Reports decoded events, wall
+//! time, and heap allocations per event for a long synthetic chat-completion
+//! stream delivered in small, misaligned network chunks. The previous parser
+//! lost events whose lines straddled a chunk boundary; the one here carries
+//! partial lines over, so both sides decode every event.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use inference_gateway_sdk::{SSEvents, SseDecoder};
+
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+const EVENTS: usize = 5_000;
+const CHUNK_SIZE: usize = 61;
+const ITERATIONS: u32 = 20;
+
+fn synthetic_stream() -> Vec<Vec<u8>> {
+    let mut body = Vec::new();
+    for i in 0..EVENTS {
+        body.extend_from_slice(
+            format!(
+                "data: {{\"id\":\"chatcmpl-123\",\"object\":\"chat.completion.chunk\",\"created\":1694268190,\"model\":\"deepseek-v4-flash\",\"choices\":[{{\"index\":0,\"delta\":{{\"content\":\"token {i}\"}},\"finish_reason\":null}}]}}\n\n"
+            )
+            .as_bytes(),
+        );
+    }
+    body.extend_from_slice(b"data: [DONE]\n\n");
+    body.chunks(CHUNK_SIZE).map(<[u8]>::to_vec).collect()
+}
+
+/// The parser `sse_stream` used before [`SseDecoder`], keeping the partial
+/// last line of each chunk for the next one.
+fn legacy_parse(chunks: &[Vec<u8>]) -> usize {
+    let mut count = 0;
+    let mut pending = String::new();
+    let mut current_event: Option<String> = None;
+    let mut current_data: Option<String> = None;
+    for chunk in chunks {
+        pending.push_str(&String::from_utf8_lossy(chunk));
+        while let Some(newline) = pending.find('\n') {
+            let line: String = pending.drain(..=newline).collect();
+            let line = line.trim_end_matches(['\r', '\n']);
+            if line.is_empty() && current_data.is_some() {
+                black_box(SSEvents {
+                    data: current_data.take().unwrap(),
+                    event: current_event.take(),
+                    retry: None,
+                });
+                count += 1;
+                continue;
+            }
+            if let Some(event) = line.strip_prefix("event:") {
+                current_event = Some(event.trim().to_string());
+            } else if let Some(data) = line.strip_prefix("data:") {
+                current_data = Some(data.trim().to_string());
+            }
+        }
+    }
+    count
+}
+
+fn decoder_parse(chunks: &[Vec<u8>]) -> usize {
+    let mut count = 0;
+    let mut decoder = SseDecoder::new();
+    for chunk in chunks {
+        decoder.push(chunk);
+        while let Some(event) = decoder.next_event() {
+            black_box(event);
+            count += 1;
+        }
+    }
+    count
+}
+
+fn measure(name: &str, chunks: &[Vec<u8>], parse: fn(&[Vec<u8>]) -> usize) -> usize {
+    let mut elapsed = Duration::ZERO;
+    let mut allocations = 0;
+    let mut events = 0;
+    for _ in 0..ITERATIONS {
+        let before = ALLOCATIONS.load(Ordering::Relaxed);
+        let start = Instant::now();
+        events = parse(black_box(chunks));
+        elapsed += start.elapsed();
+        allocations += ALLOCATIONS.load(Ordering::Relaxed) - before;
+    }
+    println!(
+        "{name:<8} {events} events  {:>10.2?}/iter  {:>6.2} allocations/event",
+        elapsed / ITERATIONS,
+        allocations as f64 / (events * ITERATIONS as usize) as f64,
+    );
+    events
+}
+
+fn main() {
+    let chunks = synthetic_stream();
+    println!(
+        "decoding {EVENTS} events in {} chunks of {CHUNK_SIZE} bytes",
+        chunks.len()
+    );
+    let legacy = measure("legacy", &chunks, legacy_parse);
+    let decoder = measure("decoder", &chunks, decoder_parse);
+    assert_eq!(legacy, decoder, "both parsers must decode the same events");
+}
@@ -17,7 +17,10 @@ const DEFAULT_MODEL: &str = "deepseek-v4-flash";
 fn message(role: MessageRole, text: &str) -> Message {
     Message {
         role,
-        content: MessageContent::String(text.to_string()),
+        annotations: Vec::new(),
+        audio: None,
+        content: MessageContent::Text(text.to_string()),
+        name: None,
         reasoning: None,
         reasoning_content: None,
         tool_call_id: None,
@@ -27,8 +30,8 @@ fn message(role: MessageRole, text: &str) -> Message {
 
 fn message_content_text(content: &MessageContent) -> String {
     match content {
-        MessageContent::String(s) => s.clone(),
-        MessageContent::Array(parts) => serde_json::to_string(parts).unwrap_or_default(),
+        MessageContent::Text(s) => s.clone(),
+        MessageContent::Parts(parts) => serde_json::to_string(parts).unwrap_or_default(),
     }
 }
 
@@ -119,8 +122,7 @@ async fn run() -> Result<(), GatewayError> {
         if event.data == "[DONE]" {
             break;
         }
-        let chunk: CreateChatCompletionStreamResponse =
-            serde_json::from_str(&event.data).map_err(GatewayError::DeserializationError)?;
+        let chunk: CreateChatCompletionStreamResponse = event.json()?;
 
         if let Some(usage) = chunk.usage.as_ref() {
             println!("\n\n[Usage: {} tokens]", usage.total_tokens);
@@ -246,14 +248,7 @@ async fn run() -> Result<(), GatewayError> {
                 let args: WeatherArgs = tool_call.function.parse_arguments()?;
                 let result = get_weather(args);
 
-                follow_up.push(Message {
-                    role: MessageRole::Tool,
-                    content: MessageContent::String(result),
-                    reasoning: None,
-                    reasoning_content: None,
-                    tool_call_id: Some(tool_call.id.clone()),
-                    tool_calls: Vec::new(),
-                });
+                follow_up.push(Message::tool(tool_call.id.clone(), result));
             }
         }
 
@@ -96,8 +96,7 @@ async fn run() -> Result<(), GatewayError> {
     let mut stdout = std::io::stdout();
     while let Some(event) = stream.next().await {
         let event = event?;
-        let event: MessagesStreamEvent =
-            serde_json::from_str(&event.data).map_err(GatewayError::DeserializationError)?;
+        let event: MessagesStreamEvent = event.json()?;
 
         match event.type_ {
             MessagesStreamEventType::ContentBlockDelta => {
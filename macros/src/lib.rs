@@ -0,0 +1,230 @@
+//! Procedural macros for the Inference Gateway Rust SDK.
+//!
+//! Use them through the SDK's `macros` feature, which re-exports them as
+//! `inference_gateway_sdk::tool`.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::spanned::Spanned;
+use syn::{Expr, FnArg, GenericArgument, ItemFn, Lit, LitStr, Meta, Pat, PathArguments, Type};
+
+/// Declares a tool the model can call from a function.
+///
+/// Next to the function, which stays as it is, the macro generates a unit
+/// struct named after it in PascalCase (`get_weather` becomes
+/// `GetWeather`) implementing `inference_gateway_sdk::Tool`, so it can be
+/// added to a `ToolRegistry` with `with_tool_impl`:
+///
+/// ```ignore
+/// use inference_gateway_sdk::{ToolRegistry, tool};
+///
+/// /// Get the current weather in a city.
+/// #[tool]
+/// async fn get_weather(city: String, celsius: Option<bool>) -> String {
+///     format!("Sunny in {city}")
+/// }
+///
+/// let registry = ToolRegistry::new().with_tool_impl(GetWeather);
+/// ```
+///
+/// The tool is named after the function and described by its doc comment,
+/// unless `name = "..."` or `description = "..."` is given. Each argument
+/// becomes a parameter of the JSON schema: strings, integers, floats,
+/// booleans and `Vec`s of them get their JSON type, `Option`s are optional,
+/// and other types that implement `Deserialize` are accepted as any JSON
+/// value. The function may be async or not, and return a `String`, a
+/// `serde_json::Value`, or a `Result` of either whose error implements
+/// `Display`.
+#[proc_macro_attribute]
+pub fn tool(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut name = None;
+    let mut description = None;
+    let parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("name") {
+            name = Some(meta.value()?.parse::<LitStr>()?.value());
+            Ok(())
+        } else if meta.path.is_ident("description") {
+            description = Some(meta.value()?.parse::<LitStr>()?.value());
+            Ok(())
+        } else {
+            Err(meta.error("expected `name` or `description`"))
+        }
+    });
+    syn::parse_macro_input!(attr with parser);
+    let function = syn::parse_macro_input!(item as ItemFn);
+    expand(function, name, description)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(
+    function: ItemFn,
+    name: Option<String>,
+    description: Option<String>,
+) -> syn::Result<TokenStream2> {
+    let ident = &function.sig.ident;
+    if !function.sig.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            function.sig.generics.span(),
+            "tool functions can't be generic",
+        ));
+    }
+    let name = name.unwrap_or_else(|| ident.to_string());
+    let description = description.or_else(|| doc_comment(&function));
+    let description = match description {
+        Some(description) => quote!(::std::option::Option::Some(#description)),
+        None => quote!(::std::option::Option::None),
+    };
+
+    let mut parameters = Vec::new();
+    let mut bindings = Vec::new();
+    let mut arguments = Vec::new();
+    for input in &function.sig.inputs {
+        let FnArg::Typed(input) = input else {
+            return Err(syn::Error::new(
+                input.span(),
+                "tool functions can't take `self`",
+            ));
+        };
+        let Pat::Ident(pattern) = &*input.pat else {
+            return Err(syn::Error::new(
+                input.pat.span(),
+                "tool arguments must be plain identifiers",
+            ));
+        };
+        if let Type::Reference(reference) = &*input.ty {
+            return Err(syn::Error::new(
+                reference.span(),
+                "tool arguments must be owned, such as `String` instead of `&str`",
+            ));
+        }
+        let argument = &pattern.ident;
+        let parameter = argument.to_string();
+        let parameter = parameter
+            .strip_prefix("r#")
+            .unwrap_or(&parameter)
+            .to_string();
+        let ty = &input.ty;
+        let (schema, required) = schema(ty);
+        parameters.push(quote!((#parameter, #schema, #required)));
+        bindings.push(quote! {
+            let #argument: #ty = ::inference_gateway_sdk::__private::argument(&arguments, #parameter)?;
+        });
+        arguments.push(argument);
+    }
+
+    let call = if function.sig.asyncness.is_some() {
+        quote!(#ident(#(#arguments),*).await)
+    } else {
+        quote!(#ident(#(#arguments),*))
+    };
+    let vis = &function.vis;
+    let tool = format_ident!("{}", pascal_case(&ident.to_string()), span = ident.span());
+    let doc = format!("The [`{ident}`] tool.");
+
+    Ok(quote! {
+        #function
+
+        #[doc = #doc]
+        #[derive(Clone, Copy, Debug, Default)]
+        #vis struct #tool;
+
+        impl ::inference_gateway_sdk::Tool for #tool {
+            fn definition(&self) -> ::inference_gateway_sdk::ChatCompletionTool {
+                ::inference_gateway_sdk::__private::definition(
+                    #name,
+                    #description,
+                    &[#(#parameters),*],
+                )
+            }
+
+            fn call(
+                &self,
+                arguments: ::inference_gateway_sdk::__private::Value,
+            ) -> ::inference_gateway_sdk::__private::BoxFuture<
+                'static,
+                ::std::result::Result<::std::string::String, ::std::string::String>,
+            > {
+                ::std::boxed::Box::pin(async move {
+                    #(#bindings)*
+                    ::inference_gateway_sdk::__private::ToolOutput::into_tool_output(#call)
+                })
+            }
+        }
+    })
+}
+
+/// The function's doc comment, with lines joined by spaces.
+fn doc_comment(function: &ItemFn) -> Option<String> {
+    let lines: Vec<String> = function
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(meta) => match &meta.value {
+                Expr::Lit(expr) => match &expr.lit {
+                    Lit::Str(line) => Some(line.value().trim().to_string()),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        })
+        .filter(|line| !line.is_empty())
+        .collect();
+    (!lines.is_empty()).then(|| lines.join(" "))
+}
+
+/// The JSON schema of a parameter of type `ty`, and whether it is
+/// required.
+fn schema(ty: &Type) -> (String, bool) {
+    let Type::Path(path) = ty else {
+        return ("{}".to_string(), true);
+    };
+    let Some(segment) = path.path.segments.last() else {
+        return ("{}".to_string(), true);
+    };
+    let inner = match &segment.arguments {
+        PathArguments::AngleBracketed(arguments) => {
+            arguments.args.iter().find_map(|argument| match argument {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            })
+        }
+        _ => None,
+    };
+    let json_type = |name: &str| format!(r#"{{"type":"{name}"}}"#);
+    let schema = match segment.ident.to_string().as_str() {
+        "String" | "char" => json_type("string"),
+        "i8" | "i16" | "i32" | "i64" | "i128" | "isize" | "u8" | "u16" | "u32" | "u64" | "u128"
+        | "usize" => json_type("integer"),
+        "f32" | "f64" => json_type("number"),
+        "bool" => json_type("boolean"),
+        "Option" => {
+            let schema = inner.map_or_else(|| "{}".to_string(), |inner| schema(inner).0);
+            return (schema, false);
+        }
+        "Vec" | "VecDeque" | "HashSet" | "BTreeSet" => match inner {
+            Some(inner) => format!(r#"{{"type":"array","items":{}}}"#, schema(inner).0),
+            None => json_type("array"),
+        },
+        _ => "{}".to_string(),
+    };
+    (schema, true)
+}
+
+fn pascal_case(snake: &str) -> String {
+    let snake = snake.strip_prefix("r#").unwrap_or(snake);
+    snake
+        .split('_')
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
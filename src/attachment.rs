@@ -0,0 +1,305 @@
+//! Utilities for binary attachments: building and parsing `data:` URLs,
+//! sniffing MIME types from magic bytes, and the inline size limits providers
+//! enforce.
+//!
+//! ```
+//! use inference_gateway_sdk::attachment::{self, AttachmentKind, DataUrl};
+//! use inference_gateway_sdk::Provider;
+//!
+//! let png = b"\x89PNG\r\n\x1a\n...";
+//! attachment::check_size(Provider::Anthropic, AttachmentKind::Image, png.len())?;
+//!
+//! let url = DataUrl::from_bytes(png.to_vec());
+//! assert_eq!(url.media_type(), "image/png");
+//!
+//! let parsed: DataUrl = url.to_string().parse()?;
+//! assert_eq!(parsed.data(), png);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use thiserror::Error;
+
+use crate::{ContentPart, GatewayError, Message, MessageContent, Provider};
+
+/// Media type of a `data:` URL that doesn't name one (RFC 2397).
+const DEFAULT_MEDIA_TYPE: &str = "text/plain;charset=US-ASCII";
+
+/// Media type used when [`sniff_mime`] doesn't recognise the bytes.
+const OCTET_STREAM: &str = "application/octet-stream";
+
+const MIB: usize = 1024 * 1024;
+
+/// Binary data with its media type, as carried in a `data:` URL.
+///
+/// Displays as a base64 `data:` URL and parses from both base64 and
+/// percent-encoded ones.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DataUrl {
+    media_type: String,
+    data: Vec<u8>,
+}
+
+/// Why a string isn't a valid `data:` URL.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum DataUrlError {
+    #[error("not a data: URL")]
+    NotDataUrl,
+
+    #[error("data: URL has no ',' before its data")]
+    MissingData,
+
+    #[error("invalid base64 in data: URL: {0}")]
+    InvalidBase64(#[from] base64::DecodeError),
+
+    #[error("invalid percent-encoding in data: URL")]
+    InvalidPercentEncoding,
+}
+
+impl DataUrl {
+    /// Data of the given media type, e.g. `image/png`.
+    pub fn new(media_type: impl Into<String>, data: impl Into<Vec<u8>>) -> Self {
+        Self {
+            media_type: media_type.into(),
+            data: data.into(),
+        }
+    }
+
+    /// Data whose media type is sniffed from its leading bytes, falling back
+    /// to `application/octet-stream`.
+    pub fn from_bytes(data: impl Into<Vec<u8>>) -> Self {
+        let data = data.into();
+        let media_type = sniff_mime(&data).unwrap_or(OCTET_STREAM);
+        Self::new(media_type, data)
+    }
+
+    /// The media type, including any parameters such as `charset`.
+    pub fn media_type(&self) -> &str {
+        &self.media_type
+    }
+
+    /// The decoded data.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Consumes the URL, returning the decoded data.
+    pub fn into_data(self) -> Vec<u8> {
+        self.data
+    }
+}
+
+impl fmt::Display for DataUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "data:{};base64,{}",
+            self.media_type,
+            STANDARD.encode(&self.data)
+        )
+    }
+}
+
+impl FromStr for DataUrl {
+    type Err = DataUrlError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (header, payload) = split_data_url(s)?;
+        let (media_type, base64) = match header.strip_suffix(";base64") {
+            Some(media_type) => (media_type, true),
+            None => (header, false),
+        };
+        let media_type = if media_type.is_empty() {
+            DEFAULT_MEDIA_TYPE
+        } else {
+            media_type
+        };
+        let data = if base64 {
+            STANDARD.decode(payload)?
+        } else {
+            percent_decode(payload)?
+        };
+        Ok(Self::new(media_type, data))
+    }
+}
+
+/// Splits a `data:` URL into the part before the comma and its data.
+fn split_data_url(s: &str) -> Result<(&str, &str), DataUrlError> {
+    let rest = s
+        .get(..5)
+        .filter(|scheme| scheme.eq_ignore_ascii_case("data:"))
+        .map(|_| &s[5..])
+        .ok_or(DataUrlError::NotDataUrl)?;
+    rest.split_once(',').ok_or(DataUrlError::MissingData)
+}
+
+fn percent_decode(s: &str) -> Result<Vec<u8>, DataUrlError> {
+    let mut out = Vec::with_capacity(s.len());
+    let mut bytes = s.bytes();
+    while let Some(byte) = bytes.next() {
+        if byte != b'%' {
+            out.push(byte);
+            continue;
+        }
+        let hex = [bytes.next(), bytes.next()];
+        let [Some(hi), Some(lo)] = hex else {
+            return Err(DataUrlError::InvalidPercentEncoding);
+        };
+        let digit = |b: u8| (b as char).to_digit(16);
+        match (digit(hi), digit(lo)) {
+            (Some(hi), Some(lo)) => out.push((hi * 16 + lo) as u8),
+            _ => return Err(DataUrlError::InvalidPercentEncoding),
+        }
+    }
+    Ok(out)
+}
+
+/// Guesses the MIME type of `bytes` from their magic number.
+///
+/// Recognises the image, audio and document formats providers accept as
+/// attachments: PNG, JPEG, GIF, WebP, PDF, WAV and MP3.
+pub fn sniff_mime(bytes: &[u8]) -> Option<&'static str> {
+    let mime = match bytes {
+        [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n', ..] => "image/png",
+        [0xff, 0xd8, 0xff, ..] => "image/jpeg",
+        [b'G', b'I', b'F', b'8', b'7' | b'9', b'a', ..] => "image/gif",
+        [
+            b'R',
+            b'I',
+            b'F',
+            b'F',
+            _,
+            _,
+            _,
+            _,
+            b'W',
+            b'E',
+            b'B',
+            b'P',
+            ..,
+        ] => "image/webp",
+        [
+            b'R',
+            b'I',
+            b'F',
+            b'F',
+            _,
+            _,
+            _,
+            _,
+            b'W',
+            b'A',
+            b'V',
+            b'E',
+            ..,
+        ] => "audio/wav",
+        [b'%', b'P', b'D', b'F', b'-', ..] => "application/pdf",
+        [b'I', b'D', b'3', ..] => "audio/mpeg",
+        // An MPEG audio frame sync without an ID3 tag.
+        [0xff, second, ..] if second & 0xe0 == 0xe0 => "audio/mpeg",
+        _ => return None,
+    };
+    Some(mime)
+}
+
+/// The kind of content part an attachment is sent in, which decides the
+/// size limit it is checked against.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AttachmentKind {
+    Image,
+    Audio,
+    File,
+}
+
+impl fmt::Display for AttachmentKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Image => "image",
+            Self::Audio => "audio",
+            Self::File => "file",
+        })
+    }
+}
+
+/// The largest attachment of `kind`, in decoded bytes, `provider` accepts
+/// inline, or `None` if it imposes none or none is documented.
+///
+/// These are the documented per-attachment limits; a request carrying
+/// several attachments may still exceed a provider's total request size.
+pub fn max_inline_bytes(provider: Provider, kind: AttachmentKind) -> Option<usize> {
+    match (provider, kind) {
+        (Provider::Anthropic, AttachmentKind::Image) => Some(5 * MIB),
+        (Provider::Groq, AttachmentKind::Image) => Some(4 * MIB),
+        (Provider::Openai, AttachmentKind::Image) => Some(20 * MIB),
+        (Provider::Google, _) => Some(20 * MIB),
+        _ => None,
+    }
+}
+
+/// Checks an attachment of `kind` and `size` decoded bytes against
+/// [`max_inline_bytes`], failing with [`GatewayError::AttachmentTooLarge`].
+pub fn check_size(
+    provider: Provider,
+    kind: AttachmentKind,
+    size: usize,
+) -> Result<(), GatewayError> {
+    match max_inline_bytes(provider, kind) {
+        Some(limit) if size > limit => Err(GatewayError::AttachmentTooLarge {
+            provider,
+            kind,
+            size,
+            limit,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Checks every inline attachment in `messages` against `provider`'s limit
+/// before the request is sent.
+pub(crate) fn check_messages(provider: Provider, messages: &[Message]) -> Result<(), GatewayError> {
+    for message in messages {
+        let MessageContent::Parts(parts) = &message.content else {
+            continue;
+        };
+        for part in parts {
+            let (kind, size) = match part {
+                ContentPart::TextContentPart(_) => continue,
+                ContentPart::ImageContentPart(image) => {
+                    (AttachmentKind::Image, data_url_size(&image.image_url.url))
+                }
+                ContentPart::InputAudioContentPart(audio) => (
+                    AttachmentKind::Audio,
+                    Some(base64_size(&audio.input_audio.data)),
+                ),
+                ContentPart::FileContentPart(file) => (
+                    AttachmentKind::File,
+                    file.file.file_data.as_deref().and_then(data_url_size),
+                ),
+            };
+            if let Some(size) = size {
+                check_size(provider, kind, size)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Decoded size of a base64 `data:` URL, or `None` for other URLs.
+fn data_url_size(url: &str) -> Option<usize> {
+    let (header, payload) = split_data_url(url).ok()?;
+    if header.ends_with(";base64") {
+        Some(base64_size(payload))
+    } else {
+        Some(payload.len())
+    }
+}
+
+/// Decoded size of base64 `data`, without decoding it.
+fn base64_size(data: &str) -> usize {
+    let padding = data.bytes().rev().take_while(|&b| b == b'=').count();
+    (data.len() / 4 * 3).saturating_sub(padding)
+}
@@ -0,0 +1,484 @@
+//! Audio transcription and speech through the provider proxy.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use bytes::Bytes;
+use futures_util::{Stream, StreamExt};
+use reqwest::multipart::Form;
+use reqwest::{Body, RequestBuilder, StatusCode};
+use serde::{Deserialize, Deserializer, Serialize};
+use tokio_util::io::ReaderStream;
+
+use crate::{
+    GatewayError, Provider, SseDecoder, StreamSettings, decode, map_error_event, map_error_status,
+};
+
+/// An update from
+/// [`InferenceGatewayAPI::transcribe_stream`](crate::InferenceGatewayAPI::transcribe_stream).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TranscriptionEvent {
+    /// Newly recognised text, to append to the text before it.
+    Delta(String),
+    /// The complete transcript. Always the last event.
+    Done(String),
+}
+
+/// An event of OpenAI's streaming transcription format.
+#[derive(Deserialize)]
+#[serde(tag = "type")]
+enum WireEvent {
+    #[serde(rename = "transcript.text.delta")]
+    Delta { delta: String },
+    #[serde(rename = "transcript.text.done")]
+    Done { text: String },
+    #[serde(other)]
+    Other,
+}
+
+/// Audio to transcribe with
+/// [`InferenceGatewayAPI::create_transcription`](crate::InferenceGatewayAPI::create_transcription):
+/// bytes already in memory, or a file streamed from disk when the request is
+/// sent.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AudioFile {
+    /// Encoded audio, uploaded under `filename`, whose extension tells
+    /// providers its format.
+    Bytes { filename: String, bytes: Bytes },
+    /// A file, uploaded under its own name.
+    Path(PathBuf),
+}
+
+impl AudioFile {
+    /// Encoded audio uploaded under `filename`, such as `call.wav`.
+    pub fn from_bytes(filename: impl Into<String>, bytes: impl Into<Bytes>) -> Self {
+        Self::Bytes {
+            filename: filename.into(),
+            bytes: bytes.into(),
+        }
+    }
+
+    /// The file name and the body to upload. A file is opened here and
+    /// streamed from disk as the request is sent, rather than read into
+    /// memory.
+    pub(crate) async fn open(self) -> Result<(String, Body, u64), GatewayError> {
+        match self {
+            Self::Bytes { filename, bytes } => {
+                let length = bytes.len() as u64;
+                Ok((filename, Body::from(bytes), length))
+            }
+            Self::Path(path) => {
+                let io_error = |e: std::io::Error| GatewayError::Other(Box::new(e));
+                let file = tokio::fs::File::open(&path).await.map_err(io_error)?;
+                let length = file.metadata().await.map_err(io_error)?.len();
+                let filename = path
+                    .file_name()
+                    .map_or_else(|| "audio".into(), |name| name.to_string_lossy());
+                Ok((
+                    filename.into_owned(),
+                    Body::wrap_stream(ReaderStream::new(file)),
+                    length,
+                ))
+            }
+        }
+    }
+}
+
+impl From<PathBuf> for AudioFile {
+    fn from(path: PathBuf) -> Self {
+        Self::Path(path)
+    }
+}
+
+impl From<&Path> for AudioFile {
+    fn from(path: &Path) -> Self {
+        Self::Path(path.to_path_buf())
+    }
+}
+
+/// The detail of timestamps in a [`Transcription`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TimestampGranularity {
+    /// Start and end times of each segment, the default.
+    Segment,
+    /// Start and end times of each word, which some providers charge
+    /// extra for.
+    Word,
+}
+
+impl fmt::Display for TimestampGranularity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Segment => "segment",
+            Self::Word => "word",
+        })
+    }
+}
+
+/// Hints and timestamp detail for
+/// [`InferenceGatewayAPI::create_transcription`](crate::InferenceGatewayAPI::create_transcription).
+///
+/// ```
+/// use inference_gateway_sdk::{TimestampGranularity, TranscriptionOptions};
+///
+/// let options = TranscriptionOptions::new()
+///     .with_language("de")
+///     .with_prompt("Inference Gateway, Rust")
+///     .with_timestamps([TimestampGranularity::Segment, TimestampGranularity::Word]);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TranscriptionOptions {
+    language: Option<String>,
+    prompt: Option<String>,
+    temperature: Option<f32>,
+    timestamps: Vec<TimestampGranularity>,
+}
+
+impl TranscriptionOptions {
+    /// Lets the provider detect the language, with segment timestamps.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the spoken language as an ISO-639-1 code such as `en`, which
+    /// improves accuracy and latency.
+    pub fn with_language(mut self, language: impl Into<String>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    /// Sets text to guide the transcript's style or spelling of names, or
+    /// to continue a previous segment.
+    pub fn with_prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.prompt = Some(prompt.into());
+        self
+    }
+
+    /// Sets the sampling temperature, from 0 to 1.
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    /// Sets which timestamps the transcription carries.
+    pub fn with_timestamps(
+        mut self,
+        granularities: impl IntoIterator<Item = TimestampGranularity>,
+    ) -> Self {
+        self.timestamps = granularities.into_iter().collect();
+        self
+    }
+
+    /// The form fields of a request to `model`, asking for `verbose_json`
+    /// so the result carries segments.
+    pub(crate) fn form(&self, model: &str) -> Form {
+        let mut form = Form::new()
+            .text("model", model.to_string())
+            .text("response_format", "verbose_json");
+        if let Some(language) = &self.language {
+            form = form.text("language", language.clone());
+        }
+        if let Some(prompt) = &self.prompt {
+            form = form.text("prompt", prompt.clone());
+        }
+        if let Some(temperature) = self.temperature {
+            form = form.text("temperature", temperature.to_string());
+        }
+        for granularity in &self.timestamps {
+            form = form.text("timestamp_granularities[]", granularity.to_string());
+        }
+        form
+    }
+}
+
+/// The result of
+/// [`InferenceGatewayAPI::create_transcription`](crate::InferenceGatewayAPI::create_transcription).
+///
+/// Providers that only return text leave everything but `text` empty.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+pub struct Transcription {
+    /// The whole transcript.
+    pub text: String,
+    /// The detected or given language.
+    #[serde(default)]
+    pub language: Option<String>,
+    /// The audio's length in seconds.
+    #[serde(default)]
+    pub duration: Option<f64>,
+    /// The transcript in timed segments.
+    #[serde(default, deserialize_with = "or_empty")]
+    pub segments: Vec<TranscriptionSegment>,
+    /// Timed words, if word timestamps were asked for.
+    #[serde(default, deserialize_with = "or_empty")]
+    pub words: Vec<TranscriptionWord>,
+}
+
+/// A timed stretch of a [`Transcription`].
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+pub struct TranscriptionSegment {
+    /// The segment's index.
+    #[serde(default)]
+    pub id: u32,
+    /// When the segment starts, in seconds.
+    pub start: f64,
+    /// When the segment ends, in seconds.
+    pub end: f64,
+    /// The segment's text.
+    pub text: String,
+    /// The average log probability of its tokens, if reported.
+    #[serde(default)]
+    pub avg_logprob: Option<f64>,
+    /// How likely the segment is silence, if reported.
+    #[serde(default)]
+    pub no_speech_prob: Option<f64>,
+}
+
+/// A timed word of a [`Transcription`].
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+pub struct TranscriptionWord {
+    /// The word.
+    pub word: String,
+    /// When the word starts, in seconds.
+    pub start: f64,
+    /// When the word ends, in seconds.
+    pub end: f64,
+}
+
+/// A list that providers may send as `null`.
+fn or_empty<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Ok(Option::<Vec<T>>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// Sends a transcription request and yields its transcript, whether the
+/// provider streams it as SSE or returns it in one JSON body.
+pub(crate) fn transcription_stream(
+    request: RequestBuilder,
+    settings: StreamSettings,
+) -> impl Stream<Item = Result<TranscriptionEvent, GatewayError>> + Send {
+    async_stream::try_stream! {
+        settings.acquire().await?;
+        let response = settings.send(request).await?;
+        settings.rate_limit.update(response.headers());
+        let status = response.status();
+        if status != StatusCode::OK {
+            Err(map_error_status(status, response).await)?;
+            return;
+        }
+        let is_sse = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.starts_with("text/event-stream"));
+        if !is_sse {
+            let transcription: Transcription =
+                decode::from_response(response, settings.max_response_bytes).await?;
+            yield TranscriptionEvent::Done(transcription.text);
+            return;
+        }
+
+        let mut stream = response.bytes_stream();
+        let mut decoder = SseDecoder::with_capacity(settings.buffer_capacity);
+        let mut text = String::new();
+        while let Some(chunk) = stream.next().await {
+            decoder.push(&chunk.map_err(GatewayError::StreamError)?);
+            while let Some(event) = decoder.next_event() {
+                settings.check_event_size(event.data.len())?;
+                if event.event.as_deref() == Some("error") {
+                    Err(map_error_event(status, &event.data))?;
+                }
+                if event.data == "[DONE]" {
+                    continue;
+                }
+                match event.json()? {
+                    WireEvent::Delta { delta } => {
+                        text.push_str(&delta);
+                        yield TranscriptionEvent::Delta(delta);
+                    }
+                    WireEvent::Done { text } => {
+                        yield TranscriptionEvent::Done(text);
+                        return;
+                    }
+                    WireEvent::Other => {}
+                }
+            }
+            settings.check_event_size(decoder.pending_len())?;
+        }
+        yield TranscriptionEvent::Done(text);
+    }
+}
+
+/// A voice for [`InferenceGatewayAPI::create_speech`](crate::InferenceGatewayAPI::create_speech).
+///
+/// The named variants are OpenAI's built-in voices; other providers' voices
+/// are given by name with [`Voice::Named`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Voice {
+    Alloy,
+    Ash,
+    Ballad,
+    Coral,
+    Echo,
+    Fable,
+    Nova,
+    Onyx,
+    Sage,
+    Shimmer,
+    Verse,
+    /// A voice by the name the provider uses, e.g. `Fritz-PlayAI` on Groq.
+    Named(String),
+}
+
+impl Voice {
+    fn is_openai(&self) -> bool {
+        !matches!(self, Self::Named(_))
+    }
+}
+
+impl fmt::Display for Voice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Alloy => "alloy",
+            Self::Ash => "ash",
+            Self::Ballad => "ballad",
+            Self::Coral => "coral",
+            Self::Echo => "echo",
+            Self::Fable => "fable",
+            Self::Nova => "nova",
+            Self::Onyx => "onyx",
+            Self::Sage => "sage",
+            Self::Shimmer => "shimmer",
+            Self::Verse => "verse",
+            Self::Named(name) => name,
+        })
+    }
+}
+
+impl From<&str> for Voice {
+    fn from(name: &str) -> Self {
+        Self::Named(name.to_string())
+    }
+}
+
+impl From<String> for Voice {
+    fn from(name: String) -> Self {
+        Self::Named(name)
+    }
+}
+
+/// The audio format [`InferenceGatewayAPI::create_speech`](crate::InferenceGatewayAPI::create_speech)
+/// returns.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SpeechFormat {
+    #[default]
+    Mp3,
+    Opus,
+    Wav,
+    /// Raw 24kHz 16-bit signed little-endian samples, without a header.
+    Pcm,
+}
+
+/// Voice, output format and speed for
+/// [`InferenceGatewayAPI::create_speech`](crate::InferenceGatewayAPI::create_speech).
+///
+/// Combinations a provider is known not to support are rejected before the
+/// request is sent, with [`GatewayError::UnsupportedSpeechOptions`].
+///
+/// ```
+/// use inference_gateway_sdk::{SpeechFormat, SpeechOptions, Voice};
+///
+/// let options = SpeechOptions::new(Voice::Coral)
+///     .with_format(SpeechFormat::Opus)
+///     .with_speed(1.25);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpeechOptions {
+    voice: Voice,
+    format: SpeechFormat,
+    speed: Option<f32>,
+}
+
+impl SpeechOptions {
+    /// Speaks in `voice`, as MP3 at normal speed.
+    pub fn new(voice: impl Into<Voice>) -> Self {
+        Self {
+            voice: voice.into(),
+            format: SpeechFormat::default(),
+            speed: None,
+        }
+    }
+
+    /// Sets the audio format of the result.
+    pub fn with_format(mut self, format: SpeechFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Sets the speaking speed, from `0.25` to `4.0` times normal.
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = Some(speed);
+        self
+    }
+
+    /// Checks these options against what `provider` supports.
+    pub(crate) fn validate(&self, provider: Provider) -> Result<(), GatewayError> {
+        let unsupported =
+            |reason: String| Err(GatewayError::UnsupportedSpeechOptions { provider, reason });
+        if let Some(speed) = self.speed
+            && !(0.25..=4.0).contains(&speed)
+        {
+            return unsupported(format!("speed {speed} is outside 0.25 to 4.0"));
+        }
+        match provider {
+            Provider::Openai => Ok(()),
+            Provider::Groq if self.voice.is_openai() => {
+                unsupported(format!("`{}` is an OpenAI voice", self.voice))
+            }
+            Provider::Groq if self.format != SpeechFormat::Wav => {
+                unsupported(format!("{:?} output; only Wav is supported", self.format))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// The request body for `input` spoken by `model`.
+    pub(crate) fn request_body(&self, model: &str, input: &str) -> serde_json::Value {
+        let mut body = serde_json::json!({
+            "model": model,
+            "input": input,
+            "voice": self.voice.to_string(),
+            "response_format": self.format,
+        });
+        if let Some(speed) = self.speed {
+            body["speed"] = speed.into();
+        }
+        body
+    }
+}
+
+/// Sends a speech request, unless building it failed, and yields the audio
+/// as it arrives.
+pub(crate) fn speech_stream(
+    request: Result<RequestBuilder, GatewayError>,
+    settings: StreamSettings,
+) -> impl Stream<Item = Result<Bytes, GatewayError>> + Send {
+    async_stream::try_stream! {
+        let request = request?;
+        settings.acquire().await?;
+        let response = settings.send(request).await?;
+        settings.rate_limit.update(response.headers());
+        let status = response.status();
+        if status != StatusCode::OK {
+            Err(map_error_status(status, response).await)?;
+            return;
+        }
+        let mut stream = response.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            yield chunk.map_err(GatewayError::StreamError)?;
+        }
+    }
+}
@@ -0,0 +1,172 @@
+//! Spreading requests across replicated gateway instances.
+//!
+//! A client built with
+//! [`InferenceGatewayClient::new_balanced`](crate::InferenceGatewayClient::new_balanced)
+//! keeps a pool of endpoints and sends each request, retries included, to
+//! the endpoint its [`LoadBalancing`] strategy picks. An endpoint that
+//! can't be connected to is ejected for a while and only gets traffic again
+//! once the ejection ends, or if every endpoint is ejected.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use reqwest::{Request, Url};
+
+use crate::GatewayError;
+
+/// How a balanced client picks the endpoint for each request.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LoadBalancing {
+    /// Each endpoint in turn.
+    #[default]
+    RoundRobin,
+    /// The endpoint with the fewest requests waiting for a response, taking
+    /// turns between equally busy ones.
+    LeastOutstanding,
+}
+
+#[derive(Debug)]
+struct Endpoint {
+    base_url: String,
+    url: Url,
+    outstanding: AtomicUsize,
+    ejected_until: Mutex<Option<Instant>>,
+}
+
+impl Endpoint {
+    fn is_ejected(&self, now: Instant) -> bool {
+        self.ejected_until
+            .lock()
+            .unwrap()
+            .is_some_and(|until| until > now)
+    }
+}
+
+/// The endpoints of a balanced client, shared by its clones.
+#[derive(Debug)]
+pub(crate) struct EndpointPool {
+    endpoints: Vec<Arc<Endpoint>>,
+    strategy: LoadBalancing,
+    ejection: Duration,
+    next: AtomicUsize,
+}
+
+impl EndpointPool {
+    /// A pool of `base_urls`, the first of which the client builds its
+    /// request URLs on. Fails if there are none or one isn't a valid URL.
+    pub(crate) fn new(base_urls: &[&str], strategy: LoadBalancing) -> Result<Self, GatewayError> {
+        if base_urls.is_empty() {
+            return Err(GatewayError::InvalidEndpoint(
+                "a balanced client needs at least one endpoint".to_string(),
+            ));
+        }
+        let endpoints = base_urls
+            .iter()
+            .map(|base_url| {
+                let base_url = base_url.trim_end_matches('/');
+                let url = Url::parse(base_url)
+                    .map_err(|e| GatewayError::InvalidEndpoint(format!("`{base_url}`: {e}")))?;
+                Ok(Arc::new(Endpoint {
+                    base_url: base_url.to_string(),
+                    url,
+                    outstanding: AtomicUsize::new(0),
+                    ejected_until: Mutex::new(None),
+                }))
+            })
+            .collect::<Result<_, GatewayError>>()?;
+        Ok(Self {
+            endpoints,
+            strategy,
+            ejection: Duration::from_secs(30),
+            next: AtomicUsize::new(0),
+        })
+    }
+
+    /// The same endpoints, in the same state, ejected for `ejection` when
+    /// they can't be connected to.
+    pub(crate) fn with_ejection(&self, ejection: Duration) -> Self {
+        Self {
+            endpoints: self.endpoints.clone(),
+            strategy: self.strategy,
+            ejection,
+            next: AtomicUsize::new(self.next.load(Ordering::Relaxed)),
+        }
+    }
+
+    /// Points `request` at the endpoint picked for it, returning the lease
+    /// to settle once it is answered. Requests for URLs outside the first
+    /// endpoint, such as artifacts served from elsewhere, are left alone.
+    pub(crate) fn route(&self, request: &mut Request) -> Option<Lease> {
+        let primary = &self.endpoints[0];
+        let url = request.url().as_str();
+        let path = url
+            .strip_prefix(primary.base_url.as_str())
+            .filter(|rest| rest.is_empty() || rest.starts_with('/') || rest.starts_with('?'));
+        if path.is_none() && request.url().origin() != primary.url.origin() {
+            return None;
+        }
+        let endpoint = self.pick();
+        let rewritten = match path {
+            Some(path) => Url::parse(&format!("{}{path}", endpoint.base_url)).ok(),
+            None => {
+                let mut url = request.url().clone();
+                let moved = url.set_scheme(endpoint.url.scheme()).is_ok()
+                    && url.set_host(endpoint.url.host_str()).is_ok()
+                    && url.set_port(endpoint.url.port()).is_ok();
+                moved.then_some(url)
+            }
+        };
+        *request.url_mut() = rewritten?;
+        endpoint.outstanding.fetch_add(1, Ordering::Relaxed);
+        Some(Lease {
+            endpoint,
+            ejection: self.ejection,
+        })
+    }
+
+    fn pick(&self) -> Arc<Endpoint> {
+        let now = Instant::now();
+        let count = self.endpoints.len();
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        let candidates = (start..start + count).map(|i| &self.endpoints[i % count]);
+        let mut healthy = candidates
+            .clone()
+            .filter(|endpoint| !endpoint.is_ejected(now));
+        let picked = match self.strategy {
+            LoadBalancing::RoundRobin => healthy.next(),
+            LoadBalancing::LeastOutstanding => {
+                healthy.min_by_key(|endpoint| endpoint.outstanding.load(Ordering::Relaxed))
+            }
+        };
+        // With every endpoint ejected, one that may have recovered beats
+        // failing outright.
+        picked
+            .or_else(|| candidates.clone().next())
+            .cloned()
+            .expect("the pool has an endpoint")
+    }
+}
+
+/// A request in flight to an endpoint.
+pub(crate) struct Lease {
+    endpoint: Arc<Endpoint>,
+    ejection: Duration,
+}
+
+impl Lease {
+    /// Ejects the endpoint if the request couldn't connect to it.
+    pub(crate) fn settle<T>(self, result: &Result<T, GatewayError>) {
+        if let Err(GatewayError::RequestError(e)) = result
+            && e.is_connect()
+        {
+            *self.endpoint.ejected_until.lock().unwrap() = Some(Instant::now() + self.ejection);
+        }
+    }
+}
+
+impl Drop for Lease {
+    fn drop(&mut self) {
+        self.endpoint.outstanding.fetch_sub(1, Ordering::Relaxed);
+    }
+}
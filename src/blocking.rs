@@ -0,0 +1,463 @@
+//! A synchronous client, for CLI tools and codebases without an async
+//! runtime.
+//!
+//! [`InferenceGatewayClient`] wraps the async
+//! [`InferenceGatewayClient`](crate::InferenceGatewayClient) and runs each
+//! call to completion on a runtime of its own, so callers never touch
+//! `async` or tokio. Streaming calls return iterators that block for each
+//! event.
+//!
+//! ```no_run
+//! use inference_gateway_sdk::blocking::InferenceGatewayClient;
+//! use inference_gateway_sdk::{Message, Provider};
+//!
+//! let client = InferenceGatewayClient::new("http://localhost:8080/v1");
+//! let answer = client.ask(Provider::Groq, "llama-3.3-70b-versatile", "Hi")?;
+//! println!("{answer}");
+//!
+//! for event in client.generate_content_stream(
+//!     Provider::Groq,
+//!     "llama-3.3-70b-versatile",
+//!     vec![Message::user("Tell me a story")],
+//! ) {
+//!     println!("{}", event?.data);
+//! }
+//! # Ok::<(), inference_gateway_sdk::GatewayError>(())
+//! ```
+//!
+//! Calls panic if made from within an async runtime; use the async client
+//! there. Requires the `blocking` feature.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use futures_util::{Stream, StreamExt};
+use tokio::runtime::Runtime;
+
+use crate::{
+    AudioFile, ChatCompletionOptions, CreateChatCompletionResponse, CreateMessagesRequest,
+    EmbeddingRequest, EmbeddingResponse, FileContentPart, FoldedCompletion, GatewayError,
+    HealthStatus, InferenceGatewayAPI, ListModelsResponse, ListToolsResponse, McpTool, Message,
+    MessagesResponse, Model, ModelRef, ModerationInput, ModerationResponse, Provider,
+    ProviderHealth, RequestOptions, SSEvents, SpeechOptions, Transcription, TranscriptionEvent,
+    TranscriptionOptions,
+};
+
+/// A synchronous version of the async
+/// [`InferenceGatewayClient`](crate::InferenceGatewayClient), with the
+/// same methods as [`InferenceGatewayAPI`].
+///
+/// Configure it by building an async client and converting it with
+/// [`From`], which keeps every setting:
+///
+/// ```no_run
+/// use inference_gateway_sdk::blocking;
+///
+/// let client: blocking::InferenceGatewayClient =
+///     inference_gateway_sdk::InferenceGatewayClient::new("http://localhost:8080/v1")
+///         .with_token("my-token")
+///         .into();
+/// ```
+///
+/// Clones share the connection pool and the runtime.
+#[derive(Clone, Debug)]
+pub struct InferenceGatewayClient {
+    client: crate::InferenceGatewayClient,
+    runtime: Arc<Runtime>,
+}
+
+impl InferenceGatewayClient {
+    /// A client for the gateway API at `base_url`, such as
+    /// `http://localhost:8080/v1`.
+    ///
+    /// # Panics
+    ///
+    /// If the client's runtime can't be started.
+    pub fn new(base_url: &str) -> Self {
+        crate::InferenceGatewayClient::new(base_url).into()
+    }
+
+    /// A client for the gateway API at `INFERENCE_GATEWAY_URL`, or
+    /// `http://localhost:8080/v1` if it isn't set.
+    ///
+    /// # Panics
+    ///
+    /// If the client's runtime can't be started.
+    pub fn new_default() -> Self {
+        crate::InferenceGatewayClient::new_default().into()
+    }
+
+    /// The async client calls are made through.
+    pub fn as_async(&self) -> &crate::InferenceGatewayClient {
+        &self.client
+    }
+
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+
+    fn iter<'a, T: 'a>(
+        &'a self,
+        stream: impl Stream<Item = Result<T, GatewayError>> + Send + 'a,
+    ) -> impl Iterator<Item = Result<T, GatewayError>> + 'a {
+        BlockingIter {
+            runtime: &self.runtime,
+            stream: Box::pin(stream),
+        }
+    }
+
+    /// Lists the models of every configured provider.
+    pub fn list_models(&self) -> Result<ListModelsResponse, GatewayError> {
+        self.block_on(self.client.list_models())
+    }
+
+    /// Lists the models of `provider`.
+    pub fn list_models_by_provider(
+        &self,
+        provider: Provider,
+    ) -> Result<ListModelsResponse, GatewayError> {
+        self.block_on(self.client.list_models_by_provider(provider))
+    }
+
+    /// Lists models, optionally of one provider, with the extra `include`
+    /// fields.
+    pub fn list_models_with_include(
+        &self,
+        provider: Option<Provider>,
+        include: &[&str],
+    ) -> Result<ListModelsResponse, GatewayError> {
+        self.block_on(self.client.list_models_with_include(provider, include))
+    }
+
+    /// Lists the models of every configured provider one at a time.
+    pub fn list_models_stream(&self) -> impl Iterator<Item = Result<Model, GatewayError>> + '_ {
+        self.iter(self.client.list_models_stream())
+    }
+
+    /// Generates a chat completion for `messages`.
+    pub fn generate_content(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef>,
+        messages: Vec<Message>,
+    ) -> Result<CreateChatCompletionResponse, GatewayError> {
+        self.block_on(
+            self.client
+                .generate_content(provider, model.into(), messages),
+        )
+    }
+
+    /// Generates a chat completion for `messages`, with per-call options.
+    pub fn generate_content_with_options(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef>,
+        messages: Vec<Message>,
+        options: impl Into<ChatCompletionOptions>,
+    ) -> Result<CreateChatCompletionResponse, GatewayError> {
+        self.block_on(self.client.generate_content_with_options(
+            provider,
+            model.into(),
+            messages,
+            options.into(),
+        ))
+    }
+
+    /// Streams a chat completion, blocking for each SSE event.
+    pub fn generate_content_stream(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef>,
+        messages: Vec<Message>,
+    ) -> impl Iterator<Item = Result<SSEvents, GatewayError>> + '_ {
+        self.iter(
+            self.client
+                .generate_content_stream(provider, model.into(), messages),
+        )
+    }
+
+    /// Streams a chat completion with per-call options, blocking for each
+    /// SSE event.
+    pub fn generate_content_stream_with_options(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef>,
+        messages: Vec<Message>,
+        options: impl Into<ChatCompletionOptions>,
+    ) -> impl Iterator<Item = Result<SSEvents, GatewayError>> + '_ {
+        self.iter(self.client.generate_content_stream_with_options(
+            provider,
+            model.into(),
+            messages,
+            options.into(),
+        ))
+    }
+
+    /// Streams a chat completion to the end and returns it folded, resuming
+    /// up to `max_resumes` times if the connection drops.
+    pub fn generate_content_resumable(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef>,
+        messages: Vec<Message>,
+        max_resumes: u32,
+    ) -> Result<FoldedCompletion, GatewayError> {
+        self.block_on(self.client.generate_content_resumable(
+            provider,
+            model.into(),
+            messages,
+            max_resumes,
+        ))
+    }
+
+    /// Sends `prompt` as a user message and returns the reply's text.
+    pub fn ask(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef>,
+        prompt: impl Into<String>,
+    ) -> Result<String, GatewayError> {
+        self.block_on(self.client.ask(provider, model.into(), prompt.into()))
+    }
+
+    /// Sends `system` and `prompt` and returns the reply's text.
+    pub fn ask_with_system(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef>,
+        system: impl Into<String>,
+        prompt: impl Into<String>,
+    ) -> Result<String, GatewayError> {
+        self.block_on(self.client.ask_with_system(
+            provider,
+            model.into(),
+            system.into(),
+            prompt.into(),
+        ))
+    }
+
+    /// Creates a message through the Anthropic-compatible Messages API.
+    pub fn create_message(
+        &self,
+        provider: Option<Provider>,
+        request: CreateMessagesRequest,
+    ) -> Result<MessagesResponse, GatewayError> {
+        self.block_on(self.client.create_message(provider, request))
+    }
+
+    /// Creates a message through the Messages API, with per-call options.
+    pub fn create_message_with_options(
+        &self,
+        provider: Option<Provider>,
+        request: CreateMessagesRequest,
+        options: RequestOptions,
+    ) -> Result<MessagesResponse, GatewayError> {
+        self.block_on(
+            self.client
+                .create_message_with_options(provider, request, options),
+        )
+    }
+
+    /// Streams a Messages API response, blocking for each SSE event.
+    pub fn create_message_stream(
+        &self,
+        provider: Option<Provider>,
+        request: CreateMessagesRequest,
+    ) -> impl Iterator<Item = Result<SSEvents, GatewayError>> + '_ {
+        self.iter(self.client.create_message_stream(provider, request))
+    }
+
+    /// Streams a Messages API response with per-call options, blocking for
+    /// each SSE event.
+    pub fn create_message_stream_with_options(
+        &self,
+        provider: Option<Provider>,
+        request: CreateMessagesRequest,
+        options: RequestOptions,
+    ) -> impl Iterator<Item = Result<SSEvents, GatewayError>> + '_ {
+        self.iter(
+            self.client
+                .create_message_stream_with_options(provider, request, options),
+        )
+    }
+
+    /// Lists the gateway's MCP tools.
+    pub fn list_tools(&self) -> Result<ListToolsResponse, GatewayError> {
+        self.block_on(self.client.list_tools())
+    }
+
+    /// Lists the gateway's MCP tools one at a time.
+    pub fn list_tools_stream(&self) -> impl Iterator<Item = Result<McpTool, GatewayError>> + '_ {
+        self.iter(self.client.list_tools_stream())
+    }
+
+    /// Uploads a file to `provider` for use in later requests.
+    pub fn upload_file(
+        &self,
+        provider: Provider,
+        filename: &str,
+        bytes: &[u8],
+        purpose: &str,
+    ) -> Result<FileContentPart, GatewayError> {
+        self.block_on(self.client.upload_file(provider, filename, bytes, purpose))
+    }
+
+    /// Counts the prompt tokens of `messages` with the provider's own
+    /// counting endpoint.
+    pub fn count_tokens_remote(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef>,
+        messages: &[Message],
+    ) -> Result<u64, GatewayError> {
+        self.block_on(
+            self.client
+                .count_tokens_remote(provider, model.into(), messages),
+        )
+    }
+
+    /// Checks that `messages` fit the model's context window.
+    pub fn validate_request(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef>,
+        messages: &[Message],
+    ) -> Result<(), GatewayError> {
+        self.block_on(
+            self.client
+                .validate_request(provider, model.into(), messages),
+        )
+    }
+
+    /// Embeds the request's inputs.
+    pub fn create_embeddings(
+        &self,
+        provider: Provider,
+        request: EmbeddingRequest,
+    ) -> Result<EmbeddingResponse, GatewayError> {
+        self.block_on(self.client.create_embeddings(provider, request))
+    }
+
+    /// Synthesizes `input` as speech and returns the audio.
+    pub fn create_speech(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef>,
+        input: &str,
+        options: SpeechOptions,
+    ) -> Result<bytes::Bytes, GatewayError> {
+        self.block_on(
+            self.client
+                .create_speech(provider, model.into(), input, options),
+        )
+    }
+
+    /// Speaks `input`, yielding the audio chunk by chunk.
+    pub fn create_speech_stream<'a>(
+        &'a self,
+        provider: Provider,
+        model: impl Into<ModelRef>,
+        input: &'a str,
+        options: SpeechOptions,
+    ) -> impl Iterator<Item = Result<bytes::Bytes, GatewayError>> + 'a {
+        self.iter(
+            self.client
+                .create_speech_stream(provider, model.into(), input, options),
+        )
+    }
+
+    /// Transcribes `audio` with timed segments.
+    pub fn create_transcription(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef>,
+        audio: impl Into<AudioFile>,
+        options: TranscriptionOptions,
+    ) -> Result<Transcription, GatewayError> {
+        self.block_on(self.client.create_transcription(
+            provider,
+            model.into(),
+            audio.into(),
+            options,
+        ))
+    }
+
+    /// Transcribes `audio`, blocking for each transcription event.
+    pub fn transcribe_stream<'a>(
+        &'a self,
+        provider: Provider,
+        model: impl Into<ModelRef>,
+        filename: &'a str,
+        audio: impl Into<bytes::Bytes>,
+    ) -> impl Iterator<Item = Result<TranscriptionEvent, GatewayError>> + 'a {
+        let audio = futures_util::stream::once(std::future::ready(Ok(audio.into())));
+        self.iter(
+            self.client
+                .transcribe_stream(provider, model.into(), filename, audio),
+        )
+    }
+
+    /// Classifies `input`, returning its flags and category scores.
+    pub fn create_moderation(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef>,
+        input: impl Into<ModerationInput>,
+    ) -> Result<ModerationResponse, GatewayError> {
+        self.block_on(
+            self.client
+                .create_moderation(provider, model.into(), input.into()),
+        )
+    }
+
+    /// Whether the gateway is up.
+    pub fn health_check(&self) -> Result<bool, GatewayError> {
+        self.block_on(self.client.health_check())
+    }
+
+    /// Whether the gateway is up and serving API requests.
+    pub fn ready_check(&self) -> HealthStatus {
+        self.block_on(self.client.ready_check())
+    }
+
+    /// Probes each configured provider.
+    pub fn provider_health(&self) -> Result<Vec<ProviderHealth>, GatewayError> {
+        self.block_on(self.client.provider_health())
+    }
+}
+
+impl From<crate::InferenceGatewayClient> for InferenceGatewayClient {
+    /// Runs `client`'s calls on a runtime of the blocking client's own.
+    ///
+    /// # Panics
+    ///
+    /// If the runtime can't be started.
+    fn from(client: crate::InferenceGatewayClient) -> Self {
+        // A worker thread keeps tasks the client spawns, such as shadow
+        // requests, running between calls.
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .expect("failed to start the blocking client's runtime");
+        Self {
+            client,
+            runtime: Arc::new(runtime),
+        }
+    }
+}
+
+/// A stream driven one item at a time on the blocking client's runtime.
+struct BlockingIter<'a, T> {
+    runtime: &'a Runtime,
+    stream: Pin<Box<dyn Stream<Item = Result<T, GatewayError>> + Send + 'a>>,
+}
+
+impl<T> Iterator for BlockingIter<'_, T> {
+    type Item = Result<T, GatewayError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.runtime.block_on(self.stream.next())
+    }
+}
@@ -0,0 +1,126 @@
+//! Caching chat completions, so repeated requests skip the gateway.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use futures_util::FutureExt;
+use futures_util::future::BoxFuture;
+use sha2::{Digest, Sha256};
+
+use crate::CreateChatCompletionResponse;
+
+/// Storage for cached chat completions, keyed by a hash of the request's
+/// URL, credential, scheduling hints and body.
+///
+/// [`InMemoryCache`] keeps responses in process memory; implement it on a
+/// client for Redis or similar to share them between processes. A store
+/// that fails should behave as if it had nothing cached, since a cache
+/// error never fails the call.
+pub trait CacheStore: Send + Sync + 'static {
+    /// The response stored under `key`, if there is one that is still
+    /// fresh.
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Option<CreateChatCompletionResponse>>;
+
+    /// Stores `response` under `key`.
+    fn put<'a>(
+        &'a self,
+        key: &'a str,
+        response: &'a CreateChatCompletionResponse,
+    ) -> BoxFuture<'a, ()>;
+}
+
+/// A [`CacheStore`] held in process memory, where responses expire after a
+/// fixed time.
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use inference_gateway_sdk::{InMemoryCache, InferenceGatewayClient};
+///
+/// let client = InferenceGatewayClient::new("http://localhost:8080/v1")
+///     .with_cache(InMemoryCache::new(Duration::from_secs(600)));
+/// ```
+#[derive(Clone, Debug)]
+pub struct InMemoryCache {
+    ttl: Duration,
+    entries: Arc<Mutex<HashMap<String, (Instant, CreateChatCompletionResponse)>>>,
+}
+
+impl InMemoryCache {
+    /// An empty cache whose responses expire `ttl` after they are stored.
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Arc::default(),
+        }
+    }
+
+    /// How many responses are stored, expired ones included until the next
+    /// [`CacheStore::put`] drops them.
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    /// Whether no responses are stored.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drops every stored response.
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+}
+
+impl CacheStore for InMemoryCache {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Option<CreateChatCompletionResponse>> {
+        let entries = self.entries.lock().unwrap();
+        let response = entries
+            .get(key)
+            .filter(|(expires, _)| *expires > Instant::now())
+            .map(|(_, response)| response.clone());
+        futures_util::future::ready(response).boxed()
+    }
+
+    fn put<'a>(
+        &'a self,
+        key: &'a str,
+        response: &'a CreateChatCompletionResponse,
+    ) -> BoxFuture<'a, ()> {
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|_, (expires, _)| *expires > now);
+        entries.insert(key.to_string(), (now + self.ttl, response.clone()));
+        futures_util::future::ready(()).boxed()
+    }
+}
+
+/// The cache key of a request with `body` to `url`, authorized by `token`
+/// and sending the scheduling hint `headers`: a SHA-256 in lowercase hex, so
+/// stores needn't care about its length or characters. Only a hash of the
+/// token goes into it, and a key can't be traced back to any of its parts.
+pub(crate) fn key<'a>(
+    url: &str,
+    token: Option<&str>,
+    headers: impl IntoIterator<Item = (&'static str, &'a str)>,
+    body: &[u8],
+) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(url.as_bytes());
+    hasher.update(b"\n");
+    if let Some(token) = token {
+        hasher.update(Sha256::digest(token.as_bytes()));
+    }
+    hasher.update(b"\n");
+    for (name, value) in headers {
+        hasher.update(format!("{name}: {value}\n").as_bytes());
+    }
+    hasher.update(b"\n");
+    hasher.update(body);
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
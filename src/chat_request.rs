@@ -0,0 +1,139 @@
+//! A builder for one chat completion call.
+
+use std::time::Duration;
+
+use futures_util::Stream;
+use futures_util::future::Either;
+
+use crate::{
+    ChatCompletionOptions, ChatCompletionTool, CreateChatCompletionResponse, GatewayError,
+    InferenceGatewayAPI, InferenceGatewayClient, Message, ModelRef, Provider, RequestOptions,
+    SSEvents,
+};
+
+/// A chat completion request to one model, built up call by call and sent
+/// with [`send`](Self::send) or [`stream`](Self::stream).
+///
+/// Tools and the token limit set here replace the client's
+/// [`with_tools`](InferenceGatewayClient::with_tools) and
+/// [`with_max_tokens`](InferenceGatewayClient::with_max_tokens) for this
+/// request only.
+///
+/// ```no_run
+/// # async fn run() -> Result<(), inference_gateway_sdk::GatewayError> {
+/// use inference_gateway_sdk::{InferenceGatewayClient, Message, Provider};
+///
+/// let client = InferenceGatewayClient::new("http://localhost:8080/v1");
+/// let response = client
+///     .chat_completion("llama-3.3-70b-versatile")
+///     .with_provider(Provider::Groq)
+///     .with_messages(vec![Message::user("Summarize this thread")])
+///     .with_max_tokens(200)
+///     .send()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+#[must_use = "a request does nothing until it is sent"]
+pub struct ChatCompletionRequestBuilder<'a> {
+    client: &'a InferenceGatewayClient,
+    provider: Option<Provider>,
+    model: ModelRef,
+    messages: Vec<Message>,
+    options: ChatCompletionOptions,
+}
+
+impl<'a> ChatCompletionRequestBuilder<'a> {
+    pub(crate) fn new(client: &'a InferenceGatewayClient, model: ModelRef) -> Self {
+        Self {
+            client,
+            provider: None,
+            model,
+            messages: Vec::new(),
+            options: ChatCompletionOptions::default(),
+        }
+    }
+
+    /// Sends the request to `provider`. Without one, the model must be
+    /// qualified, as in `groq/llama-3.3-70b-versatile`.
+    pub fn with_provider(mut self, provider: Provider) -> Self {
+        self.provider = Some(provider);
+        self
+    }
+
+    /// Replaces the conversation sent.
+    pub fn with_messages(mut self, messages: Vec<Message>) -> Self {
+        self.messages = messages;
+        self
+    }
+
+    /// Appends `message` to the conversation sent.
+    pub fn with_message(mut self, message: Message) -> Self {
+        self.messages.push(message);
+        self
+    }
+
+    /// Offers `tools` to the model instead of the client's. They aren't
+    /// sent when streaming.
+    pub fn with_tools(mut self, tools: Vec<ChatCompletionTool>) -> Self {
+        self.options = self.options.with_tools(tools);
+        self
+    }
+
+    /// Caps the tokens generated instead of the client's limit. It isn't
+    /// sent when streaming.
+    pub fn with_max_tokens(mut self, max_tokens: i64) -> Self {
+        self.options = self.options.with_max_tokens(max_tokens);
+        self
+    }
+
+    /// Fails the request with [`GatewayError::Timeout`] if it hasn't
+    /// completed within `timeout`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.options = self.options.with_timeout(timeout);
+        self
+    }
+
+    /// Sets the sampling parameters and request options, replacing tools
+    /// and a token limit set before.
+    pub fn with_options(mut self, options: impl Into<ChatCompletionOptions>) -> Self {
+        self.options = options.into();
+        self
+    }
+
+    /// Sets the per-call request options, keeping everything else.
+    pub fn with_request_options(mut self, options: RequestOptions) -> Self {
+        self.options = self.options.with_request_options(options);
+        self
+    }
+
+    /// Sends the request and waits for the whole response. Fails with
+    /// [`GatewayError::NoProvider`] if no provider is known for the model.
+    pub async fn send(self) -> Result<CreateChatCompletionResponse, GatewayError> {
+        let provider = self.provider()?;
+        self.client
+            .generate_content_with_options(provider, self.model, self.messages, self.options)
+            .await
+    }
+
+    /// Streams the response as SSE events. The stream fails with
+    /// [`GatewayError::NoProvider`] if no provider is known for the model.
+    pub fn stream(self) -> impl Stream<Item = Result<SSEvents, GatewayError>> + Send + 'a {
+        match self.provider() {
+            Ok(provider) => Either::Left(self.client.generate_content_stream_with_options(
+                provider,
+                self.model,
+                self.messages,
+                self.options,
+            )),
+            Err(e) => Either::Right(futures_util::stream::once(async { Err(e) })),
+        }
+    }
+
+    fn provider(&self) -> Result<Provider, GatewayError> {
+        self.provider
+            .or(self.model.provider())
+            .ok_or_else(|| GatewayError::NoProvider(self.model.to_string()))
+    }
+}
@@ -0,0 +1,291 @@
+//! Recorded provider responses and a harness for checking that they parse.
+//!
+//! Each [`Fixture`] is a chat completion as one provider returned it through
+//! the gateway, quirks included, together with the [`Expected`] outcome once
+//! the SDK has parsed it. [`Fixture::check`] runs a body through the same
+//! path the client uses (provider normalization, SSE decoding, stream
+//! folding) and compares the result, so a regression in any of them shows up
+//! against realistic payloads rather than hand-trimmed ones.
+//!
+//! Integrators can run the shipped fixtures in their own CI, or record their
+//! own responses and check them with [`parse`]:
+//!
+//! ```
+//! use inference_gateway_sdk::conformance;
+//!
+//! for fixture in conformance::FIXTURES {
+//!     fixture.assert_conforms();
+//! }
+//! ```
+//!
+//! Requires the `conformance` feature.
+
+use std::fmt;
+
+use futures_util::FutureExt;
+use futures_util::stream;
+
+use crate::stream::{FoldedCompletion, fold_until_error};
+use crate::{
+    CreateChatCompletionResponse, CreateChatCompletionStreamResponse, FinishReason, GatewayError,
+    Provider, SseDecoder, normalize,
+};
+
+/// How a fixture's body was delivered.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// A single JSON chat completion.
+    Json,
+    /// A `text/event-stream` of chat completion chunks.
+    Sse,
+}
+
+/// What a fixture should parse to.
+#[derive(Clone, Debug)]
+pub struct Expected {
+    /// The assistant's text.
+    pub content: &'static str,
+    /// The reasoning text, under whichever field the provider used.
+    pub reasoning_content: Option<&'static str>,
+    /// The tool calls, as `(id, function name)`.
+    pub tool_calls: &'static [(&'static str, &'static str)],
+    /// Why the model stopped, after normalization.
+    pub finish_reason: FinishReason,
+    /// Total tokens, if the provider reported usage.
+    pub total_tokens: Option<i64>,
+}
+
+/// A recorded chat completion and what it should parse to.
+#[derive(Clone, Debug)]
+pub struct Fixture {
+    /// Short identifier, unique across [`FIXTURES`].
+    pub name: &'static str,
+    /// The provider that produced the body.
+    pub provider: Provider,
+    /// How the body was delivered.
+    pub format: Format,
+    /// The body as received from the gateway.
+    pub body: &'static str,
+    /// The parsed outcome.
+    pub expected: Expected,
+}
+
+/// The shipped fixtures, at least one per provider with known quirks.
+pub const FIXTURES: &[Fixture] = &[
+    Fixture {
+        name: "openai_tool_call",
+        provider: Provider::Openai,
+        format: Format::Json,
+        body: include_str!("fixtures/openai_tool_call.json"),
+        expected: Expected {
+            content: "",
+            reasoning_content: None,
+            tool_calls: &[("call_Xk2pQ7vN4mR8tL1sW9yB3cD6", "get_weather")],
+            finish_reason: FinishReason::ToolCalls,
+            total_tokens: Some(103),
+        },
+    },
+    Fixture {
+        name: "anthropic_thinking",
+        provider: Provider::Anthropic,
+        format: Format::Json,
+        body: include_str!("fixtures/anthropic_thinking.json"),
+        expected: Expected {
+            content: "17 × 23 = 391.",
+            reasoning_content: Some("17 × 20 = 340 and 17 × 3 = 51, so 340 + 51 = 391."),
+            tool_calls: &[],
+            finish_reason: FinishReason::Stop,
+            total_tokens: Some(82),
+        },
+    },
+    Fixture {
+        name: "cohere_complete",
+        provider: Provider::Cohere,
+        format: Format::Json,
+        body: include_str!("fixtures/cohere_complete.json"),
+        expected: Expected {
+            content: "Bonjour le monde !",
+            reasoning_content: None,
+            tool_calls: &[],
+            finish_reason: FinishReason::Stop,
+            total_tokens: Some(19),
+        },
+    },
+    Fixture {
+        name: "mistral_truncated",
+        provider: Provider::Mistral,
+        format: Format::Json,
+        body: include_str!("fixtures/mistral_truncated.json"),
+        expected: Expected {
+            content: "The three laws of thermodynamics are: first, energy cannot be",
+            reasoning_content: None,
+            tool_calls: &[],
+            finish_reason: FinishReason::Length,
+            total_tokens: Some(27),
+        },
+    },
+    Fixture {
+        name: "deepseek_reasoning_stream",
+        provider: Provider::Deepseek,
+        format: Format::Sse,
+        body: include_str!("fixtures/deepseek_reasoning_stream.sse"),
+        expected: Expected {
+            content: "9.9 is larger.",
+            reasoning_content: Some("9.11 has a smaller fractional part than 9.9."),
+            tool_calls: &[],
+            finish_reason: FinishReason::Stop,
+            total_tokens: Some(53),
+        },
+    },
+    Fixture {
+        name: "google_tool_call_stream",
+        provider: Provider::Google,
+        format: Format::Sse,
+        body: include_str!("fixtures/google_tool_call_stream.sse"),
+        expected: Expected {
+            content: "",
+            reasoning_content: None,
+            tool_calls: &[("call_0", "get_weather"), ("call_1", "get_time")],
+            finish_reason: FinishReason::Stop,
+            total_tokens: Some(81),
+        },
+    },
+    Fixture {
+        name: "ollama_thinking_stream",
+        provider: Provider::Ollama,
+        format: Format::Sse,
+        body: include_str!("fixtures/ollama_thinking_stream.sse"),
+        expected: Expected {
+            content: "Crisp leaves drift and fall,\namber light on quiet paths,\nthe year exhales slow.",
+            reasoning_content: Some("The user wants a haiku about autumn."),
+            tool_calls: &[],
+            finish_reason: FinishReason::Stop,
+            total_tokens: Some(79),
+        },
+    },
+];
+
+/// Why a fixture failed its check.
+#[derive(Debug)]
+pub enum Mismatch {
+    /// The body did not parse.
+    Parse(GatewayError),
+    /// The body parsed, but `field` differs from what was expected.
+    Field {
+        field: &'static str,
+        expected: String,
+        actual: String,
+    },
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Mismatch::Parse(e) => write!(f, "failed to parse: {e}"),
+            Mismatch::Field {
+                field,
+                expected,
+                actual,
+            } => write!(f, "{field}: expected {expected}, got {actual}"),
+        }
+    }
+}
+
+impl std::error::Error for Mismatch {}
+
+impl Fixture {
+    /// Parses the body as the client would.
+    pub fn parse(&self) -> Result<FoldedCompletion, GatewayError> {
+        parse(self.provider, self.format, self.body)
+    }
+
+    /// Parses the body and compares it with [`Fixture::expected`].
+    pub fn check(&self) -> Result<FoldedCompletion, Mismatch> {
+        let folded = self.parse().map_err(Mismatch::Parse)?;
+        let expected = &self.expected;
+        let message = &folded.message;
+        compare(
+            "content",
+            expected.content,
+            message.content.as_text().unwrap_or_default(),
+        )?;
+        compare(
+            "reasoning_content",
+            expected.reasoning_content,
+            message.reasoning_content.as_deref(),
+        )?;
+        let tool_calls: Vec<_> = message
+            .tool_calls
+            .iter()
+            .map(|call| (call.id.as_str(), call.function.name.as_str()))
+            .collect();
+        compare("tool_calls", expected.tool_calls, tool_calls.as_slice())?;
+        compare(
+            "finish_reason",
+            Some(expected.finish_reason.clone()),
+            folded.finish_reason.clone(),
+        )?;
+        compare(
+            "total_tokens",
+            expected.total_tokens,
+            folded.usage.as_ref().map(|usage| usage.total_tokens),
+        )?;
+        Ok(folded)
+    }
+
+    /// Like [`Fixture::check`], but panics with the fixture's name on a
+    /// mismatch. Meant for tests.
+    #[track_caller]
+    pub fn assert_conforms(&self) {
+        if let Err(e) = self.check() {
+            panic!("fixture `{}` ({}): {e}", self.name, self.provider);
+        }
+    }
+}
+
+fn compare<T: PartialEq + fmt::Debug>(
+    field: &'static str,
+    expected: T,
+    actual: T,
+) -> Result<(), Mismatch> {
+    if expected == actual {
+        return Ok(());
+    }
+    Err(Mismatch::Field {
+        field,
+        expected: format!("{expected:?}"),
+        actual: format!("{actual:?}"),
+    })
+}
+
+/// Parses a chat completion `body` from `provider` the way the client does,
+/// folding a stream into its first choice.
+pub fn parse(
+    provider: Provider,
+    format: Format,
+    body: &str,
+) -> Result<FoldedCompletion, GatewayError> {
+    match format {
+        Format::Json => normalize::parse::<CreateChatCompletionResponse>(provider, body.as_bytes())
+            .map(FoldedCompletion::from),
+        Format::Sse => {
+            let mut decoder = SseDecoder::new();
+            decoder.push(body.as_bytes());
+            let mut chunks = Vec::new();
+            while let Some(mut event) = decoder.next_event() {
+                if event.data == "[DONE]" {
+                    break;
+                }
+                normalize::event(provider, &mut event);
+                chunks.push(event.json::<CreateChatCompletionStreamResponse>());
+            }
+            let (folded, error) = fold_until_error(stream::iter(chunks))
+                .now_or_never()
+                .expect("folding a ready stream completes immediately");
+            match error {
+                Some(e) => Err(e),
+                None => Ok(folded),
+            }
+        }
+    }
+}
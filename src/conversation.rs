@@ -0,0 +1,342 @@
+//! Multi-turn chat history.
+
+use crate::{
+    ContentPart, CreateChatCompletionResponse, GatewayError, ImageContentPart, InferenceGatewayAPI,
+    Message, MessageRole, ModelRef, Provider, TextContentPart, ToolCallId, tokens,
+};
+
+/// Instruction given to the model that summarizes older turns.
+const SUMMARY_PROMPT: &str = "Summarize the conversation below for the assistant that will \
+     continue it. Keep names, facts, decisions, open questions and what each numbered image \
+     showed. Reply with the summary only.";
+
+/// Prefix of the message that replaces summarized turns.
+const SUMMARY_PREFIX: &str = "Summary of the earlier conversation:";
+
+/// A multi-turn chat history, kept verbatim so every earlier image stays in
+/// the context of later turns.
+///
+/// Images added with [`Conversation::user_with_images`] are labelled
+/// `Image 1:`, `Image 2:`, ... numbered across the whole conversation, so a
+/// later turn can ask about "image 2" and the model sees the label next to
+/// the right image.
+///
+/// With a [`SummaryPolicy`], [`Conversation::compact`] keeps long chats
+/// within context limits by replacing older turns with a summary; with a
+/// token budget, [`Conversation::truncate`] drops them instead.
+/// [`Conversation::send`] does both before each call.
+///
+/// ```
+/// use inference_gateway_sdk::Conversation;
+///
+/// let mut conversation = Conversation::new().with_system("You compare charts.");
+/// conversation.user_with_images("What does this show?", ["https://example.com/q1.png"]);
+/// // ... push the model's reply with `push_response` ...
+/// conversation.user_with_images(
+///     "How does it differ from image 1?",
+///     ["https://example.com/q2.png"],
+/// );
+/// assert_eq!(conversation.images().count(), 2);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Conversation {
+    messages: Vec<Message>,
+    /// Images summarized away, so numbering carries on after them.
+    summarized_images: usize,
+    summary_policy: Option<SummaryPolicy>,
+    token_budget: Option<u64>,
+}
+
+/// When and with which model a [`Conversation`] summarizes its older turns.
+///
+/// ```
+/// use inference_gateway_sdk::{Conversation, Provider, SummaryPolicy};
+///
+/// let conversation = Conversation::new().with_summary_policy(
+///     SummaryPolicy::new(Provider::Groq, "llama-3.1-8b-instant", 6_000).with_keep_recent(6),
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct SummaryPolicy {
+    provider: Provider,
+    model: ModelRef,
+    threshold_tokens: u64,
+    keep_recent: usize,
+}
+
+impl SummaryPolicy {
+    /// Summarizes with `model` once the history exceeds `threshold_tokens`,
+    /// keeping the last four messages verbatim.
+    pub fn new(provider: Provider, model: impl Into<ModelRef>, threshold_tokens: u64) -> Self {
+        Self {
+            provider,
+            model: model.into(),
+            threshold_tokens,
+            keep_recent: 4,
+        }
+    }
+
+    /// Sets how many of the latest messages are never summarized.
+    pub fn with_keep_recent(mut self, messages: usize) -> Self {
+        self.keep_recent = messages;
+        self
+    }
+}
+
+impl Conversation {
+    /// Creates an empty conversation.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts the conversation with a system message.
+    pub fn with_system(mut self, text: impl Into<String>) -> Self {
+        self.messages.push(Message::system(text));
+        self
+    }
+
+    /// Opts in to summarizing older turns with [`Conversation::compact`].
+    pub fn with_summary_policy(mut self, policy: SummaryPolicy) -> Self {
+        self.summary_policy = Some(policy);
+        self
+    }
+
+    /// Keeps the history within about `tokens` prompt tokens by dropping
+    /// its oldest turns with [`Conversation::truncate`].
+    pub fn with_token_budget(mut self, tokens: u64) -> Self {
+        self.token_budget = Some(tokens);
+        self
+    }
+
+    /// Appends any message, such as a tool result.
+    pub fn push(&mut self, message: Message) -> &mut Self {
+        self.messages.push(message);
+        self
+    }
+
+    /// Appends a user message.
+    pub fn user(&mut self, text: impl Into<String>) -> &mut Self {
+        self.push(Message::user(text))
+    }
+
+    /// Appends an assistant message.
+    pub fn assistant(&mut self, text: impl Into<String>) -> &mut Self {
+        self.push(Message::assistant(text))
+    }
+
+    /// Appends the result of the tool call `tool_call_id`.
+    pub fn tool(
+        &mut self,
+        tool_call_id: impl Into<ToolCallId>,
+        content: impl Into<String>,
+    ) -> &mut Self {
+        self.push(Message::tool(tool_call_id, content))
+    }
+
+    /// Appends a user message from the participant `name`, for prompts with
+    /// several users.
+    pub fn user_named(&mut self, name: impl Into<String>, text: impl Into<String>) -> &mut Self {
+        self.push(Message::user(text).with_name(name))
+    }
+
+    /// Appends a user message with `text` followed by `images`, each
+    /// preceded by its `Image N:` label.
+    pub fn user_with_images<I>(&mut self, text: impl Into<String>, images: I) -> &mut Self
+    where
+        I: IntoIterator,
+        I::Item: Into<ImageContentPart>,
+    {
+        let mut number = self.summarized_images + self.images().count();
+        let mut parts = vec![TextContentPart::new(text).into()];
+        for image in images {
+            number += 1;
+            parts.push(TextContentPart::new(format!("Image {number}:")).into());
+            parts.push(ContentPart::from(image.into()));
+        }
+        self.push(Message::new(MessageRole::User, parts))
+    }
+
+    /// Appends the first choice of `response` as the assistant's turn.
+    pub fn push_response(&mut self, response: &CreateChatCompletionResponse) -> &mut Self {
+        if let Some(choice) = response.choices.first() {
+            self.messages.push(choice.message.clone());
+        }
+        self
+    }
+
+    /// Every image still in the conversation with its number, in the order
+    /// they were attached.
+    pub fn images(&self) -> impl Iterator<Item = (usize, &ImageContentPart)> {
+        self.messages
+            .iter()
+            .flat_map(Message::images)
+            .enumerate()
+            .map(|(index, image)| (self.summarized_images + index + 1, image))
+    }
+
+    /// Summarizes older turns if the history exceeds the
+    /// [`SummaryPolicy`]'s threshold, returning whether it did.
+    ///
+    /// Leading system messages and the most recent turns are kept; the
+    /// turns between them are replaced by one system message holding a
+    /// summary written by the policy's model. Without a policy, or if the
+    /// model returns no choices or a blank summary, the history is left as
+    /// it was.
+    pub async fn compact(
+        &mut self,
+        client: &impl InferenceGatewayAPI,
+    ) -> Result<bool, GatewayError> {
+        let Some(policy) = &self.summary_policy else {
+            return Ok(false);
+        };
+        if tokens::estimate(&self.messages) <= policy.threshold_tokens {
+            return Ok(false);
+        }
+        let start = self
+            .messages
+            .iter()
+            .take_while(|message| {
+                matches!(message.role, MessageRole::System | MessageRole::Developer)
+            })
+            .count();
+        let mut end = self.messages.len().saturating_sub(policy.keep_recent);
+        // Keep tool results with the assistant turn that requested them.
+        while end > start
+            && self
+                .messages
+                .get(end)
+                .is_some_and(|message| message.role == MessageRole::Tool)
+        {
+            end -= 1;
+        }
+        if end <= start {
+            return Ok(false);
+        }
+
+        let transcript = transcript(&self.messages[start..end]);
+        let response = client
+            .generate_content(
+                policy.provider,
+                policy.model.clone(),
+                vec![Message::system(SUMMARY_PROMPT), Message::user(transcript)],
+            )
+            .await?;
+        let Some(summary) = response
+            .choices
+            .first()
+            .map(|choice| tokens::text(&choice.message.content))
+            .filter(|summary| !summary.trim().is_empty())
+        else {
+            return Ok(false);
+        };
+
+        let summarized = self.messages.splice(
+            start..end,
+            [Message::system(format!("{SUMMARY_PREFIX}\n{summary}"))],
+        );
+        self.summarized_images += summarized
+            .map(|message| message.images().count())
+            .sum::<usize>();
+        Ok(true)
+    }
+
+    /// Drops the oldest turns until the history fits the
+    /// [token budget](Conversation::with_token_budget), returning how many
+    /// messages were dropped.
+    ///
+    /// A turn is a user message with the replies and tool results that
+    /// follow it, so tool results never lose the call they answer. Leading
+    /// system messages and the latest turn are always kept, even if they
+    /// alone exceed the budget. Without a budget this does nothing.
+    pub fn truncate(&mut self) -> usize {
+        let Some(budget) = self.token_budget else {
+            return 0;
+        };
+        let start = self
+            .messages
+            .iter()
+            .take_while(|message| {
+                matches!(message.role, MessageRole::System | MessageRole::Developer)
+            })
+            .count();
+        let mut end = start;
+        while tokens::estimate(&self.messages[..start]) + tokens::estimate(&self.messages[end..])
+            > budget
+        {
+            let Some(next) = self
+                .messages
+                .get(end + 1..)
+                .unwrap_or_default()
+                .iter()
+                .position(|message| message.role == MessageRole::User)
+            else {
+                break;
+            };
+            end += 1 + next;
+        }
+        let dropped = self.messages.drain(start..end);
+        self.summarized_images += dropped
+            .map(|message| message.images().count())
+            .sum::<usize>();
+        end - start
+    }
+
+    /// Compacts and truncates the history, sends it to `model`, and appends
+    /// the model's answer.
+    pub async fn send(
+        &mut self,
+        client: &impl InferenceGatewayAPI,
+        provider: Provider,
+        model: impl Into<ModelRef>,
+    ) -> Result<CreateChatCompletionResponse, GatewayError> {
+        let model = model.into();
+        self.compact(client).await?;
+        self.truncate();
+        let response = client
+            .generate_content(provider, model, self.messages.clone())
+            .await?;
+        self.push_response(&response);
+        Ok(response)
+    }
+
+    /// The messages to send, oldest first.
+    pub fn messages(&self) -> &[Message] {
+        &self.messages
+    }
+
+    /// Consumes the conversation, returning its messages.
+    pub fn into_messages(self) -> Vec<Message> {
+        self.messages
+    }
+}
+
+/// `messages` as plain text for the summarizing model. Images appear by
+/// their `Image N:` labels, and named participants as `role (name)`.
+fn transcript(messages: &[Message]) -> String {
+    messages
+        .iter()
+        .map(|message| {
+            let text = tokens::text(&message.content);
+            match &message.name {
+                Some(name) => format!("{} ({name}): {text}", message.role),
+                None => format!("{}: {text}", message.role),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+impl From<Vec<Message>> for Conversation {
+    /// Resumes a conversation from its stored messages.
+    fn from(messages: Vec<Message>) -> Self {
+        let mut conversation = Self::new();
+        conversation.messages = messages;
+        conversation
+    }
+}
+
+impl From<Conversation> for Vec<Message> {
+    fn from(conversation: Conversation) -> Self {
+        conversation.messages
+    }
+}
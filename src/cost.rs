@@ -0,0 +1,301 @@
+//! Estimating what requests cost from their token usage.
+//!
+//! A [`PricingTable`] holds per-token prices by provider and model, filled
+//! from the gateway's model list or set by hand, and turns a response's
+//! [`CompletionUsage`] into a [`Cost`]. A [`CostTracker`] adds those up
+//! for a session.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+
+use crate::{CompletionUsage, CreateChatCompletionResponse, ListModelsResponse, Pricing, Provider};
+
+/// What a model charges per token.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ModelPrice {
+    /// Price per prompt token.
+    pub input_per_token: f64,
+    /// Price per completion token.
+    pub output_per_token: f64,
+    /// Price per prompt token read from the provider's cache, if it differs
+    /// from `input_per_token`.
+    pub cache_read_per_token: Option<f64>,
+    /// The currency of the prices, such as `USD`.
+    pub currency: String,
+}
+
+impl ModelPrice {
+    /// A price of `input` and `output` per million tokens, in US dollars,
+    /// as providers usually list them.
+    pub fn per_million(input: f64, output: f64) -> Self {
+        Self {
+            input_per_token: input / 1_000_000.0,
+            output_per_token: output / 1_000_000.0,
+            cache_read_per_token: None,
+            currency: "USD".to_string(),
+        }
+    }
+
+    /// Sets the price per cached prompt token, per million tokens.
+    pub fn with_cache_read_per_million(mut self, price: f64) -> Self {
+        self.cache_read_per_token = Some(price / 1_000_000.0);
+        self
+    }
+}
+
+impl TryFrom<&Pricing> for ModelPrice {
+    type Error = std::num::ParseFloatError;
+
+    /// Parses the decimal prices the gateway reports.
+    fn try_from(pricing: &Pricing) -> Result<Self, Self::Error> {
+        Ok(Self {
+            input_per_token: pricing.input_per_token.parse()?,
+            output_per_token: pricing.output_per_token.parse()?,
+            cache_read_per_token: pricing
+                .cache_read_per_token
+                .as_deref()
+                .map(str::parse)
+                .transpose()?,
+            currency: pricing.currency.clone(),
+        })
+    }
+}
+
+/// The estimated cost of one or more requests.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cost {
+    /// What the prompt tokens cost.
+    pub input: f64,
+    /// What the completion tokens cost.
+    pub output: f64,
+    /// The currency of both amounts.
+    pub currency: String,
+}
+
+impl Cost {
+    /// The input and output costs together.
+    pub fn total(&self) -> f64 {
+        self.input + self.output
+    }
+}
+
+/// Per-token prices by provider and model.
+///
+/// Clones share their prices, so a table can be updated at runtime, such as
+/// from a periodic model listing, while requests are priced with it.
+///
+/// ```
+/// use inference_gateway_sdk::{CompletionUsage, ModelPrice, PricingTable, Provider};
+///
+/// let table = PricingTable::new().with_price(
+///     Provider::Openai,
+///     "gpt-4o",
+///     ModelPrice::per_million(2.5, 10.0),
+/// );
+/// let usage = CompletionUsage {
+///     prompt_tokens: 1_000,
+///     completion_tokens: 500,
+///     total_tokens: 1_500,
+///     prompt_tokens_details: None,
+///     completion_tokens_details: None,
+/// };
+/// let cost = table.estimate_cost(&usage, Provider::Openai, "gpt-4o").unwrap();
+/// assert!((cost.total() - 0.0075).abs() < 1e-9);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct PricingTable {
+    prices: Arc<RwLock<HashMap<(Provider, String), ModelPrice>>>,
+}
+
+impl PricingTable {
+    /// An empty table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A table with the prices of `models`, listed with the `"pricing"`
+    /// include. Models without a price, or with one that doesn't parse, are
+    /// left out.
+    pub fn from_models(models: &ListModelsResponse) -> Self {
+        let table = Self::new();
+        table.update_from_models(models);
+        table
+    }
+
+    /// Adds `price` for `model` served by `provider`.
+    pub fn with_price(self, provider: Provider, model: &str, price: ModelPrice) -> Self {
+        self.set_price(provider, model, price);
+        self
+    }
+
+    /// Sets the price of `model` served by `provider`, replacing any it had.
+    pub fn set_price(&self, provider: Provider, model: &str, price: ModelPrice) {
+        let model = bare_model(provider, model).to_string();
+        self.prices
+            .write()
+            .unwrap()
+            .insert((provider, model), price);
+    }
+
+    /// Sets the prices of `models`, as [`PricingTable::from_models`] does.
+    /// Prices set for models not in the list are kept.
+    pub fn update_from_models(&self, models: &ListModelsResponse) {
+        for model in &models.data {
+            if let Some(price) = model
+                .pricing
+                .as_ref()
+                .and_then(|pricing| ModelPrice::try_from(pricing).ok())
+            {
+                self.set_price(model.served_by, &model.id, price);
+            }
+        }
+    }
+
+    /// The price of `model` served by `provider`, if the table has one.
+    pub fn price(&self, provider: Provider, model: &str) -> Option<ModelPrice> {
+        let model = bare_model(provider, model);
+        let prices = self.prices.read().unwrap();
+        prices.get(&(provider, model.to_string())).cloned()
+    }
+
+    /// What `usage` costs on `model` served by `provider`, or `None` if the
+    /// table has no price for it. Cached prompt tokens are charged at the
+    /// cache read price when the model has one.
+    pub fn estimate_cost(
+        &self,
+        usage: &CompletionUsage,
+        provider: Provider,
+        model: &str,
+    ) -> Option<Cost> {
+        let price = self.price(provider, model)?;
+        let prompt = usage.prompt_tokens.max(0) as f64;
+        let cached = usage
+            .prompt_tokens_details
+            .as_ref()
+            .map_or(0, |details| details.cached_tokens)
+            .clamp(0, usage.prompt_tokens.max(0)) as f64;
+        let input = match price.cache_read_per_token {
+            Some(cache_read) => (prompt - cached) * price.input_per_token + cached * cache_read,
+            None => prompt * price.input_per_token,
+        };
+        Some(Cost {
+            input,
+            output: usage.completion_tokens.max(0) as f64 * price.output_per_token,
+            currency: price.currency,
+        })
+    }
+}
+
+/// `model` without a leading `provider/`, as the gateway lists model ids.
+fn bare_model(provider: Provider, model: &str) -> &str {
+    model
+        .strip_prefix(provider.to_string().as_str())
+        .and_then(|model| model.strip_prefix('/'))
+        .unwrap_or(model)
+}
+
+/// Adds up the cost of requests, such as those of one user session.
+///
+/// Clones share their totals.
+///
+/// ```
+/// use inference_gateway_sdk::{CostTracker, ModelPrice, PricingTable, Provider};
+///
+/// let table = PricingTable::new().with_price(
+///     Provider::Openai,
+///     "gpt-4o",
+///     ModelPrice::per_million(2.5, 10.0),
+/// );
+/// let session = CostTracker::new(table);
+/// // After each response:
+/// // session.record_response(Provider::Openai, "gpt-4o", &response);
+/// println!("{:?}", session.totals());
+/// ```
+#[derive(Clone, Debug)]
+pub struct CostTracker {
+    table: PricingTable,
+    spent: Arc<Mutex<Spent>>,
+}
+
+#[derive(Debug, Default)]
+struct Spent {
+    by_model: HashMap<(Provider, String), Cost>,
+    unpriced: u64,
+}
+
+impl CostTracker {
+    /// A tracker with nothing spent, pricing requests with `table`.
+    pub fn new(table: PricingTable) -> Self {
+        Self {
+            table,
+            spent: Arc::default(),
+        }
+    }
+
+    /// Adds what `usage` cost on `model` served by `provider`, returning
+    /// that cost. A model the table has no price for is counted in
+    /// [`CostTracker::unpriced`] instead.
+    pub fn record(&self, provider: Provider, model: &str, usage: &CompletionUsage) -> Option<Cost> {
+        let cost = self.table.estimate_cost(usage, provider, model);
+        let mut spent = self.spent.lock().unwrap();
+        match &cost {
+            Some(cost) => {
+                let model = bare_model(provider, model).to_string();
+                let total = spent
+                    .by_model
+                    .entry((provider, model))
+                    .or_insert_with(|| Cost {
+                        input: 0.0,
+                        output: 0.0,
+                        currency: cost.currency.clone(),
+                    });
+                total.input += cost.input;
+                total.output += cost.output;
+            }
+            None => spent.unpriced += 1,
+        }
+        cost
+    }
+
+    /// Adds what `response` cost, as [`CostTracker::record`] does. A
+    /// response without usage counts as unpriced.
+    pub fn record_response(
+        &self,
+        provider: Provider,
+        model: &str,
+        response: &CreateChatCompletionResponse,
+    ) -> Option<Cost> {
+        match &response.usage {
+            Some(usage) => self.record(provider, model, usage),
+            None => {
+                self.spent.lock().unwrap().unpriced += 1;
+                None
+            }
+        }
+    }
+
+    /// What was spent on each provider and model.
+    pub fn by_model(&self) -> HashMap<(Provider, String), Cost> {
+        self.spent.lock().unwrap().by_model.clone()
+    }
+
+    /// What was spent in each currency.
+    pub fn totals(&self) -> HashMap<String, f64> {
+        let spent = self.spent.lock().unwrap();
+        let mut totals = HashMap::new();
+        for cost in spent.by_model.values() {
+            *totals.entry(cost.currency.clone()).or_default() += cost.total();
+        }
+        totals
+    }
+
+    /// How many recorded requests couldn't be priced.
+    pub fn unpriced(&self) -> u64 {
+        self.spent.lock().unwrap().unpriced
+    }
+
+    /// Forgets everything recorded so far.
+    pub fn reset(&self) {
+        *self.spent.lock().unwrap() = Spent::default();
+    }
+}
@@ -0,0 +1,142 @@
+//! JSON decoding that reports where and in what a body failed to parse.
+//!
+//! Gateway responses pass provider quirks straight through, so a bare serde
+//! message is rarely enough to tell what went wrong. Failures here become
+//! [`GatewayError::DeserializationError`] with the path of the offending
+//! field and a snippet of the body.
+
+use bytes::{Bytes, BytesMut};
+use serde::de::DeserializeOwned;
+
+use crate::{GatewayError, privacy};
+
+/// Longest snippet kept from a body, in characters.
+const MAX_SNIPPET_CHARS: usize = 512;
+
+/// Object keys whose values never appear in a snippet.
+const REDACTED_KEYS: &[&str] = &[
+    "access_token",
+    "api_key",
+    "apikey",
+    "authorization",
+    "client_secret",
+    "password",
+    "refresh_token",
+    "secret",
+    "token",
+];
+
+/// Parses `body` as `T`.
+pub(crate) fn from_str<T: DeserializeOwned>(body: &str) -> Result<T, GatewayError> {
+    let deserializer = &mut serde_json::Deserializer::from_str(body);
+    serde_path_to_error::deserialize(deserializer).map_err(|e| error(e, body))
+}
+
+/// Parses `value`, which was read from `body`, as `T`. Errors quote
+/// `body`.
+pub(crate) fn from_value<T: DeserializeOwned>(
+    value: serde_json::Value,
+    body: &[u8],
+) -> Result<T, GatewayError> {
+    serde_path_to_error::deserialize(value).map_err(|e| error(e, &String::from_utf8_lossy(body)))
+}
+
+/// Reads `response`'s body, failing once it exceeds `limit` bytes, and
+/// parses it as `T`.
+pub(crate) async fn from_response<T: DeserializeOwned>(
+    response: reqwest::Response,
+    limit: Option<usize>,
+) -> Result<T, GatewayError> {
+    from_slice(&read_body(response, limit).await?)
+}
+
+/// Parses the raw `body` as `T`.
+pub(crate) fn from_slice<T: DeserializeOwned>(body: &[u8]) -> Result<T, GatewayError> {
+    let deserializer = &mut serde_json::Deserializer::from_slice(body);
+    serde_path_to_error::deserialize(deserializer)
+        .map_err(|e| error(e, &String::from_utf8_lossy(body)))
+}
+
+/// Reads `response`'s body, failing with
+/// [`GatewayError::ResponseTooLarge`] as soon as it is known to exceed
+/// `limit` bytes rather than buffering all of it.
+pub(crate) async fn read_body(
+    mut response: reqwest::Response,
+    limit: Option<usize>,
+) -> Result<Bytes, GatewayError> {
+    let Some(limit) = limit else {
+        return Ok(response.bytes().await?);
+    };
+    let too_large = || GatewayError::ResponseTooLarge { limit };
+    if response
+        .content_length()
+        .is_some_and(|length| length > limit as u64)
+    {
+        return Err(too_large());
+    }
+    let mut body = BytesMut::new();
+    while let Some(chunk) = response.chunk().await? {
+        if body.len() + chunk.len() > limit {
+            return Err(too_large());
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body.freeze())
+}
+
+fn error(error: serde_path_to_error::Error<serde_json::Error>, body: &str) -> GatewayError {
+    let path = error.path().to_string();
+    GatewayError::DeserializationError {
+        source: error.into_inner(),
+        path,
+        snippet: snippet(body),
+    }
+}
+
+/// `body` shortened to [`MAX_SNIPPET_CHARS`], with the values of
+/// [`REDACTED_KEYS`] replaced. A body that isn't valid JSON can't be
+/// redacted, so only its length and what it looks like are kept, as is
+/// only the length and hash of any body in privacy mode.
+fn snippet(body: &str) -> String {
+    if privacy::is_enabled() {
+        return privacy::summary(body);
+    }
+    let redacted = match serde_json::from_str::<serde_json::Value>(body) {
+        Ok(mut value) => {
+            redact(&mut value);
+            value.to_string()
+        }
+        Err(_) => return elided(body),
+    };
+    match redacted.char_indices().nth(MAX_SNIPPET_CHARS) {
+        Some((end, _)) => format!("{}...", &redacted[..end]),
+        None => redacted,
+    }
+}
+
+/// A description of a body that isn't valid JSON, without its contents.
+fn elided(body: &str) -> String {
+    let kind = match body.trim_start().chars().next() {
+        None => "empty body",
+        Some('{' | '[') => "truncated or malformed JSON",
+        Some('<') => "HTML or XML",
+        Some(_) => "text",
+    };
+    format!("<{} bytes of {kind}>", body.len())
+}
+
+fn redact(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map {
+                if REDACTED_KEYS.contains(&key.to_ascii_lowercase().as_str()) {
+                    *value = "[redacted]".into();
+                } else {
+                    redact(value);
+                }
+            }
+        }
+        serde_json::Value::Array(values) => values.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
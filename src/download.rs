@@ -0,0 +1,219 @@
+//! Resumable downloads of large artifacts.
+//!
+//! Batch output files and generated media can run to gigabytes, so they are
+//! streamed to an [`AsyncWrite`] as they arrive rather than buffered. When
+//! the connection drops mid-body, the download picks up where it left off
+//! with a `Range` request; `If-Range` makes sure the rest comes from the
+//! same version of the artifact. The bytes are hashed on the way through
+//! and checked against the expected SHA-256, if one is given.
+
+use std::path::Path;
+
+use futures_util::StreamExt;
+use reqwest::header::{CONTENT_RANGE, ETAG, IF_RANGE, RANGE};
+use reqwest::{Method, StatusCode};
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::{GatewayError, InferenceGatewayClient, Priority, map_error_status};
+
+/// An artifact to download: where it is served, and what it should hash to.
+///
+/// ```
+/// use inference_gateway_sdk::Artifact;
+///
+/// let artifact = Artifact::new("files/file-abc123/content")
+///     .with_sha256("9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08");
+/// ```
+#[derive(Clone, Debug)]
+pub struct Artifact {
+    location: String,
+    sha256: Option<String>,
+    max_resumes: u32,
+}
+
+impl Artifact {
+    /// The artifact served at `location`, absolute or relative to the
+    /// client's API URL. Interrupted downloads resume up to 3 times.
+    pub fn new(location: impl Into<String>) -> Self {
+        Self {
+            location: location.into(),
+            sha256: None,
+            max_resumes: 3,
+        }
+    }
+
+    /// Fails the download with [`GatewayError::ChecksumMismatch`] unless
+    /// the whole artifact hashes to `sha256`, given in hex.
+    pub fn with_sha256(mut self, sha256: impl Into<String>) -> Self {
+        self.sha256 = Some(sha256.into());
+        self
+    }
+
+    /// Resumes an interrupted download at most `resumes` times before
+    /// failing with [`GatewayError::StreamInterrupted`].
+    pub fn with_max_resumes(mut self, resumes: u32) -> Self {
+        self.max_resumes = resumes;
+        self
+    }
+
+    /// The location the artifact was created with.
+    pub fn location(&self) -> &str {
+        &self.location
+    }
+}
+
+/// A finished download.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DownloadSummary {
+    /// The artifact's size, including bytes already on disk when resuming
+    /// a file.
+    pub bytes: u64,
+    /// The artifact's SHA-256, in lowercase hex.
+    pub sha256: String,
+    /// How many times the download was resumed after an interruption.
+    pub resumes: u32,
+}
+
+/// Downloads `artifact` to `writer`, after the `offset` bytes already
+/// written, which `hasher` has seen.
+pub(crate) async fn download<W: AsyncWrite + Unpin + ?Sized>(
+    client: &InferenceGatewayClient,
+    artifact: &Artifact,
+    writer: &mut W,
+    mut offset: u64,
+    mut hasher: Sha256,
+) -> Result<DownloadSummary, GatewayError> {
+    let url = client.resolve_url(&artifact.location)?;
+    let mut etag = None;
+    let mut resumes = 0;
+    loop {
+        let mut request = client.request(Method::GET, &url);
+        if offset > 0 {
+            request = request.header(RANGE, format!("bytes={offset}-"));
+            if let Some(etag) = &etag {
+                request = request.header(IF_RANGE, etag);
+            }
+        }
+        let response = client.send(request, Priority::Normal, true).await?;
+        // Bytes of the body that are already written, when a server
+        // ignores the range and sends the whole artifact again.
+        let mut skip = 0;
+        match response.status() {
+            StatusCode::PARTIAL_CONTENT => {
+                let start = response
+                    .headers()
+                    .get(CONTENT_RANGE)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(range_start);
+                if start != Some(offset) {
+                    return Err(GatewayError::ArtifactChanged {
+                        location: artifact.location.clone(),
+                    });
+                }
+            }
+            StatusCode::OK => {
+                let current = response.headers().get(ETAG);
+                if offset > 0 && etag.is_some() && current != etag.as_ref() {
+                    return Err(GatewayError::ArtifactChanged {
+                        location: artifact.location.clone(),
+                    });
+                }
+                skip = offset;
+            }
+            // Everything is already written.
+            StatusCode::RANGE_NOT_SATISFIABLE if offset > 0 => break,
+            status => return Err(map_error_status(status, response).await),
+        }
+        if etag.is_none() {
+            etag = response.headers().get(ETAG).cloned();
+        }
+
+        let mut body = response.bytes_stream();
+        let interrupted = loop {
+            match body.next().await {
+                Some(Ok(chunk)) => {
+                    let skipped = skip.min(chunk.len() as u64);
+                    skip -= skipped;
+                    let chunk = &chunk[skipped as usize..];
+                    writer.write_all(chunk).await.map_err(io_error)?;
+                    hasher.update(chunk);
+                    offset += chunk.len() as u64;
+                }
+                Some(Err(e)) => break Some(e),
+                None => break None,
+            }
+        };
+        match interrupted {
+            None => break,
+            Some(_) if resumes < artifact.max_resumes => resumes += 1,
+            Some(source) => {
+                return Err(GatewayError::StreamInterrupted {
+                    bytes_received: offset,
+                    last_event_id: None,
+                    source,
+                });
+            }
+        }
+    }
+    writer.flush().await.map_err(io_error)?;
+
+    let sha256: String = hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    if let Some(expected) = &artifact.sha256
+        && !expected.eq_ignore_ascii_case(&sha256)
+    {
+        return Err(GatewayError::ChecksumMismatch {
+            expected: expected.clone(),
+            actual: sha256,
+        });
+    }
+    Ok(DownloadSummary {
+        bytes: offset,
+        sha256,
+        resumes,
+    })
+}
+
+/// Downloads `artifact` to the file at `path`, continuing after whatever a
+/// previous attempt left in it.
+pub(crate) async fn download_to_file(
+    client: &InferenceGatewayClient,
+    artifact: &Artifact,
+    path: &Path,
+) -> Result<DownloadSummary, GatewayError> {
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .read(true)
+        .append(true)
+        .open(path)
+        .await
+        .map_err(io_error)?;
+    // The checksum covers the whole artifact, so hash what is on disk.
+    let mut hasher = Sha256::new();
+    let mut offset = 0;
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer).await.map_err(io_error)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        offset += read as u64;
+    }
+    download(client, artifact, &mut file, offset, hasher).await
+}
+
+/// The first byte position of a `Content-Range` such as
+/// `bytes 100-199/200`.
+fn range_start(content_range: &str) -> Option<u64> {
+    let range = content_range.strip_prefix("bytes ")?;
+    range.split('-').next()?.trim().parse().ok()
+}
+
+fn io_error(e: std::io::Error) -> GatewayError {
+    GatewayError::Other(Box::new(e))
+}
@@ -0,0 +1,302 @@
+//! Embeddings through the provider proxy.
+//!
+//! The gateway spec has no embeddings route, so these types follow the
+//! OpenAI-compatible `/embeddings` API that providers expose behind the
+//! proxy.
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::{GatewayError, ModelRef};
+
+/// How the provider encodes vectors in its response.
+///
+/// Either way, [`Embedding::embedding`] holds the decoded floats; `Base64`
+/// only shrinks the response body, which adds up on large batches.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EncodingFormat {
+    /// JSON arrays of numbers.
+    #[default]
+    Float,
+    /// Base64 of the little-endian `f32` bytes.
+    Base64,
+}
+
+/// A request for [`InferenceGatewayAPI::create_embeddings`](crate::InferenceGatewayAPI::create_embeddings).
+///
+/// ```
+/// use inference_gateway_sdk::{EmbeddingRequest, EncodingFormat};
+///
+/// let request = EmbeddingRequest::new("text-embedding-3-small", ["first", "second"])
+///     .with_dimensions(256)
+///     .with_encoding_format(EncodingFormat::Base64);
+/// ```
+#[derive(Clone, Debug, Serialize)]
+pub struct EmbeddingRequest {
+    model: String,
+    input: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dimensions: Option<u32>,
+    encoding_format: EncodingFormat,
+}
+
+impl EmbeddingRequest {
+    /// Embeds each of `input` with `model`. The request goes straight to the
+    /// provider, so only the model's own id is sent.
+    pub fn new<I>(model: impl Into<ModelRef>, input: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        Self {
+            model: model.into().model().to_string(),
+            input: input.into_iter().map(Into::into).collect(),
+            dimensions: None,
+            encoding_format: EncodingFormat::default(),
+        }
+    }
+
+    pub(crate) fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// The number of inputs to embed.
+    pub fn len(&self) -> usize {
+        self.input.len()
+    }
+
+    /// Whether there are no inputs.
+    pub fn is_empty(&self) -> bool {
+        self.input.is_empty()
+    }
+
+    /// Asks for vectors shortened to `dimensions`, for models that support
+    /// it.
+    pub fn with_dimensions(mut self, dimensions: u32) -> Self {
+        self.dimensions = Some(dimensions);
+        self
+    }
+
+    /// Sets how vectors are encoded on the wire.
+    pub fn with_encoding_format(mut self, encoding_format: EncodingFormat) -> Self {
+        self.encoding_format = encoding_format;
+        self
+    }
+}
+
+/// How [`InferenceGatewayClient::embed_stream`](crate::InferenceGatewayClient::embed_stream)
+/// batches inputs and how many batches it keeps in flight.
+///
+/// ```
+/// use inference_gateway_sdk::EmbeddingPipeline;
+///
+/// let pipeline = EmbeddingPipeline::new("text-embedding-3-small")
+///     .with_batch_size(256)
+///     .with_concurrency(8);
+/// ```
+#[derive(Clone, Debug)]
+pub struct EmbeddingPipeline {
+    model: ModelRef,
+    batch_size: usize,
+    concurrency: usize,
+    dimensions: Option<u32>,
+    encoding_format: EncodingFormat,
+}
+
+impl EmbeddingPipeline {
+    /// Embeds with `model`, 64 inputs per request and 4 requests at a time.
+    pub fn new(model: impl Into<ModelRef>) -> Self {
+        Self {
+            model: model.into(),
+            batch_size: 64,
+            concurrency: 4,
+            dimensions: None,
+            encoding_format: EncodingFormat::default(),
+        }
+    }
+
+    /// Sends up to `batch_size` inputs per request, at least one.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Keeps up to `requests` requests in flight, at least one.
+    pub fn with_concurrency(mut self, requests: usize) -> Self {
+        self.concurrency = requests.max(1);
+        self
+    }
+
+    /// Asks for vectors shortened to `dimensions`, for models that support
+    /// it.
+    pub fn with_dimensions(mut self, dimensions: u32) -> Self {
+        self.dimensions = Some(dimensions);
+        self
+    }
+
+    /// Sets how vectors are encoded on the wire.
+    pub fn with_encoding_format(mut self, encoding_format: EncodingFormat) -> Self {
+        self.encoding_format = encoding_format;
+        self
+    }
+
+    pub(crate) fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    pub(crate) fn concurrency(&self) -> usize {
+        self.concurrency
+    }
+
+    /// The request embedding one batch.
+    pub(crate) fn request(&self, input: Vec<String>) -> EmbeddingRequest {
+        let mut request = EmbeddingRequest::new(self.model.clone(), input)
+            .with_encoding_format(self.encoding_format);
+        request.dimensions = self.dimensions;
+        request
+    }
+}
+
+/// One input of an embedding pipeline with its vector.
+#[derive(Clone, Debug)]
+pub struct EmbeddedText {
+    /// Position of the input in the pipeline's input stream.
+    pub index: u64,
+    /// The input text.
+    pub text: String,
+    /// The input's vector.
+    pub embedding: Vec<f32>,
+}
+
+/// The response to an [`EmbeddingRequest`].
+///
+/// [`InferenceGatewayAPI::create_embeddings`](crate::InferenceGatewayAPI::create_embeddings)
+/// orders `data` by [`Embedding::index`] and checks there is exactly one
+/// vector per input, so `data[i]` always belongs to the `i`th input.
+#[derive(Clone, Debug, Deserialize)]
+pub struct EmbeddingResponse {
+    pub data: Vec<Embedding>,
+    pub model: String,
+    /// Tokens consumed, when the provider reports them.
+    #[serde(default)]
+    pub usage: Option<EmbeddingUsage>,
+}
+
+/// Token usage of an embeddings request.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+pub struct EmbeddingUsage {
+    pub prompt_tokens: i64,
+    pub total_tokens: i64,
+}
+
+impl EmbeddingResponse {
+    /// The vectors, in the order of the request's inputs.
+    pub fn into_vectors(self) -> Vec<Vec<f32>> {
+        self.data
+            .into_iter()
+            .map(|embedding| embedding.embedding)
+            .collect()
+    }
+
+    /// Puts `data` in input order, failing with
+    /// [`GatewayError::EmbeddingsMismatch`] unless it holds one vector for
+    /// each of `inputs`.
+    pub(crate) fn align(mut self, inputs: usize) -> Result<Self, GatewayError> {
+        self.data.sort_by_key(|embedding| embedding.index);
+        let aligned = self.data.len() == inputs
+            && self
+                .data
+                .iter()
+                .enumerate()
+                .all(|(position, embedding)| embedding.index == position);
+        if !aligned {
+            return Err(GatewayError::EmbeddingsMismatch {
+                expected: inputs,
+                indices: self.data.iter().map(|e| e.index).collect(),
+            });
+        }
+        Ok(self)
+    }
+}
+
+/// The vector for one input.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Embedding {
+    /// Position of the input this vector belongs to.
+    pub index: usize,
+    #[serde(deserialize_with = "deserialize_vector")]
+    pub embedding: Vec<f32>,
+}
+
+fn deserialize_vector<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<f32>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Wire {
+        Float(Vec<f32>),
+        Base64(String),
+    }
+    match Wire::deserialize(deserializer)? {
+        Wire::Float(vector) => Ok(vector),
+        Wire::Base64(encoded) => {
+            let bytes = STANDARD.decode(encoded).map_err(serde::de::Error::custom)?;
+            if bytes.len() % 4 != 0 {
+                return Err(serde::de::Error::custom(
+                    "base64 embedding is not a whole number of f32s",
+                ));
+            }
+            Ok(bytes
+                .chunks_exact(4)
+                .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+                .collect())
+        }
+    }
+}
+
+#[cfg(feature = "quantize")]
+impl Embedding {
+    /// The vector at half precision, halving its memory.
+    pub fn to_f16(&self) -> Vec<half::f16> {
+        self.embedding
+            .iter()
+            .map(|&x| half::f16::from_f32(x))
+            .collect()
+    }
+
+    /// The vector quantized to `i8`, a quarter of its memory.
+    pub fn to_i8(&self) -> QuantizedEmbedding {
+        QuantizedEmbedding::quantize(&self.embedding)
+    }
+}
+
+/// A vector symmetrically quantized to `i8`: each component is approximately
+/// `values[i] as f32 * scale`.
+#[cfg(feature = "quantize")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct QuantizedEmbedding {
+    pub values: Vec<i8>,
+    pub scale: f32,
+}
+
+#[cfg(feature = "quantize")]
+impl QuantizedEmbedding {
+    /// Quantizes `vector`, scaling its largest magnitude to `127`.
+    pub fn quantize(vector: &[f32]) -> Self {
+        let max = vector.iter().fold(0f32, |max, x| max.max(x.abs()));
+        let scale = if max > 0.0 { max / 127.0 } else { 1.0 };
+        let values = vector
+            .iter()
+            .map(|x| (x / scale).round().clamp(-127.0, 127.0) as i8)
+            .collect();
+        Self { values, scale }
+    }
+
+    /// The approximate original vector.
+    pub fn dequantize(&self) -> Vec<f32> {
+        self.values
+            .iter()
+            .map(|&value| f32::from(value) * self.scale)
+            .collect()
+    }
+}
@@ -0,0 +1,204 @@
+//! A/B experiments across models.
+//!
+//! An [`Experiment`] splits traffic between weighted variants by hashing a
+//! caller-chosen key, such as a user or session id, so the same key always
+//! lands in the same arm. Each call through
+//! [`InferenceGatewayClient::generate_in_experiment`](crate::InferenceGatewayClient::generate_in_experiment)
+//! is tagged with its variant and counted towards that arm's [`ArmStats`].
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::{CreateChatCompletionResponse, FinishReason, GatewayError, ModelRef, Provider};
+
+/// One arm of an [`Experiment`].
+#[derive(Clone, Debug)]
+pub struct Variant {
+    name: String,
+    provider: Provider,
+    model: ModelRef,
+    weight: u32,
+}
+
+impl Variant {
+    /// The variant's name, as given to [`Experiment::with_variant`].
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The provider the variant's requests go to.
+    pub fn provider(&self) -> Provider {
+        self.provider
+    }
+
+    /// The model the variant's requests go to.
+    pub fn model(&self) -> &ModelRef {
+        &self.model
+    }
+}
+
+/// Aggregated results of one variant's calls.
+#[derive(Clone, Debug, Default)]
+pub struct ArmStats {
+    /// Calls made, including failed ones.
+    pub requests: u64,
+    /// Calls that failed.
+    pub errors: u64,
+    /// Summed latency of all calls.
+    pub total_latency: Duration,
+    /// Summed prompt tokens of the calls that reported usage.
+    pub prompt_tokens: i64,
+    /// Summed completion tokens of the calls that reported usage.
+    pub completion_tokens: i64,
+    /// How many successful calls stopped for each reason.
+    pub finish_reasons: HashMap<FinishReason, u64>,
+}
+
+impl ArmStats {
+    /// Mean latency per call, or `None` before the first one.
+    pub fn mean_latency(&self) -> Option<Duration> {
+        let requests = u32::try_from(self.requests).ok().filter(|n| *n > 0)?;
+        Some(self.total_latency / requests)
+    }
+
+    fn record(
+        &mut self,
+        latency: Duration,
+        result: &Result<CreateChatCompletionResponse, GatewayError>,
+    ) {
+        self.requests += 1;
+        self.total_latency += latency;
+        match result {
+            Ok(response) => {
+                if let Some(usage) = &response.usage {
+                    self.prompt_tokens += usage.prompt_tokens;
+                    self.completion_tokens += usage.completion_tokens;
+                }
+                if let Some(choice) = response.choices.first() {
+                    *self
+                        .finish_reasons
+                        .entry(choice.finish_reason.clone())
+                        .or_default() += 1;
+                }
+            }
+            Err(_) => self.errors += 1,
+        }
+    }
+}
+
+/// A response from an experiment, tagged with the variant that produced it.
+#[derive(Clone, Debug)]
+pub struct ExperimentResponse {
+    /// The name of the variant the request was assigned to.
+    pub variant: String,
+    /// The variant's response.
+    pub response: CreateChatCompletionResponse,
+}
+
+/// Weighted model variants and the stats collected for each.
+///
+/// Clones share their stats, so an experiment can be handed to every task
+/// serving it.
+///
+/// ```
+/// use inference_gateway_sdk::{Experiment, Provider};
+///
+/// let experiment = Experiment::new("summary-model")
+///     .with_variant("control", Provider::Openai, "gpt-4o", 90)
+///     .with_variant("candidate", Provider::Anthropic, "claude-sonnet-5", 10);
+/// assert_eq!(
+///     experiment.assign("user-42").unwrap().name(),
+///     experiment.assign("user-42").unwrap().name(),
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct Experiment {
+    name: String,
+    variants: Vec<Variant>,
+    stats: Arc<Mutex<HashMap<String, ArmStats>>>,
+}
+
+impl Experiment {
+    /// An experiment without variants. `name` is mixed into assignment, so
+    /// different experiments split the same keys independently.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            variants: Vec::new(),
+            stats: Arc::default(),
+        }
+    }
+
+    /// Adds a variant sending `weight` parts of the traffic to `model`,
+    /// served by `provider`. Changing the variants or their weights moves
+    /// some keys to other arms.
+    pub fn with_variant(
+        mut self,
+        name: impl Into<String>,
+        provider: Provider,
+        model: impl Into<ModelRef>,
+        weight: u32,
+    ) -> Self {
+        self.variants.push(Variant {
+            name: name.into(),
+            provider,
+            model: model.into(),
+            weight,
+        });
+        self
+    }
+
+    /// The experiment's name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The variant `key` is assigned to, or `None` if no variant has any
+    /// weight. The same key gets the same variant in every process.
+    pub fn assign(&self, key: &str) -> Option<&Variant> {
+        let total: u64 = self.variants.iter().map(|v| u64::from(v.weight)).sum();
+        if total == 0 {
+            return None;
+        }
+        let mut bucket = fnv1a(&[self.name.as_bytes(), b"\0", key.as_bytes()]) % total;
+        self.variants.iter().find(|variant| {
+            let weight = u64::from(variant.weight);
+            if bucket < weight {
+                return true;
+            }
+            bucket -= weight;
+            false
+        })
+    }
+
+    /// The stats collected so far, by variant name.
+    pub fn stats(&self) -> HashMap<String, ArmStats> {
+        self.stats.lock().unwrap().clone()
+    }
+
+    pub(crate) fn record(
+        &self,
+        variant: &str,
+        latency: Duration,
+        result: &Result<CreateChatCompletionResponse, GatewayError>,
+    ) {
+        self.stats
+            .lock()
+            .unwrap()
+            .entry(variant.to_string())
+            .or_default()
+            .record(latency, result);
+    }
+}
+
+/// 64-bit FNV-1a over `parts`. Unlike the standard library's hashers, its
+/// output is fixed, so assignments survive toolchain upgrades.
+fn fnv1a(parts: &[&[u8]]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in parts.iter().flat_map(|part| part.iter()) {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash
+}
@@ -0,0 +1,37 @@
+use crate::generated::schemas::{Message, UrlCitation};
+
+impl UrlCitation {
+    /// The span of `text` this citation supports, or `None` if its indexes
+    /// don't fit `text`. Indexes count characters, not bytes.
+    pub fn cited_text<'a>(&self, text: &'a str) -> Option<&'a str> {
+        let start = usize::try_from(self.start_index).ok()?;
+        let end = usize::try_from(self.end_index).ok()?;
+        if start > end {
+            return None;
+        }
+        let mut offsets = text
+            .char_indices()
+            .map(|(offset, _)| offset)
+            .chain(std::iter::once(text.len()));
+        let start_offset = offsets.nth(start)?;
+        let end_offset = if end == start {
+            start_offset
+        } else {
+            offsets.nth(end - start - 1)?
+        };
+        Some(&text[start_offset..end_offset])
+    }
+}
+
+impl Message {
+    /// The URL citations among this message's `annotations`, each with the
+    /// span of the message text it supports. Citations that don't fit the
+    /// text are skipped.
+    pub fn citations(&self) -> impl Iterator<Item = (&UrlCitation, &str)> {
+        let text = self.content.as_text().unwrap_or_default();
+        self.annotations
+            .iter()
+            .filter_map(|annotation| annotation.url_citation.as_ref())
+            .filter_map(move |citation| Some((citation, citation.cited_text(text)?)))
+    }
+}
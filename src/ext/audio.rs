@@ -0,0 +1,24 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+
+use crate::generated::schemas::{
+    ChatCompletionMessageAudio, ChatCompletionStreamResponseDeltaAudio,
+};
+
+impl ChatCompletionMessageAudio {
+    /// Decodes the base64 `data` into the audio bytes.
+    pub fn decode(&self) -> Result<Vec<u8>, base64::DecodeError> {
+        STANDARD.decode(&self.data)
+    }
+}
+
+impl ChatCompletionStreamResponseDeltaAudio {
+    /// Decodes this fragment's `data` into audio bytes, which are empty if
+    /// it carries none. Fragments decode independently, so each can be
+    /// played as it arrives.
+    pub fn decode(&self) -> Result<Vec<u8>, base64::DecodeError> {
+        self.data
+            .as_deref()
+            .map_or(Ok(Vec::new()), |data| STANDARD.decode(data))
+    }
+}
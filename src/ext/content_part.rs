@@ -0,0 +1,110 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+
+use crate::attachment::DataUrl;
+use crate::generated::schemas::{
+    FileContentPart, FileContentPartType, ImageContentPart, ImageContentPartType, ImageUrl,
+    ImageUrlDetail, InputAudio, InputAudioContentPart, InputAudioContentPartType, InputAudioFormat,
+    InputFile, TextContentPart, TextContentPartType,
+};
+
+impl TextContentPart {
+    /// A text part.
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            type_: TextContentPartType::Text,
+        }
+    }
+}
+
+impl ImageContentPart {
+    /// An image part for `url`, which may be a `data:` URL, at the provider's
+    /// default (`auto`) detail level.
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            image_url: ImageUrl {
+                detail: ImageUrlDetail::Auto,
+                url: url.into(),
+            },
+            type_: ImageContentPartType::ImageUrl,
+        }
+    }
+
+    /// Sets the detail level the model processes the image at. `Low` costs
+    /// a fixed, small number of tokens; `High` lets the model see fine detail
+    /// at a higher token cost.
+    pub fn with_detail(mut self, detail: ImageUrlDetail) -> Self {
+        self.image_url.detail = detail;
+        self
+    }
+}
+
+impl From<String> for ImageContentPart {
+    fn from(url: String) -> Self {
+        Self::new(url)
+    }
+}
+
+impl From<&str> for ImageContentPart {
+    fn from(url: &str) -> Self {
+        Self::new(url)
+    }
+}
+
+impl From<DataUrl> for ImageContentPart {
+    fn from(url: DataUrl) -> Self {
+        Self::new(url.to_string())
+    }
+}
+
+impl InputAudioContentPart {
+    /// An audio part holding `bytes` encoded as `format`, base64-encoded for
+    /// the wire.
+    pub fn new(format: InputAudioFormat, bytes: &[u8]) -> Self {
+        Self {
+            input_audio: InputAudio {
+                data: STANDARD.encode(bytes),
+                format,
+            },
+            type_: InputAudioContentPartType::InputAudio,
+        }
+    }
+
+    /// An audio part from the bytes of a WAV file.
+    pub fn wav(bytes: &[u8]) -> Self {
+        Self::new(InputAudioFormat::Wav, bytes)
+    }
+
+    /// An audio part from the bytes of an MP3 file.
+    pub fn mp3(bytes: &[u8]) -> Self {
+        Self::new(InputAudioFormat::Mp3, bytes)
+    }
+}
+
+impl FileContentPart {
+    /// A file part referencing a file already uploaded to the provider, for
+    /// example with [`InferenceGatewayAPI::upload_file`](crate::InferenceGatewayAPI::upload_file).
+    pub fn from_id(file_id: impl Into<String>) -> Self {
+        Self::with_file(InputFile {
+            file_id: Some(file_id.into()),
+            ..Default::default()
+        })
+    }
+
+    /// A file part carrying `bytes` inline as a base64 data URL.
+    pub fn inline(filename: impl Into<String>, media_type: &str, bytes: &[u8]) -> Self {
+        Self::with_file(InputFile {
+            file_data: Some(DataUrl::new(media_type, bytes).to_string()),
+            filename: Some(filename.into()),
+            ..Default::default()
+        })
+    }
+
+    fn with_file(file: InputFile) -> Self {
+        Self {
+            file,
+            type_: FileContentPartType::File,
+        }
+    }
+}
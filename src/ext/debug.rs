@@ -0,0 +1,149 @@
+//! `Debug` for generated types that carry prompts, completions or attachment
+//! data. `gen-types` drops their derived `Debug` so these can elide that
+//! text in privacy mode; otherwise they print what the derive would.
+
+use std::fmt;
+
+use crate::generated::schemas::{
+    ChatCompletionMessageAudio, ChatCompletionMessageToolCallChunkFunction,
+    ChatCompletionMessageToolCallFunction, ChatCompletionStreamResponseDelta,
+    ChatCompletionStreamResponseDeltaAudio, ImageUrl, InputAudio, InputFile, Message,
+    MessagesDocumentSource, MessagesImageSource, MessagesMessageContent, MessagesStreamEventDelta,
+    MessagesTextBlock, MessagesThinkingBlock, MessagesToolResultBlockContent, MessagesToolUseBlock,
+    TextContentPart,
+};
+use crate::privacy::Elided;
+
+/// Implements `Debug` for a struct, listing its fields in order. Fields
+/// marked `elided` are printed through [`Elided`].
+macro_rules! debug_struct {
+    ($ty:ident { $($field:ident $(: $elided:ident)?),* $(,)? }) => {
+        impl fmt::Debug for $ty {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.debug_struct(stringify!($ty))
+                    $(.field(stringify!($field), debug_struct!(@value self.$field $(, $elided)?)))*
+                    .finish()
+            }
+        }
+    };
+    (@value $value:expr) => {
+        &$value
+    };
+    (@value $value:expr, elided) => {
+        &Elided(&$value)
+    };
+}
+
+debug_struct!(ChatCompletionMessageAudio {
+    data: elided,
+    expires_at,
+    id,
+    transcript: elided,
+});
+debug_struct!(ChatCompletionMessageToolCallChunkFunction {
+    arguments: elided,
+    name,
+});
+debug_struct!(ChatCompletionMessageToolCallFunction {
+    arguments: elided,
+    name,
+});
+debug_struct!(ChatCompletionStreamResponseDelta {
+    annotations,
+    audio,
+    content: elided,
+    reasoning: elided,
+    reasoning_content: elided,
+    refusal: elided,
+    role,
+    tool_calls,
+});
+debug_struct!(ChatCompletionStreamResponseDeltaAudio {
+    data: elided,
+    expires_at,
+    id,
+    transcript: elided,
+});
+debug_struct!(ImageUrl {
+    detail,
+    url: elided,
+});
+debug_struct!(InputAudio {
+    data: elided,
+    format,
+});
+debug_struct!(InputFile {
+    file_data: elided,
+    file_id,
+    filename,
+});
+debug_struct!(Message {
+    annotations,
+    audio,
+    content,
+    name,
+    reasoning: elided,
+    reasoning_content: elided,
+    role,
+    tool_call_id,
+    tool_calls,
+});
+debug_struct!(MessagesDocumentSource {
+    data: elided,
+    media_type,
+    type_,
+    url: elided,
+});
+debug_struct!(MessagesImageSource {
+    data: elided,
+    media_type,
+    type_,
+    url: elided,
+});
+debug_struct!(MessagesStreamEventDelta {
+    partial_json: elided,
+    signature,
+    stop_reason,
+    stop_sequence,
+    text: elided,
+    thinking: elided,
+    type_,
+});
+debug_struct!(MessagesTextBlock {
+    cache_control,
+    text: elided,
+    type_,
+});
+debug_struct!(MessagesThinkingBlock {
+    signature,
+    thinking: elided,
+    type_,
+});
+debug_struct!(MessagesToolUseBlock {
+    id,
+    input: elided,
+    name,
+    type_,
+});
+debug_struct!(TextContentPart {
+    text: elided,
+    type_,
+});
+
+impl fmt::Debug for MessagesMessageContent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::String(text) => f.debug_tuple("String").field(&Elided(text)).finish(),
+            Self::Array(blocks) => f.debug_tuple("Array").field(blocks).finish(),
+        }
+    }
+}
+
+impl fmt::Debug for MessagesToolResultBlockContent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::String(text) => f.debug_tuple("String").field(&Elided(text)).finish(),
+            Self::Array(blocks) => f.debug_tuple("Array").field(blocks).finish(),
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use crate::generated::schemas::{
+    ContentPart, ImageContentPart, Message, MessageRole, TextContentPart,
+};
+use crate::{MessageContent, ToolCallId};
+
+impl Message {
+    /// A message from `role` with `content` and nothing else set.
+    pub fn new(role: MessageRole, content: impl Into<MessageContent>) -> Self {
+        Self {
+            annotations: Vec::new(),
+            audio: None,
+            content: content.into(),
+            name: None,
+            reasoning: None,
+            reasoning_content: None,
+            role,
+            tool_call_id: None,
+            tool_calls: Vec::new(),
+        }
+    }
+
+    /// A system message.
+    pub fn system(text: impl Into<String>) -> Self {
+        Self::new(MessageRole::System, text.into())
+    }
+
+    /// A developer message, which newer OpenAI models take in place of a
+    /// system message. The client sends it as a system message to providers
+    /// that don't know the role; see
+    /// [`InstructionRole`](crate::InstructionRole).
+    pub fn developer(text: impl Into<String>) -> Self {
+        Self::new(MessageRole::Developer, text.into())
+    }
+
+    /// A user message.
+    pub fn user(text: impl Into<String>) -> Self {
+        Self::new(MessageRole::User, text.into())
+    }
+
+    /// An assistant message, e.g. a canned earlier turn.
+    pub fn assistant(text: impl Into<String>) -> Self {
+        Self::new(MessageRole::Assistant, text.into())
+    }
+
+    /// The result of the tool call `tool_call_id`, to send back to the model.
+    pub fn tool(tool_call_id: impl Into<ToolCallId>, text: impl Into<String>) -> Self {
+        Self {
+            tool_call_id: Some(tool_call_id.into()),
+            ..Self::new(MessageRole::Tool, text.into())
+        }
+    }
+
+    /// Sets the participant `name`, to tell apart several users in one
+    /// conversation or name the tool a message comes from.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// A user message with `text` followed by `images`, each given as a URL,
+    /// a [`DataUrl`](crate::attachment::DataUrl) or an [`ImageContentPart`].
+    ///
+    /// To refer back to images across turns, use
+    /// [`Conversation::user_with_images`](crate::Conversation::user_with_images),
+    /// which numbers them.
+    pub fn user_with_images<I>(text: impl Into<String>, images: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<ImageContentPart>,
+    {
+        let parts = std::iter::once(TextContentPart::new(text).into())
+            .chain(images.into_iter().map(|image| image.into().into()))
+            .collect::<Vec<ContentPart>>();
+        Self::new(MessageRole::User, parts)
+    }
+
+    /// The image parts of this message, in order.
+    pub fn images(&self) -> impl Iterator<Item = &ImageContentPart> {
+        let parts = match &self.content {
+            MessageContent::Parts(parts) => parts.as_slice(),
+            MessageContent::Text(_) => &[],
+        };
+        parts.iter().filter_map(|part| match part {
+            ContentPart::ImageContentPart(image) => Some(image),
+            _ => None,
+        })
+    }
+}
@@ -3,7 +3,18 @@
 //! Generated types live in `crate::generated::schemas`. typify already emits
 //! `Display`, `FromStr`, and `TryFrom<&str>` for enums, so this module only
 //! holds behavior the schema cannot describe - argument parsing on tool-call
-//! functions and a `Default` for the chat-completion request.
+//! functions, decoding of generated audio, resolving citations against the
+//! text they support, a `Default` for the chat-completion request, and
+//! constructors for messages and for content parts that need encoding - plus
+//! `Debug` for the types privacy mode elides and, with the `timestamps`
+//! feature, `DateTime` accessors for `created` fields.
 
+mod annotation;
+mod audio;
 mod chat_request;
+mod content_part;
+mod debug;
+mod message;
+#[cfg(feature = "timestamps")]
+mod timestamps;
 mod tool_call;
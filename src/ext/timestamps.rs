@@ -0,0 +1,31 @@
+//! `created` timestamps as `DateTime<Utc>`, behind the `timestamps` feature.
+//! The raw Unix seconds stay in the `created` fields.
+
+use chrono::{DateTime, Utc};
+
+use crate::generated::schemas::{
+    CreateChatCompletionResponse, CreateChatCompletionStreamResponse, Model,
+};
+
+impl Model {
+    /// When the model was created, or `None` if `created` is out of range.
+    pub fn created_at(&self) -> Option<DateTime<Utc>> {
+        DateTime::from_timestamp(self.created, 0)
+    }
+}
+
+impl CreateChatCompletionResponse {
+    /// When the completion was created, or `None` if `created` is out of
+    /// range.
+    pub fn created_at(&self) -> Option<DateTime<Utc>> {
+        DateTime::from_timestamp(self.created, 0)
+    }
+}
+
+impl CreateChatCompletionStreamResponse {
+    /// When the completion was created, the same for every chunk, or `None`
+    /// if `created` is out of range.
+    pub fn created_at(&self) -> Option<DateTime<Utc>> {
+        DateTime::from_timestamp(self.created, 0)
+    }
+}
@@ -1,4 +1,7 @@
-use crate::generated::schemas::ChatCompletionMessageToolCallFunction;
+use crate::generated::schemas::{
+    ChatCompletionMessageToolCallChunkFunction, ChatCompletionMessageToolCallFunction,
+};
+use crate::partial_json::parse_partial_json;
 
 impl ChatCompletionMessageToolCallFunction {
     /// Deserialize the `arguments` JSON string into a typed value.
@@ -8,4 +11,22 @@ impl ChatCompletionMessageToolCallFunction {
     pub fn parse_arguments<T: serde::de::DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
         serde_json::from_str(&self.arguments)
     }
+
+    /// Best-effort parse of `arguments` while they may still be incomplete.
+    ///
+    /// See [`parse_partial_json`] for how truncated input is handled.
+    pub fn parse_partial_arguments(&self) -> Option<serde_json::Value> {
+        parse_partial_json(&self.arguments)
+    }
+}
+
+impl ChatCompletionMessageToolCallChunkFunction {
+    /// Best-effort parse of the `arguments` accumulated so far.
+    ///
+    /// Useful after merging fragments (for example with
+    /// [`ChatCompletionStreamExt::coalesce`](crate::ChatCompletionStreamExt::coalesce))
+    /// to show tool parameters while the model is still generating them.
+    pub fn parse_partial_arguments(&self) -> Option<serde_json::Value> {
+        parse_partial_json(self.arguments.as_deref().unwrap_or_default())
+    }
 }
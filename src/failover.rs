@@ -0,0 +1,142 @@
+//! Failing over to other providers and models.
+//!
+//! With a [`FailoverConfig`] on the client, a chat completion that fails
+//! with a server error, a rate limit or a timeout is sent again, with the
+//! same messages and options, to the next provider and model of the chain,
+//! until one succeeds or the chain runs out.
+
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use reqwest::StatusCode;
+
+use crate::{GatewayError, GatewayErrorKind, ModelRef, Provider};
+
+type AttemptHook = Arc<dyn Fn(&FailoverEvent<'_>) + Send + Sync>;
+
+/// The providers and models a failed chat completion falls back to, in
+/// order.
+///
+/// ```
+/// use inference_gateway_sdk::{FailoverConfig, InferenceGatewayClient, Provider};
+///
+/// let client = InferenceGatewayClient::new("http://localhost:8080/v1").with_failover(
+///     FailoverConfig::new([
+///         (Provider::Anthropic, "claude-sonnet-5"),
+///         (Provider::Groq, "llama-3.3-70b-versatile"),
+///     ])
+///     .on_attempt(|event| {
+///         if let Some(error) = event.error {
+///             eprintln!("{} {} failed: {error}", event.provider, event.model);
+///         }
+///     }),
+/// );
+/// ```
+#[derive(Clone)]
+pub struct FailoverConfig {
+    fallbacks: Vec<(Provider, ModelRef)>,
+    on_attempt: Option<AttemptHook>,
+}
+
+/// A finished attempt, passed to [`FailoverConfig::on_attempt`].
+#[derive(Debug)]
+pub struct FailoverEvent<'a> {
+    /// The attempt's number, starting at 1 for the requested model.
+    pub attempt: u32,
+    /// The provider the attempt was sent to.
+    pub provider: Provider,
+    /// The model the attempt was sent to.
+    pub model: &'a str,
+    /// How long the attempt took, retries included.
+    pub duration: Duration,
+    /// Why the attempt failed, or `None` if it succeeded.
+    pub error: Option<&'a GatewayError>,
+}
+
+impl FailoverConfig {
+    /// Falls back to `fallbacks`, tried in order after the requested
+    /// provider and model.
+    pub fn new<M: Into<ModelRef>>(fallbacks: impl IntoIterator<Item = (Provider, M)>) -> Self {
+        Self {
+            fallbacks: fallbacks
+                .into_iter()
+                .map(|(provider, model)| (provider, model.into()))
+                .collect(),
+            on_attempt: None,
+        }
+    }
+
+    /// Calls `hook` after each attempt, successful or not.
+    pub fn on_attempt(mut self, hook: impl Fn(&FailoverEvent<'_>) + Send + Sync + 'static) -> Self {
+        self.on_attempt = Some(Arc::new(hook));
+        self
+    }
+
+    /// Sends `attempt` to `provider` and `model`, then to each fallback in
+    /// turn while it fails over. Returns the result of the last attempt
+    /// with the provider and model it was sent to.
+    ///
+    /// `attempt` resolves to the call's result and the status of the last
+    /// response it got, if any.
+    pub(crate) async fn run<T, F, Fut>(
+        &self,
+        provider: Provider,
+        model: String,
+        mut attempt: F,
+    ) -> (Provider, String, Result<T, GatewayError>)
+    where
+        F: FnMut(Provider, String) -> Fut,
+        Fut: Future<Output = (Result<T, GatewayError>, Option<StatusCode>)>,
+    {
+        let mut targets = self
+            .fallbacks
+            .iter()
+            .map(|(provider, model)| (*provider, model.to_string()));
+        let (mut provider, mut model) = (provider, model);
+        let mut number = 0;
+        loop {
+            number += 1;
+            let started = Instant::now();
+            let (result, status) = attempt(provider, model.clone()).await;
+            if let Some(hook) = &self.on_attempt {
+                hook(&FailoverEvent {
+                    attempt: number,
+                    provider,
+                    model: &model,
+                    duration: started.elapsed(),
+                    error: result.as_ref().err(),
+                });
+            }
+            let next = match &result {
+                Err(error) if fails_over(error, status) => targets.next(),
+                _ => None,
+            };
+            match next {
+                Some(target) => (provider, model) = target,
+                None => return (provider, model, result),
+            }
+        }
+    }
+}
+
+impl fmt::Debug for FailoverConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FailoverConfig")
+            .field("fallbacks", &self.fallbacks)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Whether a call that failed with `error`, after a response with `status`
+/// if it got one, should be sent to the next model: server errors, rate
+/// limits and timeouts are worth another provider, while a request the
+/// gateway rejected would be rejected everywhere.
+fn fails_over(error: &GatewayError, status: Option<StatusCode>) -> bool {
+    status.is_some_and(|status| status.is_server_error())
+        || matches!(
+            error.kind(),
+            GatewayErrorKind::Provider | GatewayErrorKind::RateLimit | GatewayErrorKind::Timeout
+        )
+}
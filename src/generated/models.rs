@@ -0,0 +1,88 @@
+// @generated - DO NOT EDIT.
+// Regenerate with `task generate-types` (or `cargo run -p gen-types --release`).
+// Source: models.yaml.
+
+//! Commonly available model IDs, grouped by provider.
+//!
+//! These are plain `&str` constants, so any other model ID can be used
+//! wherever one of them can.
+
+/// Models served by [`Provider::Anthropic`](crate::Provider::Anthropic).
+pub mod anthropic {
+    pub const CLAUDE_SONNET_5: &str = "claude-sonnet-5";
+    pub const CLAUDE_OPUS_4_1: &str = "claude-opus-4-1";
+    pub const CLAUDE_3_5_HAIKU_LATEST: &str = "claude-3-5-haiku-latest";
+
+    /// Every model listed above.
+    pub const ALL: &[&str] = &[CLAUDE_SONNET_5, CLAUDE_OPUS_4_1, CLAUDE_3_5_HAIKU_LATEST];
+}
+
+/// Models served by [`Provider::Cohere`](crate::Provider::Cohere).
+pub mod cohere {
+    pub const COMMAND_R_PLUS: &str = "command-r-plus";
+    pub const COMMAND_R: &str = "command-r";
+
+    /// Every model listed above.
+    pub const ALL: &[&str] = &[COMMAND_R_PLUS, COMMAND_R];
+}
+
+/// Models served by [`Provider::Deepseek`](crate::Provider::Deepseek).
+pub mod deepseek {
+    pub const DEEPSEEK_V4_FLASH: &str = "deepseek-v4-flash";
+    pub const DEEPSEEK_CHAT: &str = "deepseek-chat";
+    pub const DEEPSEEK_REASONER: &str = "deepseek-reasoner";
+
+    /// Every model listed above.
+    pub const ALL: &[&str] = &[DEEPSEEK_V4_FLASH, DEEPSEEK_CHAT, DEEPSEEK_REASONER];
+}
+
+/// Models served by [`Provider::Google`](crate::Provider::Google).
+pub mod google {
+    pub const GEMINI_3_PRO: &str = "gemini-3-pro";
+    pub const GEMINI_2_5_PRO: &str = "gemini-2.5-pro";
+    pub const GEMINI_2_5_FLASH: &str = "gemini-2.5-flash";
+
+    /// Every model listed above.
+    pub const ALL: &[&str] = &[GEMINI_3_PRO, GEMINI_2_5_PRO, GEMINI_2_5_FLASH];
+}
+
+/// Models served by [`Provider::Groq`](crate::Provider::Groq).
+pub mod groq {
+    pub const LLAMA_3_3_70B_VERSATILE: &str = "llama-3.3-70b-versatile";
+    pub const LLAMA_3_1_8B_INSTANT: &str = "llama-3.1-8b-instant";
+
+    /// Every model listed above.
+    pub const ALL: &[&str] = &[LLAMA_3_3_70B_VERSATILE, LLAMA_3_1_8B_INSTANT];
+}
+
+/// Models served by [`Provider::Mistral`](crate::Provider::Mistral).
+pub mod mistral {
+    pub const MISTRAL_LARGE_LATEST: &str = "mistral-large-latest";
+    pub const MISTRAL_SMALL_LATEST: &str = "mistral-small-latest";
+    pub const CODESTRAL_LATEST: &str = "codestral-latest";
+
+    /// Every model listed above.
+    pub const ALL: &[&str] = &[MISTRAL_LARGE_LATEST, MISTRAL_SMALL_LATEST, CODESTRAL_LATEST];
+}
+
+/// Models served by [`Provider::Ollama`](crate::Provider::Ollama).
+pub mod ollama {
+    pub const LLAMA3_2: &str = "llama3.2";
+    pub const QWEN2_5: &str = "qwen2.5";
+
+    /// Every model listed above.
+    pub const ALL: &[&str] = &[LLAMA3_2, QWEN2_5];
+}
+
+/// Models served by [`Provider::Openai`](crate::Provider::Openai).
+pub mod openai {
+    pub const GPT_4O: &str = "gpt-4o";
+    pub const GPT_4O_MINI: &str = "gpt-4o-mini";
+    pub const GPT_4_1: &str = "gpt-4.1";
+    pub const GPT_4_1_MINI: &str = "gpt-4.1-mini";
+    pub const O3: &str = "o3";
+    pub const O4_MINI: &str = "o4-mini";
+
+    /// Every model listed above.
+    pub const ALL: &[&str] = &[GPT_4O, GPT_4O_MINI, GPT_4_1, GPT_4_1_MINI, O3, O4_MINI];
+}
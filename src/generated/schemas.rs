@@ -133,6 +133,98 @@ impl ::std::convert::TryFrom<::std::string::String> for CacheControlType {
         value.parse()
     }
 }
+///A source the model cited, with the span of the message text it supports.
+///
+/// <details><summary>JSON schema</summary>
+///
+/// ```json
+///{
+///  "description": "A source the model cited, with the span of the message text it supports.",
+///  "type": "object",
+///  "required": [
+///    "type"
+///  ],
+///  "properties": {
+///    "type": {
+///      "$ref": "#/definitions/ChatCompletionAnnotationType"
+///    },
+///    "url_citation": {
+///      "$ref": "#/definitions/UrlCitation"
+///    }
+///  }
+///}
+/// ```
+/// </details>
+#[derive(::serde::Deserialize, ::serde::Serialize, Clone, Debug)]
+pub struct ChatCompletionAnnotation {
+    #[serde(rename = "type")]
+    pub type_: ChatCompletionAnnotationType,
+    #[serde(default, skip_serializing_if = "::std::option::Option::is_none")]
+    pub url_citation: ::std::option::Option<UrlCitation>,
+}
+///The kind of annotation.
+///
+/// <details><summary>JSON schema</summary>
+///
+/// ```json
+///{
+///  "description": "The kind of annotation.",
+///  "type": "string",
+///  "enum": [
+///    "url_citation"
+///  ]
+///}
+/// ```
+/// </details>
+#[derive(
+    ::serde::Deserialize, ::serde::Serialize, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd,
+)]
+pub enum ChatCompletionAnnotationType {
+    #[serde(rename = "url_citation")]
+    UrlCitation,
+    /// A value this SDK doesn't know yet, as it was sent.
+    #[serde(untagged)]
+    Other(::std::string::String),
+}
+impl ::std::fmt::Display for ChatCompletionAnnotationType {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        match *self {
+            Self::UrlCitation => f.write_str("url_citation"),
+            Self::Other(ref value) => f.write_str(value),
+        }
+    }
+}
+impl ::std::str::FromStr for ChatCompletionAnnotationType {
+    type Err = self::error::ConversionError;
+    fn from_str(value: &str) -> ::std::result::Result<Self, self::error::ConversionError> {
+        match value {
+            "url_citation" => Ok(Self::UrlCitation),
+            _ => Ok(Self::Other(value.to_string())),
+        }
+    }
+}
+impl ::std::convert::TryFrom<&str> for ChatCompletionAnnotationType {
+    type Error = self::error::ConversionError;
+    fn try_from(value: &str) -> ::std::result::Result<Self, self::error::ConversionError> {
+        value.parse()
+    }
+}
+impl ::std::convert::TryFrom<&::std::string::String> for ChatCompletionAnnotationType {
+    type Error = self::error::ConversionError;
+    fn try_from(
+        value: &::std::string::String,
+    ) -> ::std::result::Result<Self, self::error::ConversionError> {
+        value.parse()
+    }
+}
+impl ::std::convert::TryFrom<::std::string::String> for ChatCompletionAnnotationType {
+    type Error = self::error::ConversionError;
+    fn try_from(
+        value: ::std::string::String,
+    ) -> ::std::result::Result<Self, self::error::ConversionError> {
+        value.parse()
+    }
+}
 ///`ChatCompletionChoice`
 ///
 /// <details><summary>JSON schema</summary>
@@ -233,6 +325,52 @@ pub struct ChatCompletionChoiceLogprobs {
     ///A list of message refusal tokens with log probability information.
     pub refusal: ::std::vec::Vec<ChatCompletionTokenLogprob>,
 }
+///Audio generated by an audio-output-capable model.
+///
+/// <details><summary>JSON schema</summary>
+///
+/// ```json
+///{
+///  "description": "Audio generated by an audio-output-capable model.",
+///  "type": "object",
+///  "required": [
+///    "data",
+///    "expires_at",
+///    "id",
+///    "transcript"
+///  ],
+///  "properties": {
+///    "data": {
+///      "description": "Base64-encoded audio bytes, in the format the request asked for.",
+///      "type": "string"
+///    },
+///    "expires_at": {
+///      "description": "The Unix timestamp (in seconds) after which the audio can no longer be referenced in a follow-up request.",
+///      "type": "integer"
+///    },
+///    "id": {
+///      "description": "Identifier to refer back to this audio in a follow-up request.",
+///      "type": "string"
+///    },
+///    "transcript": {
+///      "description": "Transcript of the audio.",
+///      "type": "string"
+///    }
+///  }
+///}
+/// ```
+/// </details>
+#[derive(::serde::Deserialize, ::serde::Serialize, Clone)]
+pub struct ChatCompletionMessageAudio {
+    ///Base64-encoded audio bytes, in the format the request asked for.
+    pub data: ::std::string::String,
+    ///The Unix timestamp (in seconds) after which the audio can no longer be referenced in a follow-up request.
+    pub expires_at: i64,
+    ///Identifier to refer back to this audio in a follow-up request.
+    pub id: ::std::string::String,
+    ///Transcript of the audio.
+    pub transcript: ::std::string::String,
+}
 ///`ChatCompletionMessageToolCall`
 ///
 /// <details><summary>JSON schema</summary>
@@ -254,7 +392,7 @@ pub struct ChatCompletionChoiceLogprobs {
 ///    },
 ///    "id": {
 ///      "description": "The ID of the tool call.",
-///      "type": "string"
+///      "$ref": "#/definitions/ToolCallId"
 ///    },
 ///    "type": {
 ///      "$ref": "#/definitions/ChatCompletionToolType"
@@ -269,7 +407,7 @@ pub struct ChatCompletionMessageToolCall {
     pub extra_content: ::std::option::Option<ToolCallExtraContent>,
     pub function: ChatCompletionMessageToolCallFunction,
     ///The ID of the tool call.
-    pub id: ::std::string::String,
+    pub id: crate::ToolCallId,
     #[serde(rename = "type")]
     pub type_: ChatCompletionToolType,
 }
@@ -288,11 +426,22 @@ pub struct ChatCompletionMessageToolCall {
 ///      "$ref": "#/definitions/ToolCallExtraContent"
 ///    },
 ///    "function": {
-///      "$ref": "#/definitions/ChatCompletionMessageToolCallFunction"
+///      "description": "A fragment of the function that the model called.",
+///      "type": "object",
+///      "properties": {
+///        "arguments": {
+///          "description": "A slice of the JSON-encoded arguments. Concatenate the fragments that share an `index` to rebuild the full string.",
+///          "type": "string"
+///        },
+///        "name": {
+///          "description": "The name of the function to call. Only sent with the first fragment.",
+///          "type": "string"
+///        }
+///      }
 ///    },
 ///    "id": {
 ///      "description": "The ID of the tool call.",
-///      "type": "string"
+///      "$ref": "#/definitions/ToolCallId"
 ///    },
 ///    "index": {
 ///      "type": "integer"
@@ -310,10 +459,10 @@ pub struct ChatCompletionMessageToolCallChunk {
     #[serde(default, skip_serializing_if = "::std::option::Option::is_none")]
     pub extra_content: ::std::option::Option<ToolCallExtraContent>,
     #[serde(default, skip_serializing_if = "::std::option::Option::is_none")]
-    pub function: ::std::option::Option<ChatCompletionMessageToolCallFunction>,
+    pub function: ::std::option::Option<ChatCompletionMessageToolCallChunkFunction>,
     ///The ID of the tool call.
     #[serde(default, skip_serializing_if = "::std::option::Option::is_none")]
-    pub id: ::std::option::Option<::std::string::String>,
+    pub id: ::std::option::Option<crate::ToolCallId>,
     pub index: i64,
     ///The type of the tool. Currently, only `function` is supported.
     #[serde(
@@ -323,6 +472,44 @@ pub struct ChatCompletionMessageToolCallChunk {
     )]
     pub type_: ::std::option::Option<::std::string::String>,
 }
+///A fragment of the function that the model called.
+///
+/// <details><summary>JSON schema</summary>
+///
+/// ```json
+///{
+///  "description": "A fragment of the function that the model called.",
+///  "type": "object",
+///  "properties": {
+///    "arguments": {
+///      "description": "A slice of the JSON-encoded arguments. Concatenate the fragments that share an `index` to rebuild the full string.",
+///      "type": "string"
+///    },
+///    "name": {
+///      "description": "The name of the function to call. Only sent with the first fragment.",
+///      "type": "string"
+///    }
+///  }
+///}
+/// ```
+/// </details>
+#[derive(::serde::Deserialize, ::serde::Serialize, Clone)]
+pub struct ChatCompletionMessageToolCallChunkFunction {
+    ///A slice of the JSON-encoded arguments. Concatenate the fragments that share an `index` to rebuild the full string.
+    #[serde(default, skip_serializing_if = "::std::option::Option::is_none")]
+    pub arguments: ::std::option::Option<::std::string::String>,
+    ///The name of the function to call. Only sent with the first fragment.
+    #[serde(default, skip_serializing_if = "::std::option::Option::is_none")]
+    pub name: ::std::option::Option<::std::string::String>,
+}
+impl ::std::default::Default for ChatCompletionMessageToolCallChunkFunction {
+    fn default() -> Self {
+        Self {
+            arguments: Default::default(),
+            name: Default::default(),
+        }
+    }
+}
 ///The function that the model called.
 ///
 /// <details><summary>JSON schema</summary>
@@ -348,7 +535,7 @@ pub struct ChatCompletionMessageToolCallChunk {
 ///}
 /// ```
 /// </details>
-#[derive(::serde::Deserialize, ::serde::Serialize, Clone, Debug)]
+#[derive(::serde::Deserialize, ::serde::Serialize, Clone)]
 pub struct ChatCompletionMessageToolCallFunction {
     ///The arguments to call the function with, as generated by the model in JSON format. Note that the model does not always generate valid JSON, and may hallucinate parameters not defined by your function schema. Validate the arguments in your code before calling your function.
     pub arguments: ::std::string::String,
@@ -552,6 +739,16 @@ pub struct ChatCompletionStreamOptions {
 ///  "description": "A chat completion delta generated by streamed model responses.",
 ///  "type": "object",
 ///  "properties": {
+///    "annotations": {
+///      "description": "Sources the model cited, for web-search-enabled models.",
+///      "type": "array",
+///      "items": {
+///        "$ref": "#/definitions/ChatCompletionAnnotation"
+///      }
+///    },
+///    "audio": {
+///      "$ref": "#/definitions/ChatCompletionStreamResponseDeltaAudio"
+///    },
 ///    "content": {
 ///      "description": "The contents of the chunk message.",
 ///      "type": "string"
@@ -581,8 +778,13 @@ pub struct ChatCompletionStreamOptions {
 ///}
 /// ```
 /// </details>
-#[derive(::serde::Deserialize, ::serde::Serialize, Clone, Debug)]
+#[derive(::serde::Deserialize, ::serde::Serialize, Clone)]
 pub struct ChatCompletionStreamResponseDelta {
+    ///Sources the model cited, for web-search-enabled models.
+    #[serde(default, skip_serializing_if = "::std::vec::Vec::is_empty")]
+    pub annotations: ::std::vec::Vec<ChatCompletionAnnotation>,
+    #[serde(default, skip_serializing_if = "::std::option::Option::is_none")]
+    pub audio: ::std::option::Option<ChatCompletionStreamResponseDeltaAudio>,
     ///The contents of the chunk message.
     #[serde(default, skip_serializing_if = "::std::option::Option::is_none")]
     pub content: ::std::option::Option<::std::string::String>,
@@ -603,6 +805,8 @@ pub struct ChatCompletionStreamResponseDelta {
 impl ::std::default::Default for ChatCompletionStreamResponseDelta {
     fn default() -> Self {
         Self {
+            annotations: Default::default(),
+            audio: Default::default(),
             content: Default::default(),
             reasoning: Default::default(),
             reasoning_content: Default::default(),
@@ -612,6 +816,60 @@ impl ::std::default::Default for ChatCompletionStreamResponseDelta {
         }
     }
 }
+///A fragment of the audio generated by an audio-output-capable model.
+///
+/// <details><summary>JSON schema</summary>
+///
+/// ```json
+///{
+///  "description": "A fragment of the audio generated by an audio-output-capable model.",
+///  "type": "object",
+///  "properties": {
+///    "data": {
+///      "description": "A slice of the base64-encoded audio bytes.",
+///      "type": "string"
+///    },
+///    "expires_at": {
+///      "description": "The Unix timestamp (in seconds) after which the audio can no longer be referenced in a follow-up request.",
+///      "type": "integer"
+///    },
+///    "id": {
+///      "description": "Identifier to refer back to this audio in a follow-up request.",
+///      "type": "string"
+///    },
+///    "transcript": {
+///      "description": "A slice of the transcript.",
+///      "type": "string"
+///    }
+///  }
+///}
+/// ```
+/// </details>
+#[derive(::serde::Deserialize, ::serde::Serialize, Clone)]
+pub struct ChatCompletionStreamResponseDeltaAudio {
+    ///A slice of the base64-encoded audio bytes.
+    #[serde(default, skip_serializing_if = "::std::option::Option::is_none")]
+    pub data: ::std::option::Option<::std::string::String>,
+    ///The Unix timestamp (in seconds) after which the audio can no longer be referenced in a follow-up request.
+    #[serde(default, skip_serializing_if = "::std::option::Option::is_none")]
+    pub expires_at: ::std::option::Option<i64>,
+    ///Identifier to refer back to this audio in a follow-up request.
+    #[serde(default, skip_serializing_if = "::std::option::Option::is_none")]
+    pub id: ::std::option::Option<::std::string::String>,
+    ///A slice of the transcript.
+    #[serde(default, skip_serializing_if = "::std::option::Option::is_none")]
+    pub transcript: ::std::option::Option<::std::string::String>,
+}
+impl ::std::default::Default for ChatCompletionStreamResponseDeltaAudio {
+    fn default() -> Self {
+        Self {
+            data: Default::default(),
+            expires_at: Default::default(),
+            id: Default::default(),
+            transcript: Default::default(),
+        }
+    }
+}
 ///`ChatCompletionTokenLogprob`
 ///
 /// <details><summary>JSON schema</summary>
@@ -890,25 +1148,20 @@ impl ::std::convert::TryFrom<::std::string::String> for ChatCompletionToolChoice
 /// ```
 /// </details>
 #[derive(
-    ::serde::Deserialize,
-    ::serde::Serialize,
-    Clone,
-    Copy,
-    Debug,
-    Eq,
-    Hash,
-    Ord,
-    PartialEq,
-    PartialOrd,
+    ::serde::Deserialize, ::serde::Serialize, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd,
 )]
 pub enum ChatCompletionToolType {
     #[serde(rename = "function")]
     Function,
+    /// A value this SDK doesn't know yet, as it was sent.
+    #[serde(untagged)]
+    Other(::std::string::String),
 }
 impl ::std::fmt::Display for ChatCompletionToolType {
     fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
         match *self {
             Self::Function => f.write_str("function"),
+            Self::Other(ref value) => f.write_str(value),
         }
     }
 }
@@ -917,7 +1170,7 @@ impl ::std::str::FromStr for ChatCompletionToolType {
     fn from_str(value: &str) -> ::std::result::Result<Self, self::error::ConversionError> {
         match value {
             "function" => Ok(Self::Function),
-            _ => Err("invalid value".into()),
+            _ => Ok(Self::Other(value.to_string())),
         }
     }
 }
@@ -1185,6 +1438,12 @@ impl ::std::convert::From<::serde_json::Value> for Config {
 ///    },
 ///    {
 ///      "$ref": "#/definitions/ImageContentPart"
+///    },
+///    {
+///      "$ref": "#/definitions/InputAudioContentPart"
+///    },
+///    {
+///      "$ref": "#/definitions/FileContentPart"
 ///    }
 ///  ]
 ///}
@@ -1195,6 +1454,8 @@ impl ::std::convert::From<::serde_json::Value> for Config {
 pub enum ContentPart {
     TextContentPart(TextContentPart),
     ImageContentPart(ImageContentPart),
+    InputAudioContentPart(InputAudioContentPart),
+    FileContentPart(FileContentPart),
 }
 impl ::std::convert::From<TextContentPart> for ContentPart {
     fn from(value: TextContentPart) -> Self {
@@ -1206,6 +1467,16 @@ impl ::std::convert::From<ImageContentPart> for ContentPart {
         Self::ImageContentPart(value)
     }
 }
+impl ::std::convert::From<InputAudioContentPart> for ContentPart {
+    fn from(value: InputAudioContentPart) -> Self {
+        Self::InputAudioContentPart(value)
+    }
+}
+impl ::std::convert::From<FileContentPart> for ContentPart {
+    fn from(value: FileContentPart) -> Self {
+        Self::FileContentPart(value)
+    }
+}
 ///Context window information for a model
 ///
 /// <details><summary>JSON schema</summary>
@@ -1774,7 +2045,7 @@ impl ::std::convert::From<::std::vec::Vec<::std::string::String>>
 ///    },
 ///    "id": {
 ///      "description": "A unique identifier for the chat completion.",
-///      "type": "string"
+///      "$ref": "#/definitions/CompletionId"
 ///    },
 ///    "model": {
 ///      "description": "The model used for the chat completion.",
@@ -1784,6 +2055,14 @@ impl ::std::convert::From<::std::vec::Vec<::std::string::String>>
 ///      "description": "The object type, which is always `chat.completion`.",
 ///      "type": "string"
 ///    },
+///    "service_tier": {
+///      "description": "The service tier the provider processed the request with, when it reports one.",
+///      "type": "string"
+///    },
+///    "system_fingerprint": {
+///      "description": "This fingerprint represents the backend configuration that the model runs with.\nCan be used in conjunction with the `seed` request parameter to understand when backend changes have been made that might impact determinism.\n",
+///      "type": "string"
+///    },
 ///    "usage": {
 ///      "$ref": "#/definitions/CompletionUsage"
 ///    }
@@ -1798,11 +2077,19 @@ pub struct CreateChatCompletionResponse {
     ///The Unix timestamp (in seconds) of when the chat completion was created.
     pub created: i64,
     ///A unique identifier for the chat completion.
-    pub id: ::std::string::String,
+    pub id: crate::CompletionId,
     ///The model used for the chat completion.
     pub model: ::std::string::String,
     ///The object type, which is always `chat.completion`.
     pub object: ::std::string::String,
+    ///The service tier the provider processed the request with, when it reports one.
+    #[serde(default, skip_serializing_if = "::std::option::Option::is_none")]
+    pub service_tier: ::std::option::Option<::std::string::String>,
+    /**This fingerprint represents the backend configuration that the model runs with.
+    Can be used in conjunction with the `seed` request parameter to understand when backend changes have been made that might impact determinism.
+    */
+    #[serde(default, skip_serializing_if = "::std::option::Option::is_none")]
+    pub system_fingerprint: ::std::option::Option<::std::string::String>,
     #[serde(default, skip_serializing_if = "::std::option::Option::is_none")]
     pub usage: ::std::option::Option<CompletionUsage>,
 }
@@ -1837,7 +2124,7 @@ by the model, based on the provided input.
 ///    },
 ///    "id": {
 ///      "description": "A unique identifier for the chat completion. Each chunk has the same ID.",
-///      "type": "string"
+///      "$ref": "#/definitions/CompletionId"
 ///    },
 ///    "model": {
 ///      "description": "The model to generate the completion.",
@@ -1871,7 +2158,7 @@ pub struct CreateChatCompletionStreamResponse {
     ///The Unix timestamp (in seconds) of when the chat completion was created. Each chunk has the same timestamp.
     pub created: i64,
     ///A unique identifier for the chat completion. Each chunk has the same ID.
-    pub id: ::std::string::String,
+    pub id: crate::CompletionId,
     ///The model to generate the completion.
     pub model: ::std::string::String,
     ///The object type, which is always `chat.completion.chunk`.
@@ -2415,24 +2702,50 @@ impl ::std::default::Default for Error {
         }
     }
 }
-/**The reason the model stopped generating tokens. This will be `stop` if the model hit a natural stop point or a provided stop sequence,
-`length` if the maximum number of tokens specified in the request was reached,
-`content_filter` if content was omitted due to a flag from our content filters,
-`tool_calls` if the model called a tool.
-*/
+///File content part, for providers that accept document attachments
 ///
 /// <details><summary>JSON schema</summary>
 ///
 /// ```json
 ///{
-///  "description": "The reason the model stopped generating tokens. This will be `stop` if the model hit a natural stop point or a provided stop sequence,\n`length` if the maximum number of tokens specified in the request was reached,\n`content_filter` if content was omitted due to a flag from our content filters,\n`tool_calls` if the model called a tool.\n",
+///  "description": "File content part, for providers that accept document attachments",
+///  "type": "object",
+///  "required": [
+///    "file",
+///    "type"
+///  ],
+///  "properties": {
+///    "file": {
+///      "$ref": "#/definitions/InputFile"
+///    },
+///    "type": {
+///      "description": "Content type identifier",
+///      "type": "string",
+///      "enum": [
+///        "file"
+///      ]
+///    }
+///  }
+///}
+/// ```
+/// </details>
+#[derive(::serde::Deserialize, ::serde::Serialize, Clone, Debug)]
+pub struct FileContentPart {
+    pub file: InputFile,
+    ///Content type identifier
+    #[serde(rename = "type")]
+    pub type_: FileContentPartType,
+}
+///Content type identifier
+///
+/// <details><summary>JSON schema</summary>
+///
+/// ```json
+///{
+///  "description": "Content type identifier",
 ///  "type": "string",
 ///  "enum": [
-///    "stop",
-///    "length",
-///    "tool_calls",
-///    "content_filter",
-///    "function_call"
+///    "file"
 ///  ]
 ///}
 /// ```
@@ -2449,17 +2762,87 @@ impl ::std::default::Default for Error {
     PartialEq,
     PartialOrd,
 )]
-pub enum FinishReason {
-    #[serde(rename = "stop")]
-    Stop,
-    #[serde(rename = "length")]
-    Length,
-    #[serde(rename = "tool_calls")]
-    ToolCalls,
-    #[serde(rename = "content_filter")]
-    ContentFilter,
+pub enum FileContentPartType {
+    #[serde(rename = "file")]
+    File,
+}
+impl ::std::fmt::Display for FileContentPartType {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        match *self {
+            Self::File => f.write_str("file"),
+        }
+    }
+}
+impl ::std::str::FromStr for FileContentPartType {
+    type Err = self::error::ConversionError;
+    fn from_str(value: &str) -> ::std::result::Result<Self, self::error::ConversionError> {
+        match value {
+            "file" => Ok(Self::File),
+            _ => Err("invalid value".into()),
+        }
+    }
+}
+impl ::std::convert::TryFrom<&str> for FileContentPartType {
+    type Error = self::error::ConversionError;
+    fn try_from(value: &str) -> ::std::result::Result<Self, self::error::ConversionError> {
+        value.parse()
+    }
+}
+impl ::std::convert::TryFrom<&::std::string::String> for FileContentPartType {
+    type Error = self::error::ConversionError;
+    fn try_from(
+        value: &::std::string::String,
+    ) -> ::std::result::Result<Self, self::error::ConversionError> {
+        value.parse()
+    }
+}
+impl ::std::convert::TryFrom<::std::string::String> for FileContentPartType {
+    type Error = self::error::ConversionError;
+    fn try_from(
+        value: ::std::string::String,
+    ) -> ::std::result::Result<Self, self::error::ConversionError> {
+        value.parse()
+    }
+}
+/**The reason the model stopped generating tokens. This will be `stop` if the model hit a natural stop point or a provided stop sequence,
+`length` if the maximum number of tokens specified in the request was reached,
+`content_filter` if content was omitted due to a flag from our content filters,
+`tool_calls` if the model called a tool.
+*/
+///
+/// <details><summary>JSON schema</summary>
+///
+/// ```json
+///{
+///  "description": "The reason the model stopped generating tokens. This will be `stop` if the model hit a natural stop point or a provided stop sequence,\n`length` if the maximum number of tokens specified in the request was reached,\n`content_filter` if content was omitted due to a flag from our content filters,\n`tool_calls` if the model called a tool.\n",
+///  "type": "string",
+///  "enum": [
+///    "stop",
+///    "length",
+///    "tool_calls",
+///    "content_filter",
+///    "function_call"
+///  ]
+///}
+/// ```
+/// </details>
+#[derive(
+    ::serde::Deserialize, ::serde::Serialize, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd,
+)]
+pub enum FinishReason {
+    #[serde(rename = "stop")]
+    Stop,
+    #[serde(rename = "length")]
+    Length,
+    #[serde(rename = "tool_calls")]
+    ToolCalls,
+    #[serde(rename = "content_filter")]
+    ContentFilter,
     #[serde(rename = "function_call")]
     FunctionCall,
+    /// A value this SDK doesn't know yet, as it was sent.
+    #[serde(untagged)]
+    Other(::std::string::String),
 }
 impl ::std::fmt::Display for FinishReason {
     fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
@@ -2469,6 +2852,7 @@ impl ::std::fmt::Display for FinishReason {
             Self::ToolCalls => f.write_str("tool_calls"),
             Self::ContentFilter => f.write_str("content_filter"),
             Self::FunctionCall => f.write_str("function_call"),
+            Self::Other(ref value) => f.write_str(value),
         }
     }
 }
@@ -2481,7 +2865,7 @@ impl ::std::str::FromStr for FinishReason {
             "tool_calls" => Ok(Self::ToolCalls),
             "content_filter" => Ok(Self::ContentFilter),
             "function_call" => Ok(Self::FunctionCall),
-            _ => Err("invalid value".into()),
+            _ => Ok(Self::Other(value.to_string())),
         }
     }
 }
@@ -2551,63 +2935,323 @@ pub struct FunctionObject {
     #[serde(default)]
     pub strict: bool,
 }
-/**The parameters the functions accepts, described as a JSON Schema object. See the [guide](/docs/guides/function-calling) for examples, and the [JSON Schema reference](https://json-schema.org/understanding-json-schema/) for documentation about the format.
-Omitting `parameters` defines a function with an empty parameter list.*/
+/**The parameters the functions accepts, described as a JSON Schema object. See the [guide](/docs/guides/function-calling) for examples, and the [JSON Schema reference](https://json-schema.org/understanding-json-schema/) for documentation about the format.
+Omitting `parameters` defines a function with an empty parameter list.*/
+///
+/// <details><summary>JSON schema</summary>
+///
+/// ```json
+///{
+///  "description": "The parameters the functions accepts, described as a JSON Schema object. See the [guide](/docs/guides/function-calling) for examples, and the [JSON Schema reference](https://json-schema.org/understanding-json-schema/) for documentation about the format. \nOmitting `parameters` defines a function with an empty parameter list.",
+///  "type": "object",
+///  "additionalProperties": true
+///}
+/// ```
+/// </details>
+#[derive(::serde::Deserialize, ::serde::Serialize, Clone, Debug)]
+#[serde(transparent)]
+pub struct FunctionParameters(pub ::serde_json::Map<::std::string::String, ::serde_json::Value>);
+impl ::std::ops::Deref for FunctionParameters {
+    type Target = ::serde_json::Map<::std::string::String, ::serde_json::Value>;
+    fn deref(&self) -> &::serde_json::Map<::std::string::String, ::serde_json::Value> {
+        &self.0
+    }
+}
+impl ::std::convert::From<FunctionParameters>
+    for ::serde_json::Map<::std::string::String, ::serde_json::Value>
+{
+    fn from(value: FunctionParameters) -> Self {
+        value.0
+    }
+}
+impl ::std::convert::From<::serde_json::Map<::std::string::String, ::serde_json::Value>>
+    for FunctionParameters
+{
+    fn from(value: ::serde_json::Map<::std::string::String, ::serde_json::Value>) -> Self {
+        Self(value)
+    }
+}
+///Image content part
+///
+/// <details><summary>JSON schema</summary>
+///
+/// ```json
+///{
+///  "description": "Image content part",
+///  "type": "object",
+///  "required": [
+///    "image_url",
+///    "type"
+///  ],
+///  "properties": {
+///    "image_url": {
+///      "$ref": "#/definitions/ImageURL"
+///    },
+///    "type": {
+///      "description": "Content type identifier",
+///      "type": "string",
+///      "enum": [
+///        "image_url"
+///      ]
+///    }
+///  }
+///}
+/// ```
+/// </details>
+#[derive(::serde::Deserialize, ::serde::Serialize, Clone, Debug)]
+pub struct ImageContentPart {
+    pub image_url: ImageUrl,
+    ///Content type identifier
+    #[serde(rename = "type")]
+    pub type_: ImageContentPartType,
+}
+///Content type identifier
+///
+/// <details><summary>JSON schema</summary>
+///
+/// ```json
+///{
+///  "description": "Content type identifier",
+///  "type": "string",
+///  "enum": [
+///    "image_url"
+///  ]
+///}
+/// ```
+/// </details>
+#[derive(
+    ::serde::Deserialize,
+    ::serde::Serialize,
+    Clone,
+    Copy,
+    Debug,
+    Eq,
+    Hash,
+    Ord,
+    PartialEq,
+    PartialOrd,
+)]
+pub enum ImageContentPartType {
+    #[serde(rename = "image_url")]
+    ImageUrl,
+}
+impl ::std::fmt::Display for ImageContentPartType {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        match *self {
+            Self::ImageUrl => f.write_str("image_url"),
+        }
+    }
+}
+impl ::std::str::FromStr for ImageContentPartType {
+    type Err = self::error::ConversionError;
+    fn from_str(value: &str) -> ::std::result::Result<Self, self::error::ConversionError> {
+        match value {
+            "image_url" => Ok(Self::ImageUrl),
+            _ => Err("invalid value".into()),
+        }
+    }
+}
+impl ::std::convert::TryFrom<&str> for ImageContentPartType {
+    type Error = self::error::ConversionError;
+    fn try_from(value: &str) -> ::std::result::Result<Self, self::error::ConversionError> {
+        value.parse()
+    }
+}
+impl ::std::convert::TryFrom<&::std::string::String> for ImageContentPartType {
+    type Error = self::error::ConversionError;
+    fn try_from(
+        value: &::std::string::String,
+    ) -> ::std::result::Result<Self, self::error::ConversionError> {
+        value.parse()
+    }
+}
+impl ::std::convert::TryFrom<::std::string::String> for ImageContentPartType {
+    type Error = self::error::ConversionError;
+    fn try_from(
+        value: ::std::string::String,
+    ) -> ::std::result::Result<Self, self::error::ConversionError> {
+        value.parse()
+    }
+}
+///Image URL configuration
+///
+/// <details><summary>JSON schema</summary>
+///
+/// ```json
+///{
+///  "description": "Image URL configuration",
+///  "type": "object",
+///  "required": [
+///    "url"
+///  ],
+///  "properties": {
+///    "detail": {
+///      "description": "Image detail level for vision processing",
+///      "default": "auto",
+///      "type": "string",
+///      "enum": [
+///        "auto",
+///        "low",
+///        "high"
+///      ]
+///    },
+///    "url": {
+///      "description": "URL of the image (data URLs supported)",
+///      "type": "string"
+///    }
+///  }
+///}
+/// ```
+/// </details>
+#[derive(::serde::Deserialize, ::serde::Serialize, Clone)]
+pub struct ImageUrl {
+    ///Image detail level for vision processing
+    #[serde(default = "defaults::image_url_detail")]
+    pub detail: ImageUrlDetail,
+    ///URL of the image (data URLs supported)
+    pub url: ::std::string::String,
+}
+///Image detail level for vision processing
+///
+/// <details><summary>JSON schema</summary>
+///
+/// ```json
+///{
+///  "description": "Image detail level for vision processing",
+///  "default": "auto",
+///  "type": "string",
+///  "enum": [
+///    "auto",
+///    "low",
+///    "high"
+///  ]
+///}
+/// ```
+/// </details>
+#[derive(
+    ::serde::Deserialize,
+    ::serde::Serialize,
+    Clone,
+    Copy,
+    Debug,
+    Eq,
+    Hash,
+    Ord,
+    PartialEq,
+    PartialOrd,
+)]
+pub enum ImageUrlDetail {
+    #[serde(rename = "auto")]
+    Auto,
+    #[serde(rename = "low")]
+    Low,
+    #[serde(rename = "high")]
+    High,
+}
+impl ::std::fmt::Display for ImageUrlDetail {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        match *self {
+            Self::Auto => f.write_str("auto"),
+            Self::Low => f.write_str("low"),
+            Self::High => f.write_str("high"),
+        }
+    }
+}
+impl ::std::str::FromStr for ImageUrlDetail {
+    type Err = self::error::ConversionError;
+    fn from_str(value: &str) -> ::std::result::Result<Self, self::error::ConversionError> {
+        match value {
+            "auto" => Ok(Self::Auto),
+            "low" => Ok(Self::Low),
+            "high" => Ok(Self::High),
+            _ => Err("invalid value".into()),
+        }
+    }
+}
+impl ::std::convert::TryFrom<&str> for ImageUrlDetail {
+    type Error = self::error::ConversionError;
+    fn try_from(value: &str) -> ::std::result::Result<Self, self::error::ConversionError> {
+        value.parse()
+    }
+}
+impl ::std::convert::TryFrom<&::std::string::String> for ImageUrlDetail {
+    type Error = self::error::ConversionError;
+    fn try_from(
+        value: &::std::string::String,
+    ) -> ::std::result::Result<Self, self::error::ConversionError> {
+        value.parse()
+    }
+}
+impl ::std::convert::TryFrom<::std::string::String> for ImageUrlDetail {
+    type Error = self::error::ConversionError;
+    fn try_from(
+        value: ::std::string::String,
+    ) -> ::std::result::Result<Self, self::error::ConversionError> {
+        value.parse()
+    }
+}
+impl ::std::default::Default for ImageUrlDetail {
+    fn default() -> Self {
+        ImageUrlDetail::Auto
+    }
+}
+///Base64-encoded audio input
 ///
 /// <details><summary>JSON schema</summary>
 ///
 /// ```json
 ///{
-///  "description": "The parameters the functions accepts, described as a JSON Schema object. See the [guide](/docs/guides/function-calling) for examples, and the [JSON Schema reference](https://json-schema.org/understanding-json-schema/) for documentation about the format. \nOmitting `parameters` defines a function with an empty parameter list.",
+///  "description": "Base64-encoded audio input",
 ///  "type": "object",
-///  "additionalProperties": true
+///  "required": [
+///    "data",
+///    "format"
+///  ],
+///  "properties": {
+///    "data": {
+///      "description": "Base64-encoded audio data",
+///      "type": "string"
+///    },
+///    "format": {
+///      "description": "Encoding of the audio data",
+///      "type": "string",
+///      "enum": [
+///        "wav",
+///        "mp3"
+///      ]
+///    }
+///  }
 ///}
 /// ```
 /// </details>
-#[derive(::serde::Deserialize, ::serde::Serialize, Clone, Debug)]
-#[serde(transparent)]
-pub struct FunctionParameters(pub ::serde_json::Map<::std::string::String, ::serde_json::Value>);
-impl ::std::ops::Deref for FunctionParameters {
-    type Target = ::serde_json::Map<::std::string::String, ::serde_json::Value>;
-    fn deref(&self) -> &::serde_json::Map<::std::string::String, ::serde_json::Value> {
-        &self.0
-    }
-}
-impl ::std::convert::From<FunctionParameters>
-    for ::serde_json::Map<::std::string::String, ::serde_json::Value>
-{
-    fn from(value: FunctionParameters) -> Self {
-        value.0
-    }
-}
-impl ::std::convert::From<::serde_json::Map<::std::string::String, ::serde_json::Value>>
-    for FunctionParameters
-{
-    fn from(value: ::serde_json::Map<::std::string::String, ::serde_json::Value>) -> Self {
-        Self(value)
-    }
+#[derive(::serde::Deserialize, ::serde::Serialize, Clone)]
+pub struct InputAudio {
+    ///Base64-encoded audio data
+    pub data: ::std::string::String,
+    ///Encoding of the audio data
+    pub format: InputAudioFormat,
 }
-///Image content part
+///Audio content part
 ///
 /// <details><summary>JSON schema</summary>
 ///
 /// ```json
 ///{
-///  "description": "Image content part",
+///  "description": "Audio content part",
 ///  "type": "object",
 ///  "required": [
-///    "image_url",
+///    "input_audio",
 ///    "type"
 ///  ],
 ///  "properties": {
-///    "image_url": {
-///      "$ref": "#/definitions/ImageURL"
+///    "input_audio": {
+///      "$ref": "#/definitions/InputAudio"
 ///    },
 ///    "type": {
 ///      "description": "Content type identifier",
 ///      "type": "string",
 ///      "enum": [
-///        "image_url"
+///        "input_audio"
 ///      ]
 ///    }
 ///  }
@@ -2615,11 +3259,11 @@ impl ::std::convert::From<::serde_json::Map<::std::string::String, ::serde_json:
 /// ```
 /// </details>
 #[derive(::serde::Deserialize, ::serde::Serialize, Clone, Debug)]
-pub struct ImageContentPart {
-    pub image_url: ImageUrl,
+pub struct InputAudioContentPart {
+    pub input_audio: InputAudio,
     ///Content type identifier
     #[serde(rename = "type")]
-    pub type_: ImageContentPartType,
+    pub type_: InputAudioContentPartType,
 }
 ///Content type identifier
 ///
@@ -2630,7 +3274,7 @@ pub struct ImageContentPart {
 ///  "description": "Content type identifier",
 ///  "type": "string",
 ///  "enum": [
-///    "image_url"
+///    "input_audio"
 ///  ]
 ///}
 /// ```
@@ -2647,33 +3291,33 @@ pub struct ImageContentPart {
     PartialEq,
     PartialOrd,
 )]
-pub enum ImageContentPartType {
-    #[serde(rename = "image_url")]
-    ImageUrl,
+pub enum InputAudioContentPartType {
+    #[serde(rename = "input_audio")]
+    InputAudio,
 }
-impl ::std::fmt::Display for ImageContentPartType {
+impl ::std::fmt::Display for InputAudioContentPartType {
     fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
         match *self {
-            Self::ImageUrl => f.write_str("image_url"),
+            Self::InputAudio => f.write_str("input_audio"),
         }
     }
 }
-impl ::std::str::FromStr for ImageContentPartType {
+impl ::std::str::FromStr for InputAudioContentPartType {
     type Err = self::error::ConversionError;
     fn from_str(value: &str) -> ::std::result::Result<Self, self::error::ConversionError> {
         match value {
-            "image_url" => Ok(Self::ImageUrl),
+            "input_audio" => Ok(Self::InputAudio),
             _ => Err("invalid value".into()),
         }
     }
 }
-impl ::std::convert::TryFrom<&str> for ImageContentPartType {
+impl ::std::convert::TryFrom<&str> for InputAudioContentPartType {
     type Error = self::error::ConversionError;
     fn try_from(value: &str) -> ::std::result::Result<Self, self::error::ConversionError> {
         value.parse()
     }
 }
-impl ::std::convert::TryFrom<&::std::string::String> for ImageContentPartType {
+impl ::std::convert::TryFrom<&::std::string::String> for InputAudioContentPartType {
     type Error = self::error::ConversionError;
     fn try_from(
         value: &::std::string::String,
@@ -2681,7 +3325,7 @@ impl ::std::convert::TryFrom<&::std::string::String> for ImageContentPartType {
         value.parse()
     }
 }
-impl ::std::convert::TryFrom<::std::string::String> for ImageContentPartType {
+impl ::std::convert::TryFrom<::std::string::String> for InputAudioContentPartType {
     type Error = self::error::ConversionError;
     fn try_from(
         value: ::std::string::String,
@@ -2689,57 +3333,17 @@ impl ::std::convert::TryFrom<::std::string::String> for ImageContentPartType {
         value.parse()
     }
 }
-///Image URL configuration
-///
-/// <details><summary>JSON schema</summary>
-///
-/// ```json
-///{
-///  "description": "Image URL configuration",
-///  "type": "object",
-///  "required": [
-///    "url"
-///  ],
-///  "properties": {
-///    "detail": {
-///      "description": "Image detail level for vision processing",
-///      "default": "auto",
-///      "type": "string",
-///      "enum": [
-///        "auto",
-///        "low",
-///        "high"
-///      ]
-///    },
-///    "url": {
-///      "description": "URL of the image (data URLs supported)",
-///      "type": "string"
-///    }
-///  }
-///}
-/// ```
-/// </details>
-#[derive(::serde::Deserialize, ::serde::Serialize, Clone, Debug)]
-pub struct ImageUrl {
-    ///Image detail level for vision processing
-    #[serde(default = "defaults::image_url_detail")]
-    pub detail: ImageUrlDetail,
-    ///URL of the image (data URLs supported)
-    pub url: ::std::string::String,
-}
-///Image detail level for vision processing
+///Encoding of the audio data
 ///
 /// <details><summary>JSON schema</summary>
 ///
 /// ```json
 ///{
-///  "description": "Image detail level for vision processing",
-///  "default": "auto",
+///  "description": "Encoding of the audio data",
 ///  "type": "string",
 ///  "enum": [
-///    "auto",
-///    "low",
-///    "high"
+///    "wav",
+///    "mp3"
 ///  ]
 ///}
 /// ```
@@ -2756,41 +3360,37 @@ pub struct ImageUrl {
     PartialEq,
     PartialOrd,
 )]
-pub enum ImageUrlDetail {
-    #[serde(rename = "auto")]
-    Auto,
-    #[serde(rename = "low")]
-    Low,
-    #[serde(rename = "high")]
-    High,
+pub enum InputAudioFormat {
+    #[serde(rename = "wav")]
+    Wav,
+    #[serde(rename = "mp3")]
+    Mp3,
 }
-impl ::std::fmt::Display for ImageUrlDetail {
+impl ::std::fmt::Display for InputAudioFormat {
     fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
         match *self {
-            Self::Auto => f.write_str("auto"),
-            Self::Low => f.write_str("low"),
-            Self::High => f.write_str("high"),
+            Self::Wav => f.write_str("wav"),
+            Self::Mp3 => f.write_str("mp3"),
         }
     }
 }
-impl ::std::str::FromStr for ImageUrlDetail {
+impl ::std::str::FromStr for InputAudioFormat {
     type Err = self::error::ConversionError;
     fn from_str(value: &str) -> ::std::result::Result<Self, self::error::ConversionError> {
         match value {
-            "auto" => Ok(Self::Auto),
-            "low" => Ok(Self::Low),
-            "high" => Ok(Self::High),
+            "wav" => Ok(Self::Wav),
+            "mp3" => Ok(Self::Mp3),
             _ => Err("invalid value".into()),
         }
     }
 }
-impl ::std::convert::TryFrom<&str> for ImageUrlDetail {
+impl ::std::convert::TryFrom<&str> for InputAudioFormat {
     type Error = self::error::ConversionError;
     fn try_from(value: &str) -> ::std::result::Result<Self, self::error::ConversionError> {
         value.parse()
     }
 }
-impl ::std::convert::TryFrom<&::std::string::String> for ImageUrlDetail {
+impl ::std::convert::TryFrom<&::std::string::String> for InputAudioFormat {
     type Error = self::error::ConversionError;
     fn try_from(
         value: &::std::string::String,
@@ -2798,7 +3398,7 @@ impl ::std::convert::TryFrom<&::std::string::String> for ImageUrlDetail {
         value.parse()
     }
 }
-impl ::std::convert::TryFrom<::std::string::String> for ImageUrlDetail {
+impl ::std::convert::TryFrom<::std::string::String> for InputAudioFormat {
     type Error = self::error::ConversionError;
     fn try_from(
         value: ::std::string::String,
@@ -2806,9 +3406,50 @@ impl ::std::convert::TryFrom<::std::string::String> for ImageUrlDetail {
         value.parse()
     }
 }
-impl ::std::default::Default for ImageUrlDetail {
+///A file attached by id or inline as a data URL
+///
+/// <details><summary>JSON schema</summary>
+///
+/// ```json
+///{
+///  "description": "A file attached by id or inline as a data URL",
+///  "type": "object",
+///  "properties": {
+///    "file_data": {
+///      "description": "Base64 data URL of the file contents",
+///      "type": "string"
+///    },
+///    "file_id": {
+///      "description": "ID of a file previously uploaded to the provider",
+///      "type": "string"
+///    },
+///    "filename": {
+///      "description": "Name of the file, used with inline data",
+///      "type": "string"
+///    }
+///  }
+///}
+/// ```
+/// </details>
+#[derive(::serde::Deserialize, ::serde::Serialize, Clone)]
+pub struct InputFile {
+    ///Base64 data URL of the file contents
+    #[serde(default, skip_serializing_if = "::std::option::Option::is_none")]
+    pub file_data: ::std::option::Option<::std::string::String>,
+    ///ID of a file previously uploaded to the provider
+    #[serde(default, skip_serializing_if = "::std::option::Option::is_none")]
+    pub file_id: ::std::option::Option<::std::string::String>,
+    ///Name of the file, used with inline data
+    #[serde(default, skip_serializing_if = "::std::option::Option::is_none")]
+    pub filename: ::std::option::Option<::std::string::String>,
+}
+impl ::std::default::Default for InputFile {
     fn default() -> Self {
-        ImageUrlDetail::Auto
+        Self {
+            file_data: Default::default(),
+            file_id: Default::default(),
+            filename: Default::default(),
+        }
     }
 }
 ///Response structure for listing models
@@ -2944,9 +3585,23 @@ pub struct McpTool {
 ///    "role"
 ///  ],
 ///  "properties": {
+///    "annotations": {
+///      "description": "Sources the model cited, for web-search-enabled models.",
+///      "type": "array",
+///      "items": {
+///        "$ref": "#/definitions/ChatCompletionAnnotation"
+///      }
+///    },
+///    "audio": {
+///      "$ref": "#/definitions/ChatCompletionMessageAudio"
+///    },
 ///    "content": {
 ///      "$ref": "#/definitions/MessageContent"
 ///    },
+///    "name": {
+///      "description": "An optional name for the participant, to tell apart several users or name the tool a message comes from.",
+///      "type": "string"
+///    },
 ///    "reasoning": {
 ///      "description": "The reasoning of the chunk message. Same as reasoning_content.",
 ///      "type": "string"
@@ -2959,7 +3614,7 @@ pub struct McpTool {
 ///      "$ref": "#/definitions/MessageRole"
 ///    },
 ///    "tool_call_id": {
-///      "type": "string"
+///      "$ref": "#/definitions/ToolCallId"
 ///    },
 ///    "tool_calls": {
 ///      "type": "array",
@@ -2971,9 +3626,17 @@ pub struct McpTool {
 ///}
 /// ```
 /// </details>
-#[derive(::serde::Deserialize, ::serde::Serialize, Clone, Debug)]
+#[derive(::serde::Deserialize, ::serde::Serialize, Clone)]
 pub struct Message {
-    pub content: MessageContent,
+    ///Sources the model cited, for web-search-enabled models.
+    #[serde(default, skip_serializing_if = "::std::vec::Vec::is_empty")]
+    pub annotations: ::std::vec::Vec<ChatCompletionAnnotation>,
+    #[serde(default, skip_serializing_if = "::std::option::Option::is_none")]
+    pub audio: ::std::option::Option<ChatCompletionMessageAudio>,
+    pub content: crate::MessageContent,
+    ///An optional name for the participant, to tell apart several users or name the tool a message comes from.
+    #[serde(default, skip_serializing_if = "::std::option::Option::is_none")]
+    pub name: ::std::option::Option<::std::string::String>,
     ///The reasoning of the chunk message. Same as reasoning_content.
     #[serde(default, skip_serializing_if = "::std::option::Option::is_none")]
     pub reasoning: ::std::option::Option<::std::string::String>,
@@ -2982,44 +3645,10 @@ pub struct Message {
     pub reasoning_content: ::std::option::Option<::std::string::String>,
     pub role: MessageRole,
     #[serde(default, skip_serializing_if = "::std::option::Option::is_none")]
-    pub tool_call_id: ::std::option::Option<::std::string::String>,
+    pub tool_call_id: ::std::option::Option<crate::ToolCallId>,
     #[serde(default, skip_serializing_if = "::std::vec::Vec::is_empty")]
     pub tool_calls: ::std::vec::Vec<ChatCompletionMessageToolCall>,
 }
-///Message content - either text or multimodal content parts
-///
-/// <details><summary>JSON schema</summary>
-///
-/// ```json
-///{
-///  "description": "Message content - either text or multimodal content parts",
-///  "oneOf": [
-///    {
-///      "description": "Text content (backward compatibility)",
-///      "type": "string"
-///    },
-///    {
-///      "description": "Array of content parts for multimodal messages",
-///      "type": "array",
-///      "items": {
-///        "$ref": "#/definitions/ContentPart"
-///      }
-///    }
-///  ]
-///}
-/// ```
-/// </details>
-#[derive(::serde::Deserialize, ::serde::Serialize, Clone, Debug)]
-#[serde(untagged)]
-pub enum MessageContent {
-    String(::std::string::String),
-    Array(::std::vec::Vec<ContentPart>),
-}
-impl ::std::convert::From<::std::vec::Vec<ContentPart>> for MessageContent {
-    fn from(value: ::std::vec::Vec<ContentPart>) -> Self {
-        Self::Array(value)
-    }
-}
 ///Role of the message sender
 ///
 /// <details><summary>JSON schema</summary>
@@ -3032,7 +3661,8 @@ impl ::std::convert::From<::std::vec::Vec<ContentPart>> for MessageContent {
 ///    "system",
 ///    "user",
 ///    "assistant",
-///    "tool"
+///    "tool",
+///    "developer"
 ///  ]
 ///}
 /// ```
@@ -3055,9 +3685,12 @@ pub enum MessageRole {
     #[serde(rename = "user")]
     User,
     #[serde(rename = "assistant")]
+    #[serde(alias = "model")]
     Assistant,
     #[serde(rename = "tool")]
     Tool,
+    #[serde(rename = "developer")]
+    Developer,
 }
 impl ::std::fmt::Display for MessageRole {
     fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
@@ -3066,6 +3699,7 @@ impl ::std::fmt::Display for MessageRole {
             Self::User => f.write_str("user"),
             Self::Assistant => f.write_str("assistant"),
             Self::Tool => f.write_str("tool"),
+            Self::Developer => f.write_str("developer"),
         }
     }
 }
@@ -3077,6 +3711,7 @@ impl ::std::str::FromStr for MessageRole {
             "user" => Ok(Self::User),
             "assistant" => Ok(Self::Assistant),
             "tool" => Ok(Self::Tool),
+            "developer" => Ok(Self::Developer),
             _ => Err("invalid value".into()),
         }
     }
@@ -3248,7 +3883,7 @@ document or a URL.
 ///}
 /// ```
 /// </details>
-#[derive(::serde::Deserialize, ::serde::Serialize, Clone, Debug)]
+#[derive(::serde::Deserialize, ::serde::Serialize, Clone)]
 pub struct MessagesDocumentSource {
     /**Base64-encoded document data. Required when `type` is `base64`.
      */
@@ -3636,7 +4271,7 @@ image or a URL.
 ///}
 /// ```
 /// </details>
-#[derive(::serde::Deserialize, ::serde::Serialize, Clone, Debug)]
+#[derive(::serde::Deserialize, ::serde::Serialize, Clone)]
 pub struct MessagesImageSource {
     /**Base64-encoded image data. Required when `type` is `base64`.
      */
@@ -3801,7 +4436,7 @@ content blocks.
 ///}
 /// ```
 /// </details>
-#[derive(::serde::Deserialize, ::serde::Serialize, Clone, Debug)]
+#[derive(::serde::Deserialize, ::serde::Serialize, Clone)]
 #[serde(untagged)]
 pub enum MessagesMessageContent {
     String(::std::string::String),
@@ -4338,16 +4973,7 @@ impl ::std::convert::TryFrom<::std::string::String> for MessagesResponseRole {
 /// ```
 /// </details>
 #[derive(
-    ::serde::Deserialize,
-    ::serde::Serialize,
-    Clone,
-    Copy,
-    Debug,
-    Eq,
-    Hash,
-    Ord,
-    PartialEq,
-    PartialOrd,
+    ::serde::Deserialize, ::serde::Serialize, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd,
 )]
 pub enum MessagesResponseStopReason {
     #[serde(rename = "end_turn")]
@@ -4362,6 +4988,9 @@ pub enum MessagesResponseStopReason {
     PauseTurn,
     #[serde(rename = "refusal")]
     Refusal,
+    /// A value this SDK doesn't know yet, as it was sent.
+    #[serde(untagged)]
+    Other(::std::string::String),
 }
 impl ::std::fmt::Display for MessagesResponseStopReason {
     fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
@@ -4372,6 +5001,7 @@ impl ::std::fmt::Display for MessagesResponseStopReason {
             Self::ToolUse => f.write_str("tool_use"),
             Self::PauseTurn => f.write_str("pause_turn"),
             Self::Refusal => f.write_str("refusal"),
+            Self::Other(ref value) => f.write_str(value),
         }
     }
 }
@@ -4385,7 +5015,7 @@ impl ::std::str::FromStr for MessagesResponseStopReason {
             "tool_use" => Ok(Self::ToolUse),
             "pause_turn" => Ok(Self::PauseTurn),
             "refusal" => Ok(Self::Refusal),
-            _ => Err("invalid value".into()),
+            _ => Ok(Self::Other(value.to_string())),
         }
     }
 }
@@ -4643,7 +5273,7 @@ Contains the incremental update.
 ///}
 /// ```
 /// </details>
-#[derive(::serde::Deserialize, ::serde::Serialize, Clone, Debug)]
+#[derive(::serde::Deserialize, ::serde::Serialize, Clone)]
 pub struct MessagesStreamEventDelta {
     /**The incremental JSON string of the tool input
     (for `input_json_delta`).
@@ -4712,16 +5342,7 @@ impl ::std::default::Default for MessagesStreamEventDelta {
 /// ```
 /// </details>
 #[derive(
-    ::serde::Deserialize,
-    ::serde::Serialize,
-    Clone,
-    Copy,
-    Debug,
-    Eq,
-    Hash,
-    Ord,
-    PartialEq,
-    PartialOrd,
+    ::serde::Deserialize, ::serde::Serialize, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd,
 )]
 pub enum MessagesStreamEventType {
     #[serde(rename = "message_start")]
@@ -4740,6 +5361,9 @@ pub enum MessagesStreamEventType {
     Ping,
     #[serde(rename = "error")]
     Error,
+    /// A value this SDK doesn't know yet, as it was sent.
+    #[serde(untagged)]
+    Other(::std::string::String),
 }
 impl ::std::fmt::Display for MessagesStreamEventType {
     fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
@@ -4752,6 +5376,7 @@ impl ::std::fmt::Display for MessagesStreamEventType {
             Self::MessageStop => f.write_str("message_stop"),
             Self::Ping => f.write_str("ping"),
             Self::Error => f.write_str("error"),
+            Self::Other(ref value) => f.write_str(value),
         }
     }
 }
@@ -4767,7 +5392,7 @@ impl ::std::str::FromStr for MessagesStreamEventType {
             "message_stop" => Ok(Self::MessageStop),
             "ping" => Ok(Self::Ping),
             "error" => Ok(Self::Error),
-            _ => Err("invalid value".into()),
+            _ => Ok(Self::Other(value.to_string())),
         }
     }
 }
@@ -4824,7 +5449,7 @@ impl ::std::convert::TryFrom<::std::string::String> for MessagesStreamEventType
 ///}
 /// ```
 /// </details>
-#[derive(::serde::Deserialize, ::serde::Serialize, Clone, Debug)]
+#[derive(::serde::Deserialize, ::serde::Serialize, Clone)]
 pub struct MessagesTextBlock {
     #[serde(default, skip_serializing_if = "::std::option::Option::is_none")]
     pub cache_control: ::std::option::Option<CacheControl>,
@@ -4935,7 +5560,7 @@ impl ::std::convert::TryFrom<::std::string::String> for MessagesTextBlockType {
 ///}
 /// ```
 /// </details>
-#[derive(::serde::Deserialize, ::serde::Serialize, Clone, Debug)]
+#[derive(::serde::Deserialize, ::serde::Serialize, Clone)]
 pub struct MessagesThinkingBlock {
     /**The signature for verifying the thinking content. Must be
     passed back when continuing a conversation with extended thinking.
@@ -5352,7 +5977,7 @@ pub struct MessagesToolResultBlock {
 ///}
 /// ```
 /// </details>
-#[derive(::serde::Deserialize, ::serde::Serialize, Clone, Debug)]
+#[derive(::serde::Deserialize, ::serde::Serialize, Clone)]
 #[serde(untagged)]
 pub enum MessagesToolResultBlockContent {
     String(::std::string::String),
@@ -5470,7 +6095,7 @@ impl ::std::convert::TryFrom<::std::string::String> for MessagesToolResultBlockT
 ///}
 /// ```
 /// </details>
-#[derive(::serde::Deserialize, ::serde::Serialize, Clone, Debug)]
+#[derive(::serde::Deserialize, ::serde::Serialize, Clone)]
 pub struct MessagesToolUseBlock {
     ///The unique identifier for this tool use block.
     pub id: ::std::string::String,
@@ -9582,7 +10207,7 @@ impl ::std::convert::TryFrom<::std::string::String> for SsEventEvent {
 ///}
 /// ```
 /// </details>
-#[derive(::serde::Deserialize, ::serde::Serialize, Clone, Debug)]
+#[derive(::serde::Deserialize, ::serde::Serialize, Clone)]
 pub struct TextContentPart {
     ///The text content
     pub text: ::std::string::String,
@@ -9733,6 +10358,54 @@ impl ::std::default::Default for ToolCallExtraContentGoogle {
         }
     }
 }
+///A web page cited by a web-search-enabled model.
+///
+/// <details><summary>JSON schema</summary>
+///
+/// ```json
+///{
+///  "description": "A web page cited by a web-search-enabled model.",
+///  "type": "object",
+///  "required": [
+///    "end_index",
+///    "start_index",
+///    "title",
+///    "url"
+///  ],
+///  "properties": {
+///    "end_index": {
+///      "description": "Index one past the last character of the cited span in the message text.",
+///      "type": "integer",
+///      "minimum": 0.0
+///    },
+///    "start_index": {
+///      "description": "Index of the first character of the cited span in the message text.",
+///      "type": "integer",
+///      "minimum": 0.0
+///    },
+///    "title": {
+///      "description": "Title of the cited page.",
+///      "type": "string"
+///    },
+///    "url": {
+///      "description": "URL of the cited page.",
+///      "type": "string"
+///    }
+///  }
+///}
+/// ```
+/// </details>
+#[derive(::serde::Deserialize, ::serde::Serialize, Clone, Debug)]
+pub struct UrlCitation {
+    ///Index one past the last character of the cited span in the message text.
+    pub end_index: u64,
+    ///Index of the first character of the cited span in the message text.
+    pub start_index: u64,
+    ///Title of the cited page.
+    pub title: ::std::string::String,
+    ///URL of the cited page.
+    pub url: ::std::string::String,
+}
 /// Generation of default values for serde.
 pub mod defaults {
     pub(super) fn default_bool<const V: bool>() -> bool {
@@ -0,0 +1,179 @@
+//! Polling long-running jobs to completion.
+//!
+//! Endpoints that can't answer right away, such as batches, background
+//! responses or long agent tasks, accept the work with `202 Accepted` and a
+//! URL to check on it. [`InferenceGatewayClient::poll_until_complete`]
+//! fetches that URL at growing intervals until the job reports a terminal
+//! [`JobState`], or the [`PollPolicy`]'s deadline passes.
+//!
+//! [`InferenceGatewayClient::poll_until_complete`]: crate::InferenceGatewayClient::poll_until_complete
+
+use std::time::Duration;
+
+use serde_json::Value;
+
+/// A job to poll: the URL its status is served at.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Job {
+    location: String,
+}
+
+impl Job {
+    /// The job whose status is at `location`, typically a `Location` header.
+    /// A relative location is resolved against the client's API URL, so
+    /// `"batches/batch_1"` and `"/v1/batches/batch_1"` both work.
+    pub fn new(location: impl Into<String>) -> Self {
+        Self {
+            location: location.into(),
+        }
+    }
+
+    /// The location the job was created with.
+    pub fn location(&self) -> &str {
+        &self.location
+    }
+}
+
+/// How often to poll a job, and for how long.
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use inference_gateway_sdk::PollPolicy;
+///
+/// let policy = PollPolicy::new()
+///     .with_intervals(Duration::from_millis(500), Duration::from_secs(10))
+///     .with_deadline(Duration::from_secs(30 * 60));
+/// ```
+#[derive(Clone, Debug)]
+pub struct PollPolicy {
+    interval: Duration,
+    max_interval: Duration,
+    multiplier: f64,
+    deadline: Option<Duration>,
+}
+
+impl Default for PollPolicy {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(1),
+            max_interval: Duration::from_secs(30),
+            multiplier: 2.0,
+            deadline: Some(Duration::from_secs(10 * 60)),
+        }
+    }
+}
+
+impl PollPolicy {
+    /// Polls after 1s, doubling the interval up to 30s, for at most 10
+    /// minutes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Waits `first` before the second poll, growing the interval up to
+    /// `max`.
+    pub fn with_intervals(mut self, first: Duration, max: Duration) -> Self {
+        self.interval = first;
+        self.max_interval = max.max(first);
+        self
+    }
+
+    /// Multiplies the interval by `multiplier`, at least 1.0, after each
+    /// poll.
+    pub fn with_multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier.max(1.0);
+        self
+    }
+
+    /// Gives up with [`GatewayError::JobDeadlineExceeded`](crate::GatewayError::JobDeadlineExceeded)
+    /// once the job has been polled for `deadline`.
+    pub fn with_deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Polls until the job finishes, however long that takes.
+    pub fn without_deadline(mut self) -> Self {
+        self.deadline = None;
+        self
+    }
+
+    pub(crate) fn first_interval(&self) -> Duration {
+        self.interval
+    }
+
+    pub(crate) fn deadline(&self) -> Option<Duration> {
+        self.deadline
+    }
+
+    /// The interval after `interval`.
+    pub(crate) fn next_interval(&self, interval: Duration) -> Duration {
+        interval.mul_f64(self.multiplier).min(self.max_interval)
+    }
+}
+
+/// Where a job stands.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum JobState {
+    /// Queued or running.
+    Pending,
+    /// Finished with a result. Responses that stopped early, reported as
+    /// `incomplete`, count as completed; their body says why.
+    Completed,
+    /// Failed or was rejected.
+    Failed,
+    /// Cancelled before it finished.
+    Cancelled,
+    /// Didn't finish before its provider-side time limit.
+    Expired,
+    /// Waiting on input or authorization from the caller, which polling
+    /// won't provide.
+    InputRequired,
+}
+
+impl JobState {
+    /// Reads a status from the vocabularies of batch, background response
+    /// and agent task APIs. Statuses it doesn't know are taken as pending.
+    pub fn from_status(status: &str) -> Self {
+        match status.to_ascii_lowercase().replace('-', "_").as_str() {
+            "completed" | "succeeded" | "incomplete" => Self::Completed,
+            "failed" | "rejected" => Self::Failed,
+            "cancelled" | "canceled" => Self::Cancelled,
+            "expired" => Self::Expired,
+            "input_required" | "auth_required" => Self::InputRequired,
+            _ => Self::Pending,
+        }
+    }
+
+    /// Whether the job won't change state any more.
+    pub fn is_terminal(self) -> bool {
+        self != Self::Pending
+    }
+
+    /// The state reported in a job's status `body`: its `status` string,
+    /// an agent task's `status.state`, or a top-level `state`.
+    pub(crate) fn of(body: &Value) -> Self {
+        let status = body
+            .get("status")
+            .and_then(|status| status.as_str().or_else(|| status.get("state")?.as_str()))
+            .or_else(|| body.get("state")?.as_str());
+        status.map_or(Self::Pending, Self::from_status)
+    }
+}
+
+/// A finished job: the state it ended in and its last status body.
+#[derive(Clone, Debug)]
+pub struct JobOutcome<T> {
+    /// The terminal state.
+    pub state: JobState,
+    /// The job's final status body.
+    pub body: T,
+}
+
+impl<T> JobOutcome<T> {
+    /// Whether the job completed.
+    pub fn is_completed(&self) -> bool {
+        self.state == JobState::Completed
+    }
+}
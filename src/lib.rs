@@ -7,29 +7,167 @@
 //! `openapi.yaml` and re-exported at the crate root. Run `task generate-types`
 //! to regenerate them after a spec bump.
 
+pub mod attachment;
+mod audio;
+mod balance;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+mod cache;
+mod chat_request;
+#[cfg(feature = "conformance")]
+pub mod conformance;
+mod conversation;
+mod cost;
+mod decode;
+mod download;
+mod embeddings;
+mod experiment;
 mod ext;
+mod failover;
 mod generated;
+mod ids;
+mod jobs;
+mod list_stream;
+#[cfg(feature = "loadtest")]
+pub mod loadtest;
+mod memory;
+mod message_content;
+mod metrics;
+mod middleware;
+#[cfg(feature = "mock")]
+pub mod mock;
+mod model_ref;
+mod moderation;
+mod normalize;
+mod options;
+mod partial_json;
+pub mod privacy;
+mod rate_limit;
+mod replay;
+mod retry;
+mod router;
+mod selector;
+mod shadow;
+mod sse;
+mod stream;
+#[cfg(feature = "structured")]
+mod structured;
+mod tokens;
+mod tool_registry;
+#[cfg(feature = "trace")]
+mod trace;
+mod watch;
 
+pub use audio::{
+    AudioFile, SpeechFormat, SpeechOptions, TimestampGranularity, Transcription,
+    TranscriptionEvent, TranscriptionOptions, TranscriptionSegment, TranscriptionWord, Voice,
+};
+pub use balance::LoadBalancing;
+pub use cache::{CacheStore, InMemoryCache};
+pub use chat_request::ChatCompletionRequestBuilder;
+pub use conversation::{Conversation, SummaryPolicy};
+pub use cost::{Cost, CostTracker, ModelPrice, PricingTable};
+pub use download::{Artifact, DownloadSummary};
+#[cfg(feature = "quantize")]
+pub use embeddings::QuantizedEmbedding;
+pub use embeddings::{
+    EmbeddedText, Embedding, EmbeddingPipeline, EmbeddingRequest, EmbeddingResponse,
+    EmbeddingUsage, EncodingFormat,
+};
+pub use experiment::{ArmStats, Experiment, ExperimentResponse, Variant};
+pub use failover::{FailoverConfig, FailoverEvent};
+pub use generated::models;
 pub use generated::schemas::*;
+pub use ids::{CompletionId, ToolCallId};
+#[cfg(feature = "macros")]
+pub use inference_gateway_sdk_macros::tool;
+pub use jobs::{Job, JobOutcome, JobState, PollPolicy};
+pub use memory::{FileStore, InMemoryStore, MemoryStore};
+pub use message_content::MessageContent;
+pub use metrics::{CompletionEvent, MetricsObserver, RequestMetrics};
+pub use middleware::Middleware;
+pub use model_ref::{ModelRef, ModelRefError};
+pub use moderation::{ModerationCategories, ModerationInput, ModerationResponse, ModerationResult};
+pub use options::{
+    ChatCompletionOptions, InstructionRole, Priority, RequestOptions, SchedulingHints,
+};
+pub use partial_json::parse_partial_json;
+pub use rate_limit::{OverflowPolicy, ProviderLimits};
+pub use replay::{Cassette, Exchange, ReplayClient};
+pub use reqwest::{Method, StatusCode};
+pub use retry::{FailureEvent, RetryConfig, RetryEvent};
+pub use router::{RouteRequest, RouteRule, Router};
+pub use selector::{Candidate, Capability, LatencyClass, ModelSelector, SelectionPolicy};
+pub use shadow::{ShadowComparison, ShadowConfig, ShadowResult};
+pub use sse::SseDecoder;
+pub use stream::{
+    ChatCompletionStreamExt, ChoiceStream, CoalescePolicy, FoldedCompletion, SseStreamExt,
+};
+#[cfg(feature = "structured")]
+pub use structured::json_schema_format;
+#[doc(hidden)]
+pub use tool_registry::__private;
+pub use tool_registry::{Tool, ToolRegistry, ToolRun};
+#[cfg(feature = "trace")]
+pub use trace::TraceContext;
 
+// Lets `#[tool]` output name this crate by its path in the crate's own tests.
+extern crate self as inference_gateway_sdk;
+
+use std::collections::{BTreeSet, HashMap};
 use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-use futures_util::{Stream, StreamExt};
-use reqwest::{Client, StatusCode};
+use futures_util::future::{Either, join_all};
+use futures_util::{Stream, StreamExt, TryStreamExt};
+use reqwest::multipart::{Form, Part};
+use reqwest::{Client, RequestBuilder};
 use thiserror::Error;
+use tokio::io::AsyncWrite;
+use zeroize::Zeroizing;
+
+use crate::balance::EndpointPool;
+use crate::metrics::{
+    CallRecorder, EventReading, EventSink, MetricsCallback, StatusSlot, StreamKind, record_stream,
+};
+use crate::rate_limit::{ProviderThrottle, QueueLimit, RateLimitBudget};
+
+/// How long a feature the gateway reported as disabled is refused without
+/// asking the gateway again.
+const FEATURE_RECHECK_INTERVAL: Duration = Duration::from_secs(60);
 
 /// Stream of Server-Sent Events (SSE) yielded by [`InferenceGatewayAPI::generate_content_stream`].
 ///
 /// This is the SDK's own SSE wrapper used by the streaming function. It is distinct
 /// from the spec's [`SsEvent`] (which constrains `event` to a fixed enum) - the
 /// streaming function may surface arbitrary event names produced by upstream providers.
-#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[derive(serde::Serialize, serde::Deserialize)]
 pub struct SSEvents {
     pub data: String,
     pub event: Option<String>,
     pub retry: Option<u64>,
 }
 
+impl std::fmt::Debug for SSEvents {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SSEvents")
+            .field("data", &privacy::Elided(&self.data))
+            .field("event", &self.event)
+            .field("retry", &self.retry)
+            .finish()
+    }
+}
+
+impl SSEvents {
+    /// Parses the event's `data` as JSON, failing with a
+    /// [`GatewayError::DeserializationError`] that points at the offending
+    /// field.
+    pub fn json<T: serde::de::DeserializeOwned>(&self) -> Result<T, GatewayError> {
+        decode::from_str(&self.data)
+    }
+}
+
 /// Custom error types for the Inference Gateway SDK
 #[derive(Error, Debug)]
 pub enum GatewayError {
@@ -51,29 +189,413 @@ pub enum GatewayError {
     #[error("Stream error: {0}")]
     StreamError(reqwest::Error),
 
+    #[error("Stream interrupted after {bytes_received} bytes: {source}")]
+    StreamInterrupted {
+        /// Body bytes received before the failure.
+        bytes_received: u64,
+        /// The `id` of the last SSE event seen, usable to resume the stream.
+        last_event_id: Option<String>,
+        source: reqwest::Error,
+    },
+
     #[error("Decoding error: {0}")]
     DecodingError(std::string::FromUtf8Error),
 
     #[error("Request error: {0}")]
     RequestError(#[from] reqwest::Error),
 
-    #[error("Deserialization error: {0}")]
-    DeserializationError(serde_json::Error),
+    #[error("Deserialization error at {path}: {source} in {snippet}")]
+    DeserializationError {
+        source: serde_json::Error,
+        /// Where in the body parsing failed, e.g. `choices[0].finish_reason`.
+        path: String,
+        /// The start of the body, with credentials redacted.
+        snippet: String,
+    },
 
     #[error("Serialization error: {0}")]
     SerializationError(#[from] serde_json::Error),
 
+    #[error("No tokens received within {0:?}")]
+    FirstTokenTimeout(Duration),
+
+    #[error("Request timed out after {0:?}")]
+    Timeout(Duration),
+
+    #[error("{feature} is disabled on this gateway: {hint}")]
+    FeatureDisabled {
+        feature: GatewayFeature,
+        /// How to enable the feature, as reported by the gateway.
+        hint: String,
+    },
+
+    #[error("Request queue is full")]
+    QueueFull,
+
+    #[error("Rate limited: {message}")]
+    RateLimited {
+        retry_after: Option<Duration>,
+        message: String,
+    },
+
+    #[error("{kind} attachment of {size} bytes exceeds the {limit} byte limit for {provider}")]
+    AttachmentTooLarge {
+        provider: Provider,
+        kind: attachment::AttachmentKind,
+        /// Decoded size of the attachment.
+        size: usize,
+        limit: usize,
+    },
+
+    #[error("{provider} does not support these speech options: {reason}")]
+    UnsupportedSpeechOptions { provider: Provider, reason: String },
+
+    #[error("Prompt needs {needed} tokens but the context window holds {available}")]
+    ContextWindowExceeded { needed: u64, available: u64 },
+
+    #[error("{0} has no token counting endpoint")]
+    TokenCountingUnsupported(Provider),
+
+    #[error("No default model is configured; set one with `with_default_model`")]
+    NoDefaultModel,
+
+    #[error("No provider given for model `{0}`")]
+    NoProvider(String),
+
+    #[error("Model output is not a valid `{type_name}`: {source}")]
+    StructuredOutput {
+        type_name: &'static str,
+        /// The model's output.
+        content: String,
+        source: serde_json::Error,
+    },
+
+    #[error("Model still called tools after {0} rounds")]
+    ToolRoundsExceeded(u32),
+
+    #[error("No candidate model meets the selection policy")]
+    NoModelSelected,
+
+    #[error("Experiment `{0}` has no variant with weight")]
+    NoVariant(String),
+
+    #[error("No recorded exchange matches {method} {url}")]
+    ReplayMismatch { method: String, url: String },
+
+    #[error("Job at {location} didn't finish within {deadline:?}")]
+    JobDeadlineExceeded {
+        location: String,
+        deadline: Duration,
+    },
+
+    #[error("Checksum mismatch: expected {expected}, got {actual}")]
+    ChecksumMismatch { expected: String, actual: String },
+
+    #[error("Artifact at {location} changed while downloading it")]
+    ArtifactChanged { location: String },
+
+    #[error("Response exceeds the {limit} byte limit")]
+    ResponseTooLarge { limit: usize },
+
+    #[error("Invalid gateway endpoint: {0}")]
+    InvalidEndpoint(String),
+
+    #[error("Expected one embedding for each of {expected} inputs, got indices {indices:?}")]
+    EmbeddingsMismatch {
+        expected: usize,
+        /// The indices of the vectors the response held, sorted.
+        indices: Vec<usize>,
+    },
+
     #[error("Other error: {0}")]
     Other(#[from] Box<dyn std::error::Error + Send + Sync>),
+
+    /// Any of the other variants, with the request it came from. Errors
+    /// returned by a client built with
+    /// [`InferenceGatewayClient::with_error_context`] are wrapped this way;
+    /// use [`GatewayError::root`] to match on the underlying variant.
+    #[error("{error} ({context})")]
+    WithContext {
+        context: Box<ErrorContext>,
+        #[source]
+        error: Box<GatewayError>,
+    },
+}
+
+/// The request a [`GatewayError`] came from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ErrorContext {
+    pub method: Method,
+    pub url: String,
+    pub provider: Option<Provider>,
+    pub model: Option<String>,
+}
+
+impl ErrorContext {
+    fn new(method: Method, url: &str, provider: Option<Provider>, model: Option<&str>) -> Self {
+        Self {
+            method,
+            url: url.to_string(),
+            provider,
+            model: model.map(str::to_string),
+        }
+    }
+
+    /// A closure adding this context to an error, for `map_err`, if the
+    /// client attaches contexts.
+    fn wrap(self, enabled: bool) -> impl Fn(GatewayError) -> GatewayError + Send + Sync {
+        move |error| {
+            if enabled {
+                error.with_context(&self)
+            } else {
+                error
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.method, self.url)?;
+        match (self.provider, &self.model) {
+            (Some(provider), Some(model)) => write!(f, ", provider {provider}, model {model}"),
+            (Some(provider), None) => write!(f, ", provider {provider}"),
+            (None, Some(model)) => write!(f, ", model {model}"),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+/// A coarse category of [`GatewayError`], for matching on what went wrong
+/// without depending on variant details or message text.
+///
+/// Categories may be added in later versions, but an error's category only
+/// changes if its cause turns out to be misclassified.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum GatewayErrorKind {
+    /// Missing, invalid or insufficient credentials.
+    Auth,
+    /// A rate limit or the client's request queue is exhausted.
+    RateLimit,
+    /// The request was rejected as invalid, by the gateway or before it was
+    /// sent.
+    Validation,
+    /// The requested resource doesn't exist.
+    NotFound,
+    /// The gateway or provider failed to handle a valid request.
+    Provider,
+    /// The connection failed or dropped.
+    Network,
+    /// No response, or no first token, arrived in time.
+    Timeout,
+    /// A body could not be encoded or decoded.
+    Protocol,
+    /// The gateway or provider doesn't offer the feature.
+    Unsupported,
+    /// Anything else, including unexpected status codes.
+    Other,
+}
+
+impl GatewayError {
+    /// The request this error came from, if it came from a client built
+    /// with [`InferenceGatewayClient::with_error_context`].
+    pub fn context(&self) -> Option<&ErrorContext> {
+        match self {
+            Self::WithContext { context, .. } => Some(context),
+            _ => None,
+        }
+    }
+
+    /// The error without its [`ErrorContext`].
+    pub fn root(&self) -> &GatewayError {
+        match self {
+            Self::WithContext { error, .. } => error,
+            error => error,
+        }
+    }
+
+    /// Consumes the error, returning it without its [`ErrorContext`].
+    pub fn into_root(self) -> GatewayError {
+        match self {
+            Self::WithContext { error, .. } => *error,
+            error => error,
+        }
+    }
+
+    /// Adds `context`, unless the error already has one from a more specific
+    /// request.
+    fn with_context(self, context: &ErrorContext) -> Self {
+        match self {
+            Self::WithContext { .. } => self,
+            error => Self::WithContext {
+                context: Box::new(context.clone()),
+                error: Box::new(error),
+            },
+        }
+    }
+
+    /// The category of this error.
+    pub fn kind(&self) -> GatewayErrorKind {
+        match self {
+            Self::WithContext { error, .. } => error.kind(),
+            Self::Unauthorized(_) | Self::Forbidden(_) => GatewayErrorKind::Auth,
+            Self::NotFound(_) => GatewayErrorKind::NotFound,
+            Self::BadRequest(_)
+            | Self::AttachmentTooLarge { .. }
+            | Self::UnsupportedSpeechOptions { .. }
+            | Self::ContextWindowExceeded { .. }
+            | Self::NoDefaultModel
+            | Self::NoProvider(_)
+            | Self::NoModelSelected
+            | Self::NoVariant(_)
+            | Self::ReplayMismatch { .. }
+            | Self::InvalidEndpoint(_) => GatewayErrorKind::Validation,
+            Self::InternalError(_) | Self::ToolRoundsExceeded(_) => GatewayErrorKind::Provider,
+            Self::StreamError(e) | Self::StreamInterrupted { source: e, .. } => transport_kind(e),
+            Self::RequestError(e) => transport_kind(e),
+            Self::DecodingError(_)
+            | Self::DeserializationError { .. }
+            | Self::SerializationError(_)
+            | Self::StructuredOutput { .. }
+            | Self::ResponseTooLarge { .. }
+            | Self::EmbeddingsMismatch { .. }
+            | Self::ChecksumMismatch { .. }
+            | Self::ArtifactChanged { .. } => GatewayErrorKind::Protocol,
+            Self::FirstTokenTimeout(_) | Self::Timeout(_) | Self::JobDeadlineExceeded { .. } => {
+                GatewayErrorKind::Timeout
+            }
+            Self::FeatureDisabled { .. } | Self::TokenCountingUnsupported(_) => {
+                GatewayErrorKind::Unsupported
+            }
+            Self::QueueFull | Self::RateLimited { .. } => GatewayErrorKind::RateLimit,
+            Self::Other(_) => GatewayErrorKind::Other,
+        }
+    }
+}
+
+fn transport_kind(error: &reqwest::Error) -> GatewayErrorKind {
+    if error.is_timeout() {
+        GatewayErrorKind::Timeout
+    } else if error.is_decode() {
+        GatewayErrorKind::Protocol
+    } else {
+        GatewayErrorKind::Network
+    }
+}
+
+/// Result of [`InferenceGatewayAPI::ready_check`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HealthStatus {
+    /// The gateway is up and serving API requests.
+    Healthy,
+    /// The gateway answered, but not successfully; holds the reason.
+    Degraded(String),
+    /// The gateway could not be reached.
+    Unreachable,
+}
+
+impl HealthStatus {
+    fn from_result<T>(result: Result<T, GatewayError>) -> Self {
+        match result.as_ref().map_err(GatewayError::root) {
+            Ok(_) => Self::Healthy,
+            Err(GatewayError::RequestError(e)) if e.is_connect() || e.is_timeout() => {
+                Self::Unreachable
+            }
+            Err(e) => Self::Degraded(e.to_string()),
+        }
+    }
+}
+
+/// Reachability of one provider behind the gateway, from
+/// [`InferenceGatewayAPI::provider_health`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProviderHealth {
+    /// The probed provider.
+    pub provider: Provider,
+    /// Whether the provider answered its model listing.
+    pub status: HealthStatus,
+    /// How long the probe took.
+    pub latency: Duration,
+}
+
+/// Optional gateway features that deployments can leave switched off.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GatewayFeature {
+    /// MCP tool listing, enabled with `EXPOSE_MCP=true`.
+    Mcp,
+}
+
+impl std::fmt::Display for GatewayFeature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Mcp => f.write_str("MCP"),
+        }
+    }
 }
 
 /// Client for interacting with the Inference Gateway API
+///
+/// Clones share the connection pool, the rate-limit budget, what the
+/// gateway has reported about disabled features, the context windows looked
+/// up for [`InferenceGatewayClient::with_auto_max_tokens`], and the tape of
+/// the [`ReplayClient`] they came from.
+#[derive(Clone)]
 pub struct InferenceGatewayClient {
     base_url: String,
     client: Client,
-    token: Option<String>,
+    /// What `client` was built with.
+    http: HttpSettings,
+    /// Wiped from memory when dropped.
+    token: Option<Zeroizing<String>>,
     tools: Option<Vec<ChatCompletionTool>>,
     max_tokens: Option<i64>,
+    /// Tokens kept free when `max_tokens` is derived from the context window.
+    max_tokens_reserve: Option<u64>,
+    /// Context window of each `provider/model`, or `None` if the gateway
+    /// doesn't report one.
+    context_windows: Arc<Mutex<HashMap<String, Option<u64>>>>,
+    /// Held while a context window is fetched, so concurrent misses share
+    /// one lookup.
+    context_window_fetch: Arc<tokio::sync::Mutex<()>>,
+    stream_buffer_capacity: usize,
+    first_token_timeout: Option<Duration>,
+    metrics_callback: Option<MetricsCallback>,
+    event_sink: Option<EventSink>,
+    #[cfg(feature = "trace")]
+    trace_propagation: bool,
+    /// Whether inline attachments are checked against provider limits.
+    attachment_checks: bool,
+    query_params: Vec<(String, String)>,
+    scheduling_hints: SchedulingHints,
+    api_version: Option<String>,
+    /// Features the gateway has reported as disabled, with its hint and
+    /// when that was.
+    disabled_features: Arc<Mutex<HashMap<GatewayFeature, (String, tokio::time::Instant)>>>,
+    /// Last body and `ETag` of each list endpoint, by URL.
+    list_cache: Arc<Mutex<HashMap<String, (String, bytes::Bytes)>>>,
+    /// Budget from the gateway's rate-limit headers.
+    rate_limit: RateLimitBudget,
+    provider_limits: HashMap<Provider, ProviderThrottle>,
+    /// Role mappings for instruction messages that override
+    /// [`InstructionRole::default_for`].
+    instruction_roles: HashMap<Provider, InstructionRole>,
+    queue_limit: Option<QueueLimit>,
+    retry: Option<RetryConfig>,
+    failover: Option<FailoverConfig>,
+    cache: Option<Arc<dyn CacheStore>>,
+    default_model: Option<(Provider, ModelRef)>,
+    router: Option<Router>,
+    shadow: Option<(ShadowConfig, shadow::ShadowSink)>,
+    max_response_bytes: Option<usize>,
+    max_event_bytes: Option<usize>,
+    seed: Option<i64>,
+    /// Where requests are recorded or replayed from, for a [`ReplayClient`].
+    tape: Option<Arc<replay::Tape>>,
+    middleware: Vec<Arc<dyn Middleware>>,
+    endpoints: Option<Arc<EndpointPool>>,
+    /// Whether errors are wrapped in [`GatewayError::WithContext`].
+    error_context: bool,
 }
 
 impl std::fmt::Debug for InferenceGatewayClient {
@@ -85,6 +607,37 @@ impl std::fmt::Debug for InferenceGatewayClient {
     }
 }
 
+/// Connection settings the client's `reqwest::Client` is built with.
+#[derive(Clone, Debug, Default)]
+struct HttpSettings {
+    connect_timeout: Option<Duration>,
+    request_timeout: Option<Duration>,
+    read_timeout: Option<Duration>,
+    /// `None` keeps reqwest's default keepalive.
+    tcp_keepalive: Option<Duration>,
+}
+
+impl HttpSettings {
+    fn client(&self) -> Client {
+        let mut builder = Client::builder();
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(timeout) = self.request_timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(timeout) = self.read_timeout {
+            builder = builder.read_timeout(timeout);
+        }
+        if let Some(interval) = self.tcp_keepalive {
+            builder = builder.tcp_keepalive(interval);
+        }
+        builder
+            .build()
+            .expect("the HTTP client's TLS backend failed to initialize")
+    }
+}
+
 /// Core API interface for the Inference Gateway
 pub trait InferenceGatewayAPI {
     /// Lists available models from all providers
@@ -110,18 +663,78 @@ pub trait InferenceGatewayAPI {
     fn generate_content(
         &self,
         provider: Provider,
-        model: &str,
+        model: impl Into<ModelRef> + Send,
+        messages: Vec<Message>,
+    ) -> impl Future<Output = Result<CreateChatCompletionResponse, GatewayError>> + Send;
+
+    /// Like [`InferenceGatewayAPI::generate_content`], with per-call
+    /// options: either [`RequestOptions`] or, to tune sampling too,
+    /// [`ChatCompletionOptions`].
+    fn generate_content_with_options(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef> + Send,
         messages: Vec<Message>,
+        options: impl Into<ChatCompletionOptions> + Send,
     ) -> impl Future<Output = Result<CreateChatCompletionResponse, GatewayError>> + Send;
 
     /// Streams content generation as SSE events from the gateway.
+    ///
+    /// An `event: error` frame ends the stream with the matching
+    /// [`GatewayError`] variant.
     fn generate_content_stream(
         &self,
         provider: Provider,
-        model: &str,
+        model: impl Into<ModelRef> + Send,
         messages: Vec<Message>,
     ) -> impl Stream<Item = Result<SSEvents, GatewayError>> + Send;
 
+    /// Like [`InferenceGatewayAPI::generate_content_stream`], with per-call
+    /// options: either [`RequestOptions`] or, to tune sampling too,
+    /// [`ChatCompletionOptions`].
+    fn generate_content_stream_with_options(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef> + Send,
+        messages: Vec<Message>,
+        options: impl Into<ChatCompletionOptions> + Send,
+    ) -> impl Stream<Item = Result<SSEvents, GatewayError>> + Send;
+
+    /// Streams a chat completion to the end and returns it folded, resuming
+    /// up to `max_resumes` times if the connection drops mid-generation.
+    ///
+    /// A resume re-issues the request with the text received so far as an
+    /// assistant turn, followed by an instruction to continue from where it
+    /// stopped. The returned message holds the text of every attempt joined
+    /// together and the usage of all of them. Errors other than
+    /// [`GatewayError::StreamInterrupted`] are returned as they are.
+    fn generate_content_resumable(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef> + Send,
+        messages: Vec<Message>,
+        max_resumes: u32,
+    ) -> impl Future<Output = Result<FoldedCompletion, GatewayError>> + Send;
+
+    /// Sends `prompt` as a single user message and returns the text of the
+    /// first choice, or an empty string if there is none.
+    fn ask(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef> + Send,
+        prompt: impl Into<String> + Send,
+    ) -> impl Future<Output = Result<String, GatewayError>> + Send;
+
+    /// Like [`InferenceGatewayAPI::ask`], with a system message before the
+    /// prompt.
+    fn ask_with_system(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef> + Send,
+        system: impl Into<String> + Send,
+        prompt: impl Into<String> + Send,
+    ) -> impl Future<Output = Result<String, GatewayError>> + Send;
+
     /// Creates a message via the Anthropic-compatible Messages API.
     ///
     /// Providers without Messages support return [`GatewayError::BadRequest`];
@@ -132,19 +745,183 @@ pub trait InferenceGatewayAPI {
         request: CreateMessagesRequest,
     ) -> impl Future<Output = Result<MessagesResponse, GatewayError>> + Send;
 
+    /// Like [`InferenceGatewayAPI::create_message`], with per-call options.
+    fn create_message_with_options(
+        &self,
+        provider: Option<Provider>,
+        request: CreateMessagesRequest,
+        options: RequestOptions,
+    ) -> impl Future<Output = Result<MessagesResponse, GatewayError>> + Send;
+
     /// Streams a message via the Messages API as SSE events. Each event's
-    /// `data` field holds a JSON-serialized [`MessagesStreamEvent`].
+    /// `data` field holds a JSON-serialized [`MessagesStreamEvent`]; `error`
+    /// events end the stream with the matching [`GatewayError`] variant.
     fn create_message_stream(
         &self,
         provider: Option<Provider>,
         request: CreateMessagesRequest,
     ) -> impl Stream<Item = Result<SSEvents, GatewayError>> + Send;
 
-    /// Lists available MCP tools (only when `EXPOSE_MCP=true` server-side)
+    /// Like [`InferenceGatewayAPI::create_message_stream`], with per-call
+    /// options.
+    fn create_message_stream_with_options(
+        &self,
+        provider: Option<Provider>,
+        request: CreateMessagesRequest,
+        options: RequestOptions,
+    ) -> impl Stream<Item = Result<SSEvents, GatewayError>> + Send;
+
+    /// Lists available MCP tools (only when `EXPOSE_MCP=true` server-side).
+    ///
+    /// Otherwise fails with [`GatewayError::FeatureDisabled`], and keeps
+    /// failing without a round-trip for up to a minute, or until a new token
+    /// is set with [`InferenceGatewayClient::with_token`].
     fn list_tools(&self) -> impl Future<Output = Result<ListToolsResponse, GatewayError>> + Send;
 
+    /// Like [`InferenceGatewayAPI::list_models`], but yields each model as it
+    /// is parsed instead of collecting the list, so memory stays flat on
+    /// gateways serving thousands of models.
+    fn list_models_stream(&self) -> impl Stream<Item = Result<Model, GatewayError>> + Send;
+
+    /// Like [`InferenceGatewayAPI::list_tools`], but yields each tool as it
+    /// is parsed instead of collecting the list.
+    fn list_tools_stream(&self) -> impl Stream<Item = Result<McpTool, GatewayError>> + Send;
+
+    /// Uploads `bytes` to `provider`'s Files API through the gateway proxy
+    /// and returns a content part referencing the uploaded file.
+    ///
+    /// `purpose` is passed through as the provider expects it, e.g.
+    /// `"user_data"` for OpenAI.
+    fn upload_file(
+        &self,
+        provider: Provider,
+        filename: &str,
+        bytes: &[u8],
+        purpose: &str,
+    ) -> impl Future<Output = Result<FileContentPart, GatewayError>> + Send;
+
+    /// Counts the prompt tokens `messages` take for `model`, using
+    /// `provider`'s own counting endpoint through the gateway proxy.
+    ///
+    /// Only some providers have one (currently Anthropic); others fail with
+    /// [`GatewayError::TokenCountingUnsupported`] without a round-trip.
+    fn count_tokens_remote(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef> + Send,
+        messages: &[Message],
+    ) -> impl Future<Output = Result<u64, GatewayError>> + Send;
+
+    /// Checks that `messages` fit `model`'s context window before they are
+    /// sent, failing with [`GatewayError::ContextWindowExceeded`] if not.
+    ///
+    /// The window comes from the gateway's model metadata, and the prompt is
+    /// counted with [`InferenceGatewayAPI::count_tokens_remote`] where the
+    /// provider supports it and estimated from its text length otherwise,
+    /// or when the remote count fails. Passes if the gateway doesn't report
+    /// the model's context window.
+    fn validate_request(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef> + Send,
+        messages: &[Message],
+    ) -> impl Future<Output = Result<(), GatewayError>> + Send;
+
+    /// Embeds `request`'s inputs with `provider`'s OpenAI-compatible
+    /// embeddings route, through the gateway proxy.
+    fn create_embeddings(
+        &self,
+        provider: Provider,
+        request: EmbeddingRequest,
+    ) -> impl Future<Output = Result<EmbeddingResponse, GatewayError>> + Send;
+
+    /// Speaks `input` with `provider`'s OpenAI-compatible speech route,
+    /// through the gateway proxy, returning the encoded audio.
+    ///
+    /// Fails with [`GatewayError::UnsupportedSpeechOptions`] without a
+    /// round-trip if `options` don't suit `provider`.
+    fn create_speech(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef> + Send,
+        input: &str,
+        options: SpeechOptions,
+    ) -> impl Future<Output = Result<bytes::Bytes, GatewayError>> + Send;
+
+    /// Speaks `input` as [`Self::create_speech`] does, yielding the audio
+    /// chunk by chunk as the provider sends it, so playback can start before
+    /// the whole clip is generated.
+    fn create_speech_stream(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef> + Send,
+        input: &str,
+        options: SpeechOptions,
+    ) -> impl Stream<Item = Result<bytes::Bytes, GatewayError>> + Send;
+
+    /// Transcribes `audio` with `provider`'s OpenAI-compatible transcription
+    /// route, through the gateway proxy, returning the transcript with its
+    /// timed segments and, if `options` ask for them, words.
+    ///
+    /// The audio is uploaded in one piece; for long recordings, see
+    /// [`Self::transcribe_stream`].
+    fn create_transcription(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef> + Send,
+        audio: impl Into<AudioFile> + Send,
+        options: TranscriptionOptions,
+    ) -> impl Future<Output = Result<Transcription, GatewayError>> + Send;
+
+    /// Transcribes audio with `provider`'s OpenAI-compatible transcription
+    /// route, through the gateway proxy.
+    ///
+    /// The audio is uploaded from `audio` chunk by chunk as the request body
+    /// is sent, so long recordings need not be held in memory - a file can
+    /// be passed as e.g. `tokio_util::io::ReaderStream::new(file)`. Providers
+    /// that stream transcripts send [`TranscriptionEvent::Delta`]s as text is
+    /// recognised; every stream ends with one [`TranscriptionEvent::Done`]
+    /// holding the full transcript.
+    fn transcribe_stream<S>(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef> + Send,
+        filename: &str,
+        audio: S,
+    ) -> impl Stream<Item = Result<TranscriptionEvent, GatewayError>> + Send
+    where
+        S: Stream<Item = Result<bytes::Bytes, std::io::Error>> + Send + 'static;
+
+    /// Classifies `input` with `provider`'s OpenAI-compatible moderation
+    /// route, through the gateway proxy, returning whether each text was
+    /// flagged and its score in every category.
+    fn create_moderation(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef> + Send,
+        input: impl Into<ModerationInput> + Send,
+    ) -> impl Future<Output = Result<ModerationResponse, GatewayError>> + Send;
+
     /// Health probe - returns true on HTTP 200, false otherwise.
     fn health_check(&self) -> impl Future<Output = Result<bool, GatewayError>> + Send;
+
+    /// Readiness probe: whether the gateway is live *and* serving its API.
+    ///
+    /// The gateway exposes no dedicated readiness endpoint, so this follows a
+    /// successful `/health` with a model listing - the cheapest authenticated
+    /// API call - and reports the first failure as
+    /// [`HealthStatus::Degraded`].
+    fn ready_check(&self) -> impl Future<Output = HealthStatus> + Send;
+
+    /// Probes every provider that serves at least one model, concurrently.
+    ///
+    /// The gateway does not report provider health itself, so each provider
+    /// is probed with a model listing filtered to it - cheap, but it does
+    /// reach the provider. Results are ordered by provider; fails only if the
+    /// initial model listing fails.
+    fn provider_health(
+        &self,
+    ) -> impl Future<Output = Result<Vec<ProviderHealth>, GatewayError>> + Send;
 }
 
 impl InferenceGatewayClient {
@@ -153,9 +930,42 @@ impl InferenceGatewayClient {
         Self {
             base_url: base_url.to_string(),
             client: Client::new(),
+            http: HttpSettings::default(),
             token: None,
             tools: None,
             max_tokens: None,
+            max_tokens_reserve: None,
+            context_windows: Arc::default(),
+            context_window_fetch: Arc::default(),
+            stream_buffer_capacity: sse::DEFAULT_BUFFER_CAPACITY,
+            first_token_timeout: None,
+            metrics_callback: None,
+            event_sink: None,
+            #[cfg(feature = "trace")]
+            trace_propagation: true,
+            attachment_checks: true,
+            query_params: Vec::new(),
+            scheduling_hints: SchedulingHints::default(),
+            api_version: None,
+            disabled_features: Arc::default(),
+            list_cache: Arc::default(),
+            rate_limit: RateLimitBudget::default(),
+            provider_limits: HashMap::new(),
+            instruction_roles: HashMap::new(),
+            queue_limit: None,
+            retry: None,
+            failover: None,
+            cache: None,
+            default_model: None,
+            router: None,
+            shadow: None,
+            max_response_bytes: None,
+            max_event_bytes: None,
+            seed: None,
+            tape: None,
+            middleware: Vec::new(),
+            endpoints: None,
+            error_context: false,
         }
     }
 
@@ -167,12 +977,59 @@ impl InferenceGatewayClient {
         Self {
             base_url,
             client: Client::new(),
+            http: HttpSettings::default(),
             token: None,
             tools: None,
             max_tokens: None,
+            max_tokens_reserve: None,
+            context_windows: Arc::default(),
+            context_window_fetch: Arc::default(),
+            stream_buffer_capacity: sse::DEFAULT_BUFFER_CAPACITY,
+            first_token_timeout: None,
+            metrics_callback: None,
+            event_sink: None,
+            #[cfg(feature = "trace")]
+            trace_propagation: true,
+            attachment_checks: true,
+            query_params: Vec::new(),
+            scheduling_hints: SchedulingHints::default(),
+            api_version: None,
+            disabled_features: Arc::default(),
+            list_cache: Arc::default(),
+            rate_limit: RateLimitBudget::default(),
+            provider_limits: HashMap::new(),
+            instruction_roles: HashMap::new(),
+            queue_limit: None,
+            retry: None,
+            failover: None,
+            cache: None,
+            default_model: None,
+            router: None,
+            shadow: None,
+            max_response_bytes: None,
+            max_event_bytes: None,
+            seed: None,
+            tape: None,
+            middleware: Vec::new(),
+            endpoints: None,
+            error_context: false,
         }
     }
 
+    /// Creates a client spreading requests across `base_urls`, replicas of
+    /// the same gateway, with `strategy`. Request URLs are built on the
+    /// first one. An endpoint that can't be connected to gets no requests
+    /// for 30 seconds, or as long as [`Self::with_endpoint_ejection`] sets.
+    ///
+    /// Fails with [`GatewayError::InvalidEndpoint`] if `base_urls` is empty
+    /// or one of them isn't a valid URL.
+    pub fn new_balanced(base_urls: &[&str], strategy: LoadBalancing) -> Result<Self, GatewayError> {
+        let pool = EndpointPool::new(base_urls, strategy)?;
+        let mut client = Self::new(base_urls[0]);
+        client.endpoints = Some(Arc::new(pool));
+        Ok(client)
+    }
+
     pub fn base_url(&self) -> &str {
         &self.base_url
     }
@@ -184,8 +1041,15 @@ impl InferenceGatewayClient {
     }
 
     /// Sets the bearer token used for authentication.
+    ///
+    /// The client wipes its copy of the token from memory when dropped and
+    /// never shows it in `Debug` output.
+    ///
+    /// Features the gateway refused under the previous token are tried
+    /// again, as the new one may be allowed to use them.
     pub fn with_token(mut self, token: impl Into<String>) -> Self {
-        self.token = Some(token.into());
+        self.token = Some(Zeroizing::new(token.into()));
+        self.disabled_features = Arc::default();
         self
     }
 
@@ -195,160 +1059,1499 @@ impl InferenceGatewayClient {
         self
     }
 
-    /// The gateway serves `/health` from the root server, not under the
-    /// versioned API prefix, so this strips a trailing `/v<digits>` segment
-    /// from the configured base URL before appending `/health`.
-    fn health_url(&self) -> String {
-        let trimmed = self.base_url.trim_end_matches('/');
-        let root = match trimmed.rsplit_once('/') {
-            Some((prefix, last))
-                if last.len() >= 2
-                    && last.starts_with('v')
-                    && last[1..].chars().all(|c| c.is_ascii_digit()) =>
-            {
-                prefix
-            }
-            _ => trimmed,
-        };
-        format!("{root}/health")
+    /// Sets the `seed` sent with every chat completion, asking the provider
+    /// to sample deterministically where it supports that.
+    pub fn with_seed(mut self, seed: i64) -> Self {
+        self.seed = Some(seed);
+        self
     }
 
-    fn messages_url(&self, provider: Option<Provider>) -> String {
-        match provider {
-            Some(provider) => format!("{}/messages?provider={provider}", self.base_url),
-            None => format!("{}/messages", self.base_url),
-        }
+    /// Sets `max_tokens` on each non-streaming chat request to what the
+    /// model's context window leaves after the prompt and `reserve` tokens,
+    /// so long conversations don't fail with "context length exceeded". A
+    /// prompt that leaves nothing fails with
+    /// [`GatewayError::ContextWindowExceeded`] before it is sent.
+    ///
+    /// The window is looked up once per model in the gateway's model
+    /// metadata, and the prompt is estimated from its text length, so keep
+    /// `reserve` large enough to absorb the estimate's error. Models without
+    /// a reported window, and limits set with [`Self::with_max_tokens`] that
+    /// are lower, are left as they are.
+    pub fn with_auto_max_tokens(mut self, reserve: u64) -> Self {
+        self.max_tokens_reserve = Some(reserve);
+        self
     }
 
-    fn build_chat_request(
-        &self,
-        model: &str,
-        messages: Vec<Message>,
-        stream: bool,
-    ) -> CreateChatCompletionRequest {
-        // `tools` and `max_tokens` are deliberately omitted from streaming
-        // requests; every other field falls back to the schema defaults via
-        // `Default`. See CLAUDE.md for the streaming asymmetry.
-        CreateChatCompletionRequest {
-            model: model.to_string(),
-            messages,
-            stream,
-            tools: if stream {
-                Vec::new()
-            } else {
-                self.tools.clone().unwrap_or_default()
-            },
-            max_tokens: if stream { None } else { self.max_tokens },
-            ..Default::default()
-        }
+    /// Sets the initial capacity, in bytes, of the buffer streaming responses
+    /// are decoded from. Raise it when events routinely exceed the 8 KiB
+    /// default (large tool-call arguments, base64 payloads) to avoid regrowth.
+    pub fn with_stream_buffer_capacity(mut self, capacity: usize) -> Self {
+        self.stream_buffer_capacity = capacity;
+        self
     }
-}
 
-async fn map_error_status(status: StatusCode, response: reqwest::Response) -> GatewayError {
-    // Gateway errors are `{"error": "..."}`; Messages endpoints use the
-    // Anthropic shape `{"type": "error", "error": {"type": ..., "message": ...}}`.
-    let fallback = || status.canonical_reason().unwrap_or("unknown").to_string();
-    let message = match response.json::<serde_json::Value>().await {
-        Ok(body) => match body.get("error") {
-            Some(serde_json::Value::String(error)) => error.clone(),
-            Some(error) => error
-                .get("message")
-                .and_then(|m| m.as_str())
-                .map(str::to_string)
-                .unwrap_or_else(fallback),
-            None => fallback(),
-        },
-        Err(_) => fallback(),
-    };
-    match status {
-        StatusCode::UNAUTHORIZED => GatewayError::Unauthorized(message),
-        StatusCode::FORBIDDEN => GatewayError::Forbidden(message),
-        StatusCode::NOT_FOUND => GatewayError::NotFound(message),
-        StatusCode::BAD_REQUEST => GatewayError::BadRequest(message),
-        StatusCode::INTERNAL_SERVER_ERROR => GatewayError::InternalError(message),
-        other => GatewayError::Other(Box::new(std::io::Error::other(format!(
-            "Unexpected status code: {other}"
-        )))),
+    /// Fails streaming calls with [`GatewayError::FirstTokenTimeout`] when no
+    /// content arrives within `timeout` of sending the request.
+    ///
+    /// A stalled provider often keeps the connection open without emitting
+    /// anything, so this catches hangs long before a total-duration limit
+    /// would. Once the first content delta arrives the timeout no longer
+    /// applies.
+    pub fn with_first_token_timeout(mut self, timeout: Duration) -> Self {
+        self.first_token_timeout = Some(timeout);
+        self
     }
-}
-
-fn sse_stream<B>(
-    client: Client,
-    token: Option<String>,
-    url: String,
-    body: B,
-) -> impl Stream<Item = Result<SSEvents, GatewayError>> + Send
-where
-    B: serde::Serialize + Send + 'static,
-{
-    async_stream::try_stream! {
-        let mut request = client.post(&url);
-        if let Some(token) = token {
-            request = request.bearer_auth(token);
-        }
-        let response = request.json(&body).send().await?;
-        let mut stream = response.bytes_stream();
-        let mut current_event: Option<String> = None;
-        let mut current_data: Option<String> = None;
-
-        while let Some(chunk) = stream.next().await {
-            let chunk = chunk?;
-            let chunk_str = String::from_utf8_lossy(&chunk);
-
-            for line in chunk_str.lines() {
-                if line.is_empty() && current_data.is_some() {
-                    yield SSEvents {
-                        data: current_data.take().unwrap(),
-                        event: current_event.take(),
-                        retry: None,
-                    };
-                    continue;
-                }
 
-                if let Some(event) = line.strip_prefix("event:") {
-                    current_event = Some(event.trim().to_string());
-                } else if let Some(data) = line.strip_prefix("data:") {
-                    let processed_data = data.strip_suffix('\n').unwrap_or(data);
-                    current_data = Some(processed_data.trim().to_string());
-                }
-            }
-        }
+    /// Fails connection attempts that take longer than `timeout`. Without
+    /// it the client waits as long as the operating system does.
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.http.connect_timeout = Some(timeout);
+        self.client = self.http.client();
+        self
     }
-}
 
-impl InferenceGatewayClient {
-    async fn fetch_models(&self, query: &str) -> Result<ListModelsResponse, GatewayError> {
-        let url = if query.is_empty() {
-            format!("{}/models", self.base_url)
-        } else {
-            format!("{}/models?{}", self.base_url, query)
-        };
-        let mut request = self.client.get(&url);
+    /// Fails every request not fully received within `timeout`, from
+    /// connecting to the end of the body. This includes streams, so keep it
+    /// above the longest generation expected, or use
+    /// [`with_read_timeout`](Self::with_read_timeout) instead; a single call
+    /// can set its own with [`RequestOptions::with_timeout`].
+    pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
+        self.http.request_timeout = Some(timeout);
+        self.client = self.http.client();
+        self
+    }
+
+    /// Fails a request when nothing arrives for `timeout` while reading its
+    /// response, however long the whole response takes.
+    pub fn with_read_timeout(mut self, timeout: Duration) -> Self {
+        self.http.read_timeout = Some(timeout);
+        self.client = self.http.client();
+        self
+    }
+
+    /// Sends TCP keepalive probes every `interval` on idle connections, so
+    /// proxies and load balancers don't drop them during long generations.
+    pub fn with_tcp_keepalive(mut self, interval: Duration) -> Self {
+        self.http.tcp_keepalive = Some(interval);
+        self.client = self.http.client();
+        self
+    }
+
+    /// Keeps endpoints of a [`Self::new_balanced`] client that couldn't be
+    /// connected to out of rotation for `duration`. Does nothing for other
+    /// clients.
+    pub fn with_endpoint_ejection(mut self, duration: Duration) -> Self {
+        if let Some(endpoints) = &self.endpoints {
+            self.endpoints = Some(Arc::new(endpoints.with_ejection(duration)));
+        }
+        self
+    }
+
+    /// Wraps every error the client returns in [`GatewayError::WithContext`],
+    /// naming the method, URL, provider and model of the request it came
+    /// from. Off by default, so errors can be matched on their variants
+    /// directly; with it on, match on [`GatewayError::root`] instead.
+    pub fn with_error_context(mut self) -> Self {
+        self.error_context = true;
+        self
+    }
+
+    /// Runs `middleware` around every request the client sends. Middleware
+    /// added first sees requests first and responses last.
+    pub fn with_middleware(mut self, middleware: Box<dyn Middleware>) -> Self {
+        self.middleware.push(Arc::from(middleware));
+        self
+    }
+
+    /// Calls `callback` with a [`RequestMetrics`] after every generation call,
+    /// streaming or not, once the response has been fully received or the
+    /// call has failed. The same as [`Self::with_metrics_observer`] with a
+    /// closure; whichever is set last replaces the other.
+    pub fn with_metrics_callback(
+        self,
+        callback: impl Fn(&RequestMetrics) + Send + Sync + 'static,
+    ) -> Self {
+        self.with_metrics_observer(callback)
+    }
+
+    /// Reports the [`RequestMetrics`] of every generation call to `observer`:
+    /// duration, status, provider and model, token counts and, for streams,
+    /// time to first token.
+    pub fn with_metrics_observer(mut self, observer: impl MetricsObserver) -> Self {
+        self.metrics_callback = Some(Arc::new(observer));
+        self
+    }
+
+    /// Calls `sink` with a [`CompletionEvent`] after every generation call,
+    /// successful or not, for piping SDK activity into analytics or billing.
+    pub fn with_event_sink(
+        mut self,
+        sink: impl Fn(&CompletionEvent) + Send + Sync + 'static,
+    ) -> Self {
+        self.event_sink = Some(Arc::new(sink));
+        self
+    }
+
+    /// Mirrors a share of non-streaming chat completions to a second model,
+    /// as set by `config`, for comparing providers before a migration.
+    /// Callers only ever get the primary response, without waiting for the
+    /// shadow; `sink` is called with both results once both are in.
+    ///
+    /// Shadow requests count against the client's rate limits and are
+    /// billed like any other. Must be used within a Tokio runtime.
+    pub fn with_shadow(
+        mut self,
+        config: ShadowConfig,
+        sink: impl Fn(&ShadowComparison) + Send + Sync + 'static,
+    ) -> Self {
+        self.shadow = Some((config, Arc::new(sink)));
+        self
+    }
+
+    /// Enables or disables checking inline attachments against
+    /// [`attachment::max_inline_bytes`] before chat requests are sent
+    /// (enabled by default), for gateways that accept more than the
+    /// provider documents.
+    pub fn with_attachment_checks(mut self, enabled: bool) -> Self {
+        self.attachment_checks = enabled;
+        self
+    }
+
+    /// Enables or disables the W3C `traceparent`/`tracestate` headers sent
+    /// with every request (enabled by default). See [`TraceContext`].
+    #[cfg(feature = "trace")]
+    pub fn with_trace_propagation(mut self, enabled: bool) -> Self {
+        self.trace_propagation = enabled;
+        self
+    }
+
+    /// Appends a query parameter to every request, for gateway deployments
+    /// that route or toggle features on the query string. Use
+    /// [`RequestOptions::with_query_param`] for a single call.
+    pub fn with_query_param(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.query_params.push((key.into(), value.into()));
+        self
+    }
+
+    /// Sends `hints` with every request. Use
+    /// [`RequestOptions::with_scheduling_hints`] to override them for a
+    /// single call.
+    pub fn with_scheduling_hints(mut self, hints: SchedulingHints) -> Self {
+        self.scheduling_hints = hints;
+        self
+    }
+
+    /// Starts a request with the client's authentication, trace headers,
+    /// query parameters and scheduling hints.
+    fn request(&self, method: Method, url: &str) -> RequestBuilder {
+        self.request_with(method, url, &RequestOptions::default())
+    }
+
+    /// Like [`Self::request`], with `options` applied on top.
+    fn request_with(&self, method: Method, url: &str, options: &RequestOptions) -> RequestBuilder {
+        let mut request = self.client.request(method, url);
+        if !self.query_params.is_empty() {
+            request = request.query(&self.query_params);
+        }
         if let Some(token) = &self.token {
-            request = request.bearer_auth(token);
+            request = request.bearer_auth(token.as_str());
+        }
+        options.apply(self.trace_headers(request), &self.scheduling_hints)
+    }
+
+    /// Sends `request` with [`Self::send_once`], retrying transient failures
+    /// as the client's [`RetryConfig`] allows if it is `idempotent`.
+    ///
+    /// Once retries are used up, a transient status is returned as an error
+    /// rather than a response. Requests that aren't idempotent, or whose body
+    /// can't be replayed, are sent once: a retried generation the gateway
+    /// already ran would run, and be billed, twice.
+    async fn send(
+        &self,
+        request: RequestBuilder,
+        priority: Priority,
+        idempotent: bool,
+    ) -> Result<reqwest::Response, GatewayError> {
+        self.send_counting_retries(request, priority, idempotent, &mut 0)
+            .await
+    }
+
+    /// [`Self::send`], adding each retry it makes to `retries`.
+    async fn send_counting_retries(
+        &self,
+        request: RequestBuilder,
+        priority: Priority,
+        idempotent: bool,
+        retries: &mut u32,
+    ) -> Result<reqwest::Response, GatewayError> {
+        let Some(retry) = self.retry.as_ref() else {
+            return self.send_once(request, priority).await;
+        };
+        let mut attempts = 0;
+        loop {
+            let Some(attempt) = request.try_clone() else {
+                return self.send_once(request, priority).await;
+            };
+            attempts += 1;
+            let error = match self.send_once(attempt, priority).await {
+                Ok(response) if idempotent && retry.retries_status(response.status()) => {
+                    map_error_status(response.status(), response).await
+                }
+                Ok(response) => return Ok(response),
+                Err(GatewayError::RequestError(e)) if retry::is_transient_error(&e, idempotent) => {
+                    GatewayError::RequestError(e)
+                }
+                Err(e) => return Err(e),
+            };
+            match retry.next_delay(attempts, &error) {
+                // Replayed time is frozen; waiting would only slow tests.
+                Some(_) if self.replaying() => {}
+                Some(delay) => tokio::time::sleep(delay).await,
+                None => return Err(error),
+            }
+            *retries += 1;
+        }
+    }
+
+    /// Whether requests are answered from a [`ReplayClient`]'s cassette.
+    fn replaying(&self) -> bool {
+        self.tape.as_ref().is_some_and(|tape| tape.is_replaying())
+    }
+
+    /// Sends `request` once the rate-limit budget allows it, and updates the
+    /// budget from the response.
+    async fn send_once(
+        &self,
+        request: RequestBuilder,
+        priority: Priority,
+    ) -> Result<reqwest::Response, GatewayError> {
+        self.rate_limit.acquire(priority, self.queue_limit).await?;
+        let response = self.dispatch(request).await?;
+        self.rate_limit.update(response.headers());
+        Ok(response)
+    }
+
+    /// Sends `request` through the client's middleware and tape.
+    async fn dispatch(&self, request: RequestBuilder) -> Result<reqwest::Response, GatewayError> {
+        // Boxed, as it would otherwise inflate every caller's future.
+        Box::pin(middleware::send(
+            self.tape.as_deref(),
+            &self.middleware,
+            self.endpoints.as_deref(),
+            request,
+        ))
+        .await
+    }
+
+    #[cfg(feature = "trace")]
+    fn trace_headers(&self, mut request: RequestBuilder) -> RequestBuilder {
+        if self.trace_propagation {
+            let trace = TraceContext::for_request();
+            request = request.header("traceparent", trace.traceparent());
+            if let Some(tracestate) = trace.tracestate() {
+                request = request.header("tracestate", tracestate);
+            }
+        }
+        request
+    }
+
+    #[cfg(not(feature = "trace"))]
+    fn trace_headers(&self, request: RequestBuilder) -> RequestBuilder {
+        request
+    }
+
+    fn stream_settings(
+        &self,
+        provider: Option<Provider>,
+        options: &RequestOptions,
+    ) -> StreamSettings {
+        StreamSettings {
+            provider,
+            priority: options.priority(),
+            queue_limit: self.queue_limit,
+            buffer_capacity: self.stream_buffer_capacity,
+            max_response_bytes: self.max_response_bytes,
+            max_event_bytes: self.max_event_bytes,
+            first_token_timeout: self.first_token_timeout,
+            timeout: options.timeout(),
+            rate_limit: self.rate_limit.clone(),
+            throttle: self.throttle(provider).cloned(),
+            tape: self.tape.clone(),
+            middleware: self.middleware.clone(),
+            endpoints: self.endpoints.clone(),
+            status: None,
+        }
+    }
+
+    fn throttle(&self, provider: Option<Provider>) -> Option<&ProviderThrottle> {
+        self.provider_limits.get(&provider?)
+    }
+
+    fn record_call(
+        &self,
+        url: &str,
+        provider: Option<Provider>,
+        model: &str,
+    ) -> Option<CallRecorder> {
+        CallRecorder::start(
+            self.metrics_callback.clone(),
+            self.event_sink.clone(),
+            url,
+            provider,
+            model,
+        )
+    }
+
+    /// Sets the API version path segment (for example `"v1"`) joined onto
+    /// the base URL, for callers who configure the gateway's root URL rather
+    /// than baking the version into it.
+    pub fn with_api_version(mut self, version: impl Into<String>) -> Self {
+        self.api_version = Some(version.into());
+        self
+    }
+
+    /// Throttles requests to `provider` to the given quotas, on top of any
+    /// limits the gateway enforces. Clones share the quotas.
+    pub fn with_provider_limits(mut self, provider: Provider, limits: ProviderLimits) -> Self {
+        self.provider_limits
+            .insert(provider, ProviderThrottle::new(limits));
+        self
+    }
+
+    /// Sends instruction messages (`system` and `developer`) to `provider`
+    /// with `role`. Without this, developer messages are sent as system
+    /// messages to every provider but OpenAI, and messages to OpenAI keep
+    /// the role they were written with.
+    pub fn with_instruction_role(mut self, provider: Provider, role: InstructionRole) -> Self {
+        self.instruction_roles.insert(provider, role);
+        self
+    }
+
+    /// Bounds how many requests may wait on each rate limiter (the gateway
+    /// budget and each provider's throttle), applying `policy` to the rest.
+    /// Without a limit, requests wait indefinitely.
+    pub fn with_queue_limit(mut self, depth: usize, policy: OverflowPolicy) -> Self {
+        self.queue_limit = Some(QueueLimit { depth, policy });
+        self
+    }
+
+    /// Retries requests that fail transiently (connection errors, timeouts,
+    /// `429`, `502`, `503` and `504`) as `config` describes.
+    ///
+    /// A request that failed to connect never reached the gateway, so it is
+    /// always retried. Otherwise only requests that are safe to repeat are
+    /// retried: `GET`s, token counting and embeddings. Chat completions and
+    /// Messages API calls are retried only when their [`RequestOptions`]
+    /// carry an idempotency key or opt in with
+    /// [`RequestOptions::with_retry_safe`]. Streaming calls are never
+    /// retried.
+    pub fn with_retry(mut self, config: RetryConfig) -> Self {
+        self.retry = Some(config);
+        self
+    }
+
+    /// Sends chat completions that fail with a server error, a rate limit
+    /// or a timeout, after any retries, to the next provider and model of
+    /// `config`. Streaming calls don't fail over.
+    pub fn with_failover(mut self, config: FailoverConfig) -> Self {
+        self.failover = Some(config);
+        self
+    }
+
+    /// Answers chat completions from `store` when an identical request, to
+    /// the same provider and model with the same messages and options, was
+    /// answered before, and stores each new successful response in it.
+    /// Cached answers don't reach the gateway or the metrics callbacks.
+    /// Streaming calls aren't cached.
+    ///
+    /// Entries are scoped to the gateway URL, the client's token and the
+    /// call's [`SchedulingHints`], so clients sharing a store never see each
+    /// other's tenants' answers. Requests sampled with a temperature above 0
+    /// are cached too: a hit replays the first answer rather than drawing a
+    /// new one.
+    pub fn with_cache(mut self, store: impl CacheStore) -> Self {
+        self.cache = Some(Arc::new(store));
+        self
+    }
+
+    /// Fails JSON responses larger than `bytes` with
+    /// [`GatewayError::ResponseTooLarge`] instead of buffering them. Unlimited
+    /// by default.
+    pub fn with_max_response_size(mut self, bytes: usize) -> Self {
+        self.max_response_bytes = Some(bytes);
+        self
+    }
+
+    /// Fails streams with [`GatewayError::ResponseTooLarge`] once a single
+    /// SSE event grows past `bytes`. Unlimited by default.
+    pub fn with_max_event_size(mut self, bytes: usize) -> Self {
+        self.max_event_bytes = Some(bytes);
+        self
+    }
+
+    /// Sets the provider and model used by [`generate`](Self::generate),
+    /// [`generate_stream`](Self::generate_stream) and [`chat`](Self::chat).
+    pub fn with_default_model(mut self, provider: Provider, model: impl Into<ModelRef>) -> Self {
+        self.default_model = Some((provider, model.into()));
+        self
+    }
+
+    /// Routes [`generate`](Self::generate),
+    /// [`generate_stream`](Self::generate_stream), [`chat`](Self::chat) and
+    /// [`chat_with_options`](Self::chat_with_options) by `router`'s rules.
+    /// Requests no rule matches go to the
+    /// [default model](Self::with_default_model).
+    pub fn with_router(mut self, router: Router) -> Self {
+        self.router = Some(router);
+        self
+    }
+
+    /// Sends `prompt` to the [default model](Self::with_default_model) as a
+    /// single user message and returns the text of the first choice.
+    pub async fn generate(&self, prompt: impl Into<String> + Send) -> Result<String, GatewayError> {
+        let response = self.chat(vec![Message::user(prompt)]).await?;
+        Ok(first_choice_text(&response))
+    }
+
+    /// Streams the [default model](Self::with_default_model)'s reply to
+    /// `prompt` as text deltas.
+    pub fn generate_stream(
+        &self,
+        prompt: impl Into<String>,
+    ) -> impl Stream<Item = Result<String, GatewayError>> + Send + '_ {
+        let messages = vec![Message::user(prompt)];
+        // Streaming requests are sent without tools.
+        let events = match self.target(&messages, false, &RequestOptions::default()) {
+            Ok((provider, model)) => {
+                Either::Left(self.generate_content_stream(provider, model, messages))
+            }
+            Err(e) => Either::Right(futures_util::stream::once(async { Err(e) })),
+        };
+        events.chat_chunks().try_filter_map(|chunk| async move {
+            Ok(chunk
+                .choices
+                .into_iter()
+                .next()
+                .and_then(|choice| choice.delta.content))
+        })
+    }
+
+    /// Sends `messages` to the [default model](Self::with_default_model),
+    /// or where the client's [router](Self::with_router) sends them.
+    pub async fn chat(
+        &self,
+        messages: Vec<Message>,
+    ) -> Result<CreateChatCompletionResponse, GatewayError> {
+        self.chat_with_options(messages, RequestOptions::default())
+            .await
+    }
+
+    /// Starts a chat completion request to `model`, whose provider, tools
+    /// and token limit can be set for this request alone.
+    pub fn chat_completion(&self, model: impl Into<ModelRef>) -> ChatCompletionRequestBuilder<'_> {
+        ChatCompletionRequestBuilder::new(self, model.into())
+    }
+
+    /// Sends `messages` to `model` with `registry`'s tools, runs the tool
+    /// calls the model makes and sends their results back, until it answers
+    /// without calling a tool.
+    ///
+    /// Each round's calls run concurrently. Fails with
+    /// [`GatewayError::ToolRoundsExceeded`] if the model is still calling
+    /// tools after the registry's maximum number of rounds.
+    pub async fn run_with_tools(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef>,
+        mut messages: Vec<Message>,
+        registry: &ToolRegistry,
+    ) -> Result<ToolRun, GatewayError> {
+        let model = model.into();
+        let mut rounds = 0;
+        loop {
+            let options = ChatCompletionOptions::new().with_tools(registry.tools().to_vec());
+            let response = self
+                .generate_content_with_options(provider, model.clone(), messages.clone(), options)
+                .await?;
+            let Some(message) = response
+                .choices
+                .first()
+                .map(|choice| choice.message.clone())
+            else {
+                return Ok(ToolRun {
+                    response,
+                    messages,
+                    rounds,
+                });
+            };
+            let calls = message.tool_calls.clone();
+            messages.push(message);
+            if calls.is_empty() {
+                return Ok(ToolRun {
+                    response,
+                    messages,
+                    rounds,
+                });
+            }
+            if rounds == registry.max_rounds() {
+                return Err(GatewayError::ToolRoundsExceeded(rounds));
+            }
+            rounds += 1;
+            messages.extend(registry.call_all(&calls).await);
+        }
+    }
+
+    /// Sends `messages` to `model` asking for JSON matching `T`'s schema,
+    /// and parses the answer into a `T`. Fails with
+    /// [`GatewayError::StructuredOutput`] if the model's output doesn't
+    /// parse.
+    #[cfg(feature = "structured")]
+    pub async fn generate_structured<T>(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef>,
+        messages: Vec<Message>,
+    ) -> Result<T, GatewayError>
+    where
+        T: serde::de::DeserializeOwned + schemars::JsonSchema,
+    {
+        let options = ChatCompletionOptions::new().with_response_format(json_schema_format::<T>());
+        let response = self
+            .generate_content_with_options(provider, model.into(), messages, options)
+            .await?;
+        structured::parse(&response)
+    }
+
+    /// Like [`chat`](Self::chat), with per-call options. Their language hint
+    /// and tags are what the client's [router](Self::with_router) routes on.
+    pub async fn chat_with_options(
+        &self,
+        messages: Vec<Message>,
+        options: impl Into<ChatCompletionOptions> + Send,
+    ) -> Result<CreateChatCompletionResponse, GatewayError> {
+        let options = options.into();
+        let has_tools = options
+            .tools()
+            .or(self.tools.as_deref())
+            .is_some_and(|tools| !tools.is_empty());
+        let (provider, model) = self.target(&messages, has_tools, options.request())?;
+        self.generate_content_with_options(provider, model, messages, options)
+            .await
+    }
+
+    /// Picks the model for a request with `selector`, from the gateway's
+    /// model list and pricing. When the gateway sends an `ETag`, an
+    /// unchanged list is revalidated rather than downloaded again, so this
+    /// is cheap to call per request.
+    /// Fails with [`GatewayError::NoModelSelected`] if no candidate meets
+    /// `policy`.
+    pub async fn select_model(
+        &self,
+        selector: &ModelSelector,
+        policy: &SelectionPolicy,
+    ) -> Result<(Provider, ModelRef), GatewayError> {
+        let models = self.list_models_with_include(None, &["pricing"]).await?;
+        selector
+            .select(policy, &models)
+            .ok_or(GatewayError::NoModelSelected)
+    }
+
+    /// A [`PricingTable`] of the prices the gateway lists for its models,
+    /// revalidated like [`Self::select_model`]'s list. Refresh a table in
+    /// use with [`PricingTable::update_from_models`].
+    pub async fn pricing_table(&self) -> Result<PricingTable, GatewayError> {
+        let models = self.list_models_with_include(None, &["pricing"]).await?;
+        Ok(PricingTable::from_models(&models))
+    }
+
+    /// Sends `messages` to the variant of `experiment` that `key` is
+    /// assigned to, and records the call in that variant's stats. Fails with
+    /// [`GatewayError::NoVariant`] if the experiment has no weighted
+    /// variant.
+    pub async fn generate_in_experiment(
+        &self,
+        experiment: &Experiment,
+        key: &str,
+        messages: Vec<Message>,
+    ) -> Result<ExperimentResponse, GatewayError> {
+        let variant = experiment
+            .assign(key)
+            .ok_or_else(|| GatewayError::NoVariant(experiment.name().to_string()))?;
+        let started = Instant::now();
+        let result = self
+            .generate_content(variant.provider(), variant.model().clone(), messages)
+            .await;
+        experiment.record(variant.name(), started.elapsed(), &result);
+        Ok(ExperimentResponse {
+            variant: variant.name().to_string(),
+            response: result?,
+        })
+    }
+
+    /// Where to send `messages`: the first matching route, or else the
+    /// default model.
+    fn target(
+        &self,
+        messages: &[Message],
+        has_tools: bool,
+        options: &RequestOptions,
+    ) -> Result<(Provider, ModelRef), GatewayError> {
+        let request = RouteRequest {
+            messages,
+            has_tools,
+            language: options.language(),
+            tags: options.tags(),
+        };
+        self.router
+            .as_ref()
+            .and_then(|router| router.route(&request))
+            .or_else(|| self.default_model.clone())
+            .ok_or(GatewayError::NoDefaultModel)
+    }
+
+    /// Fetches the model list, then keeps refreshing it every `interval` in a
+    /// background task so UIs can react to models being added or removed.
+    ///
+    /// Receivers see a change only when the set of models changes; failed
+    /// refreshes keep the last list. The task stops once every receiver has
+    /// been dropped. Must be called within a Tokio runtime.
+    pub async fn watch_models(
+        &self,
+        interval: Duration,
+    ) -> Result<tokio::sync::watch::Receiver<ListModelsResponse>, GatewayError> {
+        let (sender, receiver) = tokio::sync::watch::channel(self.list_models().await?);
+        watch::spawn_model_watch(self.clone(), interval, sender);
+        Ok(receiver)
+    }
+
+    /// Joins `path` onto the versioned API base, tolerating stray slashes on
+    /// either side.
+    fn api_url(&self, path: &str) -> String {
+        let mut url = self.base_url.trim_end_matches('/').to_string();
+        if let Some(version) = self.api_version.as_deref().map(|v| v.trim_matches('/'))
+            && !version.is_empty()
+        {
+            url.push('/');
+            url.push_str(version);
+        }
+        url.push('/');
+        url.push_str(path.trim_start_matches('/'));
+        url
+    }
+
+    /// `location`, which may be absolute or relative to the API URL, as a
+    /// full URL.
+    fn resolve_url(&self, location: &str) -> Result<String, GatewayError> {
+        reqwest::Url::parse(&self.api_url(""))
+            .and_then(|base| base.join(location))
+            .map(String::from)
+            .map_err(|e| GatewayError::Other(Box::new(e)))
+    }
+
+    /// The gateway serves `/health` from the root server, not under the
+    /// versioned API prefix. With an explicit [`Self::with_api_version`] the
+    /// base URL already is the root; otherwise a trailing `/v<digits>`
+    /// segment is stripped from it before appending `/health`.
+    fn health_url(&self) -> String {
+        let trimmed = self.base_url.trim_end_matches('/');
+        if self.api_version.is_some() {
+            return format!("{trimmed}/health");
+        }
+        let root = match trimmed.rsplit_once('/') {
+            Some((prefix, last))
+                if last.len() >= 2
+                    && last.starts_with('v')
+                    && last[1..].chars().all(|c| c.is_ascii_digit()) =>
+            {
+                prefix
+            }
+            _ => trimmed,
+        };
+        format!("{root}/health")
+    }
+
+    fn messages_url(&self, provider: Option<Provider>) -> String {
+        let url = self.api_url("messages");
+        match provider {
+            Some(provider) => format!("{url}?provider={provider}"),
+            None => url,
+        }
+    }
+
+    fn build_chat_request(
+        &self,
+        provider: Provider,
+        model: &str,
+        mut messages: Vec<Message>,
+        stream: bool,
+    ) -> CreateChatCompletionRequest {
+        let instruction_role = self
+            .instruction_roles
+            .get(&provider)
+            .copied()
+            .or_else(|| InstructionRole::default_for(provider));
+        if let Some(role) = instruction_role {
+            role.apply(&mut messages);
+        }
+        // `tools` and `max_tokens` are deliberately omitted from streaming
+        // requests; every other field falls back to the schema defaults via
+        // `Default`. See CLAUDE.md for the streaming asymmetry.
+        CreateChatCompletionRequest {
+            model: model.to_string(),
+            messages,
+            stream,
+            tools: if stream {
+                Vec::new()
+            } else {
+                self.tools.clone().unwrap_or_default()
+            },
+            max_tokens: if stream { None } else { self.max_tokens },
+            seed: self.seed,
+            ..Default::default()
+        }
+    }
+
+    /// Embeds `request` through the gateway proxy, queued behind the
+    /// rate-limit budget at `priority`.
+    async fn embed(
+        &self,
+        provider: Provider,
+        request: EmbeddingRequest,
+        priority: Priority,
+    ) -> Result<EmbeddingResponse, GatewayError> {
+        let url = self.api_url(&format!("proxy/{provider}/embeddings"));
+        let context = ErrorContext::new(Method::POST, &url, Some(provider), Some(request.model()));
+        let inputs = request.len();
+        let request = self.request(Method::POST, &url).json(&request);
+        async {
+            // The same input always embeds the same way.
+            let response = self.send(request, priority, true).await?;
+            match response.status() {
+                StatusCode::OK => {
+                    decode::from_response::<EmbeddingResponse>(response, self.max_response_bytes)
+                        .await?
+                        .align(inputs)
+                }
+                status => Err(map_error_status(status, response).await),
+            }
+        }
+        .await
+        .map_err(context.wrap(self.error_context))
+    }
+
+    /// Embeds every text `input` yields with `provider`, in batches of the
+    /// pipeline's size and with up to its concurrency in flight, yielding
+    /// the vectors in input order.
+    ///
+    /// `input` is only read as fast as batches complete, so a large corpus
+    /// is never held in memory at once. Batches are sent at
+    /// [`Priority::Low`] and wait behind the client's rate-limit budget, so
+    /// a long indexing run leaves room for interactive calls. A batch that
+    /// fails yields its error in place of its vectors, and the pipeline
+    /// carries on with the next one; its inputs' indices are missing from
+    /// the output.
+    pub fn embed_stream<S>(
+        &self,
+        provider: Provider,
+        pipeline: EmbeddingPipeline,
+        input: S,
+    ) -> impl Stream<Item = Result<EmbeddedText, GatewayError>> + Send
+    where
+        S: Stream + Send,
+        S::Item: Into<String>,
+    {
+        let batch_size = pipeline.batch_size();
+        let concurrency = pipeline.concurrency();
+        input
+            .map(Into::into)
+            .chunks(batch_size)
+            .enumerate()
+            .map(move |(batch, texts)| {
+                let request = pipeline.request(texts.clone());
+                async move {
+                    let response = self.embed(provider, request, Priority::Low).await?;
+                    let first = (batch * batch_size) as u64;
+                    let embedded: Vec<Result<_, GatewayError>> = (first..)
+                        .zip(texts)
+                        .zip(response.data)
+                        .map(|((index, text), embedding)| {
+                            Ok(EmbeddedText {
+                                index,
+                                text,
+                                embedding: embedding.embedding,
+                            })
+                        })
+                        .collect();
+                    Ok::<_, GatewayError>(futures_util::stream::iter(embedded))
+                }
+            })
+            .buffered(concurrency)
+            .try_flatten()
+    }
+
+    /// Polls `job` until it reaches a terminal [`JobState`], waiting longer
+    /// between polls as the policy says, or as a `Retry-After` header asks.
+    ///
+    /// A `202 Accepted` poll means the job is still running. Any other
+    /// success is a status body, whose `status` (or agent task
+    /// `status.state`) decides whether the job is done; the final body is
+    /// returned parsed as `T`. Fails with
+    /// [`GatewayError::JobDeadlineExceeded`] if the policy's deadline
+    /// passes first.
+    pub async fn poll_until_complete<T: serde::de::DeserializeOwned>(
+        &self,
+        job: &Job,
+        policy: &PollPolicy,
+    ) -> Result<JobOutcome<T>, GatewayError> {
+        let url = self.resolve_url(job.location())?;
+        let context = ErrorContext::new(Method::GET, &url, None, None);
+        let started = Instant::now();
+        let mut interval = policy.first_interval();
+        async {
+            loop {
+                let request = self.request(Method::GET, &url);
+                let response = self.send(request, Priority::Normal, true).await?;
+                let retry_after = retry::retry_after(response.headers());
+                match response.status() {
+                    StatusCode::ACCEPTED => {}
+                    status if status.is_success() => {
+                        let body = decode::read_body(response, self.max_response_bytes).await?;
+                        let value: serde_json::Value = decode::from_slice(&body)?;
+                        let state = JobState::of(&value);
+                        if state.is_terminal() {
+                            let body = decode::from_value(value, &body)?;
+                            return Ok(JobOutcome { state, body });
+                        }
+                    }
+                    status => return Err(map_error_status(status, response).await),
+                }
+                let wait = retry_after.unwrap_or(interval);
+                if let Some(deadline) = policy.deadline()
+                    && started.elapsed() + wait > deadline
+                {
+                    return Err(GatewayError::JobDeadlineExceeded {
+                        location: job.location().to_string(),
+                        deadline,
+                    });
+                }
+                if !self.replaying() {
+                    tokio::time::sleep(wait).await;
+                }
+                interval = policy.next_interval(interval);
+            }
+        }
+        .await
+        .map_err(context.wrap(self.error_context))
+    }
+
+    /// Streams `artifact` to `writer` without buffering it, resuming from
+    /// the last byte written if the connection drops, and verifies its
+    /// checksum if the artifact has one.
+    ///
+    /// Fails with [`GatewayError::ArtifactChanged`] if a resumed download
+    /// would mix two versions of the artifact. What was already written to
+    /// `writer` stays there.
+    pub async fn download_artifact<W: AsyncWrite + Unpin + ?Sized>(
+        &self,
+        artifact: &Artifact,
+        writer: &mut W,
+    ) -> Result<DownloadSummary, GatewayError> {
+        download::download(self, artifact, writer, 0, Default::default())
+            .await
+            .map_err(
+                ErrorContext::new(Method::GET, artifact.location(), None, None)
+                    .wrap(self.error_context),
+            )
+    }
+
+    /// Downloads `artifact` to the file at `path`, creating it if needed.
+    /// A file left partial by an earlier attempt, even from another
+    /// process, is continued rather than started over, and the checksum
+    /// still covers the whole artifact.
+    ///
+    /// After a [`GatewayError::ChecksumMismatch`] or
+    /// [`GatewayError::ArtifactChanged`], delete the file before trying
+    /// again.
+    pub async fn download_artifact_to_file(
+        &self,
+        artifact: &Artifact,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<DownloadSummary, GatewayError> {
+        download::download_to_file(self, artifact, path.as_ref())
+            .await
+            .map_err(
+                ErrorContext::new(Method::GET, artifact.location(), None, None)
+                    .wrap(self.error_context),
+            )
+    }
+
+    /// Calls a gateway endpoint the SDK doesn't model yet, at `path`
+    /// relative to the API URL, with `body` sent as JSON.
+    ///
+    /// The request gets the same auth, trace and scheduling headers, query
+    /// parameters, rate limiting and error mapping as the modelled calls,
+    /// and is retried if `method` is idempotent. An empty response body
+    /// parses as JSON `null`, so `T` can be `()` or an `Option`.
+    ///
+    /// To give such endpoints a typed API, wrap them in an extension trait:
+    ///
+    /// ```
+    /// use inference_gateway_sdk::{GatewayError, InferenceGatewayClient, Method};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Agent {
+    ///     name: String,
+    /// }
+    ///
+    /// trait AgentsExt {
+    ///     async fn list_agents(&self) -> Result<Vec<Agent>, GatewayError>;
+    /// }
+    ///
+    /// impl AgentsExt for InferenceGatewayClient {
+    ///     async fn list_agents(&self) -> Result<Vec<Agent>, GatewayError> {
+    ///         self.raw_request(Method::GET, "a2a/agents", None).await
+    ///     }
+    /// }
+    /// ```
+    pub async fn raw_request<T: serde::de::DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<serde_json::Value>,
+    ) -> Result<T, GatewayError> {
+        let url = self.resolve_url(path)?;
+        let context = ErrorContext::new(method.clone(), &url, None, None);
+        let idempotent = method.is_idempotent();
+        let mut request = self.request(method, &url);
+        if let Some(body) = &body {
+            request = request.json(body);
+        }
+        async {
+            let response = self.send(request, Priority::Normal, idempotent).await?;
+            let status = response.status();
+            if !status.is_success() {
+                return Err(map_error_status(status, response).await);
+            }
+            let body = decode::read_body(response, self.max_response_bytes).await?;
+            if body.is_empty() {
+                return decode::from_slice(b"null");
+            }
+            decode::from_slice(&body)
+        }
+        .await
+        .map_err(context.wrap(self.error_context))
+    }
+
+    /// POSTs `body` as JSON to a streaming gateway endpoint the SDK doesn't
+    /// model yet, at `path` relative to the API URL, and yields its
+    /// server-sent events as they arrive.
+    ///
+    /// The stream gets the same headers, rate limiting, error mapping, size
+    /// limits and first-token timeout as the modelled streams; any event
+    /// counts as the first token.
+    pub fn raw_stream(
+        &self,
+        path: &str,
+        body: serde_json::Value,
+    ) -> impl Stream<Item = Result<SSEvents, GatewayError>> + Send + use<> {
+        match self.resolve_url(path) {
+            Ok(url) => {
+                let context = ErrorContext::new(Method::POST, &url, None, None);
+                let events = sse_stream(
+                    self.request(Method::POST, &url).json(&body),
+                    self.stream_settings(None, &RequestOptions::default()),
+                    StreamKind::Raw,
+                )
+                .map_ok(|(event, _)| event);
+                Either::Left(events.map_err(context.wrap(self.error_context)))
+            }
+            Err(e) => Either::Right(futures_util::stream::once(async { Err(e) })),
+        }
+    }
+
+    /// Lowers `payload.max_tokens` to what the context window leaves after
+    /// the prompt and the reserve set with [`Self::with_auto_max_tokens`].
+    async fn budget_max_tokens(
+        &self,
+        provider: Provider,
+        payload: &mut CreateChatCompletionRequest,
+    ) -> Result<(), GatewayError> {
+        let Some(reserve) = self.max_tokens_reserve else {
+            return Ok(());
+        };
+        let Some(available) = self.context_window(provider, &payload.model).await else {
+            return Ok(());
+        };
+        let needed = tokens::estimate(&payload.messages) + reserve;
+        let left = available.saturating_sub(needed);
+        if left == 0 {
+            return Err(GatewayError::ContextWindowExceeded { needed, available });
+        }
+        let left = i64::try_from(left).unwrap_or(i64::MAX);
+        payload.max_tokens = Some(payload.max_tokens.map_or(left, |max| max.min(left)));
+        Ok(())
+    }
+
+    /// The context window of `model`, fetched once and then cached. Calls
+    /// that miss the cache at the same time wait for one fetch. A failed
+    /// lookup is not cached, and leaves the request unbudgeted.
+    async fn context_window(&self, provider: Provider, model: &str) -> Option<u64> {
+        let qualified = format!("{provider}/{model}");
+        let cached = || {
+            self.context_windows
+                .lock()
+                .unwrap()
+                .get(&qualified)
+                .copied()
+        };
+        if let Some(window) = cached() {
+            return window;
+        }
+        let _fetching = self.context_window_fetch.lock().await;
+        // Another call may have fetched it while this one waited.
+        if let Some(window) = cached() {
+            return window;
+        }
+        let models = self
+            .list_models_with_include(Some(provider), &["context_window"])
+            .await
+            .ok()?;
+        let window = context_window_of(&models, &qualified, model);
+        self.context_windows
+            .lock()
+            .unwrap()
+            .insert(qualified, window);
+        window
+    }
+}
+
+async fn map_error_status(status: StatusCode, response: reqwest::Response) -> GatewayError {
+    let retry_after = retry::retry_after(response.headers());
+    let message = match response.json::<serde_json::Value>().await {
+        Ok(body) => error_body_message(&body).unwrap_or_else(|| status_reason(status)),
+        Err(_) => status_reason(status),
+    };
+    with_retry_after(error_for_status(status, message), retry_after)
+}
+
+/// Like [`map_error_status`], for a response to a streaming request. The
+/// body is usually a JSON error, but some providers answer with an SSE
+/// `error` event instead.
+async fn map_stream_error_status(status: StatusCode, response: reqwest::Response) -> GatewayError {
+    let retry_after = retry::retry_after(response.headers());
+    let body = response.bytes().await.unwrap_or_default();
+    let error = match serde_json::from_slice::<serde_json::Value>(&body) {
+        Ok(body) => error_for_status(
+            status,
+            error_body_message(&body).unwrap_or_else(|| status_reason(status)),
+        ),
+        Err(_) => {
+            let mut decoder = SseDecoder::new();
+            decoder.push(&body);
+            std::iter::from_fn(|| decoder.next_event())
+                .find(|event| event.event.as_deref() == Some("error"))
+                .map_or_else(
+                    || error_for_status(status, status_reason(status)),
+                    |event| map_error_event(status, &event.data),
+                )
+        }
+    };
+    with_retry_after(error, retry_after)
+}
+
+fn status_reason(status: StatusCode) -> String {
+    status.canonical_reason().unwrap_or("unknown").to_string()
+}
+
+/// Fills in the `Retry-After` wait of a rate-limit error.
+fn with_retry_after(error: GatewayError, retry_after: Option<Duration>) -> GatewayError {
+    match error {
+        GatewayError::RateLimited { message, .. } => GatewayError::RateLimited {
+            retry_after,
+            message,
+        },
+        error => error,
+    }
+}
+
+/// Extracts the human-readable message from an error body.
+fn error_body_message(body: &serde_json::Value) -> Option<String> {
+    // Gateway errors are `{"error": "..."}`; Messages endpoints use the
+    // Anthropic shape `{"type": "error", "error": {"type": ..., "message": ...}}`.
+    match body.get("error")? {
+        serde_json::Value::String(error) => Some(error.clone()),
+        error => error
+            .get("message")
+            .and_then(|m| m.as_str())
+            .map(str::to_string),
+    }
+}
+
+fn error_for_status(status: StatusCode, message: String) -> GatewayError {
+    match status {
+        StatusCode::UNAUTHORIZED => GatewayError::Unauthorized(message),
+        StatusCode::FORBIDDEN => GatewayError::Forbidden(message),
+        StatusCode::NOT_FOUND => GatewayError::NotFound(message),
+        StatusCode::BAD_REQUEST => GatewayError::BadRequest(message),
+        StatusCode::INTERNAL_SERVER_ERROR => GatewayError::InternalError(message),
+        StatusCode::TOO_MANY_REQUESTS => GatewayError::RateLimited {
+            retry_after: None,
+            message,
+        },
+        other => GatewayError::Other(Box::new(std::io::Error::other(format!(
+            "Unexpected status code: {other}"
+        )))),
+    }
+}
+
+/// Maps the payload of an `event: error` SSE frame to an error.
+///
+/// Anthropic-style bodies carry an error `type` that picks the variant.
+/// Gateway bodies don't, so the response status is used when it signals an
+/// error, and [`GatewayError::InternalError`] otherwise.
+fn map_error_event(status: StatusCode, data: &str) -> GatewayError {
+    let Ok(body) = serde_json::from_str::<serde_json::Value>(data) else {
+        return GatewayError::InternalError(data.to_string());
+    };
+    let message = error_body_message(&body).unwrap_or_else(|| data.to_string());
+    let error_type = body
+        .get("error")
+        .and_then(|error| error.get("type"))
+        .and_then(|t| t.as_str());
+    let status = match error_type {
+        Some("invalid_request_error") => StatusCode::BAD_REQUEST,
+        Some("authentication_error") => StatusCode::UNAUTHORIZED,
+        Some("permission_error") => StatusCode::FORBIDDEN,
+        Some("not_found_error") => StatusCode::NOT_FOUND,
+        Some("rate_limit_error") => StatusCode::TOO_MANY_REQUESTS,
+        Some(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        None if status.is_client_error() || status.is_server_error() => status,
+        None => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    error_for_status(status, message)
+}
+
+/// Client settings that shape a streaming call, cloned out of the client so
+/// the stream does not borrow it.
+#[derive(Clone)]
+struct StreamSettings {
+    /// The provider whose quirks chat completion events are normalized for.
+    provider: Option<Provider>,
+    buffer_capacity: usize,
+    max_response_bytes: Option<usize>,
+    max_event_bytes: Option<usize>,
+    first_token_timeout: Option<Duration>,
+    timeout: Option<Duration>,
+    rate_limit: RateLimitBudget,
+    throttle: Option<ProviderThrottle>,
+    priority: Priority,
+    queue_limit: Option<QueueLimit>,
+    tape: Option<Arc<replay::Tape>>,
+    middleware: Vec<Arc<dyn Middleware>>,
+    endpoints: Option<Arc<EndpointPool>>,
+    /// Where the response status is recorded for the call's metrics.
+    status: Option<StatusSlot>,
+}
+
+impl StreamSettings {
+    /// Records the response status for `recorder`, if the call has one.
+    fn recording_status(mut self, recorder: Option<&CallRecorder>) -> Self {
+        self.status = recorder.map(CallRecorder::status_slot);
+        self
+    }
+
+    /// Waits for the provider throttle, if any, then the rate-limit budget.
+    async fn acquire(&self) -> Result<(), GatewayError> {
+        if let Some(throttle) = &self.throttle {
+            throttle.acquire(self.priority, self.queue_limit).await?;
         }
+        self.rate_limit
+            .acquire(self.priority, self.queue_limit)
+            .await
+    }
 
-        let response = request.send().await?;
-        match response.status() {
-            StatusCode::OK => Ok(response.json().await?),
-            status => Err(map_error_status(status, response).await),
+    /// Sends `request` through the client's middleware, and the tape if the
+    /// client has one.
+    async fn send(&self, request: RequestBuilder) -> Result<reqwest::Response, GatewayError> {
+        // Boxed, as it would otherwise inflate every caller's future.
+        Box::pin(middleware::send(
+            self.tape.as_deref(),
+            &self.middleware,
+            self.endpoints.as_deref(),
+            request,
+        ))
+        .await
+    }
+
+    /// Fails if an event of `len` bytes exceeds the per-event limit.
+    fn check_event_size(&self, len: usize) -> Result<(), GatewayError> {
+        match self.max_event_bytes {
+            Some(limit) if len > limit => Err(GatewayError::ResponseTooLarge { limit }),
+            _ => Ok(()),
         }
     }
 }
 
-impl InferenceGatewayAPI for InferenceGatewayClient {
-    async fn list_models(&self) -> Result<ListModelsResponse, GatewayError> {
-        self.fetch_models("").await
+fn sse_stream(
+    request: RequestBuilder,
+    settings: StreamSettings,
+    kind: StreamKind,
+) -> impl Stream<Item = Result<(SSEvents, EventReading), GatewayError>> + Send {
+    async_stream::try_stream! {
+        let deadline = settings
+            .timeout
+            .map(|timeout| (timeout, tokio::time::Instant::now() + timeout));
+        within_deadline(deadline, settings.acquire()).await?;
+        // `Some` until the first token arrives.
+        let mut first_token = settings
+            .first_token_timeout
+            .map(|timeout| (timeout, tokio::time::Instant::now() + timeout));
+
+        let send = within_deadline(deadline, settings.send(request));
+        let response = within_first_token(first_token, send).await??;
+        settings.rate_limit.update(response.headers());
+        let status = response.status();
+        record_status(settings.status.as_ref(), status);
+        if status != StatusCode::OK {
+            Err(map_stream_error_status(status, response).await)?;
+            return;
+        }
+        let mut stream = response.bytes_stream();
+        let mut decoder = SseDecoder::with_capacity(settings.buffer_capacity);
+        let mut bytes_received = 0u64;
+        let mut usage: Option<CompletionUsage> = None;
+
+        while let Some(chunk) = within_first_token(first_token, stream.next()).await? {
+            let chunk = chunk.map_err(|source| GatewayError::StreamInterrupted {
+                bytes_received,
+                last_event_id: decoder.last_event_id().map(str::to_string),
+                source,
+            })?;
+            bytes_received += chunk.len() as u64;
+            decoder.push(&chunk);
+            while let Some(mut event) = decoder.next_event() {
+                settings.check_event_size(event.data.len())?;
+                if event.event.as_deref() == Some("error") {
+                    Err(map_error_event(status, &event.data))?;
+                }
+                if let (StreamKind::ChatCompletion, Some(provider)) = (kind, settings.provider) {
+                    normalize::event(provider, &mut event);
+                }
+                let reading = kind.read(&event, usage.as_ref());
+                if reading.token {
+                    first_token = None;
+                }
+                if let Some(next) = &reading.usage {
+                    if let Some(throttle) = &settings.throttle {
+                        let charged = usage.as_ref().map_or(0, |u| u.total_tokens);
+                        throttle.charge(next.total_tokens - charged);
+                    }
+                    usage = Some(next.clone());
+                }
+                yield (event, reading);
+            }
+            settings.check_event_size(decoder.pending_len())?;
+        }
     }
+}
 
-    async fn list_models_by_provider(
+async fn within_first_token<F: Future>(
+    first_token: Option<(Duration, tokio::time::Instant)>,
+    future: F,
+) -> Result<F::Output, GatewayError> {
+    match first_token {
+        Some((timeout, deadline)) => tokio::time::timeout_at(deadline, future)
+            .await
+            .map_err(|_| GatewayError::FirstTokenTimeout(timeout)),
+        None => Ok(future.await),
+    }
+}
+
+/// `part` as the `file` of an upload form, named `filename`.
+fn file_part(part: Part, filename: &str) -> Part {
+    part.file_name(filename.to_string())
+        .mime_str("application/octet-stream")
+        .expect("a valid media type")
+}
+
+/// Stores `status` in `slot`, if the call records one.
+fn record_status(slot: Option<&StatusSlot>, status: StatusCode) {
+    if let Some(slot) = slot {
+        *slot.lock().unwrap() = Some(status);
+    }
+}
+
+/// Fails `future` with [`GatewayError::Timeout`] if it takes longer than
+/// `timeout`.
+async fn within_timeout<T>(
+    timeout: Option<Duration>,
+    future: impl Future<Output = Result<T, GatewayError>>,
+) -> Result<T, GatewayError> {
+    let deadline = timeout.map(|timeout| (timeout, tokio::time::Instant::now() + timeout));
+    within_deadline(deadline, future).await
+}
+
+/// Fails `future` with [`GatewayError::Timeout`] if it hasn't completed by
+/// the deadline of a call with the given timeout.
+async fn within_deadline<T>(
+    deadline: Option<(Duration, tokio::time::Instant)>,
+    future: impl Future<Output = Result<T, GatewayError>>,
+) -> Result<T, GatewayError> {
+    match deadline {
+        Some((timeout, deadline)) => tokio::time::timeout_at(deadline, future)
+            .await
+            .unwrap_or(Err(GatewayError::Timeout(timeout))),
+        None => future.await,
+    }
+}
+
+/// The text of `response`'s first choice, empty if it has none.
+fn first_choice_text(response: &CreateChatCompletionResponse) -> String {
+    response
+        .choices
+        .first()
+        .map(|choice| tokens::text(&choice.message.content))
+        .unwrap_or_default()
+}
+
+/// The context window the gateway reports for `model`, listed under either
+/// its `qualified` or its bare id.
+fn context_window_of(models: &ListModelsResponse, qualified: &str, model: &str) -> Option<u64> {
+    models
+        .data
+        .iter()
+        .find(|m| m.id == qualified || m.id == model)
+        .and_then(|m| m.context_window.as_ref())
+        .map(|window| window.tokens.max(0) as u64)
+}
+
+impl InferenceGatewayClient {
+    /// Sends a chat completion once, without failing over, returning its
+    /// result and the status of the last response it got.
+    async fn attempt_chat_completion(
         &self,
         provider: Provider,
-    ) -> Result<ListModelsResponse, GatewayError> {
-        self.fetch_models(&format!("provider={provider}")).await
+        model: String,
+        messages: Vec<Message>,
+        options: &ChatCompletionOptions,
+    ) -> (
+        Result<CreateChatCompletionResponse, GatewayError>,
+        Option<StatusCode>,
+    ) {
+        let url = format!("{}?provider={provider}", self.api_url("chat/completions"));
+        let mut recorder = self.record_call(&url, Some(provider), &model);
+        let throttle = self.throttle(Some(provider));
+        let timeout = options.request().timeout();
+        let status = recorder
+            .as_ref()
+            .map_or_else(StatusSlot::default, CallRecorder::status_slot);
+        let mut retries = 0;
+        let result = within_timeout(timeout, async {
+            self.check_attachments(provider, &messages)?;
+            let mut payload = self.build_chat_request(provider, &model, messages, false);
+            options.apply_to(&mut payload);
+            self.budget_max_tokens(provider, &mut payload).await?;
+            let request = self
+                .request_with(Method::POST, &url, options.request())
+                .json(&options.body(&payload));
+            let options = options.request();
+            if let Some(throttle) = throttle {
+                throttle
+                    .acquire(options.priority(), self.queue_limit)
+                    .await?;
+            }
+            let response = self
+                .send_counting_retries(
+                    request,
+                    options.priority(),
+                    options.is_retry_safe(),
+                    &mut retries,
+                )
+                .await?;
+            record_status(Some(&status), response.status());
+
+            match response.status() {
+                StatusCode::OK => {
+                    let body = decode::read_body(response, self.max_response_bytes).await?;
+                    normalize::parse::<CreateChatCompletionResponse>(provider, &body)
+                }
+                status => Err(map_error_status(status, response).await),
+            }
+        })
+        .await
+        .map_err(
+            ErrorContext::new(Method::POST, &url, Some(provider), Some(&model))
+                .wrap(self.error_context),
+        );
+        if let (
+            Some(throttle),
+            Ok(CreateChatCompletionResponse {
+                usage: Some(usage), ..
+            }),
+        ) = (throttle, &result)
+        {
+            throttle.charge(usage.total_tokens);
+        }
+
+        if let Some(recorder) = &mut recorder {
+            recorder.retries(retries);
+            match &result {
+                Ok(response) => {
+                    if let Some(usage) = &response.usage {
+                        recorder.usage(usage.clone());
+                    }
+                    if let Some(choice) = response.choices.first() {
+                        recorder.finish_reason(choice.finish_reason.clone());
+                    }
+                }
+                Err(e) => recorder.error(e),
+            }
+        }
+        let status = *status.lock().unwrap();
+        (result, status)
     }
 
-    async fn list_models_with_include(
+    /// Checks the inline attachments of `messages` against `provider`'s
+    /// limits, unless the client was told not to.
+    fn check_attachments(
+        &self,
+        provider: Provider,
+        messages: &[Message],
+    ) -> Result<(), GatewayError> {
+        if !self.attachment_checks {
+            return Ok(());
+        }
+        attachment::check_messages(provider, messages)
+    }
+
+    /// Fails without a round-trip if the gateway reported `feature` as
+    /// disabled within the last [`FEATURE_RECHECK_INTERVAL`], so a feature
+    /// enabled on the gateway later is picked up again.
+    fn check_feature(&self, feature: GatewayFeature) -> Result<(), GatewayError> {
+        let mut disabled = self.disabled_features.lock().unwrap();
+        match disabled.get(&feature) {
+            Some((_, since)) if since.elapsed() >= FEATURE_RECHECK_INTERVAL => {
+                disabled.remove(&feature);
+                Ok(())
+            }
+            Some((hint, _)) => Err(GatewayError::FeatureDisabled {
+                feature,
+                hint: hint.clone(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    fn disable_feature(&self, feature: GatewayFeature, hint: String) -> GatewayError {
+        self.disabled_features
+            .lock()
+            .unwrap()
+            .insert(feature, (hint.clone(), tokio::time::Instant::now()));
+        GatewayError::FeatureDisabled { feature, hint }
+    }
+
+    async fn fetch_models(
         &self,
         provider: Option<Provider>,
         include: &[&str],
@@ -360,90 +2563,693 @@ impl InferenceGatewayAPI for InferenceGatewayClient {
         if !include.is_empty() {
             query.push(format!("include={}", include.join(",")));
         }
-        self.fetch_models(&query.join("&")).await
+        let mut url = self.api_url("models");
+        if !query.is_empty() {
+            url.push('?');
+            url.push_str(&query.join("&"));
+        }
+        async {
+            let body = self.get_list_body(&url, None).await?;
+            decode::from_slice(&body)
+        }
+        .await
+        .map_err(ErrorContext::new(Method::GET, &url, provider, None).wrap(self.error_context))
+    }
+
+    /// Reads a list endpoint's body. A copy cached from an earlier call is
+    /// revalidated with `If-None-Match` and reused if the gateway answers
+    /// `304 Not Modified`.
+    async fn get_list_body(
+        &self,
+        url: &str,
+        feature: Option<GatewayFeature>,
+    ) -> Result<bytes::Bytes, GatewayError> {
+        let cached = self.list_cache.lock().unwrap().get(url).cloned();
+        let etag = cached.as_ref().map(|(etag, _)| etag.as_str());
+        let response = self.get_list(url, feature, etag).await?;
+        if let (StatusCode::NOT_MODIFIED, Some((_, body))) = (response.status(), &cached) {
+            return Ok(body.clone());
+        }
+        let etag = response
+            .headers()
+            .get(reqwest::header::ETAG)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let body = decode::read_body(response, self.max_response_bytes).await?;
+        let mut cache = self.list_cache.lock().unwrap();
+        match etag {
+            Some(etag) => cache.insert(url.to_string(), (etag, body.clone())),
+            None => cache.remove(url),
+        };
+        Ok(body)
+    }
+
+    /// Sends a GET to a list endpoint and returns the response if it
+    /// succeeded, or was not modified since `etag`. `feature`, if any, is
+    /// checked first, and a `403` marks it as disabled.
+    async fn get_list(
+        &self,
+        url: &str,
+        feature: Option<GatewayFeature>,
+        etag: Option<&str>,
+    ) -> Result<reqwest::Response, GatewayError> {
+        if let Some(feature) = feature {
+            self.check_feature(feature)?;
+        }
+        let mut request = self.request(Method::GET, url);
+        if let Some(etag) = etag {
+            request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+        }
+        let response = self.send(request, Priority::Normal, true).await?;
+        match (response.status(), feature) {
+            (StatusCode::OK, _) => Ok(response),
+            (StatusCode::NOT_MODIFIED, _) if etag.is_some() => Ok(response),
+            // The gateway answers 403 here only when the feature is not exposed.
+            (StatusCode::FORBIDDEN, Some(feature)) => {
+                match map_error_status(StatusCode::FORBIDDEN, response).await {
+                    GatewayError::Forbidden(hint) => Err(self.disable_feature(feature, hint)),
+                    other => Err(other),
+                }
+            }
+            (status, _) => Err(map_error_status(status, response).await),
+        }
+    }
+}
+
+impl InferenceGatewayAPI for InferenceGatewayClient {
+    async fn list_models(&self) -> Result<ListModelsResponse, GatewayError> {
+        self.fetch_models(None, &[]).await
+    }
+
+    async fn list_models_by_provider(
+        &self,
+        provider: Provider,
+    ) -> Result<ListModelsResponse, GatewayError> {
+        self.fetch_models(Some(provider), &[]).await
+    }
+
+    async fn list_models_with_include(
+        &self,
+        provider: Option<Provider>,
+        include: &[&str],
+    ) -> Result<ListModelsResponse, GatewayError> {
+        self.fetch_models(provider, include).await
     }
 
     async fn generate_content(
         &self,
         provider: Provider,
-        model: &str,
+        model: impl Into<ModelRef> + Send,
         messages: Vec<Message>,
     ) -> Result<CreateChatCompletionResponse, GatewayError> {
-        let url = format!("{}/chat/completions?provider={}", self.base_url, provider);
-        let mut request = self.client.post(&url);
-        if let Some(token) = &self.token {
-            request = request.bearer_auth(token);
-        }
-
-        let payload = self.build_chat_request(model, messages, false);
-        let response = request.json(&payload).send().await?;
+        self.generate_content_with_options(provider, model, messages, RequestOptions::default())
+            .await
+    }
 
-        match response.status() {
-            StatusCode::OK => Ok(response.json().await?),
-            status => Err(map_error_status(status, response).await),
+    async fn generate_content_with_options(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef> + Send,
+        messages: Vec<Message>,
+        options: impl Into<ChatCompletionOptions> + Send,
+    ) -> Result<CreateChatCompletionResponse, GatewayError> {
+        let options = options.into();
+        let model = model.into().to_string();
+        let cached = match &self.cache {
+            Some(cache) => {
+                let mut payload =
+                    self.build_chat_request(provider, &model, messages.clone(), false);
+                options.apply_to(&mut payload);
+                // The built URL carries the client's and the call's query
+                // parameters, which may route the call differently.
+                let url = format!("{}?provider={provider}", self.api_url("chat/completions"));
+                let url = self
+                    .request_with(Method::POST, &url, options.request())
+                    .build()
+                    .ok()
+                    .map(|request| request.url().to_string());
+                let headers = options.request().scheduling_headers(&self.scheduling_hints);
+                let body = serde_json::to_vec(&options.body(&payload)).ok();
+                url.zip(body).map(|(url, body)| {
+                    let token = self.token.as_deref().map(String::as_str);
+                    (cache, cache::key(&url, token, headers, &body))
+                })
+            }
+            None => None,
+        };
+        if let Some((cache, key)) = &cached
+            && let Some(response) = cache.get(key).await
+        {
+            return Ok(response);
+        }
+        let started = Instant::now();
+        let shadow = shadow::start(self, &messages, &options);
+        let (provider, model, result) = match &self.failover {
+            Some(failover) => {
+                failover
+                    .run(provider, model, |provider, model| {
+                        self.attempt_chat_completion(provider, model, messages.clone(), &options)
+                    })
+                    .await
+            }
+            None => {
+                let (result, _) = self
+                    .attempt_chat_completion(provider, model.clone(), messages, &options)
+                    .await;
+                (provider, model, result)
+            }
+        };
+        if let Some(shadow) = shadow {
+            // Fails only if the shadow task panicked.
+            let _ = shadow.send(ShadowResult::new(provider, &model, started, &result));
+        }
+        if let (Some((cache, key)), Ok(response)) = (&cached, &result) {
+            cache.put(key, response).await;
         }
+        result
     }
 
     fn generate_content_stream(
         &self,
         provider: Provider,
-        model: &str,
+        model: impl Into<ModelRef> + Send,
+        messages: Vec<Message>,
+    ) -> impl Stream<Item = Result<SSEvents, GatewayError>> + Send {
+        self.generate_content_stream_with_options(
+            provider,
+            model,
+            messages,
+            RequestOptions::default(),
+        )
+    }
+
+    fn generate_content_stream_with_options(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef> + Send,
         messages: Vec<Message>,
+        options: impl Into<ChatCompletionOptions> + Send,
     ) -> impl Stream<Item = Result<SSEvents, GatewayError>> + Send {
-        let url = format!("{}/chat/completions?provider={}", self.base_url, provider);
-        let request_body = self.build_chat_request(model, messages, true);
-        sse_stream(self.client.clone(), self.token.clone(), url, request_body)
+        let options = options.into();
+        let model = model.into().to_string();
+        let url = format!("{}?provider={provider}", self.api_url("chat/completions"));
+        let checked = self.check_attachments(provider, &messages);
+        let mut request_body = self.build_chat_request(provider, &model, messages, true);
+        options.apply_to(&mut request_body);
+        let recorder = self.record_call(&url, Some(provider), &model);
+        let events = sse_stream(
+            self.request_with(Method::POST, &url, options.request())
+                .json(&options.body(&request_body)),
+            self.stream_settings(Some(provider), options.request())
+                .recording_status(recorder.as_ref()),
+            StreamKind::ChatCompletion,
+        );
+        let events = futures_util::stream::once(async move { checked.map(|()| events) })
+            .try_flatten()
+            .map_err(
+                ErrorContext::new(Method::POST, &url, Some(provider), Some(&model))
+                    .wrap(self.error_context),
+            );
+        record_stream(events, recorder)
+    }
+
+    async fn generate_content_resumable(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef> + Send,
+        messages: Vec<Message>,
+        max_resumes: u32,
+    ) -> Result<FoldedCompletion, GatewayError> {
+        let model = model.into();
+        let mut received = String::new();
+        let mut usage = None;
+        let mut resumes = 0;
+        loop {
+            let mut request = messages.clone();
+            if !received.is_empty() {
+                request.push(Message::assistant(received.clone()));
+                request.push(Message::user(stream::RESUME_PROMPT));
+            }
+            let chunks = self
+                .generate_content_stream(provider, model.clone(), request)
+                .chat_chunks();
+            let (mut folded, error) = stream::fold_until_error(chunks).await;
+            received.push_str(&tokens::text(&folded.message.content));
+            stream::add_usage(&mut usage, folded.usage.take());
+            match error {
+                Some(e)
+                    if matches!(e.root(), GatewayError::StreamInterrupted { .. })
+                        && resumes < max_resumes =>
+                {
+                    resumes += 1;
+                }
+                Some(e) => return Err(e),
+                None => {
+                    folded.message.content = MessageContent::Text(received);
+                    folded.usage = usage;
+                    return Ok(folded);
+                }
+            }
+        }
+    }
+
+    async fn ask(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef> + Send,
+        prompt: impl Into<String> + Send,
+    ) -> Result<String, GatewayError> {
+        let response = self
+            .generate_content(provider, model, vec![Message::user(prompt)])
+            .await?;
+        Ok(first_choice_text(&response))
+    }
+
+    async fn ask_with_system(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef> + Send,
+        system: impl Into<String> + Send,
+        prompt: impl Into<String> + Send,
+    ) -> Result<String, GatewayError> {
+        let messages = vec![Message::system(system), Message::user(prompt)];
+        let response = self.generate_content(provider, model, messages).await?;
+        Ok(first_choice_text(&response))
     }
 
     async fn create_message(
+        &self,
+        provider: Option<Provider>,
+        request: CreateMessagesRequest,
+    ) -> Result<MessagesResponse, GatewayError> {
+        self.create_message_with_options(provider, request, RequestOptions::default())
+            .await
+    }
+
+    async fn create_message_with_options(
         &self,
         provider: Option<Provider>,
         mut request: CreateMessagesRequest,
+        options: RequestOptions,
     ) -> Result<MessagesResponse, GatewayError> {
         request.stream = false;
-        let mut req = self.client.post(self.messages_url(provider));
-        if let Some(token) = &self.token {
-            req = req.bearer_auth(token);
+        let url = self.messages_url(provider);
+        let mut recorder = self.record_call(&url, provider, &request.model);
+        let throttle = self.throttle(provider);
+        let status = recorder.as_ref().map(CallRecorder::status_slot);
+        let mut retries = 0;
+        let result = within_timeout(options.timeout(), async {
+            if let Some(throttle) = throttle {
+                throttle
+                    .acquire(options.priority(), self.queue_limit)
+                    .await?;
+            }
+            let response = self
+                .send_counting_retries(
+                    self.request_with(Method::POST, &url, &options)
+                        .json(&request),
+                    options.priority(),
+                    options.is_retry_safe(),
+                    &mut retries,
+                )
+                .await?;
+            record_status(status.as_ref(), response.status());
+            match response.status() {
+                StatusCode::OK => Ok(decode::from_response::<MessagesResponse>(
+                    response,
+                    self.max_response_bytes,
+                )
+                .await?),
+                status => Err(map_error_status(status, response).await),
+            }
+        })
+        .await
+        .map_err(
+            ErrorContext::new(Method::POST, &url, provider, Some(&request.model))
+                .wrap(self.error_context),
+        );
+        if let (Some(throttle), Ok(response)) = (throttle, &result) {
+            throttle.charge(response.usage.input_tokens + response.usage.output_tokens);
         }
 
-        let response = req.json(&request).send().await?;
-        match response.status() {
-            StatusCode::OK => Ok(response.json().await?),
-            status => Err(map_error_status(status, response).await),
+        if let Some(recorder) = &mut recorder {
+            recorder.retries(retries);
+            match &result {
+                Ok(response) => {
+                    recorder.messages_usage(&response.usage);
+                    recorder.stop_reason(response.stop_reason.clone());
+                }
+                Err(e) => recorder.error(e),
+            }
         }
+        result
     }
 
     fn create_message_stream(
+        &self,
+        provider: Option<Provider>,
+        request: CreateMessagesRequest,
+    ) -> impl Stream<Item = Result<SSEvents, GatewayError>> + Send {
+        self.create_message_stream_with_options(provider, request, RequestOptions::default())
+    }
+
+    fn create_message_stream_with_options(
         &self,
         provider: Option<Provider>,
         mut request: CreateMessagesRequest,
+        options: RequestOptions,
     ) -> impl Stream<Item = Result<SSEvents, GatewayError>> + Send {
         request.stream = true;
-        sse_stream(
-            self.client.clone(),
-            self.token.clone(),
-            self.messages_url(provider),
-            request,
+        let url = self.messages_url(provider);
+        let recorder = self.record_call(&url, provider, &request.model);
+        let events = sse_stream(
+            self.request_with(Method::POST, &url, &options)
+                .json(&request),
+            self.stream_settings(provider, &options)
+                .recording_status(recorder.as_ref()),
+            StreamKind::Messages,
         )
+        .map_err(
+            ErrorContext::new(Method::POST, &url, provider, Some(&request.model))
+                .wrap(self.error_context),
+        );
+        record_stream(events, recorder)
     }
 
     async fn list_tools(&self) -> Result<ListToolsResponse, GatewayError> {
-        let url = format!("{}/mcp/tools", self.base_url);
-        let mut request = self.client.get(&url);
-        if let Some(token) = &self.token {
-            request = request.bearer_auth(token);
+        let url = self.api_url("mcp/tools");
+        async {
+            let body = self.get_list_body(&url, Some(GatewayFeature::Mcp)).await?;
+            decode::from_slice(&body)
+        }
+        .await
+        .map_err(ErrorContext::new(Method::GET, &url, None, None).wrap(self.error_context))
+    }
+
+    fn list_models_stream(&self) -> impl Stream<Item = Result<Model, GatewayError>> + Send {
+        let url = self.api_url("models");
+        let context = ErrorContext::new(Method::GET, &url, None, None);
+        futures_util::stream::once(async move { self.get_list(&url, None, None).await })
+            .map_ok(list_stream::items)
+            .try_flatten()
+            .map_err(context.wrap(self.error_context))
+    }
+
+    fn list_tools_stream(&self) -> impl Stream<Item = Result<McpTool, GatewayError>> + Send {
+        let url = self.api_url("mcp/tools");
+        let context = ErrorContext::new(Method::GET, &url, None, None);
+        futures_util::stream::once(async move {
+            self.get_list(&url, Some(GatewayFeature::Mcp), None).await
+        })
+        .map_ok(list_stream::items)
+        .try_flatten()
+        .map_err(context.wrap(self.error_context))
+    }
+
+    async fn upload_file(
+        &self,
+        provider: Provider,
+        filename: &str,
+        bytes: &[u8],
+        purpose: &str,
+    ) -> Result<FileContentPart, GatewayError> {
+        #[derive(serde::Deserialize)]
+        struct UploadedFile {
+            id: String,
+        }
+
+        let url = self.api_url(&format!("proxy/{provider}/files"));
+        let form = Form::new()
+            .text("purpose", purpose.to_string())
+            .part("file", file_part(Part::bytes(bytes.to_vec()), filename));
+        let request = self.request(Method::POST, &url).multipart(form);
+        async {
+            let response = self.send(request, Priority::Normal, false).await?;
+            match response.status() {
+                StatusCode::OK => {
+                    let file: UploadedFile =
+                        decode::from_response(response, self.max_response_bytes).await?;
+                    Ok(FileContentPart::from_id(file.id))
+                }
+                status => Err(map_error_status(status, response).await),
+            }
+        }
+        .await
+        .map_err(
+            ErrorContext::new(Method::POST, &url, Some(provider), None).wrap(self.error_context),
+        )
+    }
+
+    async fn count_tokens_remote(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef> + Send,
+        messages: &[Message],
+    ) -> Result<u64, GatewayError> {
+        let model = model.into();
+        let Some((path, body)) = tokens::count_request(provider, model.model(), messages) else {
+            // There is no endpoint to name, so the error has no context.
+            return Err(GatewayError::TokenCountingUnsupported(provider));
+        };
+        let url = self.api_url(&format!("proxy/{provider}/{path}"));
+        let request = self.request(Method::POST, &url).json(&body);
+        async {
+            // Counting has no side effects, so it is always safe to repeat.
+            let response = self.send(request, Priority::Normal, true).await?;
+            match response.status() {
+                StatusCode::OK => Ok(decode::from_response::<tokens::CountTokensResponse>(
+                    response,
+                    self.max_response_bytes,
+                )
+                .await?
+                .input_tokens),
+                status => Err(map_error_status(status, response).await),
+            }
+        }
+        .await
+        .map_err(
+            ErrorContext::new(Method::POST, &url, Some(provider), Some(model.model()))
+                .wrap(self.error_context),
+        )
+    }
+
+    async fn validate_request(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef> + Send,
+        messages: &[Message],
+    ) -> Result<(), GatewayError> {
+        let model = model.into();
+        let qualified = format!("{provider}/{}", model.model());
+        let models = self
+            .list_models_with_include(Some(provider), &["context_window"])
+            .await?;
+        let Some(available) = context_window_of(&models, &qualified, model.model()) else {
+            return Ok(());
+        };
+        // A failed count shouldn't fail a check an estimate can answer.
+        let needed = self
+            .count_tokens_remote(provider, model.clone(), messages)
+            .await
+            .unwrap_or_else(|_| tokens::estimate(messages));
+        if needed > available {
+            // Name the chat request the messages were checked for.
+            let url = format!("{}?provider={provider}", self.api_url("chat/completions"));
+            let context =
+                ErrorContext::new(Method::POST, &url, Some(provider), Some(model.model()));
+            return Err(context.wrap(self.error_context)(
+                GatewayError::ContextWindowExceeded { needed, available },
+            ));
+        }
+        Ok(())
+    }
+
+    async fn create_embeddings(
+        &self,
+        provider: Provider,
+        request: EmbeddingRequest,
+    ) -> Result<EmbeddingResponse, GatewayError> {
+        self.embed(provider, request, Priority::Normal).await
+    }
+
+    async fn create_speech(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef> + Send,
+        input: &str,
+        options: SpeechOptions,
+    ) -> Result<bytes::Bytes, GatewayError> {
+        let model = model.into();
+        let url = self.api_url(&format!("proxy/{provider}/audio/speech"));
+        async {
+            options.validate(provider)?;
+            let request = self
+                .request(Method::POST, &url)
+                .json(&options.request_body(model.model(), input));
+            let response = self.send(request, Priority::Normal, false).await?;
+            match response.status() {
+                StatusCode::OK => Ok(response.bytes().await?),
+                status => Err(map_error_status(status, response).await),
+            }
+        }
+        .await
+        .map_err(
+            ErrorContext::new(Method::POST, &url, Some(provider), Some(model.model()))
+                .wrap(self.error_context),
+        )
+    }
+
+    fn create_speech_stream(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef> + Send,
+        input: &str,
+        options: SpeechOptions,
+    ) -> impl Stream<Item = Result<bytes::Bytes, GatewayError>> + Send {
+        let model = model.into();
+        let url = self.api_url(&format!("proxy/{provider}/audio/speech"));
+        let request = options.validate(provider).map(|()| {
+            self.request(Method::POST, &url)
+                .json(&options.request_body(model.model(), input))
+        });
+        audio::speech_stream(
+            request,
+            self.stream_settings(Some(provider), &RequestOptions::default()),
+        )
+        .map_err(
+            ErrorContext::new(Method::POST, &url, Some(provider), Some(model.model()))
+                .wrap(self.error_context),
+        )
+    }
+
+    async fn create_transcription(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef> + Send,
+        audio: impl Into<AudioFile> + Send,
+        options: TranscriptionOptions,
+    ) -> Result<Transcription, GatewayError> {
+        let model = model.into();
+        let audio = audio.into();
+        let url = self.api_url(&format!("proxy/{provider}/audio/transcriptions"));
+        async {
+            let (filename, body, length) = audio.open().await?;
+            let form = options.form(model.model()).part(
+                "file",
+                file_part(Part::stream_with_length(body, length), &filename),
+            );
+            let request = self.request(Method::POST, &url).multipart(form);
+            let response = self.send(request, Priority::Normal, false).await?;
+            match response.status() {
+                StatusCode::OK => decode::from_response(response, self.max_response_bytes).await,
+                status => Err(map_error_status(status, response).await),
+            }
         }
+        .await
+        .map_err(
+            ErrorContext::new(Method::POST, &url, Some(provider), Some(model.model()))
+                .wrap(self.error_context),
+        )
+    }
+
+    fn transcribe_stream<S>(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef> + Send,
+        filename: &str,
+        audio: S,
+    ) -> impl Stream<Item = Result<TranscriptionEvent, GatewayError>> + Send
+    where
+        S: Stream<Item = Result<bytes::Bytes, std::io::Error>> + Send + 'static,
+    {
+        let model = model.into();
+        let url = self.api_url(&format!("proxy/{provider}/audio/transcriptions"));
+        let form = Form::new()
+            .text("model", model.model().to_string())
+            .text("stream", "true")
+            .part(
+                "file",
+                file_part(Part::stream(reqwest::Body::wrap_stream(audio)), filename),
+            );
+        let request = self.request(Method::POST, &url).multipart(form);
+        audio::transcription_stream(
+            request,
+            self.stream_settings(Some(provider), &RequestOptions::default()),
+        )
+        .map_err(
+            ErrorContext::new(Method::POST, &url, Some(provider), Some(model.model()))
+                .wrap(self.error_context),
+        )
+    }
 
-        let response = request.send().await?;
-        match response.status() {
-            StatusCode::OK => Ok(response.json().await?),
-            status => Err(map_error_status(status, response).await),
+    async fn create_moderation(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef> + Send,
+        input: impl Into<ModerationInput> + Send,
+    ) -> Result<ModerationResponse, GatewayError> {
+        let model = model.into();
+        let url = self.api_url(&format!("proxy/{provider}/moderations"));
+        let body = serde_json::json!({ "model": model.model(), "input": input.into() });
+        async {
+            let request = self.request(Method::POST, &url).json(&body);
+            let response = self.send(request, Priority::Normal, false).await?;
+            match response.status() {
+                StatusCode::OK => decode::from_response(response, self.max_response_bytes).await,
+                status => Err(map_error_status(status, response).await),
+            }
         }
+        .await
+        .map_err(
+            ErrorContext::new(Method::POST, &url, Some(provider), Some(model.model()))
+                .wrap(self.error_context),
+        )
     }
 
     async fn health_check(&self) -> Result<bool, GatewayError> {
-        let response = self.client.get(self.health_url()).send().await?;
-        Ok(response.status() == StatusCode::OK)
+        let url = self.health_url();
+        let request = self.trace_headers(self.client.get(&url));
+        match self.dispatch(request).await {
+            Ok(response) => Ok(response.status() == StatusCode::OK),
+            Err(e) => {
+                Err(ErrorContext::new(Method::GET, &url, None, None).wrap(self.error_context)(e))
+            }
+        }
+    }
+
+    async fn ready_check(&self) -> HealthStatus {
+        let request = self.trace_headers(self.client.get(self.health_url()));
+        match self.dispatch(request).await {
+            Ok(response) if response.status() == StatusCode::OK => {}
+            Ok(response) => {
+                return HealthStatus::Degraded(format!(
+                    "health check returned {}",
+                    response.status()
+                ));
+            }
+            Err(_) => return HealthStatus::Unreachable,
+        }
+        HealthStatus::from_result(self.fetch_models(None, &[]).await)
+    }
+
+    async fn provider_health(&self) -> Result<Vec<ProviderHealth>, GatewayError> {
+        let providers: BTreeSet<Provider> = self
+            .list_models()
+            .await?
+            .data
+            .into_iter()
+            .map(|model| model.served_by)
+            .collect();
+        let probes = providers.into_iter().map(|provider| async move {
+            let started = Instant::now();
+            let result = self.list_models_by_provider(provider).await;
+            ProviderHealth {
+                provider,
+                status: HealthStatus::from_result(result),
+                latency: started.elapsed(),
+            }
+        });
+        Ok(join_all(probes).await)
     }
 }
 
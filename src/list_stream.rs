@@ -0,0 +1,205 @@
+//! Incremental parsing of list responses.
+//!
+//! `{"object": "list", "data": [...]}` bodies from gateways with thousands of
+//! models or tools are read element by element: [`ArrayElements`] picks the
+//! items of the `data` array out of the byte stream as they arrive, so only
+//! the item being received is buffered and peak memory stays flat however
+//! long the list is.
+
+use futures_util::{Stream, StreamExt};
+use serde::de::{DeserializeOwned, Error as _};
+
+use crate::{GatewayError, decode};
+
+/// Yields the items of `response`'s `data` array as `T`s.
+pub(crate) fn items<T: DeserializeOwned + Send>(
+    response: reqwest::Response,
+) -> impl Stream<Item = Result<T, GatewayError>> + Send {
+    async_stream::try_stream! {
+        let mut stream = response.bytes_stream();
+        let mut elements = ArrayElements::new("data");
+        while let Some(chunk) = stream.next().await {
+            elements.push(&chunk?);
+            while let Some(element) = elements.next_element() {
+                yield decode::from_str::<T>(&String::from_utf8_lossy(&element))?;
+            }
+        }
+        if !elements.is_done() {
+            Err(GatewayError::DeserializationError {
+                source: serde_json::Error::custom("the body ended before the end of the list"),
+                path: "data".to_string(),
+                snippet: String::new(),
+            })?;
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum State {
+    /// Looking for the array under the key.
+    Seeking,
+    /// Inside the array, between elements.
+    Between,
+    /// Inside an element.
+    Element,
+    /// Past the end of the array.
+    Done,
+}
+
+/// Splits the elements of the array under a top-level `key` out of a JSON
+/// object fed in chunks.
+///
+/// The input is only scanned for structure, not validated; each element is
+/// handed out as raw JSON for the caller to parse.
+pub(crate) struct ArrayElements {
+    key: &'static str,
+    buffer: Vec<u8>,
+    /// Next byte of `buffer` to scan.
+    pos: usize,
+    state: State,
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+    /// Start of the string being scanned, after its opening quote.
+    string_start: usize,
+    /// Whether the last complete string in the top-level object was `key`.
+    after_key: bool,
+    element_start: usize,
+}
+
+/// Nesting depth of the array's elements: inside the object and the array.
+const ELEMENT_DEPTH: usize = 2;
+
+impl ArrayElements {
+    pub(crate) fn new(key: &'static str) -> Self {
+        Self {
+            key,
+            buffer: Vec::new(),
+            pos: 0,
+            state: State::Seeking,
+            depth: 0,
+            in_string: false,
+            escaped: false,
+            string_start: 0,
+            after_key: false,
+            element_start: 0,
+        }
+    }
+
+    /// Appends a chunk of the body.
+    pub(crate) fn push(&mut self, chunk: &[u8]) {
+        if self.state != State::Done {
+            self.buffer.extend_from_slice(chunk);
+        }
+    }
+
+    /// Whether the whole array has been seen.
+    pub(crate) fn is_done(&self) -> bool {
+        self.state == State::Done
+    }
+
+    /// Returns the next complete element, or `None` until more input
+    /// arrives.
+    pub(crate) fn next_element(&mut self) -> Option<Vec<u8>> {
+        while self.pos < self.buffer.len() && self.state != State::Done {
+            let byte = self.buffer[self.pos];
+            let pos = self.pos;
+            self.pos += 1;
+
+            if self.in_string {
+                if self.escaped {
+                    self.escaped = false;
+                } else if byte == b'\\' {
+                    self.escaped = true;
+                } else if byte == b'"' {
+                    self.in_string = false;
+                    if self.state == State::Seeking && self.depth == 1 {
+                        self.after_key =
+                            &self.buffer[self.string_start..pos] == self.key.as_bytes();
+                    }
+                }
+                continue;
+            }
+
+            if self.state == State::Between {
+                match byte {
+                    b',' | b' ' | b'\t' | b'\n' | b'\r' => continue,
+                    b']' => {
+                        self.finish();
+                        return None;
+                    }
+                    _ => {
+                        self.state = State::Element;
+                        self.element_start = pos;
+                    }
+                }
+            }
+
+            match byte {
+                b'"' => {
+                    self.in_string = true;
+                    self.string_start = pos + 1;
+                }
+                b'{' | b'[' => {
+                    if self.state == State::Seeking
+                        && self.depth == 1
+                        && byte == b'['
+                        && self.after_key
+                    {
+                        self.state = State::Between;
+                    }
+                    self.depth += 1;
+                }
+                b'}' | b']' if self.state == State::Element && self.depth == ELEMENT_DEPTH => {
+                    // A scalar element followed by the end of the array.
+                    let element = self.take_element(pos);
+                    self.finish();
+                    return element;
+                }
+                b'}' | b']' => {
+                    self.depth = self.depth.saturating_sub(1);
+                    if self.state == State::Element && self.depth == ELEMENT_DEPTH {
+                        self.state = State::Between;
+                        return self.take_element(pos + 1);
+                    }
+                }
+                b',' if self.state == State::Element && self.depth == ELEMENT_DEPTH => {
+                    self.state = State::Between;
+                    return self.take_element(pos);
+                }
+                _ => {}
+            }
+        }
+        self.compact();
+        None
+    }
+
+    /// Hands out the element ending before `end` and drops the bytes up to
+    /// it.
+    fn take_element(&mut self, end: usize) -> Option<Vec<u8>> {
+        let element = self.buffer[self.element_start..end].trim_ascii().to_vec();
+        self.buffer.drain(..end);
+        self.pos -= end;
+        Some(element)
+    }
+
+    /// Marks the array as complete and drops whatever follows it.
+    fn finish(&mut self) {
+        self.state = State::Done;
+        self.buffer = Vec::new();
+        self.pos = 0;
+    }
+
+    /// Drops scanned bytes that no pending string or element still needs.
+    fn compact(&mut self) {
+        let keep_from = match self.state {
+            State::Element => self.element_start,
+            State::Seeking if self.in_string => self.string_start,
+            _ => self.pos,
+        };
+        self.buffer.drain(..keep_from);
+        self.pos -= keep_from;
+        self.element_start -= keep_from.min(self.element_start);
+        self.string_start -= keep_from.min(self.string_start);
+    }
+}
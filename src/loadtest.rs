@@ -0,0 +1,226 @@
+//! Load generation for gateway capacity testing.
+//!
+//! A [`LoadTest`] fires the same chat request at the gateway from a number of
+//! concurrent workers until a request count or a duration is reached, and
+//! collects the [`RequestMetrics`] the client reports for every call. The
+//! resulting [`LoadReport`] gives throughput, error rate and latency
+//! percentiles.
+//!
+//! ```no_run
+//! # async fn run() {
+//! use std::time::Duration;
+//!
+//! use inference_gateway_sdk::loadtest::LoadTest;
+//! use inference_gateway_sdk::{InferenceGatewayClient, Message, Provider};
+//!
+//! let client = InferenceGatewayClient::new("http://localhost:8080/v1");
+//! let report = LoadTest::new(Provider::Groq, "llama-3.1-8b-instant", vec![Message::user("Hi")])
+//!     .with_concurrency(32)
+//!     .with_duration(Duration::from_secs(60))
+//!     .streaming()
+//!     .run(&client)
+//!     .await;
+//! println!(
+//!     "{:.1} req/s, {:.1}% errors, p99 {:?}",
+//!     report.throughput(),
+//!     report.error_rate() * 100.0,
+//!     report.latency_percentile(99.0),
+//! );
+//! # }
+//! ```
+//!
+//! Requires the `loadtest` feature.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use futures_util::future::join_all;
+use futures_util::{StreamExt, pin_mut};
+
+use crate::{
+    InferenceGatewayAPI, InferenceGatewayClient, Message, ModelRef, Provider, RequestMetrics,
+};
+
+/// The chat request a load test sends, and how hard it sends it.
+#[derive(Clone, Debug)]
+pub struct LoadTest {
+    provider: Provider,
+    model: ModelRef,
+    messages: Vec<Message>,
+    stream: bool,
+    concurrency: usize,
+    requests: Option<u64>,
+    duration: Option<Duration>,
+}
+
+impl LoadTest {
+    /// Sends `messages` to `model`, served by `provider`, from one worker,
+    /// 100 times. Set the request count or a duration to change when it
+    /// stops.
+    pub fn new(provider: Provider, model: impl Into<ModelRef>, messages: Vec<Message>) -> Self {
+        Self {
+            provider,
+            model: model.into(),
+            messages,
+            stream: false,
+            concurrency: 1,
+            requests: None,
+            duration: None,
+        }
+    }
+
+    /// Streams the responses instead of waiting for them whole, which adds
+    /// time to first token to the report.
+    pub fn streaming(mut self) -> Self {
+        self.stream = true;
+        self
+    }
+
+    /// Runs `workers` requests at a time, at least one.
+    pub fn with_concurrency(mut self, workers: usize) -> Self {
+        self.concurrency = workers.max(1);
+        self
+    }
+
+    /// Stops after `requests` requests have been sent.
+    pub fn with_requests(mut self, requests: u64) -> Self {
+        self.requests = Some(requests);
+        self
+    }
+
+    /// Stops sending new requests after `duration`. Without a request count
+    /// too, requests are sent until then.
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+
+    /// Runs the test through a copy of `client`, whose own metrics callback
+    /// still sees every call.
+    pub async fn run(&self, client: &InferenceGatewayClient) -> LoadReport {
+        let limit = match (self.requests, self.duration) {
+            (Some(requests), _) => requests,
+            (None, Some(_)) => u64::MAX,
+            (None, None) => 100,
+        };
+        let started = Instant::now();
+        let deadline = self.duration.map(|duration| started + duration);
+        let metrics = Arc::new(Mutex::new(Vec::new()));
+        let mut client = client.clone();
+        let previous = client.metrics_callback.take();
+        let collected = metrics.clone();
+        client = client.with_metrics_callback(move |request: &RequestMetrics| {
+            if let Some(previous) = &previous {
+                previous.on_call(request);
+            }
+            collected.lock().unwrap().push(request.clone());
+        });
+
+        let claimed = AtomicU64::new(0);
+        let sent = AtomicU64::new(0);
+        let errors = AtomicU64::new(0);
+        let worker = || async {
+            while deadline.is_none_or(|deadline| Instant::now() < deadline)
+                && claimed.fetch_add(1, Ordering::Relaxed) < limit
+            {
+                sent.fetch_add(1, Ordering::Relaxed);
+                if !self.send(&client).await {
+                    errors.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        };
+        join_all((0..self.concurrency).map(|_| worker())).await;
+
+        let metrics = std::mem::take(&mut *metrics.lock().unwrap());
+        LoadReport {
+            requests: sent.into_inner(),
+            errors: errors.into_inner(),
+            elapsed: started.elapsed(),
+            metrics,
+        }
+    }
+
+    /// Sends one request, reporting whether it succeeded.
+    async fn send(&self, client: &InferenceGatewayClient) -> bool {
+        let messages = self.messages.clone();
+        if !self.stream {
+            return client
+                .generate_content(self.provider, self.model.clone(), messages)
+                .await
+                .is_ok();
+        }
+        let stream = client.generate_content_stream(self.provider, self.model.clone(), messages);
+        pin_mut!(stream);
+        while let Some(event) = stream.next().await {
+            if event.is_err() {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// What a [`LoadTest`] measured.
+#[derive(Clone, Debug)]
+pub struct LoadReport {
+    /// Requests sent.
+    pub requests: u64,
+    /// Requests that failed.
+    pub errors: u64,
+    /// Wall-clock time from the first request until the last one finished.
+    pub elapsed: Duration,
+    /// The metrics the client reported for each call, in the order the
+    /// calls finished.
+    pub metrics: Vec<RequestMetrics>,
+}
+
+impl LoadReport {
+    /// Finished requests per second.
+    pub fn throughput(&self) -> f64 {
+        let seconds = self.elapsed.as_secs_f64();
+        if seconds == 0.0 {
+            return 0.0;
+        }
+        self.requests as f64 / seconds
+    }
+
+    /// The share of requests that failed, from 0.0 to 1.0.
+    pub fn error_rate(&self) -> f64 {
+        if self.requests == 0 {
+            return 0.0;
+        }
+        self.errors as f64 / self.requests as f64
+    }
+
+    /// The `percentile`th (0 to 100) total call duration, or `None` if no
+    /// call reported metrics.
+    pub fn latency_percentile(&self, percentile: f64) -> Option<Duration> {
+        nearest_rank(
+            self.metrics.iter().map(|m| m.total_duration).collect(),
+            percentile,
+        )
+    }
+
+    /// The `percentile`th (0 to 100) time to first token, over the streamed
+    /// calls that produced one.
+    pub fn time_to_first_token_percentile(&self, percentile: f64) -> Option<Duration> {
+        nearest_rank(
+            self.metrics
+                .iter()
+                .filter_map(|m| m.time_to_first_token)
+                .collect(),
+            percentile,
+        )
+    }
+}
+
+/// The nearest-rank percentile of `values`.
+fn nearest_rank(mut values: Vec<Duration>, percentile: f64) -> Option<Duration> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_unstable();
+    let rank = (percentile.clamp(0.0, 100.0) / 100.0 * values.len() as f64).ceil() as usize;
+    Some(values[rank.saturating_sub(1)])
+}
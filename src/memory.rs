@@ -0,0 +1,202 @@
+//! Persistence for conversation histories.
+
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::future::Future;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::{GatewayError, Message, decode};
+
+/// Storage for chat histories keyed by conversation id, such as one per user
+/// session of a web service.
+///
+/// ```no_run
+/// use inference_gateway_sdk::{Conversation, FileStore, MemoryStore, Message};
+///
+/// # async fn run() -> Result<(), inference_gateway_sdk::GatewayError> {
+/// let store = FileStore::new("/var/lib/chat")?;
+/// store.append("user-42", &[Message::user("Hi")]).await?;
+/// let conversation = Conversation::from(store.get("user-42").await?);
+/// store.trim("user-42", 50).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub trait MemoryStore: Send + Sync {
+    /// The messages of conversation `id`, oldest first; empty if there are
+    /// none.
+    fn get(&self, id: &str) -> impl Future<Output = Result<Vec<Message>, GatewayError>> + Send;
+
+    /// Adds `messages` to the end of conversation `id`, creating it if
+    /// needed.
+    fn append(
+        &self,
+        id: &str,
+        messages: &[Message],
+    ) -> impl Future<Output = Result<(), GatewayError>> + Send;
+
+    /// Drops all but the last `keep_last` messages of conversation `id`.
+    fn trim(
+        &self,
+        id: &str,
+        keep_last: usize,
+    ) -> impl Future<Output = Result<(), GatewayError>> + Send;
+}
+
+/// A [`MemoryStore`] held in process memory, lost on restart.
+#[derive(Clone, Debug, Default)]
+pub struct InMemoryStore {
+    conversations: Arc<Mutex<HashMap<String, Vec<Message>>>>,
+}
+
+impl InMemoryStore {
+    /// Creates an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl MemoryStore for InMemoryStore {
+    async fn get(&self, id: &str) -> Result<Vec<Message>, GatewayError> {
+        let conversations = self.conversations.lock().unwrap();
+        Ok(conversations.get(id).cloned().unwrap_or_default())
+    }
+
+    async fn append(&self, id: &str, messages: &[Message]) -> Result<(), GatewayError> {
+        let mut conversations = self.conversations.lock().unwrap();
+        conversations
+            .entry(id.to_string())
+            .or_default()
+            .extend_from_slice(messages);
+        Ok(())
+    }
+
+    async fn trim(&self, id: &str, keep_last: usize) -> Result<(), GatewayError> {
+        let mut conversations = self.conversations.lock().unwrap();
+        if let Some(messages) = conversations.get_mut(id) {
+            let excess = messages.len().saturating_sub(keep_last);
+            messages.drain(..excess);
+        }
+        Ok(())
+    }
+}
+
+/// A [`MemoryStore`] keeping each conversation in a JSON Lines file, one
+/// message per line, in a directory.
+///
+/// Appends only add lines, so a conversation is never rewritten except by
+/// [`MemoryStore::trim`]. File access runs on tokio's blocking pool.
+#[derive(Clone, Debug)]
+pub struct FileStore {
+    dir: PathBuf,
+    /// Serializes file access within this process.
+    lock: Arc<Mutex<()>>,
+}
+
+impl FileStore {
+    /// Stores conversations in `dir`, creating it if needed.
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self, GatewayError> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir).map_err(storage_error)?;
+        Ok(Self {
+            dir,
+            lock: Arc::default(),
+        })
+    }
+
+    /// The file holding conversation `id`. Ids are escaped so any string is
+    /// a safe file name.
+    fn path(&self, id: &str) -> PathBuf {
+        let mut name = String::with_capacity(id.len() + 6);
+        for byte in id.bytes() {
+            match byte {
+                b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'_' => name.push(byte as char),
+                _ => name.push_str(&format!("%{byte:02X}")),
+            }
+        }
+        name.push_str(".jsonl");
+        self.dir.join(name)
+    }
+
+    /// Runs `f` on the blocking pool while holding the store's lock.
+    async fn with_file<T, F>(&self, id: &str, f: F) -> Result<T, GatewayError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Path) -> Result<T, GatewayError> + Send + 'static,
+    {
+        let path = self.path(id);
+        let lock = self.lock.clone();
+        tokio::task::spawn_blocking(move || {
+            let _guard = lock.lock().unwrap();
+            f(&path)
+        })
+        .await
+        .map_err(|e| GatewayError::Other(Box::new(e)))?
+    }
+}
+
+impl MemoryStore for FileStore {
+    async fn get(&self, id: &str) -> Result<Vec<Message>, GatewayError> {
+        self.with_file(id, read_messages).await
+    }
+
+    async fn append(&self, id: &str, messages: &[Message]) -> Result<(), GatewayError> {
+        let lines = to_lines(messages)?;
+        self.with_file(id, move |path| {
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .map_err(storage_error)?;
+            file.write_all(lines.as_bytes()).map_err(storage_error)
+        })
+        .await
+    }
+
+    async fn trim(&self, id: &str, keep_last: usize) -> Result<(), GatewayError> {
+        self.with_file(id, move |path| {
+            let mut messages = read_messages(path)?;
+            let excess = messages.len().saturating_sub(keep_last);
+            if excess == 0 {
+                return Ok(());
+            }
+            messages.drain(..excess);
+            // Replace the file in one step so a crash can't leave it half
+            // written.
+            let temp = path.with_extension("jsonl.tmp");
+            fs::write(&temp, to_lines(&messages)?).map_err(storage_error)?;
+            fs::rename(&temp, path).map_err(storage_error)
+        })
+        .await
+    }
+}
+
+fn read_messages(path: &Path) -> Result<Vec<Message>, GatewayError> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(storage_error(e)),
+    };
+    let mut messages = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(storage_error)?;
+        if !line.trim().is_empty() {
+            messages.push(decode::from_str(&line)?);
+        }
+    }
+    Ok(messages)
+}
+
+fn to_lines(messages: &[Message]) -> Result<String, GatewayError> {
+    let mut lines = String::new();
+    for message in messages {
+        lines.push_str(&serde_json::to_string(message)?);
+        lines.push('\n');
+    }
+    Ok(lines)
+}
+
+fn storage_error(e: std::io::Error) -> GatewayError {
+    GatewayError::Other(Box::new(e))
+}
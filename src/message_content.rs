@@ -0,0 +1,99 @@
+//! Chat message content: plain text or multimodal parts.
+//!
+//! This replaces the generated untagged enum (see `gen-types`) so the
+//! variants can carry meaningful names and so text-only content keeps
+//! serializing as a plain JSON string.
+
+use std::fmt;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::ContentPart;
+use crate::privacy::Elided;
+
+/// Message content - either text or multimodal content parts.
+///
+/// Content that is only text serializes as a JSON string, the form every
+/// provider accepts; that includes [`MessageContent::Parts`] holding a single
+/// text part.
+#[derive(Clone)]
+pub enum MessageContent {
+    /// Plain text.
+    Text(String),
+    /// Content parts for multimodal messages.
+    Parts(Vec<ContentPart>),
+}
+
+impl MessageContent {
+    /// The content as text, if it is text only.
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            Self::Text(text) => Some(text),
+            Self::Parts(parts) => match parts.as_slice() {
+                [ContentPart::TextContentPart(part)] => Some(&part.text),
+                _ => None,
+            },
+        }
+    }
+}
+
+impl Default for MessageContent {
+    fn default() -> Self {
+        Self::Text(String::new())
+    }
+}
+
+impl From<String> for MessageContent {
+    fn from(text: String) -> Self {
+        Self::Text(text)
+    }
+}
+
+impl From<&str> for MessageContent {
+    fn from(text: &str) -> Self {
+        Self::Text(text.to_string())
+    }
+}
+
+impl From<Vec<ContentPart>> for MessageContent {
+    fn from(parts: Vec<ContentPart>) -> Self {
+        Self::Parts(parts)
+    }
+}
+
+impl fmt::Debug for MessageContent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Text(text) => f.debug_tuple("Text").field(&Elided(text)).finish(),
+            Self::Parts(parts) => f.debug_tuple("Parts").field(parts).finish(),
+        }
+    }
+}
+
+impl Serialize for MessageContent {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match (self, self.as_text()) {
+            (_, Some(text)) => serializer.serialize_str(text),
+            (Self::Parts(parts), None) => parts.serialize(serializer),
+            (Self::Text(text), None) => serializer.serialize_str(text),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for MessageContent {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Wire {
+            Text(String),
+            Parts(Vec<ContentPart>),
+            // Assistant replies that only carry tool calls or audio.
+            Null(()),
+        }
+        Ok(match Wire::deserialize(deserializer)? {
+            Wire::Text(text) => Self::Text(text),
+            Wire::Parts(parts) => Self::Parts(parts),
+            Wire::Null(()) => Self::Text(String::new()),
+        })
+    }
+}
@@ -0,0 +1,412 @@
+//! Per-request measurements and completion events.
+//!
+//! A [`CallRecorder`] is created when a generation call starts. When it is
+//! dropped - after a response is parsed, when a stream ends or fails, or when
+//! the caller drops a stream early - it reports a [`RequestMetrics`] to the
+//! client's [`MetricsObserver`] and a [`CompletionEvent`] to its event sink.
+//!
+//! The two serve different consumers. [`RequestMetrics`] holds numbers and
+//! labels for a metrics system: durations, status, error kind and token
+//! counts. [`CompletionEvent`] is a record of what the call produced, for
+//! analytics and billing: the full usage, the finish reason and the error
+//! message.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use futures_util::{Stream, StreamExt, pin_mut};
+use reqwest::StatusCode;
+
+use crate::{
+    CompletionUsage, CreateChatCompletionStreamResponse, FinishReason, GatewayError,
+    GatewayErrorKind, MessagesResponseStopReason, MessagesUsage, Provider, SSEvents,
+};
+
+/// Receiver of the metrics of every generation call.
+pub(crate) type MetricsCallback = Arc<dyn MetricsObserver>;
+
+/// Callback invoked with a [`CompletionEvent`] after every generation call.
+pub(crate) type EventSink = Arc<dyn Fn(&CompletionEvent) + Send + Sync>;
+
+/// Everything measured about one generation call, labelled for exporting to
+/// a metrics system.
+#[derive(Clone, Debug)]
+pub struct RequestMetrics {
+    /// The provider the call was routed to, if one was given.
+    pub provider: Option<Provider>,
+    /// The requested model.
+    pub model: String,
+    /// The URL the request was sent to.
+    pub endpoint: String,
+    /// Whether the response was streamed.
+    pub streaming: bool,
+    /// The status of the last response received, if any was.
+    pub status: Option<StatusCode>,
+    /// The category of the error the call failed with.
+    pub error: Option<GatewayErrorKind>,
+    /// Time from sending the request until the first content delta. `None`
+    /// for non-streaming calls and streams that produced no content.
+    pub time_to_first_token: Option<Duration>,
+    /// Time from sending the request until the call finished.
+    pub total_duration: Duration,
+    /// Prompt tokens, if the gateway reported usage.
+    pub prompt_tokens: Option<i64>,
+    /// Completion tokens, if the gateway reported usage.
+    pub completion_tokens: Option<i64>,
+    /// Completion tokens per second over the generation window: from the
+    /// first token when streaming, from the start of the request otherwise.
+    pub output_tokens_per_second: Option<f64>,
+    /// How many times the request was retried before this attempt. Always
+    /// 0 for streams, which aren't retried.
+    pub retries: u32,
+}
+
+/// Summary of a finished generation call, for analytics and billing.
+///
+/// Messages API calls are reported in the same shape: input and output tokens
+/// map to prompt and completion tokens, and stop reasons to the closest
+/// [`FinishReason`].
+#[derive(Clone, Debug)]
+pub struct CompletionEvent {
+    /// The provider the call was routed to, if one was given.
+    pub provider: Option<Provider>,
+    /// The requested model.
+    pub model: String,
+    /// Token usage, if the gateway reported it.
+    pub usage: Option<CompletionUsage>,
+    /// Time from sending the request until the call finished.
+    pub latency: Duration,
+    /// Why the model stopped, if the call got that far.
+    pub finish_reason: Option<FinishReason>,
+    /// The error the call failed with, rendered as text.
+    pub error: Option<String>,
+}
+
+/// Receives the [`RequestMetrics`] of every generation call, streaming or
+/// not, once it has finished or failed, for exporting them to Prometheus or
+/// similar. Closures taking `&RequestMetrics` implement it.
+///
+/// ```
+/// use inference_gateway_sdk::{InferenceGatewayClient, RequestMetrics};
+///
+/// let client = InferenceGatewayClient::new("http://localhost:8080/v1").with_metrics_observer(
+///     |call: &RequestMetrics| {
+///         let outcome = match call.error {
+///             Some(kind) => format!("{kind:?}"),
+///             None => "ok".to_string(),
+///         };
+///         println!("{} {outcome} in {:?}", call.model, call.total_duration);
+///     },
+/// );
+/// ```
+pub trait MetricsObserver: Send + Sync + 'static {
+    /// Called once per call, from the task that finished it.
+    fn on_call(&self, metrics: &RequestMetrics);
+}
+
+impl<F> MetricsObserver for F
+where
+    F: Fn(&RequestMetrics) + Send + Sync + 'static,
+{
+    fn on_call(&self, metrics: &RequestMetrics) {
+        self(metrics)
+    }
+}
+
+/// Where a call's transport stores the status of each response it gets.
+pub(crate) type StatusSlot = Arc<Mutex<Option<StatusCode>>>;
+
+/// The API a call goes to, which decides how its stream events are read.
+#[derive(Clone, Copy)]
+pub(crate) enum StreamKind {
+    ChatCompletion,
+    Messages,
+    /// An endpoint the SDK doesn't model, whose events are passed through
+    /// unread.
+    Raw,
+}
+
+/// What one stream event says about its call, decoded once and shared by
+/// the first-token timeout, the provider throttle and the [`CallRecorder`].
+#[derive(Clone, Debug, Default)]
+pub(crate) struct EventReading {
+    /// Whether the event carries generated output (or ends the stream, after
+    /// which there is nothing left to wait for).
+    pub(crate) token: bool,
+    /// The usage reported so far, if the event updates it.
+    pub(crate) usage: Option<CompletionUsage>,
+    pub(crate) finish_reason: Option<FinishReason>,
+}
+
+impl StreamKind {
+    /// Reads `event`. Messages streams report input and output tokens in
+    /// separate events, so `usage`, the usage read so far, fills in whichever
+    /// one `event` leaves out.
+    pub(crate) fn read(self, event: &SSEvents, usage: Option<&CompletionUsage>) -> EventReading {
+        match self {
+            Self::ChatCompletion => {
+                if event.data == "[DONE]" {
+                    return EventReading {
+                        token: true,
+                        ..EventReading::default()
+                    };
+                }
+                let Ok(chunk) =
+                    serde_json::from_str::<CreateChatCompletionStreamResponse>(&event.data)
+                else {
+                    return EventReading::default();
+                };
+                EventReading {
+                    token: chat_chunk_has_token(&chunk),
+                    finish_reason: chunk.choices.iter().find_map(|c| c.finish_reason.clone()),
+                    usage: chunk.usage,
+                }
+            }
+            Self::Messages => {
+                let token = matches!(
+                    event.event.as_deref(),
+                    Some("content_block_delta" | "message_stop")
+                );
+                let Ok(body) = serde_json::from_str::<serde_json::Value>(&event.data) else {
+                    return EventReading {
+                        token,
+                        ..EventReading::default()
+                    };
+                };
+                let finish_reason = body
+                    .get("delta")
+                    .and_then(|delta| delta.get("stop_reason"))
+                    .and_then(|reason| serde_json::from_value(reason.clone()).ok())
+                    .map(finish_reason_for);
+                EventReading {
+                    token,
+                    usage: messages_event_usage(&body, usage),
+                    finish_reason,
+                }
+            }
+            Self::Raw => EventReading {
+                token: true,
+                ..EventReading::default()
+            },
+        }
+    }
+}
+
+fn messages_event_usage(
+    body: &serde_json::Value,
+    current: Option<&CompletionUsage>,
+) -> Option<CompletionUsage> {
+    let usage = body
+        .get("usage")
+        .or_else(|| body.get("message").and_then(|m| m.get("usage")))?;
+    let count = |field| usage.get(field).and_then(serde_json::Value::as_i64);
+    let input = count("input_tokens")
+        .or(current.map(|u| u.prompt_tokens))
+        .unwrap_or_default();
+    let output = count("output_tokens")
+        .or(current.map(|u| u.completion_tokens))
+        .unwrap_or_default();
+    Some(usage_from_counts(input, output))
+}
+
+fn chat_chunk_has_token(chunk: &CreateChatCompletionStreamResponse) -> bool {
+    let non_empty = |s: &Option<String>| s.as_deref().is_some_and(|s| !s.is_empty());
+    chunk.choices.iter().any(|choice| {
+        let delta = &choice.delta;
+        non_empty(&delta.content)
+            || non_empty(&delta.reasoning)
+            || non_empty(&delta.reasoning_content)
+            || non_empty(&delta.refusal)
+            || !delta.tool_calls.is_empty()
+            || choice.finish_reason.is_some()
+    })
+}
+
+pub(crate) struct CallRecorder {
+    metrics_callback: Option<MetricsCallback>,
+    event_sink: Option<EventSink>,
+    endpoint: String,
+    provider: Option<Provider>,
+    model: String,
+    started: Instant,
+    time_to_first_token: Option<Duration>,
+    usage: Option<CompletionUsage>,
+    finish_reason: Option<FinishReason>,
+    error: Option<String>,
+    error_kind: Option<GatewayErrorKind>,
+    streaming: bool,
+    status: StatusSlot,
+    retries: u32,
+}
+
+impl CallRecorder {
+    /// Starts recording a call, or returns `None` when nobody is listening.
+    pub(crate) fn start(
+        metrics_callback: Option<MetricsCallback>,
+        event_sink: Option<EventSink>,
+        endpoint: &str,
+        provider: Option<Provider>,
+        model: &str,
+    ) -> Option<Self> {
+        if metrics_callback.is_none() && event_sink.is_none() {
+            return None;
+        }
+        Some(Self {
+            metrics_callback,
+            event_sink,
+            endpoint: endpoint.to_string(),
+            provider,
+            model: model.to_string(),
+            started: Instant::now(),
+            time_to_first_token: None,
+            usage: None,
+            finish_reason: None,
+            error: None,
+            error_kind: None,
+            streaming: false,
+            status: StatusSlot::default(),
+            retries: 0,
+        })
+    }
+
+    /// The slot the call's responses should record their status in.
+    pub(crate) fn status_slot(&self) -> StatusSlot {
+        self.status.clone()
+    }
+
+    pub(crate) fn usage(&mut self, usage: CompletionUsage) {
+        self.usage = Some(usage);
+    }
+
+    pub(crate) fn messages_usage(&mut self, usage: &MessagesUsage) {
+        self.usage = Some(usage_from_counts(usage.input_tokens, usage.output_tokens));
+    }
+
+    pub(crate) fn retries(&mut self, retries: u32) {
+        self.retries = retries;
+    }
+
+    pub(crate) fn finish_reason(&mut self, reason: FinishReason) {
+        self.finish_reason = Some(reason);
+    }
+
+    pub(crate) fn stop_reason(&mut self, reason: MessagesResponseStopReason) {
+        self.finish_reason = Some(finish_reason_for(reason));
+    }
+
+    pub(crate) fn error(&mut self, error: &GatewayError) {
+        // The event already names the call, so leave out the context.
+        self.error = Some(error.root().to_string());
+        self.error_kind = Some(error.kind());
+    }
+
+    fn first_token(&mut self) {
+        if self.time_to_first_token.is_none() {
+            self.time_to_first_token = Some(self.started.elapsed());
+        }
+    }
+
+    fn observe(&mut self, reading: EventReading) {
+        if reading.token {
+            self.first_token();
+        }
+        if let Some(reason) = reading.finish_reason {
+            self.finish_reason(reason);
+        }
+        if let Some(usage) = reading.usage {
+            self.usage(usage);
+        }
+    }
+}
+
+impl Drop for CallRecorder {
+    fn drop(&mut self) {
+        let total_duration = self.started.elapsed();
+        if let Some(callback) = &self.metrics_callback {
+            let completion_tokens = self.usage.as_ref().map(|u| u.completion_tokens);
+            let generating = total_duration - self.time_to_first_token.unwrap_or_default();
+            let output_tokens_per_second = completion_tokens
+                .filter(|_| !generating.is_zero())
+                .map(|tokens| tokens as f64 / generating.as_secs_f64());
+            callback.on_call(&RequestMetrics {
+                provider: self.provider,
+                model: self.model.clone(),
+                endpoint: std::mem::take(&mut self.endpoint),
+                streaming: self.streaming,
+                status: *self.status.lock().unwrap(),
+                error: self.error_kind,
+                time_to_first_token: self.time_to_first_token,
+                total_duration,
+                prompt_tokens: self.usage.as_ref().map(|u| u.prompt_tokens),
+                completion_tokens,
+                output_tokens_per_second,
+                retries: self.retries,
+            });
+        }
+        if let Some(sink) = &self.event_sink {
+            sink(&CompletionEvent {
+                provider: self.provider,
+                model: std::mem::take(&mut self.model),
+                usage: self.usage.take(),
+                latency: total_duration,
+                finish_reason: self.finish_reason.clone(),
+                error: self.error.take(),
+            });
+        }
+    }
+}
+
+/// Records the reading of every event, and every error, of `stream` into
+/// `recorder`, which reports once the stream is finished or dropped.
+pub(crate) fn record_stream(
+    stream: impl Stream<Item = Result<(SSEvents, EventReading), GatewayError>> + Send,
+    recorder: Option<CallRecorder>,
+) -> impl Stream<Item = Result<SSEvents, GatewayError>> + Send {
+    async_stream::stream! {
+        let mut recorder = recorder;
+        // Streams are lazy: the request goes out on the first poll.
+        if let Some(recorder) = &mut recorder {
+            recorder.started = Instant::now();
+            recorder.streaming = true;
+        }
+        pin_mut!(stream);
+        while let Some(item) = stream.next().await {
+            yield match item {
+                Ok((event, reading)) => {
+                    if let Some(recorder) = &mut recorder {
+                        recorder.observe(reading);
+                    }
+                    Ok(event)
+                }
+                Err(e) => {
+                    if let Some(recorder) = &mut recorder {
+                        recorder.error(&e);
+                    }
+                    Err(e)
+                }
+            };
+        }
+    }
+}
+
+fn usage_from_counts(input: i64, output: i64) -> CompletionUsage {
+    CompletionUsage {
+        completion_tokens: output,
+        completion_tokens_details: None,
+        prompt_tokens: input,
+        prompt_tokens_details: None,
+        total_tokens: input + output,
+    }
+}
+
+fn finish_reason_for(reason: MessagesResponseStopReason) -> FinishReason {
+    match reason {
+        MessagesResponseStopReason::EndTurn
+        | MessagesResponseStopReason::StopSequence
+        | MessagesResponseStopReason::PauseTurn => FinishReason::Stop,
+        MessagesResponseStopReason::MaxTokens => FinishReason::Length,
+        MessagesResponseStopReason::ToolUse => FinishReason::ToolCalls,
+        MessagesResponseStopReason::Refusal => FinishReason::ContentFilter,
+        MessagesResponseStopReason::Other(reason) => FinishReason::Other(reason),
+    }
+}
@@ -0,0 +1,94 @@
+//! Hooks run around every request the client sends.
+
+use std::sync::Arc;
+
+use reqwest::{Request, RequestBuilder, Response};
+
+use crate::GatewayError;
+use crate::balance::EndpointPool;
+use crate::replay::{self, Tape};
+#[cfg(feature = "trace")]
+use crate::trace;
+
+/// Hooks called for every HTTP request the client sends, across all
+/// endpoints, for injecting headers, rewriting requests or audit logging.
+///
+/// Registered with
+/// [`InferenceGatewayClient::with_middleware`](crate::InferenceGatewayClient::with_middleware).
+/// Each retry attempt is a request of its own and passes through again.
+///
+/// ```
+/// use inference_gateway_sdk::{GatewayError, InferenceGatewayClient, Middleware};
+///
+/// struct TenantHeader;
+///
+/// impl Middleware for TenantHeader {
+///     fn before_request(&self, request: &mut reqwest::Request) -> Result<(), GatewayError> {
+///         request
+///             .headers_mut()
+///             .insert("x-tenant", reqwest::header::HeaderValue::from_static("acme"));
+///         Ok(())
+///     }
+/// }
+///
+/// let client = InferenceGatewayClient::new("http://localhost:8080/v1")
+///     .with_middleware(Box::new(TenantHeader));
+/// ```
+pub trait Middleware: Send + Sync + 'static {
+    /// Called with each request just before it is sent. An error fails the
+    /// call without sending it.
+    fn before_request(&self, request: &mut Request) -> Result<(), GatewayError> {
+        let _ = request;
+        Ok(())
+    }
+
+    /// Called with each response as soon as its headers arrive, before the
+    /// client looks at it. Error statuses are passed too.
+    fn after_response(&self, response: &mut Response) {
+        let _ = response;
+    }
+}
+
+impl<M: Middleware + ?Sized> Middleware for Box<M> {
+    fn before_request(&self, request: &mut Request) -> Result<(), GatewayError> {
+        (**self).before_request(request)
+    }
+
+    fn after_response(&self, response: &mut Response) {
+        (**self).after_response(response)
+    }
+}
+
+/// Sends `request` to the endpoint `endpoints` picks for it, if the client
+/// is balanced, through `middleware`, first registered outermost, and the
+/// tape if there is one.
+pub(crate) async fn send(
+    tape: Option<&Tape>,
+    middleware: &[Arc<dyn Middleware>],
+    endpoints: Option<&EndpointPool>,
+    request: RequestBuilder,
+) -> Result<Response, GatewayError> {
+    if middleware.is_empty() && endpoints.is_none() {
+        let response = replay::send(tape, request).await?;
+        #[cfg(feature = "trace")]
+        trace::record_response(response.headers());
+        return Ok(response);
+    }
+    let (client, request) = request.build_split();
+    let mut request = request?;
+    let lease = endpoints.and_then(|endpoints| endpoints.route(&mut request));
+    for layer in middleware {
+        layer.before_request(&mut request)?;
+    }
+    let response = replay::send(tape, RequestBuilder::from_parts(client, request)).await;
+    if let Some(lease) = lease {
+        lease.settle(&response);
+    }
+    let mut response = response?;
+    #[cfg(feature = "trace")]
+    trace::record_response(response.headers());
+    for layer in middleware.iter().rev() {
+        layer.after_response(&mut response);
+    }
+    Ok(response)
+}
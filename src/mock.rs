@@ -0,0 +1,581 @@
+//! An in-memory [`InferenceGatewayAPI`] for unit tests.
+//!
+//! [`MockInferenceGatewayClient`] answers calls from responses queued on it
+//! instead of a gateway, and records every call, so code written against
+//! the trait can be tested without an HTTP server:
+//!
+//! ```
+//! # async fn run() -> Result<(), inference_gateway_sdk::GatewayError> {
+//! use inference_gateway_sdk::mock::MockInferenceGatewayClient;
+//! use inference_gateway_sdk::{InferenceGatewayAPI, Provider};
+//!
+//! let client = MockInferenceGatewayClient::new()
+//!     .with_text("It's sunny")
+//!     .with_stream_chunks(["Still ", "sunny"]);
+//!
+//! let answer = client.ask(Provider::Openai, "gpt-4o", "Weather?").await?;
+//! assert_eq!(answer, "It's sunny");
+//! assert_eq!(client.calls()[0].model.as_deref(), Some("gpt-4o"));
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Requires the `mock` feature.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use futures_util::Stream;
+use serde_json::json;
+
+use crate::{
+    AudioFile, ChatCompletionOptions, ChatCompletionStreamExt, CreateChatCompletionResponse,
+    CreateMessagesRequest, EmbeddingRequest, EmbeddingResponse, FileContentPart, FoldedCompletion,
+    GatewayError, HealthStatus, InferenceGatewayAPI, ListModelsResponse, ListToolsResponse,
+    McpTool, Message, MessagesResponse, Model, ModelRef, ModerationInput, ModerationResponse,
+    Provider, ProviderHealth, RequestOptions, SSEvents, SpeechOptions, SseStreamExt, Transcription,
+    TranscriptionEvent, TranscriptionOptions, first_choice_text,
+};
+
+/// A call made to a [`MockInferenceGatewayClient`].
+#[derive(Clone, Debug)]
+pub struct MockCall {
+    /// The [`InferenceGatewayAPI`] method called, such as
+    /// `"generate_content"`.
+    pub method: &'static str,
+    /// The provider the call named, if any.
+    pub provider: Option<Provider>,
+    /// The model the call named, if any.
+    pub model: Option<String>,
+    /// The chat messages sent, for chat completion calls.
+    pub messages: Vec<Message>,
+}
+
+/// Responses waiting to be returned, and the calls made so far.
+#[derive(Debug, Default)]
+struct State {
+    completions: VecDeque<Result<CreateChatCompletionResponse, GatewayError>>,
+    streams: VecDeque<Vec<Result<SSEvents, GatewayError>>>,
+    messages: VecDeque<Result<MessagesResponse, GatewayError>>,
+    embeddings: VecDeque<Result<EmbeddingResponse, GatewayError>>,
+    models: Option<ListModelsResponse>,
+    tools: Option<ListToolsResponse>,
+    calls: Vec<MockCall>,
+}
+
+/// An [`InferenceGatewayAPI`] that answers from queued responses.
+///
+/// Chat completions, streams, Messages API responses and embeddings are
+/// each queued in order and used up one per call; a call with nothing
+/// queued fails. Streaming calls, including Messages API streams and
+/// [`generate_content_resumable`](InferenceGatewayAPI::generate_content_resumable),
+/// share the stream queue. The model and tool lists, once set, answer every
+/// listing. Health checks report a healthy gateway with no providers probed.
+///
+/// Clones share their queues and recorded calls, so a clone can be handed to
+/// the code under test and the original inspected afterwards.
+#[derive(Clone, Debug, Default)]
+pub struct MockInferenceGatewayClient {
+    state: Arc<Mutex<State>>,
+}
+
+impl MockInferenceGatewayClient {
+    /// A mock with nothing queued.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues `response` for a chat completion call.
+    pub fn with_completion(self, response: CreateChatCompletionResponse) -> Self {
+        self.state
+            .lock()
+            .unwrap()
+            .completions
+            .push_back(Ok(response));
+        self
+    }
+
+    /// Queues a chat completion whose only choice answers `text`.
+    pub fn with_text(self, text: impl Into<String>) -> Self {
+        let response = json!({
+            "id": "chatcmpl-mock",
+            "object": "chat.completion",
+            "created": 0,
+            "model": "mock",
+            "choices": [{
+                "index": 0,
+                "finish_reason": "stop",
+                "message": {"role": "assistant", "content": text.into()}
+            }]
+        });
+        self.with_completion(
+            serde_json::from_value(response).expect("mock completion is a valid response"),
+        )
+    }
+
+    /// Queues `error` for a chat completion call.
+    pub fn with_completion_error(self, error: GatewayError) -> Self {
+        self.state.lock().unwrap().completions.push_back(Err(error));
+        self
+    }
+
+    /// Queues a stream of `events` for a streaming call. An error among them
+    /// ends the stream there, as it would from the gateway.
+    pub fn with_stream(
+        self,
+        events: impl IntoIterator<Item = Result<SSEvents, GatewayError>>,
+    ) -> Self {
+        let mut queued = Vec::new();
+        for event in events {
+            let failed = event.is_err();
+            queued.push(event);
+            if failed {
+                break;
+            }
+        }
+        self.state.lock().unwrap().streams.push_back(queued);
+        self
+    }
+
+    /// Queues a chat completion stream delivering `chunks` of content, then
+    /// a `stop` finish reason and `[DONE]`.
+    pub fn with_stream_chunks<I>(self, chunks: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        let chunk = |delta: serde_json::Value, finish_reason: Option<&str>| {
+            let data = json!({
+                "id": "chatcmpl-mock",
+                "object": "chat.completion.chunk",
+                "created": 0,
+                "model": "mock",
+                "choices": [{"index": 0, "delta": delta, "finish_reason": finish_reason}]
+            });
+            Ok(event(data.to_string()))
+        };
+        let mut events: Vec<_> = chunks
+            .into_iter()
+            .map(|text| chunk(json!({"content": text.into()}), None))
+            .collect();
+        events.push(chunk(json!({}), Some("stop")));
+        events.push(Ok(event("[DONE]".to_string())));
+        self.with_stream(events)
+    }
+
+    /// Queues `response` for a Messages API call.
+    pub fn with_message_response(self, response: MessagesResponse) -> Self {
+        self.state.lock().unwrap().messages.push_back(Ok(response));
+        self
+    }
+
+    /// Queues `response` for an embeddings call.
+    pub fn with_embeddings(self, response: EmbeddingResponse) -> Self {
+        self.state
+            .lock()
+            .unwrap()
+            .embeddings
+            .push_back(Ok(response));
+        self
+    }
+
+    /// Answers every model listing with `models`.
+    pub fn with_models(self, models: ListModelsResponse) -> Self {
+        self.state.lock().unwrap().models = Some(models);
+        self
+    }
+
+    /// Answers every MCP tool listing with `tools`.
+    pub fn with_mcp_tools(self, tools: ListToolsResponse) -> Self {
+        self.state.lock().unwrap().tools = Some(tools);
+        self
+    }
+
+    /// The calls made so far, in order.
+    pub fn calls(&self) -> Vec<MockCall> {
+        self.state.lock().unwrap().calls.clone()
+    }
+
+    fn record(
+        &self,
+        method: &'static str,
+        provider: Option<Provider>,
+        model: Option<String>,
+        messages: Vec<Message>,
+    ) {
+        self.state.lock().unwrap().calls.push(MockCall {
+            method,
+            provider,
+            model,
+            messages,
+        });
+    }
+
+    fn completion(
+        &self,
+        method: &'static str,
+        provider: Provider,
+        model: ModelRef,
+        messages: Vec<Message>,
+    ) -> Result<CreateChatCompletionResponse, GatewayError> {
+        self.record(method, Some(provider), Some(model.to_string()), messages);
+        let mut state = self.state.lock().unwrap();
+        state
+            .completions
+            .pop_front()
+            .unwrap_or_else(|| Err(unprogrammed(method)))
+    }
+
+    fn stream(
+        &self,
+        method: &'static str,
+        provider: Option<Provider>,
+        model: String,
+        messages: Vec<Message>,
+    ) -> impl Stream<Item = Result<SSEvents, GatewayError>> + Send + use<> {
+        self.record(method, provider, Some(model), messages);
+        let mut state = self.state.lock().unwrap();
+        let events = state
+            .streams
+            .pop_front()
+            .unwrap_or_else(|| vec![Err(unprogrammed(method))]);
+        futures_util::stream::iter(events)
+    }
+
+    fn models(
+        &self,
+        method: &'static str,
+        provider: Option<Provider>,
+    ) -> Result<ListModelsResponse, GatewayError> {
+        self.record(method, provider, None, Vec::new());
+        let state = self.state.lock().unwrap();
+        state.models.clone().ok_or_else(|| unprogrammed(method))
+    }
+
+    fn tools(&self, method: &'static str) -> Result<ListToolsResponse, GatewayError> {
+        self.record(method, None, None, Vec::new());
+        let state = self.state.lock().unwrap();
+        state.tools.clone().ok_or_else(|| unprogrammed(method))
+    }
+}
+
+fn event(data: String) -> SSEvents {
+    SSEvents {
+        data,
+        event: None,
+        retry: None,
+    }
+}
+
+/// The error for a call with nothing queued to answer it.
+fn unprogrammed(method: &str) -> GatewayError {
+    GatewayError::Other(Box::new(std::io::Error::other(format!(
+        "no mock response queued for `{method}`"
+    ))))
+}
+
+impl InferenceGatewayAPI for MockInferenceGatewayClient {
+    async fn list_models(&self) -> Result<ListModelsResponse, GatewayError> {
+        self.models("list_models", None)
+    }
+
+    async fn list_models_by_provider(
+        &self,
+        provider: Provider,
+    ) -> Result<ListModelsResponse, GatewayError> {
+        self.models("list_models_by_provider", Some(provider))
+    }
+
+    async fn list_models_with_include(
+        &self,
+        provider: Option<Provider>,
+        _include: &[&str],
+    ) -> Result<ListModelsResponse, GatewayError> {
+        self.models("list_models_with_include", provider)
+    }
+
+    async fn generate_content(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef> + Send,
+        messages: Vec<Message>,
+    ) -> Result<CreateChatCompletionResponse, GatewayError> {
+        self.completion("generate_content", provider, model.into(), messages)
+    }
+
+    async fn generate_content_with_options(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef> + Send,
+        messages: Vec<Message>,
+        _options: impl Into<ChatCompletionOptions> + Send,
+    ) -> Result<CreateChatCompletionResponse, GatewayError> {
+        self.completion(
+            "generate_content_with_options",
+            provider,
+            model.into(),
+            messages,
+        )
+    }
+
+    fn generate_content_stream(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef> + Send,
+        messages: Vec<Message>,
+    ) -> impl Stream<Item = Result<SSEvents, GatewayError>> + Send {
+        let model = model.into().to_string();
+        self.stream("generate_content_stream", Some(provider), model, messages)
+    }
+
+    fn generate_content_stream_with_options(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef> + Send,
+        messages: Vec<Message>,
+        _options: impl Into<ChatCompletionOptions> + Send,
+    ) -> impl Stream<Item = Result<SSEvents, GatewayError>> + Send {
+        let model = model.into().to_string();
+        self.stream(
+            "generate_content_stream_with_options",
+            Some(provider),
+            model,
+            messages,
+        )
+    }
+
+    async fn generate_content_resumable(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef> + Send,
+        messages: Vec<Message>,
+        _max_resumes: u32,
+    ) -> Result<FoldedCompletion, GatewayError> {
+        let model = model.into().to_string();
+        self.stream(
+            "generate_content_resumable",
+            Some(provider),
+            model,
+            messages,
+        )
+        .chat_chunks()
+        .fold_stream()
+        .await
+    }
+
+    async fn ask(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef> + Send,
+        prompt: impl Into<String> + Send,
+    ) -> Result<String, GatewayError> {
+        let messages = vec![Message::user(prompt)];
+        let response = self.completion("ask", provider, model.into(), messages)?;
+        Ok(first_choice_text(&response))
+    }
+
+    async fn ask_with_system(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef> + Send,
+        system: impl Into<String> + Send,
+        prompt: impl Into<String> + Send,
+    ) -> Result<String, GatewayError> {
+        let messages = vec![Message::system(system), Message::user(prompt)];
+        let response = self.completion("ask_with_system", provider, model.into(), messages)?;
+        Ok(first_choice_text(&response))
+    }
+
+    async fn create_message(
+        &self,
+        provider: Option<Provider>,
+        request: CreateMessagesRequest,
+    ) -> Result<MessagesResponse, GatewayError> {
+        self.record("create_message", provider, Some(request.model), Vec::new());
+        let mut state = self.state.lock().unwrap();
+        state
+            .messages
+            .pop_front()
+            .unwrap_or_else(|| Err(unprogrammed("create_message")))
+    }
+
+    async fn create_message_with_options(
+        &self,
+        provider: Option<Provider>,
+        request: CreateMessagesRequest,
+        _options: RequestOptions,
+    ) -> Result<MessagesResponse, GatewayError> {
+        self.create_message(provider, request).await
+    }
+
+    fn create_message_stream(
+        &self,
+        provider: Option<Provider>,
+        request: CreateMessagesRequest,
+    ) -> impl Stream<Item = Result<SSEvents, GatewayError>> + Send {
+        self.stream("create_message_stream", provider, request.model, Vec::new())
+    }
+
+    fn create_message_stream_with_options(
+        &self,
+        provider: Option<Provider>,
+        request: CreateMessagesRequest,
+        _options: RequestOptions,
+    ) -> impl Stream<Item = Result<SSEvents, GatewayError>> + Send {
+        self.stream(
+            "create_message_stream_with_options",
+            provider,
+            request.model,
+            Vec::new(),
+        )
+    }
+
+    async fn list_tools(&self) -> Result<ListToolsResponse, GatewayError> {
+        self.tools("list_tools")
+    }
+
+    fn list_models_stream(&self) -> impl Stream<Item = Result<Model, GatewayError>> + Send {
+        let models = self.models("list_models_stream", None);
+        futures_util::stream::iter(match models {
+            Ok(models) => models.data.into_iter().map(Ok).collect(),
+            Err(e) => vec![Err(e)],
+        })
+    }
+
+    fn list_tools_stream(&self) -> impl Stream<Item = Result<McpTool, GatewayError>> + Send {
+        let tools = self.tools("list_tools_stream");
+        futures_util::stream::iter(match tools {
+            Ok(tools) => tools.data.into_iter().map(Ok).collect(),
+            Err(e) => vec![Err(e)],
+        })
+    }
+
+    async fn upload_file(
+        &self,
+        provider: Provider,
+        _filename: &str,
+        _bytes: &[u8],
+        _purpose: &str,
+    ) -> Result<FileContentPart, GatewayError> {
+        self.record("upload_file", Some(provider), None, Vec::new());
+        Err(unprogrammed("upload_file"))
+    }
+
+    async fn count_tokens_remote(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef> + Send,
+        messages: &[Message],
+    ) -> Result<u64, GatewayError> {
+        let model = Some(model.into().to_string());
+        self.record(
+            "count_tokens_remote",
+            Some(provider),
+            model,
+            messages.to_vec(),
+        );
+        Err(GatewayError::TokenCountingUnsupported(provider))
+    }
+
+    async fn validate_request(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef> + Send,
+        messages: &[Message],
+    ) -> Result<(), GatewayError> {
+        let model = Some(model.into().to_string());
+        self.record("validate_request", Some(provider), model, messages.to_vec());
+        Ok(())
+    }
+
+    async fn create_embeddings(
+        &self,
+        provider: Provider,
+        request: EmbeddingRequest,
+    ) -> Result<EmbeddingResponse, GatewayError> {
+        let model = Some(request.model().to_string());
+        self.record("create_embeddings", Some(provider), model, Vec::new());
+        let mut state = self.state.lock().unwrap();
+        state
+            .embeddings
+            .pop_front()
+            .unwrap_or_else(|| Err(unprogrammed("create_embeddings")))
+    }
+
+    async fn create_speech(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef> + Send,
+        _input: &str,
+        _options: SpeechOptions,
+    ) -> Result<bytes::Bytes, GatewayError> {
+        let model = Some(model.into().to_string());
+        self.record("create_speech", Some(provider), model, Vec::new());
+        Err(unprogrammed("create_speech"))
+    }
+
+    fn create_speech_stream(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef> + Send,
+        _input: &str,
+        _options: SpeechOptions,
+    ) -> impl Stream<Item = Result<bytes::Bytes, GatewayError>> + Send {
+        let model = Some(model.into().to_string());
+        self.record("create_speech_stream", Some(provider), model, Vec::new());
+        futures_util::stream::once(async { Err(unprogrammed("create_speech_stream")) })
+    }
+
+    async fn create_transcription(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef> + Send,
+        _audio: impl Into<AudioFile> + Send,
+        _options: TranscriptionOptions,
+    ) -> Result<Transcription, GatewayError> {
+        let model = Some(model.into().to_string());
+        self.record("create_transcription", Some(provider), model, Vec::new());
+        Err(unprogrammed("create_transcription"))
+    }
+
+    fn transcribe_stream<S>(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef> + Send,
+        _filename: &str,
+        _audio: S,
+    ) -> impl Stream<Item = Result<TranscriptionEvent, GatewayError>> + Send
+    where
+        S: Stream<Item = Result<bytes::Bytes, std::io::Error>> + Send + 'static,
+    {
+        let model = Some(model.into().to_string());
+        self.record("transcribe_stream", Some(provider), model, Vec::new());
+        futures_util::stream::once(async { Err(unprogrammed("transcribe_stream")) })
+    }
+
+    async fn create_moderation(
+        &self,
+        provider: Provider,
+        model: impl Into<ModelRef> + Send,
+        _input: impl Into<ModerationInput> + Send,
+    ) -> Result<ModerationResponse, GatewayError> {
+        let model = Some(model.into().to_string());
+        self.record("create_moderation", Some(provider), model, Vec::new());
+        Err(unprogrammed("create_moderation"))
+    }
+
+    async fn health_check(&self) -> Result<bool, GatewayError> {
+        self.record("health_check", None, None, Vec::new());
+        Ok(true)
+    }
+
+    async fn ready_check(&self) -> HealthStatus {
+        self.record("ready_check", None, None, Vec::new());
+        HealthStatus::Healthy
+    }
+
+    async fn provider_health(&self) -> Result<Vec<ProviderHealth>, GatewayError> {
+        self.record("provider_health", None, None, Vec::new());
+        Ok(Vec::new())
+    }
+}
@@ -0,0 +1,111 @@
+//! Model references in the gateway's `provider/model` form.
+
+use std::fmt;
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::Provider;
+
+/// A model ID, optionally qualified with the provider serving it, as in the
+/// gateway's model listing (`openai/gpt-4o`).
+///
+/// Parsing with [`str::parse`] validates the provider prefix. Plain strings
+/// convert without validation and are sent as given, so any model ID the
+/// gateway understands can still be used.
+///
+/// ```
+/// use inference_gateway_sdk::{ModelRef, Provider};
+///
+/// let model: ModelRef = "groq/llama-3.3-70b-versatile".parse().unwrap();
+/// assert_eq!(model.provider(), Some(Provider::Groq));
+/// assert_eq!(model.model(), "llama-3.3-70b-versatile");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ModelRef {
+    provider: Option<Provider>,
+    model: String,
+}
+
+/// Why a string is not a valid [`ModelRef`].
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ModelRefError {
+    #[error("model reference is empty")]
+    Empty,
+
+    #[error("unknown provider `{0}`")]
+    UnknownProvider(String),
+}
+
+impl ModelRef {
+    /// A model served by `provider`.
+    pub fn new(provider: Provider, model: impl Into<String>) -> Self {
+        Self {
+            provider: Some(provider),
+            model: model.into(),
+        }
+    }
+
+    /// The provider prefix, if the reference has one.
+    pub fn provider(&self) -> Option<Provider> {
+        self.provider
+    }
+
+    /// The model ID without the provider prefix.
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+}
+
+impl FromStr for ModelRef {
+    type Err = ModelRefError;
+
+    /// Parses `provider/model` or a bare `model`. Anything before the first
+    /// `/` must be a known provider.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        match s.split_once('/') {
+            Some((provider, model)) => {
+                let provider = provider
+                    .parse()
+                    .map_err(|_| ModelRefError::UnknownProvider(provider.to_string()))?;
+                if model.is_empty() {
+                    return Err(ModelRefError::Empty);
+                }
+                Ok(Self::new(provider, model))
+            }
+            None if s.is_empty() => Err(ModelRefError::Empty),
+            None => Ok(s.into()),
+        }
+    }
+}
+
+impl fmt::Display for ModelRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.provider {
+            Some(provider) => write!(f, "{provider}/{}", self.model),
+            None => f.write_str(&self.model),
+        }
+    }
+}
+
+impl From<&str> for ModelRef {
+    fn from(model: &str) -> Self {
+        model.to_string().into()
+    }
+}
+
+impl From<&String> for ModelRef {
+    fn from(model: &String) -> Self {
+        model.clone().into()
+    }
+}
+
+impl From<String> for ModelRef {
+    fn from(model: String) -> Self {
+        Self {
+            provider: None,
+            model,
+        }
+    }
+}
@@ -0,0 +1,134 @@
+//! Content moderation through the provider proxy.
+//!
+//! The gateway spec has no moderations route, so these types follow the
+//! OpenAI-compatible `/moderations` API that providers expose behind the
+//! proxy.
+
+use serde::{Deserialize, Serialize};
+
+/// Text to screen with
+/// [`InferenceGatewayAPI::create_moderation`](crate::InferenceGatewayAPI::create_moderation):
+/// one string, or several classified in one request.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum ModerationInput {
+    Text(String),
+    Texts(Vec<String>),
+}
+
+impl From<&str> for ModerationInput {
+    fn from(text: &str) -> Self {
+        Self::Text(text.to_string())
+    }
+}
+
+impl From<String> for ModerationInput {
+    fn from(text: String) -> Self {
+        Self::Text(text)
+    }
+}
+
+impl From<Vec<String>> for ModerationInput {
+    fn from(texts: Vec<String>) -> Self {
+        Self::Texts(texts)
+    }
+}
+
+impl From<&[&str]> for ModerationInput {
+    fn from(texts: &[&str]) -> Self {
+        Self::Texts(texts.iter().map(|text| text.to_string()).collect())
+    }
+}
+
+/// The verdicts of a moderation request, one per input, in input order.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct ModerationResponse {
+    pub id: String,
+    /// The model that classified the input.
+    pub model: String,
+    pub results: Vec<ModerationResult>,
+}
+
+impl ModerationResponse {
+    /// Whether any input was flagged.
+    pub fn flagged(&self) -> bool {
+        self.results.iter().any(|result| result.flagged)
+    }
+}
+
+/// The verdict on one input.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct ModerationResult {
+    /// Whether the model considers the input harmful in any category.
+    pub flagged: bool,
+    /// Which categories the input was flagged for.
+    pub categories: ModerationCategories<bool>,
+    /// The model's confidence, from 0 to 1, that the input falls in each
+    /// category.
+    pub category_scores: ModerationCategories<f64>,
+}
+
+impl ModerationResult {
+    /// The names of the categories the input was flagged for, as the API
+    /// spells them, such as `"self-harm/intent"`.
+    pub fn flagged_categories(&self) -> Vec<&'static str> {
+        self.categories
+            .iter()
+            .filter(|(_, flagged)| *flagged)
+            .map(|(name, _)| name)
+            .collect()
+    }
+}
+
+/// A value for each moderation category: whether the input was flagged for
+/// it, or its score.
+///
+/// Categories an older model doesn't classify are left at their default,
+/// `false` or `0.0`.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ModerationCategories<T> {
+    pub harassment: T,
+    #[serde(rename = "harassment/threatening")]
+    pub harassment_threatening: T,
+    pub hate: T,
+    #[serde(rename = "hate/threatening")]
+    pub hate_threatening: T,
+    pub illicit: T,
+    #[serde(rename = "illicit/violent")]
+    pub illicit_violent: T,
+    #[serde(rename = "self-harm")]
+    pub self_harm: T,
+    #[serde(rename = "self-harm/intent")]
+    pub self_harm_intent: T,
+    #[serde(rename = "self-harm/instructions")]
+    pub self_harm_instructions: T,
+    pub sexual: T,
+    #[serde(rename = "sexual/minors")]
+    pub sexual_minors: T,
+    pub violence: T,
+    #[serde(rename = "violence/graphic")]
+    pub violence_graphic: T,
+}
+
+impl<T: Copy> ModerationCategories<T> {
+    /// Each category's name, as the API spells it, with its value.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, T)> {
+        [
+            ("harassment", self.harassment),
+            ("harassment/threatening", self.harassment_threatening),
+            ("hate", self.hate),
+            ("hate/threatening", self.hate_threatening),
+            ("illicit", self.illicit),
+            ("illicit/violent", self.illicit_violent),
+            ("self-harm", self.self_harm),
+            ("self-harm/intent", self.self_harm_intent),
+            ("self-harm/instructions", self.self_harm_instructions),
+            ("sexual", self.sexual),
+            ("sexual/minors", self.sexual_minors),
+            ("violence", self.violence),
+            ("violence/graphic", self.violence_graphic),
+        ]
+        .into_iter()
+    }
+}
@@ -0,0 +1,241 @@
+//! Smoothing over provider quirks the gateway passes through.
+//!
+//! The gateway forwards chat completions in the OpenAI shape, but some
+//! providers' deviations survive the trip: finish reasons spelled their own
+//! way, reasoning under another field name, tool calls without an `id`.
+//! Each provider's [`Quirks`] table lists what to rewrite, and the rewrite
+//! happens on the raw JSON, before parsing into the SDK's types would turn
+//! an unknown finish reason into `Other`. To handle a new deviation, extend
+//! the provider's table; a provider without one gets [`NONE`].
+
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+use crate::{GatewayError, Provider, SSEvents, decode};
+
+/// What to rewrite in one provider's chat completions.
+pub(crate) struct Quirks {
+    /// Finish reasons to rename, as `(provider's, canonical)`.
+    finish_reasons: &'static [(&'static str, &'static str)],
+    /// Fields of a message or delta holding reasoning text, moved to
+    /// `reasoning_content` when that is absent.
+    reasoning_fields: &'static [&'static str],
+    /// Fields of a message or delta sent as `null` where an array is
+    /// expected, dropped so they parse as empty.
+    null_fields: &'static [&'static str],
+    /// Whether tool calls may come without an `id`. One is then derived from
+    /// the call's index, so results can still be matched to calls.
+    missing_tool_call_ids: bool,
+}
+
+const NONE: Quirks = Quirks {
+    finish_reasons: &[],
+    reasoning_fields: &[],
+    null_fields: &[],
+    missing_tool_call_ids: false,
+};
+
+const ANTHROPIC: Quirks = Quirks {
+    finish_reasons: &[
+        ("end_turn", "stop"),
+        ("stop_sequence", "stop"),
+        ("pause_turn", "stop"),
+        ("max_tokens", "length"),
+        ("tool_use", "tool_calls"),
+        ("refusal", "content_filter"),
+    ],
+    reasoning_fields: &["thinking"],
+    null_fields: &[],
+    missing_tool_call_ids: false,
+};
+
+const COHERE: Quirks = Quirks {
+    finish_reasons: &[
+        ("COMPLETE", "stop"),
+        ("STOP_SEQUENCE", "stop"),
+        ("MAX_TOKENS", "length"),
+        ("TOOL_CALL", "tool_calls"),
+        ("ERROR_TOXIC", "content_filter"),
+    ],
+    reasoning_fields: &[],
+    null_fields: &[],
+    missing_tool_call_ids: false,
+};
+
+const GOOGLE: Quirks = Quirks {
+    finish_reasons: &[
+        ("STOP", "stop"),
+        ("MAX_TOKENS", "length"),
+        ("SAFETY", "content_filter"),
+        ("RECITATION", "content_filter"),
+        ("PROHIBITED_CONTENT", "content_filter"),
+    ],
+    reasoning_fields: &[],
+    null_fields: &[],
+    missing_tool_call_ids: true,
+};
+
+const MISTRAL: Quirks = Quirks {
+    finish_reasons: &[("model_length", "length")],
+    reasoning_fields: &[],
+    null_fields: &["tool_calls"],
+    missing_tool_call_ids: false,
+};
+
+const OLLAMA: Quirks = Quirks {
+    finish_reasons: &[],
+    reasoning_fields: &["thinking"],
+    null_fields: &[],
+    missing_tool_call_ids: true,
+};
+
+impl Quirks {
+    /// The table for `provider`.
+    pub(crate) fn for_provider(provider: Provider) -> &'static Self {
+        match provider {
+            Provider::Anthropic => &ANTHROPIC,
+            Provider::Cohere => &COHERE,
+            Provider::Google => &GOOGLE,
+            Provider::Mistral => &MISTRAL,
+            Provider::Ollama | Provider::OllamaCloud => &OLLAMA,
+            _ => &NONE,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.finish_reasons.is_empty()
+            && self.reasoning_fields.is_empty()
+            && self.null_fields.is_empty()
+            && !self.missing_tool_call_ids
+    }
+
+    /// Rewrites the choices of a response or stream chunk in place,
+    /// returning whether anything changed.
+    fn normalize(&self, body: &mut Value) -> bool {
+        let Some(choices) = body.get_mut("choices").and_then(Value::as_array_mut) else {
+            return false;
+        };
+        let mut changed = false;
+        for choice in choices.iter_mut().filter_map(Value::as_object_mut) {
+            changed |= self.finish_reason(choice);
+            for key in ["message", "delta"] {
+                if let Some(message) = choice.get_mut(key).and_then(Value::as_object_mut) {
+                    changed |= self.reasoning(message);
+                    changed |= self.null_fields(message);
+                    changed |= self.tool_call_ids(message, key == "delta");
+                }
+            }
+        }
+        changed
+    }
+
+    fn finish_reason(&self, choice: &mut Map<String, Value>) -> bool {
+        let Some(Value::String(reason)) = choice.get_mut("finish_reason") else {
+            return false;
+        };
+        match self.finish_reasons.iter().find(|(from, _)| from == reason) {
+            Some((_, to)) => {
+                *reason = to.to_string();
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn reasoning(&self, message: &mut Map<String, Value>) -> bool {
+        let mut changed = false;
+        for field in self.reasoning_fields {
+            if message.contains_key("reasoning_content")
+                || !message.get(*field).is_some_and(Value::is_string)
+            {
+                continue;
+            }
+            if let Some(text) = message.remove(*field) {
+                message.insert("reasoning_content".into(), text);
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    fn null_fields(&self, message: &mut Map<String, Value>) -> bool {
+        let mut changed = false;
+        for field in self.null_fields {
+            if message.get(*field).is_some_and(Value::is_null) {
+                message.remove(*field);
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Fills in missing tool-call ids as `call_<index>`. In a stream only
+    /// the fragment that starts a call, the one naming the function, gets
+    /// one.
+    fn tool_call_ids(&self, message: &mut Map<String, Value>, streamed: bool) -> bool {
+        if !self.missing_tool_call_ids {
+            return false;
+        }
+        let Some(calls) = message.get_mut("tool_calls").and_then(Value::as_array_mut) else {
+            return false;
+        };
+        let mut changed = false;
+        for (position, call) in calls.iter_mut().enumerate() {
+            let Some(call) = call.as_object_mut() else {
+                continue;
+            };
+            let has_id = call
+                .get("id")
+                .and_then(Value::as_str)
+                .is_some_and(|id| !id.is_empty());
+            let starts_call = !streamed
+                || call
+                    .get("function")
+                    .and_then(|function| function.get("name"))
+                    .is_some();
+            if has_id || !starts_call {
+                continue;
+            }
+            let index = call
+                .get("index")
+                .and_then(Value::as_u64)
+                .unwrap_or(position as u64);
+            call.insert("id".into(), format!("call_{index}").into());
+            changed = true;
+        }
+        changed
+    }
+}
+
+/// Parses a chat completion `body` from `provider` after smoothing over its
+/// quirks.
+pub(crate) fn parse<T: DeserializeOwned>(
+    provider: Provider,
+    body: &[u8],
+) -> Result<T, GatewayError> {
+    let quirks = Quirks::for_provider(provider);
+    if quirks.is_empty() {
+        return decode::from_slice(body);
+    }
+    if let Ok(mut value) = serde_json::from_slice::<Value>(body)
+        && quirks.normalize(&mut value)
+    {
+        return decode::from_value(value, body);
+    }
+    // Unchanged or not JSON: parse as-is, reporting any error against the
+    // original body.
+    decode::from_slice(body)
+}
+
+/// Smooths over the quirks of `provider` in a streamed chunk's data.
+pub(crate) fn event(provider: Provider, event: &mut SSEvents) {
+    let quirks = Quirks::for_provider(provider);
+    if quirks.is_empty() || event.data == "[DONE]" {
+        return;
+    }
+    if let Ok(mut value) = serde_json::from_str::<Value>(&event.data)
+        && quirks.normalize(&mut value)
+    {
+        event.data = value.to_string();
+    }
+}
@@ -0,0 +1,465 @@
+//! Per-call request options, and how requests are adapted to providers.
+
+use std::time::Duration;
+
+use reqwest::RequestBuilder;
+use serde::Serialize;
+
+use crate::{
+    ChatCompletionTool, CreateChatCompletionRequest, CreateChatCompletionRequestResponseFormat,
+    Message, MessageRole, Provider,
+};
+
+/// Options applied to a single call on top of the client's configuration.
+///
+/// ```
+/// use inference_gateway_sdk::RequestOptions;
+///
+/// let options = RequestOptions::new().with_query_param("route", "canary");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct RequestOptions {
+    query: Vec<(String, String)>,
+    priority: Priority,
+    idempotency_key: Option<String>,
+    retry_safe: bool,
+    scheduling_hints: SchedulingHints,
+    language: Option<String>,
+    tags: Vec<String>,
+    timeout: Option<Duration>,
+}
+
+/// How urgently a request should be sent when the client's rate limiters are
+/// saturated. Waiting requests are let through highest priority first.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    /// Interactive requests a user is waiting on.
+    High,
+    /// The default.
+    #[default]
+    Normal,
+    /// Batch and background work.
+    Low,
+}
+
+impl RequestOptions {
+    /// Creates empty options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a query parameter to this call's URL, after the client-level
+    /// parameters and the `provider` query.
+    pub fn with_query_param(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.query.push((key.into(), value.into()));
+        self
+    }
+
+    /// Sets how this call is ordered against others waiting on the client's
+    /// rate limiters.
+    pub fn with_priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Sends `key` as this call's `Idempotency-Key` header, so a gateway
+    /// that deduplicates on it answers a repeat with the first result. The
+    /// call then counts as safe to retry.
+    pub fn with_idempotency_key(mut self, key: impl Into<String>) -> Self {
+        self.idempotency_key = Some(key.into());
+        self
+    }
+
+    /// Lets the client's [`RetryConfig`](crate::RetryConfig) retry this call
+    /// even without an idempotency key, accepting that a request the gateway
+    /// already processed may generate, and be billed, twice.
+    pub fn with_retry_safe(mut self, safe: bool) -> Self {
+        self.retry_safe = safe;
+        self
+    }
+
+    /// Sends `hints` with this call. Each hint set here replaces the
+    /// client's, and the others are kept.
+    pub fn with_scheduling_hints(mut self, hints: SchedulingHints) -> Self {
+        self.scheduling_hints = hints;
+        self
+    }
+
+    /// Hints the language of the conversation to the client's
+    /// [`Router`](crate::Router). Not sent to the gateway.
+    pub fn with_language(mut self, language: impl Into<String>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    /// Tags this call for the client's [`Router`](crate::Router). Not sent
+    /// to the gateway.
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Fails this call with [`GatewayError::Timeout`](crate::GatewayError::Timeout)
+    /// if it hasn't completed within `timeout`, including time spent queued
+    /// and retrying. A stream must start arriving within it.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub(crate) fn without_idempotency_key(mut self) -> Self {
+        self.idempotency_key = None;
+        self
+    }
+
+    pub(crate) fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    pub(crate) fn language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    pub(crate) fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    pub(crate) fn priority(&self) -> Priority {
+        self.priority
+    }
+
+    pub(crate) fn is_retry_safe(&self) -> bool {
+        self.retry_safe || self.idempotency_key.is_some()
+    }
+
+    /// The scheduling hint headers this call sends, taking any hint not set
+    /// here from `client_hints`.
+    pub(crate) fn scheduling_headers<'a>(
+        &'a self,
+        client_hints: &'a SchedulingHints,
+    ) -> impl Iterator<Item = (&'static str, &'a str)> {
+        self.scheduling_hints.headers(client_hints)
+    }
+
+    /// Adds this call's query parameters and headers to `request`, taking
+    /// any scheduling hint not set here from `client_hints`.
+    pub(crate) fn apply(
+        &self,
+        mut request: RequestBuilder,
+        client_hints: &SchedulingHints,
+    ) -> RequestBuilder {
+        for (name, value) in self.scheduling_headers(client_hints) {
+            request = request.header(name, value);
+        }
+        if !self.query.is_empty() {
+            request = request.query(&self.query);
+        }
+        if let Some(key) = &self.idempotency_key {
+            request = request.header("idempotency-key", key);
+        }
+        request
+    }
+}
+
+/// Tools, token limit and sampling parameters for a single chat completion,
+/// on top of the call's [`RequestOptions`]. Parameters left unset keep the
+/// client's or the provider's defaults.
+///
+/// ```
+/// use inference_gateway_sdk::{ChatCompletionOptions, RequestOptions};
+///
+/// let options = ChatCompletionOptions::new()
+///     .with_temperature(0.2)
+///     .with_top_p(0.9)
+///     .with_stop("\n\n")
+///     .with_request_options(RequestOptions::new().with_tag("summaries"));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ChatCompletionOptions {
+    request: RequestOptions,
+    temperature: Option<f64>,
+    top_p: Option<f64>,
+    top_k: Option<u32>,
+    frequency_penalty: Option<f64>,
+    presence_penalty: Option<f64>,
+    seed: Option<i64>,
+    stop: Vec<String>,
+    tools: Option<Vec<ChatCompletionTool>>,
+    max_tokens: Option<i64>,
+    response_format: Option<CreateChatCompletionRequestResponseFormat>,
+}
+
+impl ChatCompletionOptions {
+    /// Creates options that change nothing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the query parameters, headers and scheduling of the call.
+    pub fn with_request_options(mut self, request: RequestOptions) -> Self {
+        self.request = request;
+        self
+    }
+
+    /// Sets the sampling temperature, from 0 to 2. Lower is more focused
+    /// and deterministic.
+    pub fn with_temperature(mut self, temperature: f64) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    /// Samples only from the tokens making up the top `top_p` of the
+    /// probability mass.
+    pub fn with_top_p(mut self, top_p: f64) -> Self {
+        self.top_p = Some(top_p);
+        self
+    }
+
+    /// Samples only from the `top_k` most likely tokens. Not every provider
+    /// supports it.
+    pub fn with_top_k(mut self, top_k: u32) -> Self {
+        self.top_k = Some(top_k);
+        self
+    }
+
+    /// Penalizes tokens by how often they already appear, from -2 to 2.
+    pub fn with_frequency_penalty(mut self, penalty: f64) -> Self {
+        self.frequency_penalty = Some(penalty);
+        self
+    }
+
+    /// Penalizes tokens that already appear at all, from -2 to 2.
+    pub fn with_presence_penalty(mut self, penalty: f64) -> Self {
+        self.presence_penalty = Some(penalty);
+        self
+    }
+
+    /// Sets the `seed`, overriding the client's
+    /// [`with_seed`](crate::InferenceGatewayClient::with_seed).
+    pub fn with_seed(mut self, seed: i64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Adds a sequence that stops generation. Providers accept up to 4.
+    pub fn with_stop(mut self, sequence: impl Into<String>) -> Self {
+        self.stop.push(sequence.into());
+        self
+    }
+
+    /// Offers `tools` to the model instead of the client's
+    /// [`with_tools`](crate::InferenceGatewayClient::with_tools). Like the
+    /// client's, they aren't sent with streaming requests.
+    pub fn with_tools(mut self, tools: Vec<ChatCompletionTool>) -> Self {
+        self.tools = Some(tools);
+        self
+    }
+
+    /// Caps the tokens generated instead of the client's
+    /// [`with_max_tokens`](crate::InferenceGatewayClient::with_max_tokens).
+    /// Like the client's, it isn't sent with streaming requests.
+    pub fn with_max_tokens(mut self, max_tokens: i64) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Sets this call's [timeout](RequestOptions::with_timeout), keeping
+    /// its other request options.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.request = self.request.with_timeout(timeout);
+        self
+    }
+
+    /// Asks for output in `format`: plain text, any JSON object, or JSON
+    /// matching a schema.
+    pub fn with_response_format(
+        mut self,
+        format: impl Into<CreateChatCompletionRequestResponseFormat>,
+    ) -> Self {
+        self.response_format = Some(format.into());
+        self
+    }
+
+    pub(crate) fn request(&self) -> &RequestOptions {
+        &self.request
+    }
+
+    /// The tools this call offers, if they replace the client's.
+    pub(crate) fn tools(&self) -> Option<&[ChatCompletionTool]> {
+        self.tools.as_deref()
+    }
+
+    pub(crate) fn without_idempotency_key(mut self) -> Self {
+        self.request = self.request.without_idempotency_key();
+        self
+    }
+
+    /// Sets this call's parameters on `payload`.
+    pub(crate) fn apply_to(&self, payload: &mut CreateChatCompletionRequest) {
+        if !payload.stream {
+            if let Some(tools) = &self.tools {
+                payload.tools = tools.clone();
+            }
+            if self.max_tokens.is_some() {
+                payload.max_tokens = self.max_tokens;
+            }
+        }
+        let set = |field: &mut f64, value: Option<f64>| {
+            if let Some(value) = value {
+                *field = value;
+            }
+        };
+        set(&mut payload.temperature, self.temperature);
+        set(&mut payload.top_p, self.top_p);
+        set(&mut payload.frequency_penalty, self.frequency_penalty);
+        set(&mut payload.presence_penalty, self.presence_penalty);
+        if self.seed.is_some() {
+            payload.seed = self.seed;
+        }
+        if !self.stop.is_empty() {
+            payload.stop = Some(self.stop.clone().into());
+        }
+        if self.response_format.is_some() {
+            payload.response_format = self.response_format.clone();
+        }
+    }
+
+    /// The body to send for `payload`, once [applied](Self::apply_to).
+    pub(crate) fn body<'a>(
+        &self,
+        payload: &'a CreateChatCompletionRequest,
+    ) -> ChatCompletionBody<'a> {
+        ChatCompletionBody {
+            request: payload,
+            top_k: self.top_k,
+        }
+    }
+}
+
+impl From<RequestOptions> for ChatCompletionOptions {
+    fn from(request: RequestOptions) -> Self {
+        Self::new().with_request_options(request)
+    }
+}
+
+/// A chat completion request with the parameters the spec doesn't model.
+#[derive(Serialize)]
+pub(crate) struct ChatCompletionBody<'a> {
+    #[serde(flatten)]
+    request: &'a CreateChatCompletionRequest,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_k: Option<u32>,
+}
+
+/// Accounting and routing hints for the gateway's scheduler, sent as
+/// headers. Multi-tenant platforms set them once on the client with
+/// [`InferenceGatewayClient::with_scheduling_hints`](crate::InferenceGatewayClient::with_scheduling_hints)
+/// and override them per call with [`RequestOptions::with_scheduling_hints`].
+///
+/// Unlike [`Priority`], which only orders requests waiting on the client's
+/// own rate limiters, these are left to the gateway to act on.
+///
+/// ```
+/// use inference_gateway_sdk::{InferenceGatewayClient, RequestOptions, SchedulingHints};
+///
+/// let client = InferenceGatewayClient::new("http://localhost:8080/v1")
+///     .with_scheduling_hints(SchedulingHints::new().with_tenant_id("acme"));
+/// let options = RequestOptions::new()
+///     .with_scheduling_hints(SchedulingHints::new().with_priority_class("batch"));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SchedulingHints {
+    priority_class: Option<String>,
+    tenant_id: Option<String>,
+    cost_center: Option<String>,
+}
+
+impl SchedulingHints {
+    /// Creates empty hints.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sends `class` as the `X-Priority-Class` header.
+    pub fn with_priority_class(mut self, class: impl Into<String>) -> Self {
+        self.priority_class = Some(class.into());
+        self
+    }
+
+    /// Sends `tenant` as the `X-Tenant-Id` header.
+    pub fn with_tenant_id(mut self, tenant: impl Into<String>) -> Self {
+        self.tenant_id = Some(tenant.into());
+        self
+    }
+
+    /// Sends `cost_center` as the `X-Cost-Center` header.
+    pub fn with_cost_center(mut self, cost_center: impl Into<String>) -> Self {
+        self.cost_center = Some(cost_center.into());
+        self
+    }
+
+    /// The headers for these hints, with any unset one taken from
+    /// `fallback`.
+    fn headers<'a>(&'a self, fallback: &'a Self) -> impl Iterator<Item = (&'static str, &'a str)> {
+        [
+            (
+                "x-priority-class",
+                self.priority_class
+                    .as_ref()
+                    .or(fallback.priority_class.as_ref()),
+            ),
+            (
+                "x-tenant-id",
+                self.tenant_id.as_ref().or(fallback.tenant_id.as_ref()),
+            ),
+            (
+                "x-cost-center",
+                self.cost_center.as_ref().or(fallback.cost_center.as_ref()),
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| Some((name, value?.as_str())))
+    }
+}
+
+/// The role instruction messages are sent with. Newer OpenAI models take
+/// them as `developer` messages, while most other providers only know
+/// `system` and reject the newer role.
+///
+/// ```
+/// use inference_gateway_sdk::{InferenceGatewayClient, InstructionRole, Provider};
+///
+/// let client = InferenceGatewayClient::new("http://localhost:8080/v1")
+///     .with_instruction_role(Provider::Openai, InstructionRole::Developer);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum InstructionRole {
+    /// Sends developer messages as system messages.
+    System,
+    /// Sends system messages as developer messages.
+    Developer,
+}
+
+impl InstructionRole {
+    /// The mapping applied to `provider` unless the client configures one:
+    /// none for OpenAI, which accepts both roles, and [`Self::System`] for
+    /// the rest.
+    pub(crate) fn default_for(provider: Provider) -> Option<Self> {
+        match provider {
+            Provider::Openai => None,
+            _ => Some(Self::System),
+        }
+    }
+
+    /// Rewrites the role of each instruction message in `messages` to this
+    /// one.
+    pub(crate) fn apply(self, messages: &mut [Message]) {
+        let (from, to) = match self {
+            Self::System => (MessageRole::Developer, MessageRole::System),
+            Self::Developer => (MessageRole::System, MessageRole::Developer),
+        };
+        for message in messages.iter_mut().filter(|message| message.role == from) {
+            message.role = to;
+        }
+    }
+}
@@ -0,0 +1,227 @@
+//! Best-effort parsing of truncated JSON.
+//!
+//! Tool-call `arguments` arrive as string fragments while streaming. This
+//! parser accepts any prefix of a valid JSON document and returns the value
+//! that prefix describes so far: open strings, arrays, and objects are closed,
+//! a trailing key without a value is dropped, and partial literals and
+//! numbers are completed or trimmed.
+
+use serde_json::{Map, Number, Value};
+
+/// Parses a possibly-truncated JSON document.
+///
+/// Returns `None` when `input` holds no value yet (empty or whitespace) or is
+/// not a prefix of valid JSON. Complete documents parse exactly as they would
+/// with [`serde_json::from_str`].
+///
+/// ```
+/// use inference_gateway_sdk::parse_partial_json;
+/// use serde_json::json;
+///
+/// let value = parse_partial_json(r#"{"city": "Lond"#).unwrap();
+/// assert_eq!(value, json!({"city": "Lond"}));
+/// ```
+pub fn parse_partial_json(input: &str) -> Option<Value> {
+    let mut parser = Parser {
+        bytes: input.as_bytes(),
+        input,
+        pos: 0,
+    };
+    let value = parser.value()?;
+    parser.skip_whitespace();
+    if parser.pos < parser.bytes.len() {
+        return None;
+    }
+    Some(value)
+}
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    input: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn at_end(&self) -> bool {
+        self.pos >= self.bytes.len()
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    /// Parses one value. `None` means nothing usable was found; syntax errors
+    /// set `pos` past the end marker so the caller rejects the document.
+    fn value(&mut self) -> Option<Value> {
+        self.skip_whitespace();
+        match self.peek()? {
+            b'{' => self.object(),
+            b'[' => self.array(),
+            b'"' => self.string().map(|(s, _)| Value::String(s)),
+            b't' => self.literal("true", Value::Bool(true)),
+            b'f' => self.literal("false", Value::Bool(false)),
+            b'n' => self.literal("null", Value::Null),
+            b'-' | b'0'..=b'9' => self.number(),
+            _ => self.fail(),
+        }
+    }
+
+    fn fail<T>(&mut self) -> Option<T> {
+        self.pos = usize::MAX;
+        None
+    }
+
+    fn object(&mut self) -> Option<Value> {
+        self.pos += 1;
+        let mut map = Map::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                None => return Some(Value::Object(map)),
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Some(Value::Object(map));
+                }
+                Some(b'"') => {}
+                Some(_) => return self.fail(),
+            }
+            // An unterminated key is not shown.
+            let (key, complete) = self.string()?;
+            if !complete {
+                return Some(Value::Object(map));
+            }
+            self.skip_whitespace();
+            match self.peek() {
+                None => return Some(Value::Object(map)),
+                Some(b':') => self.pos += 1,
+                Some(_) => return self.fail(),
+            }
+            self.skip_whitespace();
+            if self.at_end() {
+                return Some(Value::Object(map));
+            }
+            let value = self.value();
+            if self.pos == usize::MAX {
+                return None;
+            }
+            if let Some(value) = value {
+                map.insert(key, value);
+            }
+            self.skip_whitespace();
+            match self.peek() {
+                None => return Some(Value::Object(map)),
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {}
+                Some(_) => return self.fail(),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Option<Value> {
+        self.pos += 1;
+        let mut items = Vec::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                None => return Some(Value::Array(items)),
+                Some(b']') => {
+                    self.pos += 1;
+                    return Some(Value::Array(items));
+                }
+                Some(_) => {}
+            }
+            let value = self.value();
+            if self.pos == usize::MAX {
+                return None;
+            }
+            if let Some(value) = value {
+                items.push(value);
+            }
+            self.skip_whitespace();
+            match self.peek() {
+                None => return Some(Value::Array(items)),
+                Some(b',') => self.pos += 1,
+                Some(b']') => {}
+                Some(_) => return self.fail(),
+            }
+        }
+    }
+
+    /// Parses a string, closing it if the input ends inside; the flag reports
+    /// whether the closing quote was seen. An incomplete escape sequence at
+    /// the end is dropped.
+    fn string(&mut self) -> Option<(String, bool)> {
+        let start = self.pos;
+        self.pos += 1;
+        while let Some(b) = self.peek() {
+            match b {
+                b'"' => {
+                    self.pos += 1;
+                    return match serde_json::from_str(&self.input[start..self.pos]) {
+                        Ok(s) => Some((s, true)),
+                        Err(_) => self.fail(),
+                    };
+                }
+                b'\\' => self.pos += 2,
+                _ => self.pos += 1,
+            }
+        }
+        self.pos = self.bytes.len();
+
+        let mut body = &self.input[start + 1..];
+        // Trim a dangling backslash or a partial `\uXXXX` escape.
+        if let Some(slash) = body.rfind('\\') {
+            let escape = &body[slash..];
+            let backslashes = body[..slash]
+                .chars()
+                .rev()
+                .take_while(|&c| c == '\\')
+                .count();
+            let complete = escape.len() >= 2 && (!escape.starts_with("\\u") || escape.len() >= 6);
+            if backslashes % 2 == 0 && !complete {
+                body = &body[..slash];
+            }
+        }
+        match serde_json::from_str(&format!("\"{body}\"")) {
+            Ok(s) => Some((s, false)),
+            Err(_) => self.fail(),
+        }
+    }
+
+    fn literal(&mut self, word: &str, value: Value) -> Option<Value> {
+        let rest = &self.bytes[self.pos..];
+        let len = rest.len().min(word.len());
+        if rest[..len] != word.as_bytes()[..len] {
+            return self.fail();
+        }
+        self.pos += len;
+        Some(value)
+    }
+
+    fn number(&mut self) -> Option<Value> {
+        let start = self.pos;
+        while matches!(
+            self.peek(),
+            Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9')
+        ) {
+            self.pos += 1;
+        }
+        let mut text = &self.input[start..self.pos];
+        if self.at_end() {
+            text = text.trim_end_matches(['-', '+', '.', 'e', 'E']);
+            if text.is_empty() {
+                return None;
+            }
+        }
+        match serde_json::from_str::<Number>(text) {
+            Ok(number) => Some(Value::Number(number)),
+            Err(_) => self.fail(),
+        }
+    }
+}
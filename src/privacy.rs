@@ -0,0 +1,91 @@
+//! Keeping prompts and completions out of `Debug` output.
+//!
+//! Once privacy mode is on, `Debug` for messages, content parts, deltas and
+//! tool-call arguments prints each piece of text as its length and a hash
+//! instead of the text itself, so requests and responses can be logged
+//! with `{:?}` without leaking what was said. Equal texts hash equally
+//! within a process, which is enough to tell them apart in a log.
+//!
+//! `Debug` has no access to a client, so the switch is process-wide:
+//!
+//! ```
+//! use inference_gateway_sdk::{Message, privacy};
+//!
+//! privacy::enable();
+//! let logged = format!("{:?}", Message::user("Tell me a secret"));
+//! assert!(!logged.contains("secret"));
+//! privacy::disable();
+//! ```
+
+use std::borrow::Cow;
+use std::fmt;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Turns privacy mode on for every client and value in the process, until
+/// [`disable`] is called.
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Turns privacy mode off again.
+pub fn disable() {
+    ENABLED.store(false, Ordering::Relaxed);
+}
+
+/// Whether privacy mode is on.
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// A value whose text privacy mode elides.
+pub(crate) trait Sensitive: fmt::Debug {
+    /// The text to measure and hash, or `None` if there is none.
+    fn text(&self) -> Option<Cow<'_, str>>;
+}
+
+impl Sensitive for String {
+    fn text(&self) -> Option<Cow<'_, str>> {
+        Some(Cow::Borrowed(self))
+    }
+}
+
+impl Sensitive for Option<String> {
+    fn text(&self) -> Option<Cow<'_, str>> {
+        self.as_deref().map(Cow::Borrowed)
+    }
+}
+
+impl Sensitive for serde_json::Map<String, serde_json::Value> {
+    fn text(&self) -> Option<Cow<'_, str>> {
+        serde_json::to_string(self).ok().map(Cow::Owned)
+    }
+}
+
+/// Formats its value with `Debug`, or as `<N chars #hash>` in privacy mode.
+pub(crate) struct Elided<'a, T: ?Sized>(pub(crate) &'a T);
+
+impl<T: Sensitive + ?Sized> fmt::Debug for Elided<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !is_enabled() {
+            return self.0.fmt(f);
+        }
+        match self.0.text() {
+            Some(text) => f.write_str(&summary(&text)),
+            None => f.write_str("None"),
+        }
+    }
+}
+
+/// `text` as its length in characters and a short hash.
+pub(crate) fn summary(text: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    format!(
+        "<{} chars #{:08x}>",
+        text.chars().count(),
+        hasher.finish() >> 32
+    )
+}
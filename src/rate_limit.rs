@@ -0,0 +1,397 @@
+//! Client-side tracking of the gateway's rate-limit budget.
+//!
+//! Responses that carry `X-RateLimit-Remaining` and `X-RateLimit-Reset`
+//! update a budget shared by every clone of the client. Once the budget is
+//! spent, requests wait for the reset instead of being sent only to come back
+//! as `429 Too Many Requests`.
+//!
+//! Independently, [`ProviderLimits`] configured on the client throttle the
+//! requests and tokens sent to each provider.
+//!
+//! Requests waiting on either limiter are let through by [`Priority`], and
+//! in arrival order within a priority: a request only takes a slot when no
+//! request of higher priority, or of the same priority that arrived earlier,
+//! is waiting. The client's queue limit bounds how many may wait on each
+//! limiter.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use reqwest::header::HeaderMap;
+use tokio::sync::Notify;
+use tokio::time::Instant;
+
+use crate::{GatewayError, Priority};
+
+/// `X-RateLimit-Reset` values at or above this are Unix timestamps rather
+/// than a number of seconds.
+const EPOCH_THRESHOLD: u64 = 1_000_000_000;
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct RateLimitBudget {
+    window: Arc<Mutex<Option<Window>>>,
+    waiters: Arc<Waiters>,
+}
+
+#[derive(Debug)]
+struct Window {
+    remaining: u64,
+    reset_at: Instant,
+}
+
+impl RateLimitBudget {
+    /// Waits until the budget allows another request and reserves it.
+    pub(crate) async fn acquire(
+        &self,
+        priority: Priority,
+        limit: Option<QueueLimit>,
+    ) -> Result<(), GatewayError> {
+        self.waiters
+            .acquire(priority, limit, || {
+                let mut window = self.window.lock().unwrap();
+                match window.as_mut() {
+                    None => Ok(()),
+                    Some(w) if Instant::now() >= w.reset_at => {
+                        *window = None;
+                        Ok(())
+                    }
+                    Some(w) if w.remaining > 0 => {
+                        w.remaining -= 1;
+                        Ok(())
+                    }
+                    Some(w) => Err(w.reset_at),
+                }
+            })
+            .await
+    }
+
+    /// Replaces the budget with the one reported in `headers`, if any.
+    pub(crate) fn update(&self, headers: &HeaderMap) {
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse::<u64>().ok())
+        };
+        let (Some(remaining), Some(reset)) =
+            (header("x-ratelimit-remaining"), header("x-ratelimit-reset"))
+        else {
+            return;
+        };
+        *self.window.lock().unwrap() = Some(Window {
+            remaining,
+            reset_at: Instant::now() + until_reset(reset),
+        });
+    }
+}
+
+fn until_reset(reset: u64) -> Duration {
+    if reset < EPOCH_THRESHOLD {
+        return Duration::from_secs(reset);
+    }
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    Duration::from_secs(reset).saturating_sub(now)
+}
+
+/// Request and token quotas for one provider, enforced on the client.
+///
+/// Each quota is a token bucket that starts full and refills evenly over a
+/// minute. Token usage is only known once a response arrives, so it is
+/// charged afterwards and may overdraw the bucket; later requests to the
+/// provider then wait until it is positive again.
+///
+/// ```
+/// use inference_gateway_sdk::{InferenceGatewayClient, Provider, ProviderLimits};
+///
+/// let client = InferenceGatewayClient::new("http://localhost:8080/v1").with_provider_limits(
+///     Provider::Groq,
+///     ProviderLimits::new()
+///         .with_requests_per_minute(30)
+///         .with_tokens_per_minute(6_000),
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ProviderLimits {
+    requests_per_minute: Option<u32>,
+    tokens_per_minute: Option<u32>,
+}
+
+impl ProviderLimits {
+    /// Creates limits that don't restrict anything yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Caps the number of requests sent to the provider per minute.
+    pub fn with_requests_per_minute(mut self, requests: u32) -> Self {
+        self.requests_per_minute = Some(requests);
+        self
+    }
+
+    /// Caps the total (prompt plus completion) tokens used per minute.
+    pub fn with_tokens_per_minute(mut self, tokens: u32) -> Self {
+        self.tokens_per_minute = Some(tokens);
+        self
+    }
+}
+
+/// The buckets enforcing one provider's [`ProviderLimits`], shared by every
+/// clone of the client.
+#[derive(Clone, Debug)]
+pub(crate) struct ProviderThrottle {
+    buckets: Arc<Mutex<Buckets>>,
+    waiters: Arc<Waiters>,
+}
+
+#[derive(Debug)]
+struct Buckets {
+    requests: Option<TokenBucket>,
+    tokens: Option<TokenBucket>,
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    capacity: f64,
+    available: f64,
+    per_second: f64,
+    refilled: Instant,
+}
+
+impl TokenBucket {
+    fn per_minute(amount: u32) -> Self {
+        let capacity = f64::from(amount.max(1));
+        Self {
+            capacity,
+            available: capacity,
+            per_second: capacity / 60.0,
+            refilled: Instant::now(),
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.duration_since(self.refilled).as_secs_f64();
+        self.available = (self.available + elapsed * self.per_second).min(self.capacity);
+        self.refilled = now;
+    }
+
+    /// How long until at least one unit is available.
+    fn wait(&self) -> Duration {
+        if self.available >= 1.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64((1.0 - self.available) / self.per_second)
+        }
+    }
+}
+
+impl ProviderThrottle {
+    pub(crate) fn new(limits: ProviderLimits) -> Self {
+        Self {
+            buckets: Arc::new(Mutex::new(Buckets {
+                requests: limits.requests_per_minute.map(TokenBucket::per_minute),
+                tokens: limits.tokens_per_minute.map(TokenBucket::per_minute),
+            })),
+            waiters: Arc::default(),
+        }
+    }
+
+    /// Waits until both buckets allow a request and takes one from the
+    /// request bucket.
+    pub(crate) async fn acquire(
+        &self,
+        priority: Priority,
+        limit: Option<QueueLimit>,
+    ) -> Result<(), GatewayError> {
+        self.waiters
+            .acquire(priority, limit, || {
+                let mut buckets = self.buckets.lock().unwrap();
+                let now = Instant::now();
+                let Buckets { requests, tokens } = &mut *buckets;
+                let mut wait = Duration::ZERO;
+                for bucket in [requests.as_mut(), tokens.as_mut()].into_iter().flatten() {
+                    bucket.refill(now);
+                    wait = wait.max(bucket.wait());
+                }
+                if !wait.is_zero() {
+                    return Err(now + wait);
+                }
+                if let Some(requests) = requests {
+                    requests.available -= 1.0;
+                }
+                Ok(())
+            })
+            .await
+    }
+
+    /// Takes `tokens` used by a finished (or progressing) call from the token
+    /// bucket.
+    pub(crate) fn charge(&self, tokens: i64) {
+        if tokens <= 0 {
+            return;
+        }
+        let mut buckets = self.buckets.lock().unwrap();
+        if let Some(bucket) = &mut buckets.tokens {
+            bucket.refill(Instant::now());
+            bucket.available -= tokens as f64;
+        }
+    }
+}
+
+/// What happens to a request that finds a limiter's queue full.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Wait for room in the queue.
+    Block,
+    /// Fail the new request with [`GatewayError::QueueFull`].
+    RejectWithError,
+    /// Fail the longest-waiting request with [`GatewayError::QueueFull`] and
+    /// queue the new one in its place.
+    DropOldest,
+}
+
+/// How many requests may wait on each limiter, and what happens beyond that.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct QueueLimit {
+    pub(crate) depth: usize,
+    pub(crate) policy: OverflowPolicy,
+}
+
+/// Requests waiting on a limiter, oldest first.
+#[derive(Debug, Default)]
+struct Waiters {
+    queue: Mutex<Queue>,
+    /// Notified whenever the queue changes, so waiters re-check.
+    changed: Notify,
+}
+
+#[derive(Debug, Default)]
+struct Queue {
+    next_id: u64,
+    waiting: VecDeque<(u64, Priority)>,
+    /// Requests dropped by [`OverflowPolicy::DropOldest`] that haven't
+    /// noticed yet.
+    evicted: Vec<u64>,
+}
+
+impl Queue {
+    /// Whether the request queued as `id` has to let another go first: one
+    /// of higher priority, or of the same priority queued before it. Ids
+    /// grow with arrival, so `next_id` asks for a request yet to be queued.
+    fn outranked(&self, id: u64, priority: Priority) -> bool {
+        self.waiting
+            .iter()
+            .any(|&(other_id, other)| (other, other_id) < (priority, id))
+    }
+}
+
+impl Waiters {
+    /// Calls `try_take` until it succeeds, sleeping until the instant it
+    /// returns on failure, and never while a request ahead of this one waits.
+    /// Requests that have to wait are queued subject to `limit`.
+    async fn acquire(
+        &self,
+        priority: Priority,
+        limit: Option<QueueLimit>,
+        mut try_take: impl FnMut() -> Result<(), Instant>,
+    ) -> Result<(), GatewayError> {
+        let outranked = {
+            let queue = self.queue.lock().unwrap();
+            queue.outranked(queue.next_id, priority)
+        };
+        if !outranked && try_take().is_ok() {
+            return Ok(());
+        }
+        let queued = self.enqueue(priority, limit).await?;
+        let id = queued.id;
+        loop {
+            let changed = self.changed.notified();
+            tokio::pin!(changed);
+            changed.as_mut().enable();
+            let outranked = {
+                let queue = self.queue.lock().unwrap();
+                if queue.evicted.contains(&id) {
+                    return Err(GatewayError::QueueFull);
+                }
+                queue.outranked(id, priority)
+            };
+            if outranked {
+                changed.await;
+                continue;
+            }
+            match try_take() {
+                Ok(()) => return Ok(()),
+                Err(until) => {
+                    tokio::select! {
+                        _ = tokio::time::sleep_until(until) => {}
+                        _ = changed => {}
+                    }
+                }
+            }
+        }
+    }
+
+    async fn enqueue(
+        &self,
+        priority: Priority,
+        limit: Option<QueueLimit>,
+    ) -> Result<Queued<'_>, GatewayError> {
+        loop {
+            let changed = self.changed.notified();
+            tokio::pin!(changed);
+            changed.as_mut().enable();
+            if let Some(id) = self.try_enqueue(priority, limit)? {
+                return Ok(Queued { waiters: self, id });
+            }
+            // Blocked until a place frees up.
+            changed.await;
+        }
+    }
+
+    /// Queues a request unless the queue is full and `limit` says to block,
+    /// returning its id.
+    fn try_enqueue(
+        &self,
+        priority: Priority,
+        limit: Option<QueueLimit>,
+    ) -> Result<Option<u64>, GatewayError> {
+        let mut queue = self.queue.lock().unwrap();
+        if let Some(limit) = limit
+            && queue.waiting.len() >= limit.depth
+        {
+            match limit.policy {
+                OverflowPolicy::Block => return Ok(None),
+                OverflowPolicy::RejectWithError => return Err(GatewayError::QueueFull),
+                OverflowPolicy::DropOldest => {
+                    let Some((oldest, _)) = queue.waiting.pop_front() else {
+                        return Err(GatewayError::QueueFull);
+                    };
+                    queue.evicted.push(oldest);
+                    self.changed.notify_waiters();
+                }
+            }
+        }
+        let id = queue.next_id;
+        queue.next_id += 1;
+        queue.waiting.push_back((id, priority));
+        Ok(Some(id))
+    }
+}
+
+/// A place in the queue, given up when dropped.
+struct Queued<'a> {
+    waiters: &'a Waiters,
+    id: u64,
+}
+
+impl Drop for Queued<'_> {
+    fn drop(&mut self) {
+        let mut queue = self.waiters.queue.lock().unwrap();
+        queue.waiting.retain(|&(id, _)| id != self.id);
+        queue.evicted.retain(|&id| id != self.id);
+        drop(queue);
+        self.waiters.changed.notify_waiters();
+    }
+}
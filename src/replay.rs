@@ -0,0 +1,325 @@
+//! Recording a run's gateway traffic and replaying it without a network.
+//!
+//! A [`ReplayClient`] wraps an [`InferenceGatewayClient`] whose every HTTP
+//! exchange goes through a tape. Recording sends requests as usual, pinned
+//! to a fixed `seed`, and keeps each response's status, content type and
+//! body in a [`Cassette`]. Replaying answers each request with the matching
+//! recorded response, byte for byte, so code under test sees exactly what
+//! it saw when the cassette was made.
+//!
+//! Replay also runs on a frozen clock: retry backoff and job polling
+//! intervals are skipped, recorded rate-limit headers are not kept, and
+//! [`ReplayClient::now`] returns the time the cassette was recorded.
+
+use std::ops::Deref;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use chrono::{DateTime, Utc};
+use reqwest::header::{CONTENT_TYPE, ETAG};
+use reqwest::{RequestBuilder, Response};
+use serde::{Deserialize, Serialize};
+
+use crate::{GatewayError, InferenceGatewayClient};
+
+/// A recorded run: the seed its requests were sent with and every exchange
+/// with the gateway, in the order they completed.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Cassette {
+    /// When recording started; the replay's frozen clock.
+    pub recorded_at: DateTime<Utc>,
+    /// The `seed` sent with every chat completion.
+    pub seed: i64,
+    /// The recorded exchanges.
+    pub exchanges: Vec<Exchange>,
+}
+
+/// One recorded request and its response.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Exchange {
+    /// The request method, such as `POST`.
+    pub method: String,
+    /// The full request URL, query included.
+    pub url: String,
+    /// The request body, for JSON requests. Replayed requests must match
+    /// it; other bodies, such as multipart uploads with their random
+    /// boundaries, aren't compared.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_body: Option<String>,
+    /// The response status code.
+    pub status: u16,
+    /// The response `Content-Type`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+    /// The response `ETag`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    /// The response body, base64-encoded if `base64` is set.
+    pub body: String,
+    /// Whether `body` is base64, for responses that aren't UTF-8 text.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub base64: bool,
+}
+
+impl Cassette {
+    /// Reads a cassette saved with [`Cassette::save`].
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let json = std::fs::read(path)?;
+        serde_json::from_slice(&json).map_err(std::io::Error::other)
+    }
+
+    /// Writes the cassette as pretty-printed JSON, for committing next to
+    /// the tests that replay it.
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let json = serde_json::to_vec_pretty(self).map_err(std::io::Error::other)?;
+        std::fs::write(path, json)
+    }
+}
+
+/// Whether the tape records or plays back.
+#[derive(Debug)]
+enum Mode {
+    Record,
+    /// Which recorded exchanges have been played back.
+    Replay(Vec<bool>),
+}
+
+/// What an [`InferenceGatewayClient`] sends its requests through while
+/// recording or replaying.
+#[derive(Debug)]
+pub(crate) struct Tape {
+    recorded_at: DateTime<Utc>,
+    seed: i64,
+    state: Mutex<(Mode, Vec<Exchange>)>,
+}
+
+impl Tape {
+    pub(crate) fn is_replaying(&self) -> bool {
+        matches!(self.state.lock().unwrap().0, Mode::Replay(_))
+    }
+
+    async fn send(&self, request: RequestBuilder) -> Result<Response, GatewayError> {
+        let (client, request) = request.build_split();
+        let request = request?;
+        let mut recorded = Exchange {
+            method: request.method().to_string(),
+            url: request.url().to_string(),
+            request_body: None,
+            status: 0,
+            content_type: None,
+            etag: None,
+            body: String::new(),
+            base64: false,
+        };
+        let is_json = request
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.starts_with("application/json"));
+        if is_json && let Some(body) = request.body().and_then(|body| body.as_bytes()) {
+            recorded.request_body = Some(String::from_utf8_lossy(body).into_owned());
+        }
+
+        if self.is_replaying() {
+            return self.play(&recorded);
+        }
+
+        let response = client.execute(request).await?;
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = response.bytes().await?;
+        recorded.status = status.as_u16();
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|value: &reqwest::header::HeaderValue| value.to_str().ok())
+                .map(str::to_string)
+        };
+        recorded.content_type = header(CONTENT_TYPE);
+        recorded.etag = header(ETAG);
+        match std::str::from_utf8(&body) {
+            Ok(text) => recorded.body = text.to_string(),
+            Err(_) => {
+                recorded.body = STANDARD.encode(&body);
+                recorded.base64 = true;
+            }
+        }
+        self.state.lock().unwrap().1.push(recorded);
+
+        let mut rebuilt = http::Response::builder().status(status);
+        if let Some(rebuilt_headers) = rebuilt.headers_mut() {
+            *rebuilt_headers = headers;
+        }
+        Ok(rebuilt
+            .body(body)
+            .map_err(|e| GatewayError::Other(Box::new(e)))?
+            .into())
+    }
+
+    /// The first unplayed exchange `request` matches, as a response.
+    fn play(&self, request: &Exchange) -> Result<Response, GatewayError> {
+        let mut state = self.state.lock().unwrap();
+        let (Mode::Replay(played), exchanges) = &mut *state else {
+            unreachable!("only called while replaying");
+        };
+        let position = exchanges
+            .iter()
+            .zip(played.iter())
+            .position(|(exchange, played)| {
+                !played
+                    && exchange.method == request.method
+                    && exchange.url == request.url
+                    && (exchange.request_body.is_none()
+                        || exchange.request_body == request.request_body)
+            });
+        let Some(position) = position else {
+            return Err(GatewayError::ReplayMismatch {
+                method: request.method.clone(),
+                url: request.url.clone(),
+            });
+        };
+        played[position] = true;
+        let exchange = &exchanges[position];
+        let body = if exchange.base64 {
+            STANDARD
+                .decode(&exchange.body)
+                .map_err(|e| GatewayError::Other(Box::new(e)))?
+        } else {
+            exchange.body.clone().into_bytes()
+        };
+        let mut response = http::Response::builder().status(exchange.status);
+        if let Some(content_type) = &exchange.content_type {
+            response = response.header(CONTENT_TYPE, content_type);
+        }
+        if let Some(etag) = &exchange.etag {
+            response = response.header(ETAG, etag);
+        }
+        Ok(response
+            .body(body)
+            .map_err(|e| GatewayError::Other(Box::new(e)))?
+            .into())
+    }
+}
+
+/// Sends `request` through `tape` if there is one, or over the network.
+pub(crate) async fn send(
+    tape: Option<&Tape>,
+    request: RequestBuilder,
+) -> Result<Response, GatewayError> {
+    match tape {
+        Some(tape) => tape.send(request).await,
+        None => Ok(request.send().await?),
+    }
+}
+
+/// A client that records its gateway traffic to a [`Cassette`], or replays
+/// one, so integration tests get the same responses on every run.
+///
+/// It dereferences to the wrapped [`InferenceGatewayClient`], so every API
+/// method is called on it directly.
+///
+/// ```no_run
+/// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+/// use inference_gateway_sdk::{
+///     Cassette, InferenceGatewayAPI, InferenceGatewayClient, Message, Provider, ReplayClient,
+/// };
+///
+/// let client = InferenceGatewayClient::new("http://localhost:8080/v1");
+/// let path = "tests/cassettes/weather.json";
+/// let replay = if std::env::var("RECORD").is_ok() {
+///     ReplayClient::record(client, 42)
+/// } else {
+///     ReplayClient::replay(client, Cassette::load(path)?)
+/// };
+///
+/// replay
+///     .generate_content(Provider::Openai, "gpt-4o", vec![Message::user("Weather?")])
+///     .await?;
+///
+/// if std::env::var("RECORD").is_ok() {
+///     replay.cassette().save(path)?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ReplayClient {
+    client: InferenceGatewayClient,
+    tape: Arc<Tape>,
+}
+
+impl ReplayClient {
+    /// Sends requests through `client` as usual, with chat completions
+    /// pinned to `seed`, and records every exchange.
+    ///
+    /// Responses are read in full before they are handed on, so streams
+    /// arrive all at once while recording.
+    pub fn record(client: InferenceGatewayClient, seed: i64) -> Self {
+        Self::with_tape(
+            client,
+            Tape {
+                recorded_at: now(),
+                seed,
+                state: Mutex::new((Mode::Record, Vec::new())),
+            },
+        )
+    }
+
+    /// Answers requests through `client` from `cassette`, without a
+    /// network. Configure `client` as it was while recording, since the
+    /// URL and body of each request must match a recorded one; a request
+    /// that matches none fails with [`GatewayError::ReplayMismatch`].
+    pub fn replay(client: InferenceGatewayClient, cassette: Cassette) -> Self {
+        let played = vec![false; cassette.exchanges.len()];
+        Self::with_tape(
+            client,
+            Tape {
+                recorded_at: cassette.recorded_at,
+                seed: cassette.seed,
+                state: Mutex::new((Mode::Replay(played), cassette.exchanges)),
+            },
+        )
+    }
+
+    fn with_tape(client: InferenceGatewayClient, tape: Tape) -> Self {
+        let tape = Arc::new(tape);
+        let mut client = client.with_seed(tape.seed);
+        client.tape = Some(tape.clone());
+        Self { client, tape }
+    }
+
+    /// The time recording started. Code under test that stamps times should
+    /// read this clock instead of the system's, so its output is the same
+    /// on every replay.
+    pub fn now(&self) -> DateTime<Utc> {
+        self.tape.recorded_at
+    }
+
+    /// The exchanges recorded so far, or the replayed cassette.
+    pub fn cassette(&self) -> Cassette {
+        Cassette {
+            recorded_at: self.tape.recorded_at,
+            seed: self.tape.seed,
+            exchanges: self.tape.state.lock().unwrap().1.clone(),
+        }
+    }
+}
+
+/// The system time, to the second.
+fn now() -> DateTime<Utc> {
+    let since_epoch = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    DateTime::from_timestamp(since_epoch.as_secs() as i64, 0).unwrap_or_default()
+}
+
+impl Deref for ReplayClient {
+    type Target = InferenceGatewayClient;
+
+    fn deref(&self) -> &Self::Target {
+        &self.client
+    }
+}
@@ -0,0 +1,213 @@
+//! Retrying requests that fail transiently.
+//!
+//! With a [`RetryConfig`] on the client, requests that fail to connect, time
+//! out, or come back as `429`, `502`, `503` or `504` are sent again after an
+//! exponentially growing, optionally jittered delay. Hooks on the config
+//! report each retry and each request that still fails after its last one.
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use chrono::DateTime;
+use reqwest::StatusCode;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+
+use crate::GatewayError;
+
+type RetryHook = Arc<dyn Fn(&RetryEvent<'_>) + Send + Sync>;
+type FailureHook = Arc<dyn Fn(&FailureEvent<'_>) + Send + Sync>;
+
+/// How the client retries requests that fail transiently.
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use inference_gateway_sdk::{InferenceGatewayClient, RetryConfig};
+///
+/// let client = InferenceGatewayClient::new("http://localhost:8080/v1").with_retry(
+///     RetryConfig::new(3)
+///         .with_backoff(Duration::from_millis(250), Duration::from_secs(5))
+///         .with_jitter(0.5)
+///         .on_retry(|event| eprintln!("retry {} after: {}", event.attempt, event.error))
+///         .on_failure(|event| eprintln!("gave up: {}", event.error)),
+/// );
+/// ```
+#[derive(Clone)]
+pub struct RetryConfig {
+    max_retries: u32,
+    backoff: Duration,
+    max_backoff: Duration,
+    max_retry_after: Duration,
+    jitter: f64,
+    retry_on: Vec<StatusCode>,
+    on_retry: Option<RetryHook>,
+    on_failure: Option<FailureHook>,
+}
+
+/// A retry about to happen, passed to [`RetryConfig::on_retry`].
+#[derive(Debug)]
+pub struct RetryEvent<'a> {
+    /// The retry's number, starting at 1 for the first retry.
+    pub attempt: u32,
+    /// How long the client waits before sending it.
+    pub delay: Duration,
+    /// Why the previous attempt failed.
+    pub error: &'a GatewayError,
+}
+
+/// A request that failed on its last allowed attempt, passed to
+/// [`RetryConfig::on_failure`].
+#[derive(Debug)]
+pub struct FailureEvent<'a> {
+    /// How many times the request was sent.
+    pub attempts: u32,
+    /// The error returned to the caller.
+    pub error: &'a GatewayError,
+}
+
+impl RetryConfig {
+    /// Retries up to `max_retries` times, waiting 200ms before the first
+    /// retry and doubling the wait up to 10s.
+    pub fn new(max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(10),
+            max_retry_after: Duration::from_secs(60),
+            jitter: 0.0,
+            retry_on: vec![
+                StatusCode::TOO_MANY_REQUESTS,
+                StatusCode::BAD_GATEWAY,
+                StatusCode::SERVICE_UNAVAILABLE,
+                StatusCode::GATEWAY_TIMEOUT,
+            ],
+            on_retry: None,
+            on_failure: None,
+        }
+    }
+
+    /// Sets the wait before the first retry, doubled for each later one up
+    /// to `max`.
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Gives up on a rate-limited request, returning its
+    /// [`GatewayError::RateLimited`], when its `Retry-After` asks for a
+    /// longer wait than `max`. 60s by default.
+    pub fn with_max_retry_after(mut self, max: Duration) -> Self {
+        self.max_retry_after = max;
+        self
+    }
+
+    /// Shortens each wait by a random share of up to `jitter`, from 0.0 to
+    /// 1.0, so clients that failed together don't retry together. No
+    /// jitter by default.
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Retries responses with these statuses instead of `429`, `502`, `503`
+    /// and `504`. Connection errors and timeouts are retried either way.
+    pub fn with_retry_on(mut self, statuses: impl IntoIterator<Item = StatusCode>) -> Self {
+        self.retry_on = statuses.into_iter().collect();
+        self
+    }
+
+    /// Calls `hook` before each retry.
+    pub fn on_retry(mut self, hook: impl Fn(&RetryEvent<'_>) + Send + Sync + 'static) -> Self {
+        self.on_retry = Some(Arc::new(hook));
+        self
+    }
+
+    /// Calls `hook` when a request fails transiently on its last allowed
+    /// attempt, before the error is returned.
+    pub fn on_failure(mut self, hook: impl Fn(&FailureEvent<'_>) + Send + Sync + 'static) -> Self {
+        self.on_failure = Some(Arc::new(hook));
+        self
+    }
+
+    /// The wait before retry number `attempt`, or `None` once retries are
+    /// used up.
+    fn delay(&self, attempt: u32) -> Option<Duration> {
+        if attempt > self.max_retries {
+            return None;
+        }
+        let factor = 2u32.saturating_pow(attempt - 1);
+        let delay = self.backoff.saturating_mul(factor).min(self.max_backoff);
+        Some(delay.mul_f64(1.0 - self.jitter * rand::random::<f64>()))
+    }
+
+    /// Whether a response with `status` is worth retrying.
+    pub(crate) fn retries_status(&self, status: StatusCode) -> bool {
+        self.retry_on.contains(&status)
+    }
+
+    /// Decides what follows failed attempt number `attempts`: the wait
+    /// before the next one, after reporting it, or `None` after reporting
+    /// the failure. A rate limit's `Retry-After` replaces the backoff, unless
+    /// it is longer than the config allows waiting.
+    pub(crate) fn next_delay(&self, attempts: u32, error: &GatewayError) -> Option<Duration> {
+        let delay = self.delay(attempts).and_then(|delay| match error {
+            GatewayError::RateLimited {
+                retry_after: Some(retry_after),
+                ..
+            } => Some(*retry_after).filter(|wait| *wait <= self.max_retry_after),
+            _ => Some(delay),
+        });
+        match delay {
+            Some(delay) => {
+                if let Some(hook) = &self.on_retry {
+                    hook(&RetryEvent {
+                        attempt: attempts,
+                        delay,
+                        error,
+                    });
+                }
+                Some(delay)
+            }
+            None => {
+                if let Some(hook) = &self.on_failure {
+                    hook(&FailureEvent { attempts, error });
+                }
+                None
+            }
+        }
+    }
+}
+
+impl fmt::Debug for RetryConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryConfig")
+            .field("max_retries", &self.max_retries)
+            .field("backoff", &self.backoff)
+            .field("max_backoff", &self.max_backoff)
+            .field("max_retry_after", &self.max_retry_after)
+            .field("jitter", &self.jitter)
+            .field("retry_on", &self.retry_on)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The wait a `Retry-After` header in `headers` asks for, given in seconds
+/// or as an HTTP date.
+pub(crate) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let at = DateTime::parse_from_rfc2822(value).ok()?;
+    let at = SystemTime::UNIX_EPOCH + Duration::from_secs(at.timestamp().try_into().ok()?);
+    Some(at.duration_since(SystemTime::now()).unwrap_or_default())
+}
+
+/// Whether a request that failed with `error` is worth retrying. A request
+/// that failed to connect never reached the gateway, so it is safe to send
+/// again even if it isn't `idempotent`.
+pub(crate) fn is_transient_error(error: &reqwest::Error, idempotent: bool) -> bool {
+    error.is_connect() || (idempotent && error.is_timeout())
+}
@@ -0,0 +1,131 @@
+//! Routing requests to models by what they look like.
+
+use crate::{Message, ModelRef, Provider, tokens};
+
+/// A target model and the requests it takes. A rule with no conditions
+/// takes every request, which makes it a catch-all when added last.
+#[derive(Clone, Debug)]
+pub struct RouteRule {
+    provider: Provider,
+    model: ModelRef,
+    min_prompt_tokens: Option<u64>,
+    max_prompt_tokens: Option<u64>,
+    tools: Option<bool>,
+    language: Option<String>,
+    tag: Option<String>,
+}
+
+impl RouteRule {
+    /// Sends matching requests to `model`, served by `provider`.
+    pub fn new(provider: Provider, model: impl Into<ModelRef>) -> Self {
+        Self {
+            provider,
+            model: model.into(),
+            min_prompt_tokens: None,
+            max_prompt_tokens: None,
+            tools: None,
+            language: None,
+            tag: None,
+        }
+    }
+
+    /// Matches prompts estimated at fewer than `tokens` tokens.
+    pub fn when_prompt_tokens_below(mut self, tokens: u64) -> Self {
+        self.max_prompt_tokens = Some(tokens);
+        self
+    }
+
+    /// Matches prompts estimated at `tokens` tokens or more.
+    pub fn when_prompt_tokens_at_least(mut self, tokens: u64) -> Self {
+        self.min_prompt_tokens = Some(tokens);
+        self
+    }
+
+    /// Matches requests that do, or don't, send tools.
+    pub fn when_tools(mut self, tools: bool) -> Self {
+        self.tools = Some(tools);
+        self
+    }
+
+    /// Matches requests whose language hint is `language`, compared
+    /// case-insensitively.
+    pub fn when_language(mut self, language: impl Into<String>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    /// Matches requests tagged `tag`.
+    pub fn when_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tag = Some(tag.into());
+        self
+    }
+
+    fn matches(&self, request: &RouteRequest<'_>, prompt_tokens: u64) -> bool {
+        self.min_prompt_tokens
+            .is_none_or(|min| prompt_tokens >= min)
+            && self.max_prompt_tokens.is_none_or(|max| prompt_tokens < max)
+            && self.tools.is_none_or(|tools| tools == request.has_tools)
+            && self.language.as_deref().is_none_or(|language| {
+                request
+                    .language
+                    .is_some_and(|hint| hint.eq_ignore_ascii_case(language))
+            })
+            && self
+                .tag
+                .as_deref()
+                .is_none_or(|tag| request.tags.iter().any(|t| t == tag))
+    }
+}
+
+/// What a router sees of a request.
+#[derive(Clone, Copy, Debug)]
+pub struct RouteRequest<'a> {
+    /// The messages to send. Prompt length is estimated from their text.
+    pub messages: &'a [Message],
+    /// Whether tool definitions are sent with the request.
+    pub has_tools: bool,
+    /// The language the caller expects the conversation to be in.
+    pub language: Option<&'a str>,
+    /// Free-form labels the caller attached to the request.
+    pub tags: &'a [String],
+}
+
+/// Rules mapping requests to models, tried in the order they were added.
+///
+/// ```
+/// use inference_gateway_sdk::{InferenceGatewayClient, Provider, RouteRule, Router};
+///
+/// let router = Router::new()
+///     .with_rule(RouteRule::new(Provider::Openai, "gpt-4o").when_tools(true))
+///     .with_rule(
+///         RouteRule::new(Provider::Groq, "llama-3.1-8b-instant").when_prompt_tokens_below(500),
+///     )
+///     .with_rule(RouteRule::new(Provider::Groq, "llama-3.3-70b-versatile"));
+/// let client = InferenceGatewayClient::new("http://localhost:8080/v1").with_router(router);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Router {
+    rules: Vec<RouteRule>,
+}
+
+impl Router {
+    /// A router with no rules.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `rule`, tried after those added before it.
+    pub fn with_rule(mut self, rule: RouteRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// The target of the first rule `request` matches.
+    pub fn route(&self, request: &RouteRequest<'_>) -> Option<(Provider, ModelRef)> {
+        let prompt_tokens = tokens::estimate(request.messages);
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(request, prompt_tokens))
+            .map(|rule| (rule.provider, rule.model.clone()))
+    }
+}
@@ -0,0 +1,194 @@
+//! Picking a model per request from a declarative policy.
+//!
+//! The gateway reports which models it serves and what they cost, but not
+//! what they can do or how fast they answer, so each [`Candidate`] declares
+//! its capabilities and latency class. [`ModelSelector::select`] then takes
+//! the first candidate, in the order they were added, that the gateway
+//! serves and that meets the [`SelectionPolicy`].
+
+use crate::{ListModelsResponse, Model, ModelRef, Provider};
+
+/// Something a request may need from a model.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Capability {
+    /// Calling tools.
+    Tools,
+    /// Image input.
+    Vision,
+    /// Audio input or output.
+    Audio,
+    /// Reasoning before answering.
+    Reasoning,
+    /// Replies constrained to a JSON schema.
+    StructuredOutput,
+}
+
+/// How quickly a model typically answers, fastest first.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LatencyClass {
+    /// Small or specially served models, for interactive use.
+    Fast,
+    /// The default.
+    #[default]
+    Standard,
+    /// Large or reasoning models that may take minutes.
+    Slow,
+}
+
+/// A model the selector may pick, with what it can do.
+#[derive(Clone, Debug)]
+pub struct Candidate {
+    provider: Provider,
+    model: ModelRef,
+    capabilities: Vec<Capability>,
+    latency: LatencyClass,
+}
+
+impl Candidate {
+    /// `model`, served by `provider`, with no capabilities and
+    /// [`LatencyClass::Standard`].
+    pub fn new(provider: Provider, model: impl Into<ModelRef>) -> Self {
+        Self {
+            provider,
+            model: model.into(),
+            capabilities: Vec::new(),
+            latency: LatencyClass::default(),
+        }
+    }
+
+    /// Declares that the model has `capability`.
+    pub fn with_capability(mut self, capability: Capability) -> Self {
+        if !self.capabilities.contains(&capability) {
+            self.capabilities.push(capability);
+        }
+        self
+    }
+
+    /// Sets how quickly the model typically answers.
+    pub fn with_latency(mut self, latency: LatencyClass) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// Whether the gateway's listing `model` is this candidate.
+    fn is(&self, model: &Model) -> bool {
+        model.served_by == self.provider
+            && (model.id == self.model.model()
+                || model.id == format!("{}/{}", self.provider, self.model.model()))
+    }
+}
+
+/// What a request needs from the model picked for it.
+///
+/// ```
+/// use inference_gateway_sdk::{Capability, LatencyClass, SelectionPolicy};
+///
+/// let policy = SelectionPolicy::new()
+///     .require(Capability::Tools)
+///     .with_max_cost_per_1k_tokens(0.002)
+///     .with_max_latency(LatencyClass::Fast);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct SelectionPolicy {
+    required: Vec<Capability>,
+    max_cost_per_1k_tokens: Option<f64>,
+    max_latency: Option<LatencyClass>,
+}
+
+impl SelectionPolicy {
+    /// A policy any served candidate meets.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requires the model to have `capability`.
+    pub fn require(mut self, capability: Capability) -> Self {
+        self.required.push(capability);
+        self
+    }
+
+    /// Requires neither input nor output tokens to cost more than `cost` per
+    /// thousand, in the currency of the gateway's pricing. Models the
+    /// gateway has no price for are skipped.
+    pub fn with_max_cost_per_1k_tokens(mut self, cost: f64) -> Self {
+        self.max_cost_per_1k_tokens = Some(cost);
+        self
+    }
+
+    /// Requires the model to be at most as slow as `latency`.
+    pub fn with_max_latency(mut self, latency: LatencyClass) -> Self {
+        self.max_latency = Some(latency);
+        self
+    }
+
+    fn allows(&self, candidate: &Candidate, listing: &Model) -> bool {
+        self.required
+            .iter()
+            .all(|capability| candidate.capabilities.contains(capability))
+            && self.max_latency.is_none_or(|max| candidate.latency <= max)
+            && self
+                .max_cost_per_1k_tokens
+                .is_none_or(|max| cost_per_1k_tokens(listing).is_some_and(|cost| cost <= max))
+    }
+}
+
+/// The higher of `model`'s input and output price per thousand tokens.
+fn cost_per_1k_tokens(model: &Model) -> Option<f64> {
+    let pricing = model.pricing.as_ref()?;
+    let input: f64 = pricing.input_per_token.parse().ok()?;
+    let output: f64 = pricing.output_per_token.parse().ok()?;
+    Some(input.max(output) * 1000.0)
+}
+
+/// Candidate models in order of preference.
+///
+/// ```
+/// use inference_gateway_sdk::{Candidate, Capability, LatencyClass, ModelSelector, Provider};
+///
+/// let selector = ModelSelector::new()
+///     .with_candidate(
+///         Candidate::new(Provider::Groq, "llama-3.1-8b-instant").with_latency(LatencyClass::Fast),
+///     )
+///     .with_candidate(
+///         Candidate::new(Provider::Openai, "gpt-4o")
+///             .with_capability(Capability::Tools)
+///             .with_capability(Capability::Vision),
+///     );
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ModelSelector {
+    candidates: Vec<Candidate>,
+}
+
+impl ModelSelector {
+    /// A selector with no candidates.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `candidate`, preferred less than those added before it.
+    pub fn with_candidate(mut self, candidate: Candidate) -> Self {
+        self.candidates.push(candidate);
+        self
+    }
+
+    /// The first candidate listed in `models` that meets `policy`. For
+    /// cost limits, `models` must have been listed with `"pricing"`
+    /// included.
+    pub fn select(
+        &self,
+        policy: &SelectionPolicy,
+        models: &ListModelsResponse,
+    ) -> Option<(Provider, ModelRef)> {
+        self.candidates
+            .iter()
+            .find(|candidate| {
+                models
+                    .data
+                    .iter()
+                    .find(|listing| candidate.is(listing))
+                    .is_some_and(|listing| policy.allows(candidate, listing))
+            })
+            .map(|candidate| (candidate.provider, candidate.model.clone()))
+    }
+}
@@ -0,0 +1,148 @@
+//! Mirroring a share of requests to a second model for comparison.
+//!
+//! A sampled chat completion is sent to the shadow model from a spawned
+//! task while the primary request proceeds as usual. The caller only ever
+//! waits for, and sees, the primary response; once both have finished, the
+//! pair is reported to the client's comparison sink.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio::sync::oneshot;
+
+use crate::{
+    ChatCompletionOptions, CompletionUsage, CreateChatCompletionResponse, FinishReason,
+    GatewayError, InferenceGatewayAPI, InferenceGatewayClient, Message, ModelRef, Provider,
+};
+
+/// Callback invoked with each primary and shadow result pair.
+pub(crate) type ShadowSink = Arc<dyn Fn(&ShadowComparison) + Send + Sync>;
+
+/// Which model to mirror requests to, and how many of them.
+///
+/// ```
+/// use inference_gateway_sdk::{InferenceGatewayClient, Provider, ShadowConfig};
+///
+/// let client = InferenceGatewayClient::new("http://localhost:8080/v1").with_shadow(
+///     ShadowConfig::new(Provider::Anthropic, "claude-sonnet-5", 0.1),
+///     |comparison| println!("{:?} vs {:?}", comparison.primary.latency, comparison.shadow.latency),
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct ShadowConfig {
+    provider: Provider,
+    model: ModelRef,
+    fraction: f64,
+}
+
+impl ShadowConfig {
+    /// Mirrors `fraction` of requests, from 0.0 to 1.0, to `model` served
+    /// by `provider`.
+    pub fn new(provider: Provider, model: impl Into<ModelRef>, fraction: f64) -> Self {
+        Self {
+            provider,
+            model: model.into(),
+            fraction: fraction.clamp(0.0, 1.0),
+        }
+    }
+
+    fn sampled(&self) -> bool {
+        self.fraction > 0.0 && rand::random::<f64>() < self.fraction
+    }
+}
+
+/// How one side of a shadowed request went.
+#[derive(Clone, Debug)]
+pub struct ShadowResult {
+    /// The provider the request was sent to.
+    pub provider: Provider,
+    /// The model the request was sent to.
+    pub model: String,
+    /// Time from sending the request until the response was parsed.
+    pub latency: Duration,
+    /// Token usage, if the gateway reported it.
+    pub usage: Option<CompletionUsage>,
+    /// Why the model stopped, if the request succeeded.
+    pub finish_reason: Option<FinishReason>,
+    /// The text of the first choice, empty on failure.
+    pub text: String,
+    /// The error the request failed with, rendered as text.
+    pub error: Option<String>,
+}
+
+impl ShadowResult {
+    pub(crate) fn new(
+        provider: Provider,
+        model: &str,
+        started: Instant,
+        result: &Result<CreateChatCompletionResponse, GatewayError>,
+    ) -> Self {
+        let mut shadow = Self {
+            provider,
+            model: model.to_string(),
+            latency: started.elapsed(),
+            usage: None,
+            finish_reason: None,
+            text: String::new(),
+            error: None,
+        };
+        match result {
+            Ok(response) => {
+                shadow.usage = response.usage.clone();
+                shadow.finish_reason = response.choices.first().map(|c| c.finish_reason.clone());
+                shadow.text = crate::first_choice_text(response);
+            }
+            Err(e) => shadow.error = Some(e.root().to_string()),
+        }
+        shadow
+    }
+}
+
+/// A request's primary result next to its shadow's.
+#[derive(Clone, Debug)]
+pub struct ShadowComparison {
+    /// The result returned to the caller.
+    pub primary: ShadowResult,
+    /// The result of the mirrored request.
+    pub shadow: ShadowResult,
+}
+
+/// Mirrors the request if `client` shadows and this one is sampled,
+/// returning where to send the primary result once it is in.
+///
+/// The mirrored request goes through a copy of `client` that doesn't
+/// shadow, fail over or cache and reports no metrics or events of its own,
+/// and without the caller's idempotency key, which would have the gateway
+/// answer it with the primary's response.
+pub(crate) fn start(
+    client: &InferenceGatewayClient,
+    messages: &[Message],
+    options: &ChatCompletionOptions,
+) -> Option<oneshot::Sender<ShadowResult>> {
+    let (config, sink) = client.shadow.clone()?;
+    if !config.sampled() {
+        return None;
+    }
+    let mut shadow_client = client.clone();
+    shadow_client.shadow = None;
+    shadow_client.failover = None;
+    shadow_client.cache = None;
+    shadow_client.metrics_callback = None;
+    shadow_client.event_sink = None;
+    let messages = messages.to_vec();
+    let options = options.clone().without_idempotency_key();
+    let (sender, receiver) = oneshot::channel();
+    tokio::spawn(async move {
+        let started = Instant::now();
+        let result = shadow_client
+            .generate_content_with_options(config.provider, config.model.clone(), messages, options)
+            .await;
+        let shadow = ShadowResult::new(config.provider, config.model.model(), started, &result);
+        // No primary result means the caller dropped the request, leaving
+        // nothing to compare against.
+        if let Ok(primary) = receiver.await {
+            sink(&ShadowComparison { primary, shadow });
+        }
+    });
+    Some(sender)
+}
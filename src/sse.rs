@@ -0,0 +1,128 @@
+//! Incremental Server-Sent Events decoder.
+//!
+//! Network chunks are appended to a single reusable [`BytesMut`] buffer and
+//! complete lines are split off it without copying. [`SSEvents`] owns its
+//! payloads, so an event's `data` is copied once, into the allocation that
+//! becomes its `String`, and `event` once if present. The cost of a long
+//! generation thus scales with the number of events rather than with the
+//! number of chunks and lines the transport happens to deliver.
+
+use bytes::BytesMut;
+
+use crate::SSEvents;
+
+/// Default capacity of the decoder's line buffer, in bytes.
+pub(crate) const DEFAULT_BUFFER_CAPACITY: usize = 8 * 1024;
+
+/// Decodes a `text/event-stream` byte stream into [`SSEvents`].
+///
+/// Feed raw chunks with [`SseDecoder::push`] and drain complete events with
+/// [`SseDecoder::next_event`]. Lines may be split across chunks arbitrarily.
+/// Multiple `data:` lines in one event are joined with `\n`, comment lines
+/// (`:`) are ignored, and the `id:` field is tracked in
+/// [`SseDecoder::last_event_id`]. `retry:` is a stream-level reconnection
+/// hint rather than part of an event, so it is not surfaced on [`SSEvents`].
+#[derive(Debug)]
+pub struct SseDecoder {
+    buffer: BytesMut,
+    /// Bytes of `buffer` already known not to contain a newline.
+    scanned: usize,
+    event: Option<String>,
+    data: Option<Vec<u8>>,
+    last_event_id: Option<String>,
+}
+
+impl Default for SseDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SseDecoder {
+    /// Creates a decoder with an 8 KiB line buffer.
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_BUFFER_CAPACITY)
+    }
+
+    /// Creates a decoder whose line buffer starts with `capacity` bytes.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            buffer: BytesMut::with_capacity(capacity),
+            scanned: 0,
+            event: None,
+            data: None,
+            last_event_id: None,
+        }
+    }
+
+    /// Appends a chunk of the raw byte stream.
+    pub fn push(&mut self, chunk: &[u8]) {
+        self.buffer.extend_from_slice(chunk);
+    }
+
+    /// Bytes held for the event in progress: its `data` so far and any
+    /// incomplete line.
+    pub fn pending_len(&self) -> usize {
+        self.buffer.len() + self.data.as_ref().map_or(0, Vec::len)
+    }
+
+    /// The value of the most recent `id:` field, if any.
+    pub fn last_event_id(&self) -> Option<&str> {
+        self.last_event_id.as_deref()
+    }
+
+    /// Returns the next complete event, or `None` once the buffered bytes
+    /// hold no further event terminator.
+    pub fn next_event(&mut self) -> Option<SSEvents> {
+        while let Some(pos) = self.buffer[self.scanned..].iter().position(|&b| b == b'\n') {
+            let mut line = self.buffer.split_to(self.scanned + pos + 1);
+            self.scanned = 0;
+            line.truncate(line.len() - 1);
+            if line.last() == Some(&b'\r') {
+                line.truncate(line.len() - 1);
+            }
+
+            if line.is_empty() {
+                let event = self.event.take();
+                if let Some(data) = self.data.take() {
+                    let data = String::from_utf8(data)
+                        .unwrap_or_else(|e| String::from_utf8_lossy(e.as_bytes()).into_owned());
+                    return Some(SSEvents {
+                        data,
+                        event,
+                        retry: None,
+                    });
+                }
+                continue;
+            }
+
+            self.process_line(&line);
+        }
+        self.scanned = self.buffer.len();
+        None
+    }
+
+    fn process_line(&mut self, line: &[u8]) {
+        if line[0] == b':' {
+            return;
+        }
+        let (field, value) = match line.iter().position(|&b| b == b':') {
+            Some(colon) => (&line[..colon], &line[colon + 1..]),
+            None => (line, &[][..]),
+        };
+        let value = value.strip_prefix(b" ").unwrap_or(value);
+
+        match field {
+            b"data" => match &mut self.data {
+                Some(data) => {
+                    data.push(b'\n');
+                    data.extend_from_slice(value);
+                }
+                None => self.data = Some(value.to_vec()),
+            },
+            b"event" => self.event = Some(String::from_utf8_lossy(value).into_owned()),
+            b"id" => self.last_event_id = Some(String::from_utf8_lossy(value).into_owned()),
+            _ => {}
+        }
+    }
+}
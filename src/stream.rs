@@ -0,0 +1,711 @@
+//! Typed adapters over the SDK's SSE streams.
+//!
+//! [`SseStreamExt::chat_chunks`] turns the raw [`SSEvents`] produced by
+//! [`InferenceGatewayAPI::generate_content_stream`](crate::InferenceGatewayAPI::generate_content_stream)
+//! into parsed [`CreateChatCompletionStreamResponse`] chunks, and
+//! [`ChatCompletionStreamExt`] layers consumer-side behavior on top of that
+//! typed stream.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use futures_util::{Stream, StreamExt, pin_mut};
+use tokio::sync::mpsc;
+use tokio::time::{Instant, sleep_until, timeout_at};
+
+use crate::tokens::CHARS_PER_TOKEN;
+use crate::{
+    ChatCompletionChoice, ChatCompletionChoiceLogprobs, ChatCompletionMessageAudio,
+    ChatCompletionMessageToolCall, ChatCompletionMessageToolCallFunction,
+    ChatCompletionStreamChoice, ChatCompletionStreamResponseDelta, ChatCompletionToolType,
+    CompletionUsage, CreateChatCompletionResponse, CreateChatCompletionStreamResponse,
+    FinishReason, GatewayError, Message, MessageContent, MessageRole, SSEvents,
+};
+
+/// Adapters for streams of raw [`SSEvents`].
+pub trait SseStreamExt: Stream<Item = Result<SSEvents, GatewayError>> + Sized {
+    /// Parses each event's `data` as a [`CreateChatCompletionStreamResponse`],
+    /// ending the stream at the `[DONE]` sentinel.
+    fn chat_chunks(
+        self,
+    ) -> impl Stream<Item = Result<CreateChatCompletionStreamResponse, GatewayError>> + Send
+    where
+        Self: Send,
+    {
+        async_stream::try_stream! {
+            let events = self;
+            pin_mut!(events);
+            while let Some(event) = events.next().await {
+                let event = event?;
+                if event.data == "[DONE]" {
+                    break;
+                }
+                let chunk: CreateChatCompletionStreamResponse = event.json()?;
+                yield chunk;
+            }
+        }
+    }
+}
+
+impl<S> SseStreamExt for S where S: Stream<Item = Result<SSEvents, GatewayError>> {}
+
+/// Adapters for typed chat-completion chunk streams.
+pub trait ChatCompletionStreamExt:
+    Stream<Item = Result<CreateChatCompletionStreamResponse, GatewayError>> + Sized
+{
+    /// Merges consecutive chunks into one, emitting at most once per
+    /// `max_latency` of the [`CoalescePolicy`] (a bare [`Duration`] works
+    /// too), or as soon as the merged text reaches its `max_chars`.
+    ///
+    /// Content, reasoning, and refusal deltas are concatenated per choice and
+    /// tool-call fragments are appended, so consumers see fewer, larger events
+    /// without losing anything. A chunk carrying a `finish_reason` or `usage`
+    /// flushes immediately, as does an error or the end of the stream.
+    fn coalesce(
+        self,
+        policy: impl Into<CoalescePolicy>,
+    ) -> impl Stream<Item = Result<CreateChatCompletionStreamResponse, GatewayError>> + Send
+    where
+        Self: Send + 'static,
+    {
+        let policy = policy.into();
+        async_stream::stream! {
+            let chunks = self;
+            pin_mut!(chunks);
+            let mut pending: Option<CreateChatCompletionStreamResponse> = None;
+            let mut pending_chars = 0;
+            let mut deadline = Instant::now();
+
+            loop {
+                let next = if pending.is_some() {
+                    match timeout_at(deadline, chunks.next()).await {
+                        Ok(next) => next,
+                        Err(_) => {
+                            yield Ok(pending.take().unwrap());
+                            continue;
+                        }
+                    }
+                } else {
+                    chunks.next().await
+                };
+
+                match next {
+                    Some(Ok(chunk)) => {
+                        let terminal = chunk.usage.is_some()
+                            || chunk.choices.iter().any(|c| c.finish_reason.is_some());
+                        let chars = delta_chars(&chunk);
+                        match &mut pending {
+                            Some(acc) => {
+                                merge_chunk(acc, chunk);
+                                pending_chars += chars;
+                            }
+                            None => {
+                                deadline = Instant::now() + policy.max_latency;
+                                pending = Some(chunk);
+                                pending_chars = chars;
+                            }
+                        }
+                        let full = policy.max_chars.is_some_and(|max| pending_chars >= max);
+                        if terminal || full {
+                            yield Ok(pending.take().unwrap());
+                        }
+                    }
+                    Some(Err(e)) => {
+                        if let Some(acc) = pending.take() {
+                            yield Ok(acc);
+                        }
+                        yield Err(e);
+                    }
+                    None => {
+                        if let Some(acc) = pending.take() {
+                            yield Ok(acc);
+                        }
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Smooths bursty output into a steady typing effect of about
+    /// `tokens_per_second`.
+    ///
+    /// A single choice's content delta is split into token-sized pieces
+    /// (estimated at four characters each), and every event waits until the
+    /// previous one's share of time has passed. The first piece keeps the
+    /// chunk's other deltas and the last its `finish_reason` and `usage`.
+    /// Output that arrives slower than the pace is not delayed further.
+    ///
+    /// # Panics
+    ///
+    /// If `tokens_per_second` is zero.
+    fn pace(
+        self,
+        tokens_per_second: u32,
+    ) -> impl Stream<Item = Result<CreateChatCompletionStreamResponse, GatewayError>> + Send
+    where
+        Self: Send + 'static,
+    {
+        assert!(tokens_per_second > 0, "tokens_per_second must be positive");
+        let per_token = Duration::from_secs(1) / tokens_per_second;
+        async_stream::stream! {
+            let chunks = self;
+            pin_mut!(chunks);
+            let mut next_at = Instant::now();
+            while let Some(next) = chunks.next().await {
+                let chunk = match next {
+                    Ok(chunk) => chunk,
+                    Err(e) => {
+                        yield Err(e);
+                        continue;
+                    }
+                };
+                for piece in split_content(chunk) {
+                    sleep_until(next_at).await;
+                    let tokens = delta_chars(&piece).div_ceil(CHARS_PER_TOKEN).max(1);
+                    next_at = Instant::now() + per_token * tokens as u32;
+                    yield Ok(piece);
+                }
+            }
+        }
+    }
+
+    /// Calls `f` with each chunk as it passes through, for logging or
+    /// metrics, leaving the stream itself unchanged.
+    ///
+    /// `f` runs when the consumer polls for the chunk, so a slow consumer
+    /// still holds back the upstream. Errors pass through without calling it.
+    fn tap<F>(
+        self,
+        mut f: F,
+    ) -> impl Stream<Item = Result<CreateChatCompletionStreamResponse, GatewayError>> + Send
+    where
+        Self: Send,
+        F: FnMut(&CreateChatCompletionStreamResponse) + Send,
+    {
+        self.inspect(move |next| {
+            if let Ok(chunk) = next {
+                f(chunk);
+            }
+        })
+    }
+
+    /// Consumes the stream and assembles the first choice into a complete
+    /// assistant [`Message`], together with its finish reason and usage.
+    ///
+    /// The result can also be built from a non-streamed
+    /// [`CreateChatCompletionResponse`] via `From`, so code downstream of a
+    /// call does not depend on whether it streamed.
+    fn fold_stream(self) -> impl Future<Output = Result<FoldedCompletion, GatewayError>> + Send
+    where
+        Self: Send,
+    {
+        async move {
+            match fold_until_error(self).await {
+                (folded, None) => Ok(folded),
+                (_, Some(e)) => Err(e),
+            }
+        }
+    }
+
+    /// Consumes the stream and merges it into the response the same request
+    /// would have got without streaming: every choice's content, reasoning
+    /// and tool calls joined up, with the trailing usage.
+    ///
+    /// A choice whose stream ended without a finish reason gets an empty
+    /// [`FinishReason::Other`]. Fails with the stream's first error, or with
+    /// [`GatewayError::Other`] if it ended before any chunk.
+    fn collect_completion(
+        self,
+    ) -> impl Future<Output = Result<CreateChatCompletionResponse, GatewayError>> + Send
+    where
+        Self: Send,
+    {
+        async move {
+            let chunks = self;
+            pin_mut!(chunks);
+            let mut acc: Option<CreateChatCompletionStreamResponse> = None;
+            while let Some(chunk) = chunks.next().await {
+                let chunk = chunk?;
+                match &mut acc {
+                    Some(acc) => merge_chunk(acc, chunk),
+                    None => acc = Some(chunk),
+                }
+            }
+            let acc =
+                acc.ok_or_else(|| GatewayError::Other("stream ended without a chunk".into()))?;
+            Ok(into_response(acc))
+        }
+    }
+
+    /// Splits an `n > 1` generation into one stream per choice.
+    ///
+    /// The returned vector has `n` entries and entry `i` yields only the
+    /// deltas (and finish reason) of the choice with `index == i`; choices
+    /// with an index outside `0..n` are discarded. The upstream is driven by a
+    /// spawned task, so the branches can be consumed independently and at
+    /// different rates. It stops once every branch has been dropped.
+    ///
+    /// Called outside a Tokio runtime, nothing can drive the upstream, so
+    /// each branch yields one [`GatewayError::Other`] and ends.
+    ///
+    /// An upstream error is delivered as-is to the lowest-indexed branch that
+    /// is still open and as [`GatewayError::Other`] to the rest, after which
+    /// every branch ends.
+    fn split_choices(self, n: usize) -> Vec<ChoiceStream>
+    where
+        Self: Send + 'static,
+    {
+        let (senders, streams): (Vec<_>, Vec<_>) = (0..n)
+            .map(|_| {
+                let (sender, receiver) = mpsc::unbounded_channel();
+                (sender, ChoiceStream { receiver })
+            })
+            .unzip();
+
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            for sender in &senders {
+                let _ = sender.send(Err(GatewayError::Other(
+                    "split_choices must be called within a Tokio runtime".into(),
+                )));
+            }
+            return streams;
+        };
+        runtime.spawn(async move {
+            let chunks = self;
+            pin_mut!(chunks);
+            while let Some(next) = chunks.next().await {
+                match next {
+                    Ok(chunk) => {
+                        for choice in chunk.choices {
+                            let sender = usize::try_from(choice.index)
+                                .ok()
+                                .and_then(|index| senders.get(index));
+                            if let Some(sender) = sender {
+                                let _ = sender.send(Ok(choice));
+                            }
+                        }
+                        if senders.iter().all(|sender| sender.is_closed()) {
+                            break;
+                        }
+                    }
+                    Err(e) => {
+                        let message = e.to_string();
+                        let mut open = senders.iter().filter(|sender| !sender.is_closed());
+                        if let Some(first) = open.next() {
+                            let _ = first.send(Err(e));
+                        }
+                        for sender in open {
+                            let _ = sender.send(Err(GatewayError::Other(
+                                format!("stream failed on another choice: {message}").into(),
+                            )));
+                        }
+                        break;
+                    }
+                }
+            }
+        });
+
+        streams
+    }
+}
+
+impl<S> ChatCompletionStreamExt for S where
+    S: Stream<Item = Result<CreateChatCompletionStreamResponse, GatewayError>>
+{
+}
+
+/// When [`ChatCompletionStreamExt::coalesce`] emits a merged chunk.
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use inference_gateway_sdk::CoalescePolicy;
+///
+/// // At most one event per 50ms, or sooner once 200 characters are waiting.
+/// let policy = CoalescePolicy::new(Duration::from_millis(50)).with_max_chars(200);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CoalescePolicy {
+    max_latency: Duration,
+    max_chars: Option<usize>,
+}
+
+impl CoalescePolicy {
+    /// Holds deltas for up to `max_latency` after the first one arrives.
+    pub fn new(max_latency: Duration) -> Self {
+        Self {
+            max_latency,
+            max_chars: None,
+        }
+    }
+
+    /// Also emits as soon as the held content, reasoning and refusal text
+    /// reaches `max_chars` characters.
+    pub fn with_max_chars(mut self, max_chars: usize) -> Self {
+        self.max_chars = Some(max_chars);
+        self
+    }
+}
+
+impl From<Duration> for CoalescePolicy {
+    fn from(max_latency: Duration) -> Self {
+        Self::new(max_latency)
+    }
+}
+
+/// Splits the content of a single-choice `chunk` into token-sized pieces
+/// for [`ChatCompletionStreamExt::pace`].
+fn split_content(
+    mut chunk: CreateChatCompletionStreamResponse,
+) -> Vec<CreateChatCompletionStreamResponse> {
+    let content = match chunk.choices.as_mut_slice() {
+        [choice] => choice.delta.content.take().unwrap_or_default(),
+        _ => return vec![chunk],
+    };
+    let chars: Vec<char> = content.chars().collect();
+    if chars.len() <= CHARS_PER_TOKEN {
+        if !content.is_empty() {
+            chunk.choices[0].delta.content = Some(content);
+        }
+        return vec![chunk];
+    }
+
+    let finish_reason = chunk.choices[0].finish_reason.take();
+    let usage = chunk.usage.take();
+    let mut rest = chunk.clone();
+    rest.choices[0].delta = ChatCompletionStreamResponseDelta::default();
+    rest.choices[0].logprobs = None;
+
+    let mut pieces: Vec<_> = chars
+        .chunks(CHARS_PER_TOKEN)
+        .enumerate()
+        .map(|(i, piece)| {
+            let mut next = if i == 0 { chunk.clone() } else { rest.clone() };
+            next.choices[0].delta.content = Some(piece.iter().collect());
+            next
+        })
+        .collect();
+    if let Some(last) = pieces.last_mut() {
+        last.choices[0].finish_reason = finish_reason;
+        last.usage = usage;
+    }
+    pieces
+}
+
+/// Characters of text carried by `chunk`'s deltas.
+fn delta_chars(chunk: &CreateChatCompletionStreamResponse) -> usize {
+    chunk
+        .choices
+        .iter()
+        .flat_map(|choice| {
+            let delta = &choice.delta;
+            [
+                &delta.content,
+                &delta.reasoning,
+                &delta.reasoning_content,
+                &delta.refusal,
+            ]
+        })
+        .flatten()
+        .map(|text| text.chars().count())
+        .sum()
+}
+
+/// A chat completion reduced to its first choice, produced by
+/// [`ChatCompletionStreamExt::fold_stream`] or converted from a
+/// [`CreateChatCompletionResponse`].
+#[derive(Clone, Debug)]
+pub struct FoldedCompletion {
+    /// The assistant message, including reasoning and tool calls.
+    pub message: Message,
+    /// Why the model stopped, if the stream reported it.
+    pub finish_reason: Option<FinishReason>,
+    /// Token usage, if the gateway reported it.
+    pub usage: Option<CompletionUsage>,
+}
+
+impl From<CreateChatCompletionResponse> for FoldedCompletion {
+    fn from(response: CreateChatCompletionResponse) -> Self {
+        let choice = response.choices.into_iter().min_by_key(|c| c.index);
+        let (message, finish_reason) = match choice {
+            Some(choice) => (choice.message, Some(choice.finish_reason)),
+            None => (
+                delta_into_message(ChatCompletionStreamResponseDelta::default()),
+                None,
+            ),
+        };
+        Self {
+            message,
+            finish_reason,
+            usage: response.usage,
+        }
+    }
+}
+
+/// Instruction sent after the partial answer when a dropped stream is
+/// resumed.
+pub(crate) const RESUME_PROMPT: &str = "Your previous reply was cut off. Continue it exactly \
+     where it stopped, without repeating any of it or adding any preamble.";
+
+/// Adds the token counts of `next` to `acc`, for calls made of several
+/// requests.
+pub(crate) fn add_usage(acc: &mut Option<CompletionUsage>, next: Option<CompletionUsage>) {
+    match (acc.as_mut(), next) {
+        (Some(acc), Some(next)) => {
+            acc.prompt_tokens += next.prompt_tokens;
+            acc.completion_tokens += next.completion_tokens;
+            acc.total_tokens += next.total_tokens;
+        }
+        (None, next) => *acc = next,
+        (Some(_), None) => {}
+    }
+}
+
+/// Folds `chunks` like [`ChatCompletionStreamExt::fold_stream`], but stops
+/// at the first error and returns it together with what arrived before it.
+pub(crate) async fn fold_until_error<S>(chunks: S) -> (FoldedCompletion, Option<GatewayError>)
+where
+    S: Stream<Item = Result<CreateChatCompletionStreamResponse, GatewayError>>,
+{
+    pin_mut!(chunks);
+    let mut acc: Option<CreateChatCompletionStreamResponse> = None;
+    let mut error = None;
+    while let Some(chunk) = chunks.next().await {
+        match chunk {
+            Ok(chunk) => match &mut acc {
+                Some(acc) => merge_chunk(acc, chunk),
+                None => acc = Some(chunk),
+            },
+            Err(e) => {
+                error = Some(e);
+                break;
+            }
+        }
+    }
+
+    let (choice, usage) = match acc {
+        Some(acc) => {
+            let choice = acc.choices.into_iter().min_by_key(|c| c.index);
+            (choice, acc.usage)
+        }
+        None => (None, None),
+    };
+    let (delta, finish_reason) = match choice {
+        Some(choice) => (choice.delta, choice.finish_reason),
+        None => (ChatCompletionStreamResponseDelta::default(), None),
+    };
+    let folded = FoldedCompletion {
+        message: delta_into_message(delta),
+        finish_reason,
+        usage,
+    };
+    (folded, error)
+}
+
+/// The non-streamed response `acc`, all of a stream's chunks merged, stands
+/// for.
+fn into_response(acc: CreateChatCompletionStreamResponse) -> CreateChatCompletionResponse {
+    let mut choices: Vec<ChatCompletionChoice> = acc
+        .choices
+        .into_iter()
+        .map(|choice| ChatCompletionChoice {
+            finish_reason: choice
+                .finish_reason
+                .unwrap_or_else(|| FinishReason::Other(String::new())),
+            index: choice.index,
+            logprobs: choice
+                .logprobs
+                .map(|logprobs| ChatCompletionChoiceLogprobs {
+                    content: logprobs.content,
+                    refusal: logprobs.refusal,
+                }),
+            message: delta_into_message(choice.delta),
+        })
+        .collect();
+    choices.sort_by_key(|choice| choice.index);
+    CreateChatCompletionResponse {
+        choices,
+        created: acc.created,
+        id: acc.id,
+        model: acc.model,
+        object: "chat.completion".to_string(),
+        service_tier: None,
+        system_fingerprint: acc.system_fingerprint,
+        usage: acc.usage,
+    }
+}
+
+fn delta_into_message(delta: ChatCompletionStreamResponseDelta) -> Message {
+    let tool_calls = delta
+        .tool_calls
+        .into_iter()
+        .map(|call| {
+            let function = call.function.unwrap_or_default();
+            ChatCompletionMessageToolCall {
+                extra_content: call.extra_content,
+                function: ChatCompletionMessageToolCallFunction {
+                    arguments: function.arguments.unwrap_or_default(),
+                    name: function.name.unwrap_or_default(),
+                },
+                id: call.id.unwrap_or_default(),
+                type_: ChatCompletionToolType::Function,
+            }
+        })
+        .collect();
+    let audio = delta.audio.and_then(|audio| {
+        Some(ChatCompletionMessageAudio {
+            data: audio.data.unwrap_or_default(),
+            expires_at: audio.expires_at.unwrap_or_default(),
+            id: audio.id?,
+            transcript: audio.transcript.unwrap_or_default(),
+        })
+    });
+    Message {
+        annotations: delta.annotations,
+        audio,
+        content: MessageContent::Text(delta.content.unwrap_or_default()),
+        name: None,
+        reasoning: delta.reasoning,
+        reasoning_content: delta.reasoning_content,
+        role: delta.role.unwrap_or(MessageRole::Assistant),
+        tool_call_id: None,
+        tool_calls,
+    }
+}
+
+/// One choice's share of a stream split by
+/// [`ChatCompletionStreamExt::split_choices`].
+#[derive(Debug)]
+pub struct ChoiceStream {
+    receiver: mpsc::UnboundedReceiver<Result<ChatCompletionStreamChoice, GatewayError>>,
+}
+
+impl Stream for ChoiceStream {
+    type Item = Result<ChatCompletionStreamChoice, GatewayError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+/// Folds `next` into `acc` as if both had been received as one chunk.
+pub(crate) fn merge_chunk(
+    acc: &mut CreateChatCompletionStreamResponse,
+    next: CreateChatCompletionStreamResponse,
+) {
+    acc.created = next.created;
+    if next.system_fingerprint.is_some() {
+        acc.system_fingerprint = next.system_fingerprint;
+    }
+    if next.usage.is_some() {
+        acc.usage = next.usage;
+    }
+    for choice in next.choices {
+        match acc.choices.iter_mut().find(|c| c.index == choice.index) {
+            Some(existing) => merge_choice(existing, choice),
+            None => acc.choices.push(choice),
+        }
+    }
+}
+
+fn merge_choice(acc: &mut ChatCompletionStreamChoice, next: ChatCompletionStreamChoice) {
+    merge_delta(&mut acc.delta, next.delta);
+    if next.finish_reason.is_some() {
+        acc.finish_reason = next.finish_reason;
+    }
+    match (&mut acc.logprobs, next.logprobs) {
+        (Some(acc), Some(next)) => {
+            acc.content.extend(next.content);
+            acc.refusal.extend(next.refusal);
+        }
+        (acc @ None, next) => *acc = next,
+        (Some(_), None) => {}
+    }
+}
+
+fn merge_delta(
+    acc: &mut ChatCompletionStreamResponseDelta,
+    next: ChatCompletionStreamResponseDelta,
+) {
+    append(&mut acc.content, next.content);
+    append(&mut acc.reasoning, next.reasoning);
+    append(&mut acc.reasoning_content, next.reasoning_content);
+    append(&mut acc.refusal, next.refusal);
+    if acc.role.is_none() {
+        acc.role = next.role;
+    }
+    acc.annotations.extend(next.annotations);
+    if let Some(next) = next.audio {
+        let audio = acc.audio.get_or_insert_with(Default::default);
+        if next.id.is_some() {
+            audio.id = next.id;
+        }
+        if next.expires_at.is_some() {
+            audio.expires_at = next.expires_at;
+        }
+        append(&mut audio.transcript, next.transcript);
+        append_base64(&mut audio.data, next.data);
+    }
+    for call in next.tool_calls {
+        match acc.tool_calls.iter_mut().find(|c| c.index == call.index) {
+            Some(last) => {
+                if call.id.is_some() {
+                    last.id = call.id;
+                }
+                if call.type_.is_some() {
+                    last.type_ = call.type_;
+                }
+                if call.extra_content.is_some() {
+                    last.extra_content = call.extra_content;
+                }
+                match (&mut last.function, call.function) {
+                    (Some(function), Some(next)) => {
+                        if next.name.is_some() {
+                            function.name = next.name;
+                        }
+                        append(&mut function.arguments, next.arguments);
+                    }
+                    (function @ None, next) => *function = next,
+                    (Some(_), None) => {}
+                }
+            }
+            None => acc.tool_calls.push(call),
+        }
+    }
+}
+
+/// Appends base64 `next` to `acc`. Fragments are encoded separately, so
+/// once `acc` ends in padding the two are re-encoded as one.
+fn append_base64(acc: &mut Option<String>, next: Option<String>) {
+    match (acc.as_mut(), next) {
+        (Some(data), Some(next)) if data.ends_with('=') => {
+            let joined = STANDARD
+                .decode(&*data)
+                .and_then(|mut bytes| {
+                    bytes.extend(STANDARD.decode(&next)?);
+                    Ok(bytes)
+                })
+                .map(|bytes| STANDARD.encode(bytes));
+            match joined {
+                Ok(joined) => *data = joined,
+                // Keep what arrived; `decode` will report the bad fragment.
+                Err(_) => data.push_str(&next),
+            }
+        }
+        (_, next) => append(acc, next),
+    }
+}
+
+fn append(acc: &mut Option<String>, next: Option<String>) {
+    if let Some(next) = next {
+        match acc {
+            Some(acc) => acc.push_str(&next),
+            None => *acc = Some(next),
+        }
+    }
+}
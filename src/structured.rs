@@ -0,0 +1,74 @@
+//! Structured outputs: asking for JSON that matches a Rust type's schema,
+//! and parsing the answer into it.
+
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+
+use crate::{
+    CreateChatCompletionRequestResponseFormat, CreateChatCompletionResponse, GatewayError,
+    ResponseFormatJsonSchema, ResponseFormatJsonSchemaJsonSchema, ResponseFormatJsonSchemaSchema,
+    ResponseFormatJsonSchemaType,
+};
+
+/// A `json_schema` response format asking for a `T`, named after the type.
+///
+/// ```
+/// use inference_gateway_sdk::{ChatCompletionOptions, json_schema_format};
+///
+/// #[derive(schemars::JsonSchema, serde::Deserialize)]
+/// struct Weather {
+///     city: String,
+///     celsius: f64,
+/// }
+///
+/// let options = ChatCompletionOptions::new().with_response_format(json_schema_format::<Weather>());
+/// ```
+pub fn json_schema_format<T: JsonSchema>() -> CreateChatCompletionRequestResponseFormat {
+    let schema = schemars::schema_for!(T);
+    let schema = match schema.to_value() {
+        serde_json::Value::Object(schema) => schema,
+        // `true` and `false` schemas accept anything and nothing.
+        _ => serde_json::Map::new(),
+    };
+    ResponseFormatJsonSchema {
+        type_: ResponseFormatJsonSchemaType::JsonSchema,
+        json_schema: ResponseFormatJsonSchemaJsonSchema {
+            description: None,
+            name: schema_name(&T::schema_name()),
+            schema: Some(ResponseFormatJsonSchemaSchema(schema)),
+            strict: false,
+        },
+    }
+    .into()
+}
+
+/// `name` with the characters providers reject replaced by `_`, cut to the
+/// 64 characters they allow.
+fn schema_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .take(64)
+        .collect()
+}
+
+/// Parses the first choice's text as a `T`.
+pub(crate) fn parse<T: DeserializeOwned>(
+    response: &CreateChatCompletionResponse,
+) -> Result<T, GatewayError> {
+    let content = response
+        .choices
+        .first()
+        .and_then(|choice| choice.message.content.as_text())
+        .unwrap_or_default();
+    serde_json::from_str(content).map_err(|source| GatewayError::StructuredOutput {
+        type_name: std::any::type_name::<T>(),
+        content: content.to_string(),
+        source,
+    })
+}
@@ -1,25 +1,43 @@
+#[cfg(feature = "trace")]
+use crate::TraceContext;
 use crate::{
-    ChatCompletionNamedToolChoice, ChatCompletionNamedToolChoiceFunction, ChatCompletionTool,
-    ChatCompletionToolChoiceOption, ChatCompletionToolChoiceOptionString, ChatCompletionToolType,
-    ContextWindowSource, CreateChatCompletionRequest, CreateChatCompletionRequestReasoningEffort,
+    Artifact, AudioFile, Candidate, Capability, Cassette, ChatCompletionMessageToolCall,
+    ChatCompletionMessageToolCallFunction, ChatCompletionNamedToolChoice,
+    ChatCompletionNamedToolChoiceFunction, ChatCompletionOptions, ChatCompletionStreamChoice,
+    ChatCompletionStreamExt, ChatCompletionTool, ChatCompletionToolChoiceOption,
+    ChatCompletionToolChoiceOptionString, ChatCompletionToolType, CoalescePolicy, CompletionUsage,
+    CompletionUsagePromptTokensDetails, ContentPart, ContextWindowSource, Conversation,
+    CostTracker, CreateChatCompletionRequest, CreateChatCompletionRequestReasoningEffort,
     CreateChatCompletionRequestResponseFormat, CreateChatCompletionRequestStop,
     CreateChatCompletionResponse, CreateChatCompletionStreamResponse, CreateMessagesRequest,
-    FinishReason, FunctionObject, FunctionParameters, GatewayError, InferenceGatewayAPI,
-    InferenceGatewayClient, Message, MessageContent, MessageRole, MessagesMessage,
-    MessagesMessageContent, MessagesMessageRole, MessagesResponseContentBlock,
-    MessagesResponseStopReason, MessagesStreamEvent, MessagesStreamEventType, PricingSource,
-    Provider, ResponseFormatJsonObject, ResponseFormatJsonObjectType, ResponseFormatJsonSchema,
+    EmbeddedText, EmbeddingPipeline, EmbeddingRequest, EmbeddingUsage, EncodingFormat, Experiment,
+    FailoverConfig, FileContentPart, FileStore, FinishReason, FunctionObject, FunctionParameters,
+    GatewayError, GatewayErrorKind, GatewayFeature, HealthStatus, ImageContentPart, ImageUrlDetail,
+    InMemoryCache, InMemoryStore, InferenceGatewayAPI, InferenceGatewayClient,
+    InputAudioContentPart, InstructionRole, Job, JobOutcome, JobState, LatencyClass, LoadBalancing,
+    MemoryStore, Message, MessageContent, MessageRole, MessagesMessage, MessagesMessageContent,
+    MessagesMessageRole, MessagesResponseContentBlock, MessagesResponseStopReason,
+    MessagesStreamEvent, MessagesStreamEventType, Method, Middleware, ModelPrice, ModelRef,
+    ModelRefError, ModelSelector, ModerationInput, OverflowPolicy, PollPolicy, PricingSource,
+    Priority, Provider, ProviderLimits, ReplayClient, RequestMetrics, RequestOptions,
+    ResponseFormatJsonObject, ResponseFormatJsonObjectType, ResponseFormatJsonSchema,
     ResponseFormatJsonSchemaJsonSchema, ResponseFormatJsonSchemaType, ResponseFormatText,
-    ResponseFormatTextType,
+    ResponseFormatTextType, RetryConfig, RouteRequest, RouteRule, Router, SSEvents,
+    SchedulingHints, SelectionPolicy, ShadowConfig, SpeechFormat, SpeechOptions, SseDecoder,
+    SseStreamExt, StatusCode, SummaryPolicy, TimestampGranularity, ToolRegistry,
+    TranscriptionEvent, TranscriptionOptions, TranscriptionWord, Voice, parse_partial_json,
 };
-use futures_util::{StreamExt, pin_mut};
+use futures_util::{FutureExt, StreamExt, TryStreamExt, pin_mut};
 use mockito::{Matcher, Server};
 use serde_json::json;
 
 fn user_message(text: &str) -> Message {
     Message {
         role: MessageRole::User,
-        content: MessageContent::String(text.to_string()),
+        annotations: Vec::new(),
+        audio: None,
+        content: MessageContent::Text(text.to_string()),
+        name: None,
         reasoning: None,
         reasoning_content: None,
         tool_call_id: None,
@@ -30,7 +48,10 @@ fn user_message(text: &str) -> Message {
 fn system_message(text: &str) -> Message {
     Message {
         role: MessageRole::System,
-        content: MessageContent::String(text.to_string()),
+        annotations: Vec::new(),
+        audio: None,
+        content: MessageContent::Text(text.to_string()),
+        name: None,
         reasoning: None,
         reasoning_content: None,
         tool_call_id: None,
@@ -111,12 +132,39 @@ fn test_provider_llamacpp_present() {
     assert_eq!(Provider::try_from("llamacpp").unwrap(), Provider::Llamacpp);
 }
 
+#[test]
+fn test_message_name() {
+    let mut conversation = Conversation::new();
+    conversation
+        .user_named("alice", "I'd like tea.")
+        .user_named("bob", "Coffee for me.");
+    let messages = serde_json::to_value(conversation.messages()).unwrap();
+    assert_eq!(
+        messages,
+        json!([
+            {"role": "user", "name": "alice", "content": "I'd like tea."},
+            {"role": "user", "name": "bob", "content": "Coffee for me."}
+        ])
+    );
+
+    let message: Message =
+        serde_json::from_value(json!({"role": "user", "content": "Hi"})).unwrap();
+    assert_eq!(message.name, None);
+    assert_eq!(
+        Message::tool("call_1", "42")
+            .with_name("calculator")
+            .name
+            .as_deref(),
+        Some("calculator")
+    );
+}
+
 #[test]
 fn test_message_serialization_with_tool_call_id() {
     let mut message_with_tool = system_message("ignored");
     message_with_tool.role = MessageRole::Tool;
-    message_with_tool.content = MessageContent::String("The weather is sunny".to_string());
-    message_with_tool.tool_call_id = Some("call_123".to_string());
+    message_with_tool.content = MessageContent::Text("The weather is sunny".to_string());
+    message_with_tool.tool_call_id = Some("call_123".into());
 
     let serialized = serde_json::to_string(&message_with_tool).unwrap();
     let actual: serde_json::Value = serde_json::from_str(&serialized).unwrap();
@@ -139,10 +187,19 @@ fn test_message_serialization_with_tool_call_id() {
     .unwrap();
     assert_eq!(deserialized.role, MessageRole::Tool);
     match deserialized.content {
-        MessageContent::String(ref s) => assert_eq!(s, "The weather is sunny"),
+        MessageContent::Text(ref s) => assert_eq!(s, "The weather is sunny"),
         _ => panic!("expected string content"),
     }
-    assert_eq!(deserialized.tool_call_id, Some("call_123".to_string()));
+    assert_eq!(deserialized.tool_call_id, Some("call_123".into()));
+
+    let helper = Message::tool("call_123", "The weather is sunny");
+    assert_eq!(
+        serde_json::to_value(&helper).unwrap(),
+        json!({"role": "tool", "content": "The weather is sunny", "tool_call_id": "call_123"})
+    );
+    let id: crate::ToolCallId = "call_123".parse().unwrap();
+    assert_eq!(id, "call_123");
+    assert_eq!(id.to_string(), "call_123");
 }
 
 #[test]
@@ -425,6 +482,7 @@ async fn test_authentication_header() -> Result<(), GatewayError> {
 
     let base_url = format!("{}/v1", server.url());
     let client = InferenceGatewayClient::new(&base_url).with_token("test-token");
+    assert!(!format!("{client:?}").contains("test-token"));
     client.list_models().await?;
     mock_with_auth.assert();
 
@@ -468,6 +526,79 @@ async fn test_unauthorized_error() -> Result<(), GatewayError> {
     Ok(())
 }
 
+#[test]
+fn test_array_elements_split_across_chunks() {
+    use crate::list_stream::ArrayElements;
+
+    let body = r#"{"tags": ["a", "]"], "object": "data", "data" : [
+        {"id": "x\"}", "nested": {"list": [1, 2]}},
+        {"id": "y"} ], "after": [3]}"#;
+    let mut elements = ArrayElements::new("data");
+    let mut found = Vec::new();
+    for byte in body.as_bytes() {
+        elements.push(std::slice::from_ref(byte));
+        while let Some(element) = elements.next_element() {
+            found.push(String::from_utf8(element).unwrap());
+        }
+    }
+    assert!(elements.is_done());
+    assert_eq!(
+        found,
+        [
+            r#"{"id": "x\"}", "nested": {"list": [1, 2]}}"#,
+            r#"{"id": "y"}"#
+        ]
+    );
+
+    let mut scalars = ArrayElements::new("data");
+    scalars.push(br#"{"data": [1, "two" , 3]}"#);
+    let found: Vec<_> = std::iter::from_fn(|| scalars.next_element()).collect();
+    assert_eq!(found, [&b"1"[..], b"\"two\"", b"3"]);
+    assert!(scalars.is_done());
+}
+
+#[tokio::test]
+async fn test_list_models_stream() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+    let model = |id: &str| json!({"id": id, "object": "model", "created": 1, "owned_by": "openai", "served_by": "openai"});
+    let complete = server
+        .mock("GET", "/v1/models")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            json!({"object": "list", "data": [model("openai/a"), model("openai/b")]}).to_string(),
+        )
+        .create();
+
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()));
+    let ids: Vec<String> = client
+        .list_models_stream()
+        .map_ok(|model| model.id)
+        .try_collect()
+        .await?;
+    assert_eq!(ids, ["openai/a", "openai/b"]);
+    complete.assert();
+    complete.remove();
+
+    let truncated = server
+        .mock("GET", "/v1/models")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(format!(
+            r#"{{"object": "list", "data": [{}, "#,
+            model("openai/a")
+        ))
+        .create();
+    let results: Vec<_> = client.list_models_stream().collect().await;
+    assert!(matches!(results.as_slice(), [Ok(_), Err(_)]));
+    assert!(matches!(
+        results[1].as_ref().unwrap_err(),
+        GatewayError::DeserializationError { .. }
+    ));
+    truncated.assert();
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_list_models() -> Result<(), GatewayError> {
     let mut server = Server::new_async().await;
@@ -505,320 +636,5518 @@ async fn test_list_models() -> Result<(), GatewayError> {
 }
 
 #[tokio::test]
-async fn test_list_models_by_provider() -> Result<(), GatewayError> {
+async fn test_list_models_revalidates_with_etag() -> Result<(), GatewayError> {
     let mut server = Server::new_async().await;
-
-    let raw_json_response = r#"{
-        "provider":"ollama",
-        "object":"list",
-        "data": [
-            {
-                "id": "llama2",
-                "object": "model",
-                "created": 1630000001,
-                "owned_by": "ollama",
-                "served_by": "ollama"
-            }
-        ]
-    }"#;
-
-    let mock = server
-        .mock("GET", "/v1/models?provider=ollama")
+    let fresh = server
+        .mock("GET", "/v1/models")
+        .match_header("if-none-match", Matcher::Missing)
         .with_status(200)
         .with_header("content-type", "application/json")
-        .with_body(raw_json_response)
+        .with_header("etag", "\"v1\"")
+        .with_body(r#"{"object":"list","data":[{"id":"openai/gpt-4o","object":"model","created":1,"owned_by":"openai","served_by":"openai"}]}"#)
+        .create();
+    let unchanged = server
+        .mock("GET", "/v1/models")
+        .match_header("if-none-match", "\"v1\"")
+        .with_status(304)
+        .expect(2)
         .create();
 
     let base_url = format!("{}/v1", server.url());
     let client = InferenceGatewayClient::new(&base_url);
-    let response = client.list_models_by_provider(Provider::Ollama).await?;
-
-    assert_eq!(response.provider, Some(Provider::Ollama));
-    assert_eq!(response.data[0].id, "llama2");
-    mock.assert();
+    let first = client.list_models().await?;
+    let second = client.list_models().await?;
+    let from_clone = client.clone().list_models().await?;
+
+    assert_eq!(second.data.len(), 1);
+    assert_eq!(second.data[0].id, first.data[0].id);
+    assert_eq!(from_clone.data[0].id, "openai/gpt-4o");
+    fresh.assert();
+    unchanged.assert();
     Ok(())
 }
 
 #[tokio::test]
-async fn test_list_models_with_include() -> Result<(), GatewayError> {
+async fn test_retry_hooks() -> Result<(), GatewayError> {
     let mut server = Server::new_async().await;
-
-    let raw_json_response = r#"{
-        "provider":"openai",
-        "object":"list",
-        "data": [
-            {
-                "id": "openai/gpt-4o",
-                "object": "model",
-                "created": 1686935002,
-                "owned_by": "openai",
-                "served_by": "openai",
-                "pricing": {
-                    "currency": "USD",
-                    "input_per_token": "0.0000025",
-                    "output_per_token": "0.00001",
-                    "source": "provider",
-                    "updated_at": "2025-01-01T00:00:00Z"
-                },
-                "context_window": {
-                    "tokens": 128000,
-                    "source": "provider"
-                }
-            }
-        ]
-    }"#;
-
-    let mock = server
-        .mock(
-            "GET",
-            "/v1/models?provider=openai&include=pricing,context_window",
-        )
+    let unavailable = server
+        .mock("GET", "/v1/models")
+        .with_status(503)
+        .expect(2)
+        .create();
+    let available = server
+        .mock("GET", "/v1/models")
         .with_status(200)
         .with_header("content-type", "application/json")
-        .with_body(raw_json_response)
+        .with_body(r#"{"object":"list","data":[]}"#)
         .create();
 
-    let base_url = format!("{}/v1", server.url());
-    let client = InferenceGatewayClient::new(&base_url);
-    let response = client
-        .list_models_with_include(Some(Provider::Openai), &["pricing", "context_window"])
-        .await?;
+    let retries = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let failures = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let config = {
+        let retries = retries.clone();
+        let failures = failures.clone();
+        RetryConfig::new(2)
+            .with_backoff(
+                std::time::Duration::from_millis(1),
+                std::time::Duration::from_millis(10),
+            )
+            .on_retry(move |event| retries.lock().unwrap().push((event.attempt, event.delay)))
+            .on_failure(move |event| failures.lock().unwrap().push(event.attempts))
+    };
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url())).with_retry(config);
 
-    let model = &response.data[0];
-    let pricing = model.pricing.as_ref().unwrap();
-    assert_eq!(pricing.currency, "USD");
-    assert_eq!(pricing.source, PricingSource::Provider);
-    let context_window = model.context_window.as_ref().unwrap();
-    assert_eq!(context_window.tokens, 128000);
-    assert_eq!(context_window.source, ContextWindowSource::Provider);
-    mock.assert();
+    client.list_models().await?;
+    unavailable.assert();
+    available.assert();
+    assert_eq!(
+        *retries.lock().unwrap(),
+        vec![
+            (1, std::time::Duration::from_millis(1)),
+            (2, std::time::Duration::from_millis(2)),
+        ]
+    );
+    assert!(failures.lock().unwrap().is_empty());
+
+    // Every attempt fails: two retries, then the failure hook.
+    available.remove();
+    let unavailable = server
+        .mock("GET", "/v1/models")
+        .with_status(503)
+        .expect(3)
+        .create();
+    retries.lock().unwrap().clear();
+    assert!(client.list_models().await.is_err());
+    unavailable.assert();
+    assert_eq!(retries.lock().unwrap().len(), 2);
+    assert_eq!(*failures.lock().unwrap(), vec![3]);
     Ok(())
 }
 
 #[tokio::test]
-async fn test_generate_content() -> Result<(), GatewayError> {
+async fn test_retry_skips_unsafe_posts() -> Result<(), GatewayError> {
     let mut server = Server::new_async().await;
-
-    let raw_json_response = r#"{
-        "id": "chatcmpl-456",
+    let completion = json!({
+        "id": "chatcmpl-1",
         "object": "chat.completion",
-        "created": 1630000001,
+        "created": 1,
         "model": "deepseek-v4-flash",
-        "choices": [
-            {
-                "index": 0,
-                "finish_reason": "stop",
-                "logprobs": null,
-                "message": {
-                    "role": "assistant",
-                    "content": "Hellloooo"
-                }
-            }
-        ]
-    }"#;
+        "choices": [{
+            "index": 0,
+            "finish_reason": "stop",
+            "message": {"role": "assistant", "content": "Hi"}
+        }]
+    });
+    let retries = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let sink = retries.clone();
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()))
+        .with_retry(RetryConfig::new(2).with_backoff(
+            std::time::Duration::from_millis(1),
+            std::time::Duration::from_millis(1),
+        ))
+        .with_metrics_callback(move |metrics| sink.lock().unwrap().push(metrics.retries));
 
-    let mock = server
+    // A plain chat completion is sent once.
+    let unavailable = server
+        .mock("POST", "/v1/chat/completions?provider=deepseek")
+        .with_status(503)
+        .expect(1)
+        .create();
+    let error = client
+        .generate_content(
+            Provider::Deepseek,
+            "deepseek-v4-flash",
+            vec![user_message("Hi")],
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(&error, GatewayError::Other(_)));
+    unavailable.assert();
+    unavailable.remove();
+
+    // With an idempotency key it is retried, carrying the key each time.
+    let unavailable = server
+        .mock("POST", "/v1/chat/completions?provider=deepseek")
+        .match_header("idempotency-key", "req-1")
+        .with_status(503)
+        .expect(1)
+        .create();
+    let available = server
         .mock("POST", "/v1/chat/completions?provider=deepseek")
+        .match_header("idempotency-key", "req-1")
         .with_status(200)
         .with_header("content-type", "application/json")
-        .with_body(raw_json_response)
+        .with_body(completion.to_string())
         .create();
-
-    let base_url = format!("{}/v1", server.url());
-    let client = InferenceGatewayClient::new(&base_url);
-
-    let messages = vec![user_message("Hello")];
-    let response = client
-        .generate_content(Provider::Deepseek, "deepseek-v4-flash", messages)
+    client
+        .generate_content_with_options(
+            Provider::Deepseek,
+            "deepseek-v4-flash",
+            vec![user_message("Hi")],
+            RequestOptions::new().with_idempotency_key("req-1"),
+        )
         .await?;
-
-    assert_eq!(response.choices[0].message.role, MessageRole::Assistant);
-    assert!(matches!(
-        response.choices[0].message.content,
-        MessageContent::String(ref s) if s == "Hellloooo"
-    ));
-    mock.assert();
+    unavailable.assert();
+    available.assert();
+    assert_eq!(*retries.lock().unwrap(), [0, 1]);
     Ok(())
 }
 
 #[tokio::test]
-async fn test_generate_content_serialization() -> Result<(), GatewayError> {
+async fn test_failover_chain() -> Result<(), GatewayError> {
     let mut server = Server::new_async().await;
-
-    let raw_json = r#"{
-        "id": "chatcmpl-456",
-        "object": "chat.completion",
-        "created": 1630000001,
-        "model": "deepseek-v4-flash",
-        "choices": [
-            {
-                "index": 0,
-                "finish_reason": "stop",
-                "logprobs": null,
-                "message": {
-                    "role": "assistant",
-                    "content": "Hello"
-                }
-            }
-        ]
-    }"#;
-
-    let mock = server
-        .mock("POST", "/v1/chat/completions?provider=deepseek")
+    let unavailable = server
+        .mock("POST", "/v1/chat/completions?provider=openai")
+        .with_status(503)
+        .expect(1)
+        .create();
+    let rate_limited = server
+        .mock("POST", "/v1/chat/completions?provider=anthropic")
+        .with_status(429)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"error":"slow down"}"#)
+        .expect(1)
+        .create();
+    let available = server
+        .mock("POST", "/v1/chat/completions?provider=groq")
+        .match_body(Matcher::PartialJson(json!({
+            "model": "llama-3.3-70b-versatile",
+            "messages": [{"role": "user", "content": "Hi"}]
+        })))
         .with_status(200)
         .with_header("content-type", "application/json")
-        .with_body(raw_json)
+        .with_body(
+            json!({
+                "id": "chatcmpl-1",
+                "object": "chat.completion",
+                "created": 1,
+                "model": "llama-3.3-70b-versatile",
+                "choices": [{
+                    "index": 0,
+                    "finish_reason": "stop",
+                    "message": {"role": "assistant", "content": "Hello"}
+                }]
+            })
+            .to_string(),
+        )
+        .expect(1)
         .create();
 
-    let base_url = format!("{}/v1", server.url());
-    let client = InferenceGatewayClient::new(&base_url);
-
-    let direct_parse: Result<CreateChatCompletionResponse, _> = serde_json::from_str(raw_json);
-    assert!(
-        direct_parse.is_ok(),
-        "direct parse: {:?}",
-        direct_parse.err()
+    let attempts = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let sink = attempts.clone();
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url())).with_failover(
+        FailoverConfig::new([
+            (Provider::Anthropic, "claude-sonnet-5"),
+            (Provider::Groq, "llama-3.3-70b-versatile"),
+            (Provider::Deepseek, "deepseek-v4-flash"),
+        ])
+        .on_attempt(move |event| {
+            sink.lock().unwrap().push((
+                event.attempt,
+                event.provider,
+                event.model.to_string(),
+                event.error.map(GatewayError::kind),
+            ))
+        }),
     );
 
-    let messages = vec![user_message("Hello")];
+    // The first fallback that answers wins, and later ones aren't tried.
     let response = client
-        .generate_content(Provider::Deepseek, "deepseek-v4-flash", messages)
+        .generate_content(Provider::Openai, "gpt-4o", vec![user_message("Hi")])
         .await?;
-
-    assert_eq!(response.choices[0].message.role, MessageRole::Assistant);
-    assert!(matches!(
-        response.choices[0].message.content,
-        MessageContent::String(ref s) if s == "Hello"
-    ));
-    mock.assert();
-    Ok(())
-}
-
-#[tokio::test]
-async fn test_generate_content_error_response() -> Result<(), GatewayError> {
-    let mut server = Server::new_async().await;
-
-    let mock = server
-        .mock("POST", "/v1/chat/completions?provider=deepseek")
+    assert_eq!(response.model, "llama-3.3-70b-versatile");
+    assert_eq!(
+        *attempts.lock().unwrap(),
+        vec![
+            (
+                1,
+                Provider::Openai,
+                "gpt-4o".to_string(),
+                Some(GatewayErrorKind::Other)
+            ),
+            (
+                2,
+                Provider::Anthropic,
+                "claude-sonnet-5".to_string(),
+                Some(GatewayErrorKind::RateLimit)
+            ),
+            (
+                3,
+                Provider::Groq,
+                "llama-3.3-70b-versatile".to_string(),
+                None
+            ),
+        ]
+    );
+    unavailable.assert();
+    rate_limited.assert();
+    available.assert();
+
+    // A rejected request isn't sent anywhere else.
+    attempts.lock().unwrap().clear();
+    let rejected = server
+        .mock("POST", "/v1/chat/completions?provider=mistral")
         .with_status(400)
         .with_header("content-type", "application/json")
-        .with_body(r#"{ "error": "Invalid request" }"#)
+        .with_body(r#"{"error":"bad request"}"#)
+        .expect(1)
         .create();
-
-    let base_url = format!("{}/v1", server.url());
-    let client = InferenceGatewayClient::new(&base_url);
-    let messages = vec![user_message("Hello")];
     let error = client
-        .generate_content(Provider::Deepseek, "deepseek-v4-flash", messages)
+        .generate_content(Provider::Mistral, "mistral-large", vec![user_message("Hi")])
         .await
         .unwrap_err();
-
-    assert!(matches!(error, GatewayError::BadRequest(_)));
-    if let GatewayError::BadRequest(msg) = error {
-        assert_eq!(msg, "Invalid request");
-    }
-    mock.assert();
+    assert!(matches!(&error, GatewayError::BadRequest(_)));
+    assert_eq!(attempts.lock().unwrap().len(), 1);
+    rejected.assert();
     Ok(())
 }
 
 #[tokio::test]
-async fn test_gateway_errors() -> Result<(), GatewayError> {
+async fn test_response_cache() -> Result<(), GatewayError> {
     let mut server = Server::new_async().await;
-
-    let unauthorized_mock = server
-        .mock("GET", "/v1/models")
-        .with_status(401)
-        .with_header("content-type", "application/json")
-        .with_body(r#"{"error":"Invalid token"}"#)
-        .create();
-
-    let base_url = format!("{}/v1", server.url());
-    let client = InferenceGatewayClient::new(&base_url);
-    match client.list_models().await {
-        Err(GatewayError::Unauthorized(msg)) => assert_eq!(msg, "Invalid token"),
-        _ => panic!("Expected Unauthorized error"),
+    let completion = |content: &str| {
+        json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion",
+            "created": 1,
+            "model": "deepseek-v4-flash",
+            "choices": [{
+                "index": 0,
+                "finish_reason": "stop",
+                "message": {"role": "assistant", "content": content}
+            }]
+        })
+        .to_string()
+    };
+    let focused = server
+        .mock("POST", "/v1/chat/completions?provider=deepseek")
+        .match_body(Matcher::PartialJson(json!({"temperature": 0.2})))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(completion("Hi"))
+        .expect(1)
+        .create();
+    let default = server
+        .mock("POST", "/v1/chat/completions?provider=deepseek")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(completion("Hello"))
+        .expect(1)
+        .create();
+
+    let cache = InMemoryCache::new(std::time::Duration::from_secs(60));
+    let client =
+        InferenceGatewayClient::new(&format!("{}/v1", server.url())).with_cache(cache.clone());
+
+    // A repeated request is answered from the cache.
+    for _ in 0..2 {
+        let response = client
+            .generate_content(
+                Provider::Deepseek,
+                "deepseek-v4-flash",
+                vec![user_message("Hi")],
+            )
+            .await?;
+        assert_eq!(response.choices[0].message.content.as_text(), Some("Hello"));
     }
-    unauthorized_mock.assert();
+    // Different options make a different request.
+    let response = client
+        .generate_content_with_options(
+            Provider::Deepseek,
+            "deepseek-v4-flash",
+            vec![user_message("Hi")],
+            ChatCompletionOptions::new().with_temperature(0.2),
+        )
+        .await?;
+    assert_eq!(response.choices[0].message.content.as_text(), Some("Hi"));
+    assert_eq!(cache.len(), 2);
+    default.assert();
+    focused.assert();
+    default.remove();
+    focused.remove();
+
+    // Another token or tenant doesn't share the first client's answers.
+    let scoped = server
+        .mock("POST", "/v1/chat/completions?provider=deepseek")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(completion("Hello"))
+        .expect(2)
+        .create();
+    let clients = [
+        client.clone().with_token("other-token"),
+        client
+            .clone()
+            .with_scheduling_hints(SchedulingHints::new().with_tenant_id("globex")),
+    ];
+    for client in clients {
+        client
+            .generate_content(
+                Provider::Deepseek,
+                "deepseek-v4-flash",
+                vec![user_message("Hi")],
+            )
+            .await?;
+    }
+    assert_eq!(cache.len(), 4);
+    scoped.assert();
 
-    let bad_request_mock = server
-        .mock("GET", "/v1/models")
-        .with_status(400)
+    // Neither do calls routed differently by a query parameter, whether set
+    // on the client or on the call.
+    let routed = server
+        .mock(
+            "POST",
+            Matcher::Regex(r"^/v1/chat/completions\?provider=deepseek&route=".to_string()),
+        )
+        .with_status(200)
         .with_header("content-type", "application/json")
-        .with_body(r#"{"error":"Invalid provider"}"#)
+        .with_body(completion("Hello"))
+        .expect(2)
         .create();
+    client
+        .clone()
+        .with_query_param("route", "canary")
+        .generate_content(
+            Provider::Deepseek,
+            "deepseek-v4-flash",
+            vec![user_message("Hi")],
+        )
+        .await?;
+    client
+        .generate_content_with_options(
+            Provider::Deepseek,
+            "deepseek-v4-flash",
+            vec![user_message("Hi")],
+            RequestOptions::new().with_query_param("route", "stable"),
+        )
+        .await?;
+    assert_eq!(cache.len(), 6);
+    routed.assert();
 
-    match client.list_models().await {
-        Err(GatewayError::BadRequest(msg)) => assert_eq!(msg, "Invalid provider"),
-        _ => panic!("Expected BadRequest error"),
+    // Expired responses are fetched again.
+    let expiring = server
+        .mock("POST", "/v1/chat/completions?provider=openai")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(completion("Hello"))
+        .expect(2)
+        .create();
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()))
+        .with_cache(InMemoryCache::new(std::time::Duration::ZERO));
+    for _ in 0..2 {
+        client
+            .generate_content(Provider::Openai, "gpt-4o", vec![user_message("Hi")])
+            .await?;
     }
-    bad_request_mock.assert();
+    expiring.assert();
+    Ok(())
+}
 
-    let internal_error_mock = server
+#[tokio::test]
+async fn test_balanced_endpoints() -> Result<(), GatewayError> {
+    let mut first = Server::new_async().await;
+    let mut second = Server::new_async().await;
+    let models = |server: &mut mockito::ServerGuard, hits| {
+        server
+            .mock("GET", "/v1/models")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"object":"list","data":[]}"#)
+            .expect(hits)
+            .create()
+    };
+
+    // Round robin takes turns.
+    let on_first = models(&mut first, 2);
+    let on_second = models(&mut second, 2);
+    let client = InferenceGatewayClient::new_balanced(
+        &[
+            &format!("{}/v1", first.url()),
+            &format!("{}/v1/", second.url()),
+        ],
+        LoadBalancing::RoundRobin,
+    )?;
+    assert_eq!(client.base_url(), format!("{}/v1", first.url()));
+    for _ in 0..4 {
+        client.list_models().await?;
+    }
+    on_first.assert();
+    on_second.assert();
+    on_first.remove();
+    on_second.remove();
+
+    // An endpoint that refuses connections is ejected, and the retry goes
+    // to the next one.
+    let down = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let on_first = models(&mut first, 3);
+    let client = InferenceGatewayClient::new_balanced(
+        &[&format!("http://{down}/v1"), &format!("{}/v1", first.url())],
+        LoadBalancing::LeastOutstanding,
+    )?
+    .with_endpoint_ejection(std::time::Duration::from_secs(60))
+    .with_retry(RetryConfig::new(1).with_backoff(
+        std::time::Duration::from_millis(1),
+        std::time::Duration::from_millis(1),
+    ));
+    for _ in 0..3 {
+        client.list_models().await?;
+    }
+    on_first.assert();
+
+    // Bad endpoint lists are reported, not panicked on.
+    for base_urls in [&[][..], &["not a url"][..]] {
+        let error =
+            InferenceGatewayClient::new_balanced(base_urls, LoadBalancing::RoundRobin).unwrap_err();
+        assert!(matches!(error, GatewayError::InvalidEndpoint(_)));
+        assert_eq!(error.kind(), GatewayErrorKind::Validation);
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_retry_policy() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+    let failing = server
         .mock("GET", "/v1/models")
         .with_status(500)
+        .expect(2)
+        .create();
+    let available = server
+        .mock("GET", "/v1/models")
+        .with_status(200)
         .with_header("content-type", "application/json")
-        .with_body(r#"{"error":"Internal server error occurred"}"#)
+        .with_body(r#"{"object":"list","data":[]}"#)
         .create();
 
-    match client.list_models().await {
-        Err(GatewayError::InternalError(msg)) => {
-            assert_eq!(msg, "Internal server error occurred")
-        }
-        _ => panic!("Expected InternalError error"),
-    }
-    internal_error_mock.assert();
+    let delays = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let failures = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let config = {
+        let delays = delays.clone();
+        let failures = failures.clone();
+        RetryConfig::new(2)
+            .with_backoff(
+                std::time::Duration::from_millis(4),
+                std::time::Duration::from_millis(4),
+            )
+            .with_jitter(1.0)
+            .with_retry_on([StatusCode::INTERNAL_SERVER_ERROR])
+            .on_retry(move |event| delays.lock().unwrap().push(event.delay))
+            .on_failure(move |event| failures.lock().unwrap().push(event.attempts))
+    };
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url())).with_retry(config);
+
+    // A 500 is retried when listed, after a jittered wait.
+    client.list_models().await?;
+    failing.assert();
+    available.assert();
+    assert_eq!(delays.lock().unwrap().len(), 2);
+    assert!(
+        delays
+            .lock()
+            .unwrap()
+            .iter()
+            .all(|delay| *delay <= std::time::Duration::from_millis(4))
+    );
+
+    // A chat completion that can't connect never reached the gateway, so
+    // it is retried even without an idempotency key.
+    let closed = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/v1", closed.local_addr().unwrap());
+    drop(closed);
+    let client = InferenceGatewayClient::new(&url).with_retry(client.retry.clone().unwrap());
+    let error = client
+        .generate_content(
+            Provider::Deepseek,
+            "deepseek-v4-flash",
+            vec![user_message("Hi")],
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(&error, GatewayError::RequestError(e) if e.is_connect()));
+    assert_eq!(*failures.lock().unwrap(), vec![3]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_rate_limited_error() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+    let limited = server
+        .mock("GET", "/v1/models")
+        .with_status(429)
+        .with_header("retry-after", "7")
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"error":"Slow down"}"#)
+        .create();
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()));
+
+    let error = client.list_models().await.unwrap_err();
+    assert!(matches!(
+        &error,
+        GatewayError::RateLimited { retry_after: Some(wait), message }
+            if *wait == std::time::Duration::from_secs(7) && message == "Slow down"
+    ));
+    assert_eq!(error.kind(), GatewayErrorKind::RateLimit);
+    limited.assert();
+    limited.remove();
+
+    // A date already past asks for no wait; without the header, none is known.
+    let limited = server
+        .mock("GET", "/v1/models")
+        .with_status(429)
+        .with_header("retry-after", "Wed, 21 Oct 2015 07:28:00 GMT")
+        .create();
+    let error = client.list_models().await.unwrap_err();
+    assert!(matches!(
+        &error,
+        GatewayError::RateLimited { retry_after: Some(wait), .. } if wait.is_zero()
+    ));
+    limited.remove();
+    let limited = server.mock("GET", "/v1/models").with_status(429).create();
+    let error = client.list_models().await.unwrap_err();
+    assert!(matches!(
+        &error,
+        GatewayError::RateLimited { retry_after: None, message } if message == "Too Many Requests"
+    ));
+    limited.assert();
+    limited.remove();
+
+    // Retries wait as long as the gateway asks.
+    let delays = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let client = client.with_retry(RetryConfig::new(1).on_retry({
+        let delays = delays.clone();
+        move |event| delays.lock().unwrap().push(event.delay)
+    }));
+    let limited = server
+        .mock("GET", "/v1/models")
+        .with_status(429)
+        .with_header("retry-after", "0")
+        .create();
+    let available = server
+        .mock("GET", "/v1/models")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"object":"list","data":[]}"#)
+        .create();
+    client.list_models().await?;
+    limited.assert();
+    available.assert();
+    assert_eq!(*delays.lock().unwrap(), vec![std::time::Duration::ZERO]);
+    limited.remove();
+    available.remove();
+
+    // ... unless that is longer than the config allows.
+    let failures = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let client = client.with_retry(
+        RetryConfig::new(3)
+            .with_max_retry_after(std::time::Duration::from_secs(30))
+            .on_failure({
+                let failures = failures.clone();
+                move |event| failures.lock().unwrap().push(event.attempts)
+            }),
+    );
+    let limited = server
+        .mock("GET", "/v1/models")
+        .with_status(429)
+        .with_header("retry-after", "86400")
+        .expect(1)
+        .create();
+    let error = tokio::time::timeout(std::time::Duration::from_secs(5), client.list_models())
+        .await
+        .expect("a long Retry-After should not be waited out")
+        .unwrap_err();
+    assert!(matches!(
+        &error,
+        GatewayError::RateLimited { retry_after: Some(wait), .. }
+            if *wait == std::time::Duration::from_secs(86400)
+    ));
+    assert_eq!(*failures.lock().unwrap(), vec![1]);
+    limited.assert();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_response_size_limits() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+    let models = server
+        .mock("GET", "/v1/models")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(json!({"object": "list", "data": [], "padding": "x".repeat(200)}).to_string())
+        .expect(2)
+        .create();
+    let stream = server
+        .mock("POST", "/v1/chat/completions?provider=deepseek")
+        .with_status(200)
+        .with_header("content-type", "text/event-stream")
+        .with_body(format!("data: {}\n\n", "x".repeat(200)))
+        .create();
+
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()));
+    client.list_models().await?;
+
+    let client = client.with_max_response_size(100).with_max_event_size(100);
+    let error = client.list_models().await.unwrap_err();
+    assert!(matches!(
+        &error,
+        GatewayError::ResponseTooLarge { limit: 100 }
+    ));
+    assert_eq!(error.kind(), GatewayErrorKind::Protocol);
+    models.assert();
+
+    let events: Vec<_> = client
+        .generate_content_stream(
+            Provider::Deepseek,
+            "deepseek-v4-flash",
+            vec![user_message("Hi")],
+        )
+        .collect()
+        .await;
+    assert!(matches!(
+        events.as_slice(),
+        [Err(error)] if matches!(&error, GatewayError::ResponseTooLarge { limit: 100 })
+    ));
+    stream.assert();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_list_models_by_provider() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+
+    let raw_json_response = r#"{
+        "provider":"ollama",
+        "object":"list",
+        "data": [
+            {
+                "id": "llama2",
+                "object": "model",
+                "created": 1630000001,
+                "owned_by": "ollama",
+                "served_by": "ollama"
+            }
+        ]
+    }"#;
+
+    let mock = server
+        .mock("GET", "/v1/models?provider=ollama")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(raw_json_response)
+        .create();
+
+    let base_url = format!("{}/v1", server.url());
+    let client = InferenceGatewayClient::new(&base_url);
+    let response = client.list_models_by_provider(Provider::Ollama).await?;
+
+    assert_eq!(response.provider, Some(Provider::Ollama));
+    assert_eq!(response.data[0].id, "llama2");
+    mock.assert();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_list_models_with_include() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+
+    let raw_json_response = r#"{
+        "provider":"openai",
+        "object":"list",
+        "data": [
+            {
+                "id": "openai/gpt-4o",
+                "object": "model",
+                "created": 1686935002,
+                "owned_by": "openai",
+                "served_by": "openai",
+                "pricing": {
+                    "currency": "USD",
+                    "input_per_token": "0.0000025",
+                    "output_per_token": "0.00001",
+                    "source": "provider",
+                    "updated_at": "2025-01-01T00:00:00Z"
+                },
+                "context_window": {
+                    "tokens": 128000,
+                    "source": "provider"
+                }
+            }
+        ]
+    }"#;
+
+    let mock = server
+        .mock(
+            "GET",
+            "/v1/models?provider=openai&include=pricing,context_window",
+        )
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(raw_json_response)
+        .create();
+
+    let base_url = format!("{}/v1", server.url());
+    let client = InferenceGatewayClient::new(&base_url);
+    let response = client
+        .list_models_with_include(Some(Provider::Openai), &["pricing", "context_window"])
+        .await?;
+
+    let model = &response.data[0];
+    let pricing = model.pricing.as_ref().unwrap();
+    assert_eq!(pricing.currency, "USD");
+    assert_eq!(pricing.source, PricingSource::Provider);
+    let context_window = model.context_window.as_ref().unwrap();
+    assert_eq!(context_window.tokens, 128000);
+    assert_eq!(context_window.source, ContextWindowSource::Provider);
+    mock.assert();
+    Ok(())
+}
+
+#[test]
+fn test_known_model_constants() {
+    use crate::models;
+
+    assert_eq!(
+        models::groq::LLAMA_3_3_70B_VERSATILE,
+        "llama-3.3-70b-versatile"
+    );
+    assert_eq!(models::openai::GPT_4_1_MINI, "gpt-4.1-mini");
+    assert!(models::deepseek::ALL.contains(&models::deepseek::DEEPSEEK_V4_FLASH));
+}
+
+#[tokio::test]
+async fn test_model_ref() -> Result<(), GatewayError> {
+    let model: ModelRef = "groq/llama-3.3-70b-versatile".parse().unwrap();
+    assert_eq!(model.provider(), Some(Provider::Groq));
+    assert_eq!(model.model(), "llama-3.3-70b-versatile");
+    assert_eq!(model.to_string(), "groq/llama-3.3-70b-versatile");
+
+    let bare: ModelRef = "gpt-4o".parse().unwrap();
+    assert_eq!((bare.provider(), bare.model()), (None, "gpt-4o"));
+    assert_eq!(
+        "acme/model".parse::<ModelRef>(),
+        Err(ModelRefError::UnknownProvider("acme".to_string()))
+    );
+    assert_eq!("groq/".parse::<ModelRef>(), Err(ModelRefError::Empty));
+
+    let mut server = Server::new_async().await;
+    let mock = server
+        .mock("POST", "/v1/chat/completions?provider=groq")
+        .match_body(Matcher::PartialJson(
+            json!({"model": "groq/llama-3.3-70b-versatile"}),
+        ))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{"id":"1","object":"chat.completion","created":1,"model":"m","choices":[{"index":0,"finish_reason":"stop","message":{"role":"assistant","content":"hi"}}]}"#,
+        )
+        .create();
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()));
+    client
+        .generate_content(Provider::Groq, model, vec![user_message("Hi")])
+        .await?;
+    mock.assert();
+    Ok(())
+}
+
+#[test]
+fn test_message_content_serialization() {
+    let text = MessageContent::from("Hello");
+    assert_eq!(serde_json::to_value(&text).unwrap(), json!("Hello"));
+
+    let single: MessageContent =
+        serde_json::from_value(json!([{"type": "text", "text": "Hi"}])).unwrap();
+    assert!(matches!(&single, MessageContent::Parts(parts) if parts.len() == 1));
+    assert_eq!(single.as_text(), Some("Hi"));
+    assert_eq!(serde_json::to_value(&single).unwrap(), json!("Hi"));
+
+    let multimodal = json!([
+        {"type": "text", "text": "What is this?"},
+        {"type": "image_url", "image_url": {"url": "https://example.com/cat.png", "detail": "low"}}
+    ]);
+    let content: MessageContent = serde_json::from_value(multimodal.clone()).unwrap();
+    assert_eq!(content.as_text(), None);
+    assert_eq!(serde_json::to_value(&content).unwrap(), multimodal);
+
+    let plain: MessageContent = serde_json::from_value(json!("Plain")).unwrap();
+    assert!(matches!(plain, MessageContent::Text(ref s) if s == "Plain"));
+}
+
+#[test]
+fn test_input_audio_content_part() {
+    let content = MessageContent::Parts(vec![
+        InputAudioContentPart::wav(b"RIFF").into(),
+        InputAudioContentPart::mp3(&[0xff, 0xfb]).into(),
+    ]);
+    assert_eq!(
+        serde_json::to_value(&content).unwrap(),
+        json!([
+            {"type": "input_audio", "input_audio": {"data": "UklGRg==", "format": "wav"}},
+            {"type": "input_audio", "input_audio": {"data": "//s=", "format": "mp3"}}
+        ])
+    );
+}
+
+#[test]
+fn test_data_url() {
+    use crate::attachment::{DataUrl, DataUrlError, sniff_mime};
+
+    let png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+    let url = DataUrl::from_bytes(png.to_vec());
+    assert_eq!(url.media_type(), "image/png");
+    assert_eq!(
+        url.to_string(),
+        "data:image/png;base64,iVBORw0KGgoAAAANSUhEUg=="
+    );
+    assert_eq!(url.to_string().parse::<DataUrl>().unwrap(), url);
+
+    let text: DataUrl = "data:,hello%20world".parse().unwrap();
+    assert_eq!(text.media_type(), "text/plain;charset=US-ASCII");
+    assert_eq!(text.data(), b"hello world");
+
+    assert_eq!(
+        "https://example.com/a.png".parse::<DataUrl>(),
+        Err(DataUrlError::NotDataUrl)
+    );
+    assert_eq!(
+        "data:image/png;base64".parse::<DataUrl>(),
+        Err(DataUrlError::MissingData)
+    );
+    assert!(matches!(
+        "data:image/png;base64,!!!".parse::<DataUrl>(),
+        Err(DataUrlError::InvalidBase64(_))
+    ));
+
+    assert_eq!(sniff_mime(b"\xff\xd8\xff\xe0"), Some("image/jpeg"));
+    assert_eq!(sniff_mime(b"GIF89a"), Some("image/gif"));
+    assert_eq!(sniff_mime(b"RIFF\0\0\0\0WEBPVP8 "), Some("image/webp"));
+    assert_eq!(sniff_mime(b"RIFF\0\0\0\0WAVEfmt "), Some("audio/wav"));
+    assert_eq!(sniff_mime(b"%PDF-1.7"), Some("application/pdf"));
+    assert_eq!(sniff_mime(b"ID3\x04"), Some("audio/mpeg"));
+    assert_eq!(sniff_mime(b"plain text"), None);
+}
+
+#[tokio::test]
+async fn test_attachment_size_checked_before_send() {
+    use crate::attachment;
+
+    let mut server = Server::new_async().await;
+    let mock = server
+        .mock("POST", "/v1/chat/completions?provider=anthropic")
+        .expect(0)
+        .create();
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()));
+
+    let image = vec![0u8; 5 * 1024 * 1024 + 1];
+    let mut message = user_message("");
+    message.content = MessageContent::Parts(vec![
+        ImageContentPart::new(attachment::DataUrl::new("image/png", image).to_string()).into(),
+    ]);
+    let messages = vec![message];
+
+    let err = client
+        .generate_content(Provider::Anthropic, "claude-sonnet-4", messages.clone())
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        GatewayError::AttachmentTooLarge {
+            provider: Provider::Anthropic,
+            kind: attachment::AttachmentKind::Image,
+            size: 5_242_881,
+            limit: 5_242_880,
+        }
+    ));
+
+    let stream =
+        client.generate_content_stream(Provider::Anthropic, "claude-sonnet-4", messages.clone());
+    pin_mut!(stream);
+    assert!(matches!(
+        stream.next().await,
+        Some(Err(GatewayError::AttachmentTooLarge { .. }))
+    ));
+    assert!(stream.next().await.is_none());
+    mock.assert();
+    mock.remove();
+
+    // With the check turned off, the gateway gets to decide.
+    let sent = server
+        .mock("POST", "/v1/chat/completions?provider=anthropic")
+        .with_status(413)
+        .expect(1)
+        .create();
+    let err = client
+        .clone()
+        .with_attachment_checks(false)
+        .generate_content(Provider::Anthropic, "claude-sonnet-4", messages)
+        .await
+        .unwrap_err();
+    assert!(!matches!(err, GatewayError::AttachmentTooLarge { .. }));
+    sent.assert();
+
+    // Limits are per kind, and kinds without a documented limit pass.
+    use attachment::AttachmentKind;
+    assert_eq!(
+        attachment::max_inline_bytes(Provider::Anthropic, AttachmentKind::Audio),
+        None
+    );
+    assert!(
+        attachment::check_size(Provider::Anthropic, AttachmentKind::File, 6 * 1024 * 1024).is_ok()
+    );
+    assert!(attachment::check_size(Provider::Ollama, AttachmentKind::Image, usize::MAX).is_ok());
+    assert!(attachment::check_size(Provider::Mistral, AttachmentKind::Image, usize::MAX).is_ok());
+}
+
+#[test]
+fn test_conversation_numbers_images_across_turns() {
+    let message = Message::user_with_images("Compare these", ["https://example.com/a.png"]);
+    assert_eq!(
+        serde_json::to_value(&message).unwrap(),
+        json!({
+            "role": "user",
+            "content": [
+                {"type": "text", "text": "Compare these"},
+                {"type": "image_url", "image_url": {"url": "https://example.com/a.png", "detail": "auto"}}
+            ]
+        })
+    );
+
+    let mut conversation = Conversation::new().with_system("You compare charts.");
+    conversation.user_with_images(
+        "What do these show?",
+        ["https://example.com/a.png", "https://example.com/b.png"],
+    );
+    let response: CreateChatCompletionResponse = serde_json::from_value(json!({
+        "id": "chatcmpl-1",
+        "object": "chat.completion",
+        "created": 1,
+        "model": "gpt-4o",
+        "choices": [{
+            "index": 0,
+            "finish_reason": "stop",
+            "message": {"role": "assistant", "content": "Two bar charts."}
+        }]
+    }))
+    .unwrap();
+    conversation.push_response(&response).user_with_images(
+        "How does this one differ from image 2?",
+        [ImageContentPart::new("https://example.com/c.png").with_detail(ImageUrlDetail::High)],
+    );
+
+    let numbered: Vec<_> = conversation
+        .images()
+        .map(|(number, image)| (number, image.image_url.url.as_str()))
+        .collect();
+    assert_eq!(
+        numbered,
+        [
+            (1, "https://example.com/a.png"),
+            (2, "https://example.com/b.png"),
+            (3, "https://example.com/c.png"),
+        ]
+    );
+
+    let messages = conversation.into_messages();
+    assert_eq!(messages.len(), 4);
+    assert_eq!(messages[2].content.as_text(), Some("Two bar charts."));
+    assert_eq!(
+        serde_json::to_value(&messages[3].content).unwrap(),
+        json!([
+            {"type": "text", "text": "How does this one differ from image 2?"},
+            {"type": "text", "text": "Image 3:"},
+            {"type": "image_url", "image_url": {"url": "https://example.com/c.png", "detail": "high"}}
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_conversation_compact() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+    let summarize = server
+        .mock("POST", "/v1/chat/completions?provider=groq")
+        .match_body(Matcher::AllOf(vec![
+            Matcher::PartialJson(json!({"model": "llama-3.1-8b-instant"})),
+            Matcher::Regex(
+                r#""content":"user: Question one\\nImage 1:\\nassistant: Answer one""#.to_string(),
+            ),
+        ]))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            json!({
+                "id": "chatcmpl-1",
+                "object": "chat.completion",
+                "created": 1,
+                "model": "llama-3.1-8b-instant",
+                "choices": [{
+                    "index": 0,
+                    "finish_reason": "stop",
+                    "message": {"role": "assistant", "content": "Image 1 was a bar chart."}
+                }]
+            })
+            .to_string(),
+        )
+        .expect(1)
+        .create();
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()));
+
+    let mut conversation = Conversation::new()
+        .with_system("Be brief.")
+        .with_summary_policy(
+            SummaryPolicy::new(Provider::Groq, "llama-3.1-8b-instant", 20).with_keep_recent(2),
+        );
+    conversation
+        .user_with_images("Question one", ["https://example.com/a.png"])
+        .push(Message::assistant("Answer one"))
+        .user("Question two")
+        .push(Message::assistant("Answer two"));
+    // Without a policy, or under the threshold, nothing is summarized.
+    assert!(!Conversation::new().compact(&client).await?);
+    assert!(
+        !Conversation::new()
+            .with_summary_policy(SummaryPolicy::new(
+                Provider::Groq,
+                "llama-3.1-8b-instant",
+                20
+            ))
+            .compact(&client)
+            .await?
+    );
+
+    assert!(conversation.compact(&client).await?);
+    summarize.assert();
+    let messages = conversation.messages();
+    assert_eq!(messages.len(), 4);
+    assert_eq!(
+        messages[1].content.as_text(),
+        Some("Summary of the earlier conversation:\nImage 1 was a bar chart.")
+    );
+    assert_eq!(messages[2].content.as_text(), Some("Question two"));
+
+    // Numbering carries on past the summarized image.
+    conversation.user_with_images("And this?", ["https://example.com/b.png"]);
+    let numbered: Vec<_> = conversation.images().map(|(number, _)| number).collect();
+    assert_eq!(numbered, [2]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_conversation_compact_keeps_history_without_summary() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+    let completion = |choices: serde_json::Value| {
+        json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion",
+            "created": 1,
+            "model": "llama-3.1-8b-instant",
+            "choices": choices
+        })
+        .to_string()
+    };
+    let empty = server
+        .mock("POST", "/v1/chat/completions?provider=groq")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(completion(json!([])))
+        .expect(1)
+        .create();
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()));
+
+    let mut conversation = Conversation::new().with_summary_policy(
+        SummaryPolicy::new(Provider::Groq, "llama-3.1-8b-instant", 10).with_keep_recent(1),
+    );
+    conversation
+        .user("Question one")
+        .push(Message::assistant("Answer one"))
+        .user("Question two");
+    let before = serde_json::to_value(conversation.messages())?;
+
+    assert!(!conversation.compact(&client).await?);
+    assert_eq!(serde_json::to_value(conversation.messages())?, before);
+    empty.assert();
+    empty.remove();
+
+    let blank = server
+        .mock("POST", "/v1/chat/completions?provider=groq")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(completion(json!([{
+            "index": 0,
+            "finish_reason": "stop",
+            "message": {"role": "assistant", "content": "  \n"}
+        }])))
+        .expect(1)
+        .create();
+    assert!(!conversation.compact(&client).await?);
+    assert_eq!(serde_json::to_value(conversation.messages())?, before);
+    blank.assert();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_conversation_token_budget() -> Result<(), GatewayError> {
+    let mut conversation = Conversation::new()
+        .with_system("Be brief.")
+        .with_token_budget(25);
+    conversation
+        .user("Question one")
+        .push(Message {
+            tool_calls: vec![ChatCompletionMessageToolCall {
+                id: "call_1".into(),
+                type_: ChatCompletionToolType::Function,
+                function: ChatCompletionMessageToolCallFunction {
+                    name: "lookup".to_string(),
+                    arguments: "{}".to_string(),
+                },
+                extra_content: None,
+            }],
+            ..Message::assistant("")
+        })
+        .tool("call_1", "42")
+        .assistant("Answer one")
+        .user("Question two")
+        .assistant("Answer two");
+
+    // The first turn goes whole, its tool result included.
+    assert_eq!(conversation.truncate(), 4);
+    let texts = |conversation: &Conversation| -> Vec<String> {
+        conversation
+            .messages()
+            .iter()
+            .map(|m| m.content.as_text().unwrap_or_default().to_string())
+            .collect()
+    };
+    assert_eq!(
+        texts(&conversation),
+        ["Be brief.", "Question two", "Answer two"]
+    );
+    assert_eq!(conversation.truncate(), 0);
+
+    // `send` makes room before the call and keeps the answer.
+    let mut server = Server::new_async().await;
+    let mock = server
+        .mock("POST", "/v1/chat/completions?provider=groq")
+        .match_body(Matcher::PartialJson(json!({
+            "messages": [
+                {"role": "system", "content": "Be brief."},
+                {"role": "user", "content": "Question three"}
+            ]
+        })))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            json!({
+                "id": "chatcmpl-1",
+                "object": "chat.completion",
+                "created": 1,
+                "model": "llama-3.1-8b-instant",
+                "choices": [{
+                    "index": 0,
+                    "finish_reason": "stop",
+                    "message": {"role": "assistant", "content": "Answer three"}
+                }]
+            })
+            .to_string(),
+        )
+        .create();
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()));
+    conversation.user("Question three");
+    let response = conversation
+        .send(&client, Provider::Groq, "llama-3.1-8b-instant")
+        .await?;
+    assert_eq!(
+        response.choices[0].message.content.as_text(),
+        Some("Answer three")
+    );
+    assert_eq!(
+        texts(&conversation),
+        ["Be brief.", "Question three", "Answer three"]
+    );
+    mock.assert();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_memory_stores() -> Result<(), GatewayError> {
+    async fn exercise(store: &impl MemoryStore) -> Result<(), GatewayError> {
+        assert!(store.get("user/42").await?.is_empty());
+        store
+            .append(
+                "user/42",
+                &[Message::user("One"), Message::assistant("Two")],
+            )
+            .await?;
+        store.append("user/42", &[Message::user("Three")]).await?;
+        store.append("other", &[Message::user("Elsewhere")]).await?;
+
+        let texts = |messages: Vec<Message>| -> Vec<String> {
+            messages
+                .iter()
+                .map(|m| m.content.as_text().unwrap().to_string())
+                .collect()
+        };
+        assert_eq!(texts(store.get("user/42").await?), ["One", "Two", "Three"]);
+
+        store.trim("user/42", 2).await?;
+        assert_eq!(texts(store.get("user/42").await?), ["Two", "Three"]);
+        store.trim("missing", 2).await?;
+        assert_eq!(texts(store.get("other").await?), ["Elsewhere"]);
+
+        let conversation = Conversation::from(store.get("user/42").await?);
+        assert_eq!(conversation.messages().len(), 2);
+        Ok(())
+    }
+
+    exercise(&InMemoryStore::new()).await?;
+
+    let dir = std::env::temp_dir().join(format!("igw-memory-{}", rand::random::<u64>()));
+    exercise(&FileStore::new(&dir)?).await?;
+    assert!(dir.join("user%2F42.jsonl").exists());
+    std::fs::remove_dir_all(&dir).unwrap();
+    Ok(())
+}
+
+#[test]
+fn test_image_content_part_detail() {
+    let part = ImageContentPart::new("https://example.com/chart.png");
+    assert_eq!(part.image_url.detail, ImageUrlDetail::Auto);
+
+    let part = part.with_detail(ImageUrlDetail::Low);
+    assert_eq!(
+        serde_json::to_value(ContentPart::from(part)).unwrap(),
+        json!({
+            "type": "image_url",
+            "image_url": {"url": "https://example.com/chart.png", "detail": "low"}
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_count_tokens_remote() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+    let mock = server
+        .mock("POST", "/v1/proxy/anthropic/messages/count_tokens")
+        .match_body(Matcher::Json(json!({
+            "model": "claude-sonnet-4",
+            "system": "Be brief.",
+            "messages": [
+                {"role": "user", "content": "Hi"},
+                {"role": "assistant", "content": "Hello!"},
+                {"role": "user", "content": "Describe this"},
+                {"role": "assistant", "content": [{
+                    "type": "tool_use",
+                    "id": "call_1",
+                    "name": "lookup",
+                    "input": {"query": "a.png"}
+                }]},
+                {"role": "user", "content": [{
+                    "type": "tool_result",
+                    "tool_use_id": "call_1",
+                    "content": "A bar chart"
+                }]}
+            ]
+        })))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"input_tokens": 21}"#)
+        .create();
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()));
+
+    let messages = [
+        Message::system("Be brief."),
+        Message::user("Hi"),
+        Message::assistant("Hello!"),
+        Message::user_with_images("Describe this", ["https://example.com/a.png"]),
+        Message {
+            tool_calls: vec![ChatCompletionMessageToolCall {
+                id: "call_1".into(),
+                type_: ChatCompletionToolType::Function,
+                function: ChatCompletionMessageToolCallFunction {
+                    name: "lookup".to_string(),
+                    arguments: r#"{"query":"a.png"}"#.to_string(),
+                },
+                extra_content: None,
+            }],
+            ..Message::assistant("")
+        },
+        Message::tool("call_1", "A bar chart"),
+    ];
+    let tokens = client
+        .count_tokens_remote(
+            Provider::Anthropic,
+            ModelRef::new(Provider::Anthropic, "claude-sonnet-4"),
+            &messages,
+        )
+        .await?;
+    assert_eq!(tokens, 21);
+    mock.assert();
+
+    let result = client
+        .count_tokens_remote(Provider::Openai, "gpt-4o", &messages)
+        .await;
+    assert!(matches!(
+        result,
+        Err(GatewayError::TokenCountingUnsupported(Provider::Openai))
+    ));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_validate_request() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+    let models = |provider: &str, id: &str| {
+        json!({
+            "object": "list",
+            "data": [{
+                "id": id,
+                "object": "model",
+                "created": 1686935002,
+                "owned_by": provider,
+                "served_by": provider,
+                "context_window": {"tokens": 20, "source": "provider"}
+            }]
+        })
+        .to_string()
+    };
+    let openai_models = server
+        .mock("GET", "/v1/models?provider=openai&include=context_window")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(models("openai", "openai/gpt-4o"))
+        .expect(3)
+        .create();
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()));
+
+    // Estimated: 2 + 4 tokens.
+    client
+        .validate_request(Provider::Openai, "gpt-4o", &[Message::user("Hi there")])
+        .await?;
+
+    // Estimated: 20 + 4 tokens.
+    let long = Message::user("x".repeat(80));
+    let result = client
+        .validate_request(Provider::Openai, "gpt-4o", std::slice::from_ref(&long))
+        .await;
+    assert!(matches!(
+        result,
+        Err(GatewayError::ContextWindowExceeded {
+            needed: 24,
+            available: 20
+        })
+    ));
+
+    // Models without a known window pass.
+    client
+        .validate_request(Provider::Openai, "gpt-unknown", std::slice::from_ref(&long))
+        .await?;
+    openai_models.assert();
+
+    let anthropic_models = server
+        .mock(
+            "GET",
+            "/v1/models?provider=anthropic&include=context_window",
+        )
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(models("anthropic", "anthropic/claude-sonnet-4"))
+        .expect(2)
+        .create();
+    let count = server
+        .mock("POST", "/v1/proxy/anthropic/messages/count_tokens")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"input_tokens": 21}"#)
+        .create();
+    let result = client
+        .validate_request(
+            Provider::Anthropic,
+            "claude-sonnet-4",
+            &[Message::user("Hi")],
+        )
+        .await;
+    assert!(matches!(
+        result,
+        Err(GatewayError::ContextWindowExceeded {
+            needed: 21,
+            available: 20
+        })
+    ));
+    count.assert();
+    count.remove();
+
+    // A failed count falls back to the estimate: 20 + 4 tokens.
+    let failed = server
+        .mock("POST", "/v1/proxy/anthropic/messages/count_tokens")
+        .with_status(500)
+        .create();
+    let result = client
+        .validate_request(Provider::Anthropic, "claude-sonnet-4", &[long])
+        .await;
+    assert!(matches!(
+        result,
+        Err(GatewayError::ContextWindowExceeded {
+            needed: 24,
+            available: 20
+        })
+    ));
+    anthropic_models.assert();
+    failed.assert();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_auto_max_tokens() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+    let models = server
+        .mock("GET", "/v1/models?provider=openai&include=context_window")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            json!({
+                "object": "list",
+                "data": [{
+                    "id": "openai/gpt-4o",
+                    "object": "model",
+                    "created": 1686935002,
+                    "owned_by": "openai",
+                    "served_by": "openai",
+                    "context_window": {"tokens": 100, "source": "provider"}
+                }]
+            })
+            .to_string(),
+        )
+        .expect(1)
+        .create();
+    let mut completion = |max_tokens: i64, hits: usize| {
+        server
+            .mock("POST", "/v1/chat/completions?provider=openai")
+            .match_body(Matcher::PartialJson(json!({"max_tokens": max_tokens})))
+            .expect(hits)
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                json!({
+                    "id": "chatcmpl-1",
+                    "object": "chat.completion",
+                    "created": 1,
+                    "model": "gpt-4o",
+                    "choices": [{
+                        "index": 0,
+                        "finish_reason": "stop",
+                        "message": {"role": "assistant", "content": "Hi"}
+                    }]
+                })
+                .to_string(),
+            )
+            .create()
+    };
+    // Window 100, estimated prompt 2 + 4 tokens, reserve 20.
+    let budgeted = completion(74, 2);
+    // The lower of the budget and the client's own limit.
+    let capped = completion(50, 1);
+
+    // Concurrent calls share one lookup of the window.
+    let client =
+        InferenceGatewayClient::new(&format!("{}/v1", server.url())).with_auto_max_tokens(20);
+    let generate =
+        || client.generate_content(Provider::Openai, "gpt-4o", vec![Message::user("Hi there")]);
+    let (first, second) = tokio::join!(generate(), generate());
+    first?;
+    second?;
+    budgeted.assert();
+
+    let capped_client = client.clone().with_max_tokens(Some(50));
+    capped_client
+        .generate_content(Provider::Openai, "gpt-4o", vec![Message::user("Hi there")])
+        .await?;
+    capped.assert();
+
+    // Estimated: 300 + 4 tokens, beyond the window before the reserve.
+    let result = client
+        .generate_content(
+            Provider::Openai,
+            "gpt-4o",
+            vec![Message::user("x".repeat(1200))],
+        )
+        .await;
+    assert!(matches!(
+        result,
+        Err(GatewayError::ContextWindowExceeded {
+            needed: 324,
+            available: 100
+        })
+    ));
+    models.assert();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cost_estimation() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+    let models = server
+        .mock("GET", "/v1/models?include=pricing")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            json!({
+                "object": "list",
+                "data": [
+                    {
+                        "id": "openai/gpt-4o",
+                        "object": "model",
+                        "created": 1,
+                        "owned_by": "openai",
+                        "served_by": "openai",
+                        "pricing": {
+                            "currency": "USD",
+                            "input_per_token": "0.0000025",
+                            "output_per_token": "0.00001",
+                            "cache_read_per_token": "0.00000125",
+                            "source": "provider",
+                            "updated_at": "2025-01-01T00:00:00Z"
+                        }
+                    },
+                    {
+                        "id": "groq/llama-3.1-8b-instant",
+                        "object": "model",
+                        "created": 1,
+                        "owned_by": "groq",
+                        "served_by": "groq"
+                    }
+                ]
+            })
+            .to_string(),
+        )
+        .create();
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()));
+    let table = client.pricing_table().await?;
+    models.assert();
+
+    let usage = |prompt: i64, cached: i64, completion: i64| CompletionUsage {
+        prompt_tokens: prompt,
+        completion_tokens: completion,
+        total_tokens: prompt + completion,
+        prompt_tokens_details: Some(CompletionUsagePromptTokensDetails {
+            audio_tokens: 0,
+            cached_tokens: cached,
+        }),
+        completion_tokens_details: None,
+    };
+    let close = |a: f64, b: f64| (a - b).abs() < 1e-12;
+
+    // Cached prompt tokens are charged at the cache read price, and model
+    // ids may carry their provider.
+    let cost = table
+        .estimate_cost(&usage(1_000, 400, 200), Provider::Openai, "openai/gpt-4o")
+        .unwrap();
+    assert!(close(cost.input, 600.0 * 0.0000025 + 400.0 * 0.00000125));
+    assert!(close(cost.output, 200.0 * 0.00001));
+    assert_eq!(cost.currency, "USD");
+    assert!(
+        table
+            .estimate_cost(&usage(10, 0, 10), Provider::Groq, "llama-3.1-8b-instant")
+            .is_none()
+    );
+
+    // Prices set at runtime reach trackers sharing the table.
+    let session = CostTracker::new(table.clone());
+    session.record(Provider::Openai, "gpt-4o", &usage(1_000, 0, 1_000));
+    assert!(
+        session
+            .record(
+                Provider::Groq,
+                "llama-3.1-8b-instant",
+                &usage(1_000, 0, 1_000)
+            )
+            .is_none()
+    );
+    table.set_price(
+        Provider::Groq,
+        "llama-3.1-8b-instant",
+        ModelPrice::per_million(0.05, 0.08),
+    );
+    session.record(
+        Provider::Groq,
+        "llama-3.1-8b-instant",
+        &usage(1_000, 0, 1_000),
+    );
+    assert_eq!(session.unpriced(), 1);
+    assert_eq!(session.by_model().len(), 2);
+    assert!(close(
+        session.totals()["USD"],
+        0.0025 + 0.01 + 0.00005 + 0.00008
+    ));
+    session.reset();
+    assert!(session.totals().is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_select_model() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+    let pricing = |input: &str, output: &str| {
+        json!({
+            "currency": "USD",
+            "input_per_token": input,
+            "output_per_token": output,
+            "source": "provider",
+            "updated_at": "2025-01-01T00:00:00Z"
+        })
+    };
+    let models = server
+        .mock("GET", "/v1/models?include=pricing")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            json!({
+                "object": "list",
+                "data": [
+                    {
+                        "id": "openai/gpt-4o",
+                        "object": "model",
+                        "created": 1,
+                        "owned_by": "openai",
+                        "served_by": "openai",
+                        "pricing": pricing("0.0000025", "0.00001")
+                    },
+                    {
+                        "id": "groq/llama-3.1-8b-instant",
+                        "object": "model",
+                        "created": 1,
+                        "owned_by": "groq",
+                        "served_by": "groq"
+                    },
+                    {
+                        "id": "openai/gpt-4o-mini",
+                        "object": "model",
+                        "created": 1,
+                        "owned_by": "openai",
+                        "served_by": "openai",
+                        "pricing": pricing("0.00000015", "0.0000006")
+                    }
+                ]
+            })
+            .to_string(),
+        )
+        .expect(3)
+        .create();
+
+    let selector = ModelSelector::new()
+        // Not served by the gateway, so never picked.
+        .with_candidate(
+            Candidate::new(Provider::Anthropic, "claude-sonnet-5")
+                .with_capability(Capability::Vision),
+        )
+        .with_candidate(
+            Candidate::new(Provider::Openai, "gpt-4o")
+                .with_capability(Capability::Tools)
+                .with_capability(Capability::Vision),
+        )
+        .with_candidate(
+            Candidate::new(Provider::Groq, "llama-3.1-8b-instant")
+                .with_capability(Capability::Tools)
+                .with_latency(LatencyClass::Fast),
+        )
+        .with_candidate(
+            Candidate::new(Provider::Openai, "gpt-4o-mini")
+                .with_capability(Capability::Vision)
+                .with_latency(LatencyClass::Fast),
+        );
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()));
+
+    let (provider, model) = client
+        .select_model(
+            &selector,
+            &SelectionPolicy::new().with_max_latency(LatencyClass::Fast),
+        )
+        .await?;
+    assert_eq!(
+        (provider, model.model()),
+        (Provider::Groq, "llama-3.1-8b-instant")
+    );
+
+    // The Groq model has no price, so a cost limit skips it.
+    let (provider, model) = client
+        .select_model(
+            &selector,
+            &SelectionPolicy::new()
+                .require(Capability::Vision)
+                .with_max_cost_per_1k_tokens(0.001),
+        )
+        .await?;
+    assert_eq!((provider, model.model()), (Provider::Openai, "gpt-4o-mini"));
+
+    let result = client
+        .select_model(
+            &selector,
+            &SelectionPolicy::new().require(Capability::Reasoning),
+        )
+        .await;
+    assert!(matches!(result, Err(GatewayError::NoModelSelected)));
+    models.assert();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_create_embeddings() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+    // [0.5, -1.0] as little-endian f32s.
+    let encoded = "AAAAPwAAgL8=";
+    let mock = server
+        .mock("POST", "/v1/proxy/openai/embeddings")
+        .match_body(Matcher::Json(json!({
+            "model": "text-embedding-3-small",
+            "input": ["first", "second"],
+            "dimensions": 2,
+            "encoding_format": "base64"
+        })))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            json!({
+                "object": "list",
+                "model": "text-embedding-3-small",
+                "data": [
+                    {"object": "embedding", "index": 1, "embedding": [0.25, 0.75]},
+                    {"object": "embedding", "index": 0, "embedding": encoded}
+                ],
+                "usage": {"prompt_tokens": 2, "total_tokens": 2}
+            })
+            .to_string(),
+        )
+        .create();
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()));
+
+    let request = EmbeddingRequest::new("text-embedding-3-small", ["first", "second"])
+        .with_dimensions(2)
+        .with_encoding_format(EncodingFormat::Base64);
+    let response = client.create_embeddings(Provider::Openai, request).await?;
+    mock.assert();
+
+    assert_eq!(response.model, "text-embedding-3-small");
+    assert_eq!(
+        response.usage,
+        Some(EmbeddingUsage {
+            prompt_tokens: 2,
+            total_tokens: 2
+        })
+    );
+    assert_eq!(response.into_vectors(), [vec![0.5, -1.0], vec![0.25, 0.75]]);
+
+    let missing = server
+        .mock("POST", "/v1/proxy/mistral/embeddings")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            json!({
+                "model": "mistral-embed",
+                "data": [{"index": 1, "embedding": [0.25]}]
+            })
+            .to_string(),
+        )
+        .create();
+    let request = EmbeddingRequest::new("mistral-embed", ["first", "second"]);
+    let error = client
+        .create_embeddings(Provider::Mistral, request)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        &error,
+        GatewayError::EmbeddingsMismatch { expected: 2, indices } if indices == &[1]
+    ));
+    assert_eq!(error.kind(), GatewayErrorKind::Protocol);
+    missing.assert();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_embed_stream_batches_in_order() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+    let mut batch = |input: &[&str], status: usize| {
+        let data: Vec<_> = (0..input.len())
+            .map(|i| json!({"index": i, "embedding": [input[i].len() as f32]}))
+            .collect();
+        server
+            .mock("POST", "/v1/proxy/openai/embeddings")
+            .match_body(Matcher::PartialJson(json!({
+                "model": "text-embedding-3-small",
+                "input": input,
+                "dimensions": 1
+            })))
+            .with_status(status)
+            .with_header("content-type", "application/json")
+            .with_body(json!({"model": "text-embedding-3-small", "data": data}).to_string())
+            .create()
+    };
+    let mocks = [
+        batch(&["a", "bb"], 200),
+        batch(&["ccc", "dddd"], 500),
+        batch(&["eeeee"], 200),
+    ];
+
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()));
+    let pipeline = EmbeddingPipeline::new("text-embedding-3-small")
+        .with_batch_size(2)
+        .with_concurrency(2)
+        .with_dimensions(1);
+    let input = futures_util::stream::iter(["a", "bb", "ccc", "dddd", "eeeee"]);
+    let results: Vec<_> = client
+        .embed_stream(Provider::Openai, pipeline, input)
+        .collect()
+        .await;
+
+    assert_eq!(results.len(), 4);
+    let embedded = |result: &Result<EmbeddedText, GatewayError>| {
+        let embedded = result.as_ref().unwrap();
+        (
+            embedded.index,
+            embedded.text.clone(),
+            embedded.embedding.clone(),
+        )
+    };
+    assert_eq!(embedded(&results[0]), (0, "a".to_string(), vec![1.0]));
+    assert_eq!(embedded(&results[1]), (1, "bb".to_string(), vec![2.0]));
+    // The failed batch yields its error and the pipeline carries on.
+    assert!(matches!(
+        results[2].as_ref().map_err(GatewayError::root),
+        Err(GatewayError::InternalError(_))
+    ));
+    assert_eq!(embedded(&results[3]), (4, "eeeee".to_string(), vec![5.0]));
+    for mock in mocks {
+        mock.assert();
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_poll_until_complete() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+    let accepted = server
+        .mock("GET", "/v1/batches/batch_1")
+        .with_status(202)
+        .with_header("retry-after", "0")
+        .create();
+    let running = server
+        .mock("GET", "/v1/batches/batch_1")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"id": "batch_1", "status": "in_progress"}"#)
+        .create();
+    let done = server
+        .mock("GET", "/v1/batches/batch_1")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"id": "batch_1", "status": "completed", "output_file_id": "file-1"}"#)
+        .create();
+    let task = server
+        .mock("GET", "/v1/tasks/task_1")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"id": "task_1", "status": {"state": "input-required"}}"#)
+        .create();
+    let stuck = server
+        .mock("GET", "/v1/batches/batch_2")
+        .with_status(202)
+        .expect_at_least(1)
+        .create();
+
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()));
+    let policy = PollPolicy::new().with_intervals(
+        std::time::Duration::from_millis(1),
+        std::time::Duration::from_millis(4),
+    );
+
+    // Absolute paths and paths relative to the API URL both resolve.
+    let outcome: JobOutcome<serde_json::Value> = client
+        .poll_until_complete(&Job::new("/v1/batches/batch_1"), &policy)
+        .await?;
+    assert!(outcome.is_completed());
+    assert_eq!(outcome.body["output_file_id"], "file-1");
+    accepted.assert();
+    running.assert();
+    done.assert();
+
+    let outcome: JobOutcome<serde_json::Value> = client
+        .poll_until_complete(&Job::new("tasks/task_1"), &policy)
+        .await?;
+    assert_eq!(outcome.state, JobState::InputRequired);
+    task.assert();
+
+    let result = client
+        .poll_until_complete::<serde_json::Value>(
+            &Job::new("batches/batch_2"),
+            &policy.with_deadline(std::time::Duration::from_millis(20)),
+        )
+        .await;
+    let error = result.unwrap_err();
+    assert_eq!(error.kind(), GatewayErrorKind::Timeout);
+    assert!(matches!(
+        &error,
+        GatewayError::JobDeadlineExceeded { location, .. } if location == "batches/batch_2"
+    ));
+    stuck.assert();
+
+    assert_eq!(JobState::from_status("cancelled"), JobState::Cancelled);
+    assert_eq!(JobState::from_status("canceled"), JobState::Cancelled);
+    assert_eq!(JobState::from_status("expired"), JobState::Expired);
+    assert_eq!(JobState::from_status("finalizing"), JobState::Pending);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_download_artifact_resumes_and_verifies() -> Result<(), GatewayError> {
+    // SHA-256 of "hello world".
+    let digest = "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9";
+    let mut server = Server::new_async().await;
+    let whole = server
+        .mock("GET", "/v1/files/file-1/content")
+        .match_header("range", Matcher::Missing)
+        .with_status(200)
+        .with_body("hello world")
+        .expect(2)
+        .create();
+    let rest = server
+        .mock("GET", "/v1/files/file-1/content")
+        .match_header("range", "bytes=6-")
+        .with_status(206)
+        .with_header("content-range", "bytes 6-10/11")
+        .with_body("world")
+        .create();
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()));
+
+    let mut buffer = Vec::new();
+    let artifact = Artifact::new("files/file-1/content").with_sha256(digest.to_uppercase());
+    let summary = client.download_artifact(&artifact, &mut buffer).await?;
+    assert_eq!(buffer, b"hello world");
+    assert_eq!((summary.bytes, summary.sha256.as_str()), (11, digest));
+
+    // A file left partial by an earlier attempt is continued from its end.
+    let path = std::env::temp_dir().join(format!("download-{}", std::process::id()));
+    std::fs::write(&path, "hello ").unwrap();
+    let summary = client.download_artifact_to_file(&artifact, &path).await?;
+    assert_eq!(std::fs::read(&path).unwrap(), b"hello world");
+    assert_eq!(summary.bytes, 11);
+    std::fs::remove_file(&path).unwrap();
+
+    let mismatched = Artifact::new("files/file-1/content").with_sha256("00");
+    let result = client.download_artifact(&mismatched, &mut Vec::new()).await;
+    let error = result.unwrap_err();
+    assert_eq!(error.kind(), GatewayErrorKind::Protocol);
+    assert!(matches!(
+        &error,
+        GatewayError::ChecksumMismatch { actual, .. } if actual == digest
+    ));
+    whole.assert();
+    rest.assert();
+
+    // Servers that ignore the range resend the whole artifact, and the
+    // part already on disk is skipped.
+    let ignoring = server
+        .mock("GET", "/v1/files/file-2/content")
+        .with_status(200)
+        .with_body("hello world")
+        .create();
+    std::fs::write(&path, "hello").unwrap();
+    let summary = client
+        .download_artifact_to_file(&Artifact::new("/v1/files/file-2/content"), &path)
+        .await?;
+    assert_eq!(std::fs::read(&path).unwrap(), b"hello world");
+    assert_eq!(summary.sha256, digest);
+    std::fs::remove_file(&path).unwrap();
+    ignoring.assert();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_raw_request_and_stream() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+    let agents = server
+        .mock("GET", "/v1/a2a/agents?limit=1")
+        .match_header("authorization", "Bearer secret")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"[{"name": "planner"}]"#)
+        .create();
+    let cancel = server
+        .mock("DELETE", "/v1/a2a/tasks/task_1")
+        .with_status(204)
+        .create();
+    let missing = server
+        .mock("POST", "/v1/a2a/tasks")
+        .match_body(Matcher::Json(json!({"agent": "ghost"})))
+        .with_status(404)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"error": "no such agent"}"#)
+        .create();
+    let stream = server
+        .mock("POST", "/v1/a2a/tasks/stream")
+        .match_body(Matcher::Json(json!({"agent": "planner"})))
+        .with_status(200)
+        .with_header("content-type", "text/event-stream")
+        .with_body("event: status\ndata: {\"state\": \"working\"}\n\ndata: done\n\n")
+        .create();
+
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url())).with_token("secret");
+    let listed: serde_json::Value = client
+        .raw_request(Method::GET, "a2a/agents?limit=1", None)
+        .await?;
+    assert_eq!(listed[0]["name"], "planner");
+    client
+        .raw_request::<()>(Method::DELETE, "/v1/a2a/tasks/task_1", None)
+        .await?;
+    let result = client
+        .raw_request::<serde_json::Value>(
+            Method::POST,
+            "a2a/tasks",
+            Some(json!({"agent": "ghost"})),
+        )
+        .await;
+    assert!(matches!(result, Err(GatewayError::NotFound(_))));
+
+    let events: Vec<SSEvents> = client
+        .raw_stream("a2a/tasks/stream", json!({"agent": "planner"}))
+        .try_collect()
+        .await?;
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].event.as_deref(), Some("status"));
+    assert_eq!(events[1].data, "done");
+
+    agents.assert();
+    cancel.assert();
+    missing.assert();
+    stream.assert();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_chat_completion_options() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+    let sampled = json!({
+        "temperature": 0.2,
+        "top_p": 0.9,
+        "top_k": 40,
+        "presence_penalty": 0.5,
+        "seed": 7,
+        "stop": ["END"]
+    });
+    let completion = server
+        .mock("POST", "/v1/chat/completions?provider=openai&route=canary")
+        .match_body(Matcher::PartialJson(sampled.clone()))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            json!({
+                "id": "chatcmpl-1",
+                "object": "chat.completion",
+                "created": 1,
+                "model": "gpt-4o",
+                "choices": [{
+                    "index": 0,
+                    "finish_reason": "stop",
+                    "message": {"role": "assistant", "content": "Hi"}
+                }]
+            })
+            .to_string(),
+        )
+        .create();
+    let stream = server
+        .mock("POST", "/v1/chat/completions?provider=deepseek")
+        .match_body(Matcher::AllOf(vec![
+            Matcher::PartialJson(sampled),
+            Matcher::PartialJson(json!({"stream": true})),
+        ]))
+        .with_status(200)
+        .with_header("content-type", "text/event-stream")
+        .with_body(format!(
+            "data: {}\n\ndata: [DONE]\n\n",
+            content_chunk(0, "Hi")
+        ))
+        .create();
+
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url())).with_seed(1);
+    let options = ChatCompletionOptions::new()
+        .with_temperature(0.2)
+        .with_top_p(0.9)
+        .with_top_k(40)
+        .with_presence_penalty(0.5)
+        .with_seed(7)
+        .with_stop("END");
+    client
+        .generate_content_with_options(
+            Provider::Openai,
+            "gpt-4o",
+            vec![user_message("Hi")],
+            options
+                .clone()
+                .with_request_options(RequestOptions::new().with_query_param("route", "canary")),
+        )
+        .await?;
+    let events: Vec<SSEvents> = client
+        .generate_content_stream_with_options(
+            Provider::Deepseek,
+            "deepseek-v4-flash",
+            vec![user_message("Hi")],
+            options,
+        )
+        .try_collect()
+        .await?;
+    assert_eq!(events.len(), 2);
+
+    completion.assert();
+    stream.assert();
+    Ok(())
+}
+
+#[cfg(feature = "structured")]
+#[tokio::test]
+async fn test_generate_structured() -> Result<(), GatewayError> {
+    #[derive(Debug, PartialEq, serde::Deserialize, schemars::JsonSchema)]
+    struct Forecast {
+        city: String,
+        celsius: f64,
+    }
+
+    let completion = |content: &str| {
+        json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion",
+            "created": 1,
+            "model": "gpt-4o",
+            "choices": [{
+                "index": 0,
+                "finish_reason": "stop",
+                "message": {"role": "assistant", "content": content}
+            }]
+        })
+        .to_string()
+    };
+    let mut server = Server::new_async().await;
+    let valid = server
+        .mock("POST", "/v1/chat/completions?provider=openai")
+        .match_body(Matcher::PartialJson(json!({
+            "response_format": {
+                "type": "json_schema",
+                "json_schema": {
+                    "name": "Forecast",
+                    "schema": {
+                        "type": "object",
+                        "properties": {
+                            "city": {"type": "string"},
+                            "celsius": {"type": "number"}
+                        },
+                        "required": ["city", "celsius"]
+                    }
+                }
+            }
+        })))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(completion(r#"{"city":"Paris","celsius":21.5}"#))
+        .expect(1)
+        .create();
+    let invalid = server
+        .mock("POST", "/v1/chat/completions?provider=openai")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(completion("It's 21.5 degrees in Paris"))
+        .create();
+
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()));
+    let messages = vec![user_message("Weather in Paris?")];
+    let forecast: Forecast = client
+        .generate_structured(Provider::Openai, "gpt-4o", messages.clone())
+        .await?;
+    assert_eq!(
+        forecast,
+        Forecast {
+            city: "Paris".to_string(),
+            celsius: 21.5
+        }
+    );
+
+    let error = client
+        .generate_structured::<Forecast>(Provider::Openai, "gpt-4o", messages)
+        .await
+        .unwrap_err();
+    assert_eq!(error.kind(), GatewayErrorKind::Protocol);
+    match &error {
+        GatewayError::StructuredOutput { content, .. } => {
+            assert_eq!(content, "It's 21.5 degrees in Paris")
+        }
+        other => panic!("expected a structured output error, got {other:?}"),
+    }
+
+    valid.assert();
+    invalid.assert();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_run_with_tools() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+    let calls_tools = server
+        .mock("POST", "/v1/chat/completions?provider=openai")
+        .match_body(Matcher::PartialJson(json!({
+            "tools": [{"type": "function", "function": {"name": "get_weather"}}]
+        })))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            json!({
+                "id": "chatcmpl-1",
+                "object": "chat.completion",
+                "created": 1,
+                "model": "gpt-4o",
+                "choices": [{
+                    "index": 0,
+                    "finish_reason": "tool_calls",
+                    "message": {
+                        "role": "assistant",
+                        "content": "",
+                        "tool_calls": [
+                            {
+                                "id": "call_1",
+                                "type": "function",
+                                "function": {"name": "get_weather", "arguments": "{\"city\":\"Paris\"}"}
+                            },
+                            {
+                                "id": "call_2",
+                                "type": "function",
+                                "function": {"name": "book_flight", "arguments": "{}"}
+                            }
+                        ]
+                    }
+                }]
+            })
+            .to_string(),
+        )
+        .expect(1)
+        .create();
+    let answers = server
+        .mock("POST", "/v1/chat/completions?provider=openai")
+        .match_body(Matcher::AllOf(vec![
+            Matcher::Regex(r#""tool_call_id":"call_1""#.to_string()),
+            Matcher::Regex(r#""content":"Sunny in Paris""#.to_string()),
+            Matcher::Regex(r#""content":"Error: unknown tool `book_flight`""#.to_string()),
+        ]))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            json!({
+                "id": "chatcmpl-2",
+                "object": "chat.completion",
+                "created": 1,
+                "model": "gpt-4o",
+                "choices": [{
+                    "index": 0,
+                    "finish_reason": "stop",
+                    "message": {"role": "assistant", "content": "It's sunny in Paris"}
+                }]
+            })
+            .to_string(),
+        )
+        .create();
+
+    let weather = ChatCompletionTool {
+        type_: ChatCompletionToolType::Function,
+        function: FunctionObject {
+            name: "get_weather".to_string(),
+            description: Some("Get the weather in a city".to_string()),
+            parameters: Some(function_params(json!({
+                "type": "object",
+                "properties": {"city": {"type": "string"}},
+                "required": ["city"]
+            }))),
+            strict: false,
+        },
+    };
+    let registry = ToolRegistry::new().with_tool(weather, |args| async move {
+        let city = args["city"].as_str().ok_or("city is required")?;
+        Ok::<_, &str>(format!("Sunny in {city}"))
+    });
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()));
+    let run = client
+        .run_with_tools(
+            Provider::Openai,
+            "gpt-4o",
+            vec![user_message("Weather in Paris?")],
+            &registry,
+        )
+        .await?;
+
+    assert_eq!(run.rounds, 1);
+    assert_eq!(
+        run.response.choices[0].message.content.as_text(),
+        Some("It's sunny in Paris")
+    );
+    let roles: Vec<_> = run.messages.iter().map(|m| m.role).collect();
+    assert_eq!(
+        roles,
+        vec![
+            MessageRole::User,
+            MessageRole::Assistant,
+            MessageRole::Tool,
+            MessageRole::Tool,
+            MessageRole::Assistant,
+        ]
+    );
+    calls_tools.assert();
+    answers.assert();
+
+    // A model that keeps calling tools is cut off.
+    answers.remove();
+    let calls_tools = server
+        .mock("POST", "/v1/chat/completions?provider=openai")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            json!({
+                "id": "chatcmpl-3",
+                "object": "chat.completion",
+                "created": 1,
+                "model": "gpt-4o",
+                "choices": [{
+                    "index": 0,
+                    "finish_reason": "tool_calls",
+                    "message": {
+                        "role": "assistant",
+                        "content": "",
+                        "tool_calls": [{
+                            "id": "call_3",
+                            "type": "function",
+                            "function": {"name": "get_weather", "arguments": "{\"city\":\"Oslo\"}"}
+                        }]
+                    }
+                }]
+            })
+            .to_string(),
+        )
+        .expect(3)
+        .create();
+    let error = client
+        .run_with_tools(
+            Provider::Openai,
+            "gpt-4o",
+            vec![user_message("Weather?")],
+            &registry.clone().with_max_rounds(2),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(error, GatewayError::ToolRoundsExceeded(2)));
+    calls_tools.assert();
+    Ok(())
+}
+
+#[cfg(feature = "macros")]
+#[tokio::test]
+async fn test_tool_macro() {
+    use crate::{Tool, tool};
+
+    /// Get the current weather
+    /// in a city.
+    #[tool]
+    async fn get_weather(city: String, days: Option<u32>, tags: Vec<String>) -> String {
+        format!(
+            "{city}: sunny for {} days ({})",
+            days.unwrap_or(1),
+            tags.len()
+        )
+    }
+
+    #[tool(name = "convert", description = "Convert an amount")]
+    fn convert_currency(amount: f64, to: String) -> Result<String, String> {
+        if to == "EUR" {
+            Ok(format!("{:.2} EUR", amount * 0.9))
+        } else {
+            Err(format!("unsupported currency {to}"))
+        }
+    }
+
+    let definition = GetWeather.definition();
+    assert_eq!(definition.function.name, "get_weather");
+    assert_eq!(
+        definition.function.description.as_deref(),
+        Some("Get the current weather in a city.")
+    );
+    assert_eq!(
+        serde_json::to_value(definition.function.parameters.unwrap()).unwrap(),
+        json!({
+            "type": "object",
+            "properties": {
+                "city": {"type": "string"},
+                "days": {"type": "integer"},
+                "tags": {"type": "array", "items": {"type": "string"}}
+            },
+            "required": ["city", "tags"]
+        })
+    );
+    assert_eq!(
+        GetWeather
+            .call(json!({"city": "Paris", "tags": ["a"]}))
+            .await
+            .unwrap(),
+        "Paris: sunny for 1 days (1)"
+    );
+    assert!(
+        GetWeather
+            .call(json!({"tags": []}))
+            .await
+            .unwrap_err()
+            .contains("invalid argument `city`")
+    );
+
+    assert_eq!(ConvertCurrency.definition().function.name, "convert");
+    assert_eq!(
+        ConvertCurrency
+            .call(json!({"amount": 10.0, "to": "EUR"}))
+            .await
+            .unwrap(),
+        "9.00 EUR"
+    );
+    assert_eq!(
+        ConvertCurrency
+            .call(json!({"amount": 10.0, "to": "JPY"}))
+            .await
+            .unwrap_err(),
+        "unsupported currency JPY"
+    );
+
+    // The plain function is still there, and the tool registers.
+    assert!(convert_currency(1.0, "EUR".to_string()).is_ok());
+    let registry = ToolRegistry::new()
+        .with_tool_impl(GetWeather)
+        .with_tool_impl(ConvertCurrency);
+    assert_eq!(registry.tools().len(), 2);
+}
+
+#[tokio::test]
+async fn test_chat_completion_request_builder() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+    let tool = ChatCompletionTool {
+        type_: ChatCompletionToolType::Function,
+        function: FunctionObject {
+            name: "lookup_order".to_string(),
+            description: Some("Look up an order by id".to_string()),
+            parameters: None,
+            strict: false,
+        },
+    };
+    let completion = server
+        .mock("POST", "/v1/chat/completions?provider=groq")
+        .match_body(Matcher::PartialJson(json!({
+            "model": "llama-3.3-70b-versatile",
+            "max_tokens": 20,
+            "tools": [{"type": "function", "function": {"name": "lookup_order"}}],
+            "messages": [
+                {"role": "system", "content": "Be brief"},
+                {"role": "user", "content": "Where is order 7?"}
+            ]
+        })))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            json!({
+                "id": "chatcmpl-1",
+                "object": "chat.completion",
+                "created": 1,
+                "model": "llama-3.3-70b-versatile",
+                "choices": [{
+                    "index": 0,
+                    "finish_reason": "stop",
+                    "message": {"role": "assistant", "content": "Shipped"}
+                }]
+            })
+            .to_string(),
+        )
+        .create();
+    let stream = server
+        .mock("POST", "/v1/chat/completions?provider=groq")
+        .match_body(Matcher::PartialJson(json!({
+            "model": "groq/llama-3.3-70b-versatile",
+            "stream": true
+        })))
+        .with_status(200)
+        .with_header("content-type", "text/event-stream")
+        .with_body(format!(
+            "data: {}\n\ndata: [DONE]\n\n",
+            content_chunk(0, "Hi")
+        ))
+        .create();
+
+    let client =
+        InferenceGatewayClient::new(&format!("{}/v1", server.url())).with_max_tokens(Some(100));
+    let response = client
+        .chat_completion("llama-3.3-70b-versatile")
+        .with_provider(Provider::Groq)
+        .with_messages(vec![Message::system("Be brief")])
+        .with_message(user_message("Where is order 7?"))
+        .with_tools(vec![tool])
+        .with_max_tokens(20)
+        .send()
+        .await?;
+    assert_eq!(
+        response.choices[0].message.content.as_text(),
+        Some("Shipped")
+    );
+
+    let model: ModelRef = "groq/llama-3.3-70b-versatile".parse().unwrap();
+    let events: Vec<SSEvents> = client
+        .chat_completion(model)
+        .with_message(user_message("Hi"))
+        .stream()
+        .try_collect()
+        .await?;
+    assert_eq!(events.len(), 2);
+
+    let error = client
+        .chat_completion("llama-3.3-70b-versatile")
+        .with_message(user_message("Hi"))
+        .send()
+        .await
+        .unwrap_err();
+    assert!(matches!(error, GatewayError::NoProvider(model) if model == "llama-3.3-70b-versatile"));
+
+    completion.assert();
+    stream.assert();
+    Ok(())
+}
+
+#[cfg(feature = "conformance")]
+#[test]
+fn test_conformance_fixtures() {
+    use crate::conformance::{self, FIXTURES, Format, Mismatch};
+
+    for fixture in FIXTURES {
+        fixture.assert_conforms();
+    }
+
+    let mut fixture = FIXTURES[0].clone();
+    fixture.expected.total_tokens = Some(1);
+    assert!(matches!(
+        fixture.check(),
+        Err(Mismatch::Field {
+            field: "total_tokens",
+            ..
+        })
+    ));
+
+    let truncated = conformance::parse(Provider::Openai, Format::Sse, "data: {\"id\":\n\n");
+    assert!(matches!(
+        truncated,
+        Err(GatewayError::DeserializationError { .. })
+    ));
+}
+
+#[cfg(feature = "blocking")]
+#[test]
+fn test_blocking_client() -> Result<(), GatewayError> {
+    let mut server = Server::new();
+    let completion = server
+        .mock("POST", "/v1/chat/completions?provider=groq")
+        .match_body(Matcher::PartialJson(json!({"stream": false})))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            json!({
+                "id": "chatcmpl-1",
+                "object": "chat.completion",
+                "created": 1,
+                "model": "llama-3.3-70b-versatile",
+                "choices": [{
+                    "index": 0,
+                    "finish_reason": "stop",
+                    "message": {"role": "assistant", "content": "Hi there"}
+                }]
+            })
+            .to_string(),
+        )
+        .create();
+    let stream = server
+        .mock("POST", "/v1/chat/completions?provider=groq")
+        .match_body(Matcher::PartialJson(json!({"stream": true})))
+        .with_status(200)
+        .with_header("content-type", "text/event-stream")
+        .with_body(format!(
+            "data: {}\n\ndata: {}\n\ndata: [DONE]\n\n",
+            content_chunk(0, "Hi"),
+            content_chunk(0, " there")
+        ))
+        .create();
+
+    let client: crate::blocking::InferenceGatewayClient =
+        InferenceGatewayClient::new(&format!("{}/v1", server.url())).into();
+    let answer = client.ask(Provider::Groq, "llama-3.3-70b-versatile", "Hi")?;
+    assert_eq!(answer, "Hi there");
+
+    let events = client
+        .generate_content_stream(
+            Provider::Groq,
+            "llama-3.3-70b-versatile",
+            vec![user_message("Hi")],
+        )
+        .collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(events.len(), 3);
+    assert_eq!(events[2].data, "[DONE]");
+
+    completion.assert();
+    stream.assert();
+    Ok(())
+}
+
+#[cfg(feature = "mock")]
+#[tokio::test]
+async fn test_mock_client() -> Result<(), GatewayError> {
+    use crate::mock::MockInferenceGatewayClient;
+
+    let client = MockInferenceGatewayClient::new()
+        .with_text("Paris")
+        .with_completion_error(GatewayError::QueueFull)
+        .with_stream_chunks(["The capital ", "is Paris"])
+        .with_stream(vec![
+            sse_data(&content_chunk(0, "Hel")),
+            Err(GatewayError::InternalError("overloaded".to_string())),
+            sse_data(&content_chunk(0, "lo")),
+        ]);
+    let recorded = client.clone();
+
+    let answer = client
+        .ask_with_system(Provider::Openai, "gpt-4o", "Be brief", "Capital of France?")
+        .await?;
+    assert_eq!(answer, "Paris");
+    let error = client
+        .generate_content(Provider::Groq, "llama-3.3-70b", vec![user_message("Hi")])
+        .await
+        .unwrap_err();
+    assert!(matches!(error, GatewayError::QueueFull));
+    let folded = client
+        .generate_content_stream(Provider::Groq, "llama-3.3-70b", vec![user_message("Hi")])
+        .chat_chunks()
+        .fold_stream()
+        .await?;
+    assert_eq!(
+        folded.message.content.as_text(),
+        Some("The capital is Paris")
+    );
+    assert_eq!(folded.finish_reason, Some(FinishReason::Stop));
+    let events: Vec<_> = client
+        .generate_content_stream(Provider::Groq, "llama-3.3-70b", vec![user_message("Hi")])
+        .collect()
+        .await;
+    assert_eq!(events.len(), 2);
+    assert!(matches!(events[1], Err(GatewayError::InternalError(_))));
+
+    // Nothing left in the queues.
+    assert!(
+        client
+            .ask(Provider::Groq, "llama-3.3-70b", "Hi")
+            .await
+            .is_err()
+    );
+    assert!(client.list_models().await.is_err());
+    assert!(client.health_check().await?);
+
+    let calls = recorded.calls();
+    let methods: Vec<_> = calls.iter().map(|call| call.method).collect();
+    assert_eq!(
+        methods,
+        vec![
+            "ask_with_system",
+            "generate_content",
+            "generate_content_stream",
+            "generate_content_stream",
+            "ask",
+            "list_models",
+            "health_check",
+        ]
+    );
+    assert_eq!(calls[0].provider, Some(Provider::Openai));
+    assert_eq!(calls[0].model.as_deref(), Some("gpt-4o"));
+    assert_eq!(calls[0].messages.len(), 2);
+    Ok(())
+}
+
+#[cfg(feature = "loadtest")]
+#[tokio::test]
+async fn test_loadtest_reports_throughput_and_errors() {
+    use crate::loadtest::LoadTest;
+
+    let mut server = Server::new_async().await;
+    let ok = server
+        .mock("POST", "/v1/chat/completions?provider=groq")
+        .with_status(200)
+        .with_header("content-type", "text/event-stream")
+        .with_body(format!(
+            "data: {}\n\ndata: [DONE]\n\n",
+            content_chunk(0, "Hi")
+        ))
+        .expect(6)
+        .create();
+    let failing = server
+        .mock("POST", "/v1/chat/completions?provider=openai")
+        .with_status(400)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"error": "bad request"}"#)
+        .expect(3)
+        .create();
+
+    let seen = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let counter = seen.clone();
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()))
+        .with_metrics_callback(move |_| {
+            counter.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        });
+
+    let report = LoadTest::new(
+        Provider::Groq,
+        "llama-3.1-8b-instant",
+        vec![user_message("Hi")],
+    )
+    .streaming()
+    .with_concurrency(3)
+    .with_requests(6)
+    .run(&client)
+    .await;
+    assert_eq!((report.requests, report.errors), (6, 0));
+    assert_eq!(report.metrics.len(), 6);
+    assert!(report.throughput() > 0.0);
+    assert!(report.time_to_first_token_percentile(50.0).is_some());
+    assert!(report.latency_percentile(99.0) >= report.latency_percentile(50.0));
+    // The client's own callback still sees the test's calls.
+    assert_eq!(seen.load(std::sync::atomic::Ordering::Relaxed), 6);
+
+    let report = LoadTest::new(Provider::Openai, "gpt-4o", vec![user_message("Hi")])
+        .with_requests(3)
+        .run(&client)
+        .await;
+    assert_eq!((report.requests, report.errors), (3, 3));
+    assert_eq!(report.error_rate(), 1.0);
+    assert_eq!(report.time_to_first_token_percentile(50.0), None);
+
+    ok.assert();
+    failing.assert();
+}
+
+#[cfg(feature = "quantize")]
+#[test]
+fn test_quantized_embeddings() {
+    let embedding: crate::Embedding =
+        serde_json::from_value(json!({"index": 0, "embedding": [0.5, -1.0, 0.25, 0.0]})).unwrap();
+
+    let half: Vec<f32> = embedding.to_f16().iter().map(|x| x.to_f32()).collect();
+    assert_eq!(half, [0.5, -1.0, 0.25, 0.0]);
+
+    let quantized = embedding.to_i8();
+    assert_eq!(quantized.values, [64, -127, 32, 0]);
+    assert_eq!(quantized.scale, 1.0 / 127.0);
+    for (restored, original) in quantized.dequantize().iter().zip(&embedding.embedding) {
+        assert!((restored - original).abs() <= quantized.scale / 2.0);
+    }
+
+    let zeros = crate::QuantizedEmbedding::quantize(&[0.0, 0.0]);
+    assert_eq!(zeros.dequantize(), [0.0, 0.0]);
+}
+
+#[cfg(feature = "timestamps")]
+#[test]
+fn test_created_at() {
+    let model: crate::Model = serde_json::from_value(json!({
+        "id": "openai/gpt-4o",
+        "object": "model",
+        "created": 1_700_000_000,
+        "owned_by": "openai",
+        "served_by": "openai"
+    }))
+    .unwrap();
+    let created = model.created_at().unwrap();
+    assert_eq!(json!(created), json!("2023-11-14T22:13:20Z"));
+    assert_eq!(created.timestamp(), model.created);
+
+    let out_of_range = crate::Model {
+        created: i64::MAX,
+        ..model
+    };
+    assert_eq!(out_of_range.created_at(), None);
+}
+
+#[tokio::test]
+async fn test_create_speech() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+    let mock = server
+        .mock("POST", "/v1/proxy/openai/audio/speech")
+        .match_body(Matcher::Json(json!({
+            "model": "gpt-4o-mini-tts",
+            "input": "Hello there",
+            "voice": "coral",
+            "response_format": "opus",
+            "speed": 1.25
+        })))
+        .with_status(200)
+        .with_header("content-type", "audio/ogg")
+        .with_body(b"OggS\0\x02")
+        .create();
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()));
+
+    let options = SpeechOptions::new(Voice::Coral)
+        .with_format(SpeechFormat::Opus)
+        .with_speed(1.25);
+    let audio = client
+        .create_speech(Provider::Openai, "gpt-4o-mini-tts", "Hello there", options)
+        .await?;
+    assert_eq!(&audio[..], b"OggS\0\x02");
+    mock.assert();
+
+    let rejected = [
+        (
+            Provider::Openai,
+            SpeechOptions::new(Voice::Alloy).with_speed(5.0),
+        ),
+        (
+            Provider::Groq,
+            SpeechOptions::new(Voice::Alloy).with_format(SpeechFormat::Wav),
+        ),
+        (Provider::Groq, SpeechOptions::new("Fritz-PlayAI")),
+    ];
+    for (provider, options) in rejected {
+        let err = client
+            .create_speech(provider, "tts", "Hello there", options)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, GatewayError::UnsupportedSpeechOptions { .. }));
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_create_speech_stream() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+    let mock = server
+        .mock("POST", "/v1/proxy/openai/audio/speech")
+        .match_body(Matcher::PartialJson(json!({
+            "model": "gpt-4o-mini-tts",
+            "input": "Hello there",
+            "voice": "nova",
+            "response_format": "pcm"
+        })))
+        .with_status(200)
+        .with_header("content-type", "audio/pcm")
+        .with_body(vec![7u8; 64 * 1024])
+        .create();
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()));
+
+    let options = SpeechOptions::new(Voice::Nova).with_format(SpeechFormat::Pcm);
+    let chunks: Vec<bytes::Bytes> = client
+        .create_speech_stream(Provider::Openai, "gpt-4o-mini-tts", "Hello there", options)
+        .try_collect()
+        .await?;
+    let audio = chunks.concat();
+    assert_eq!(audio.len(), 64 * 1024);
+    assert!(audio.iter().all(|&byte| byte == 7));
+    mock.assert();
+
+    server
+        .mock("POST", "/v1/proxy/groq/audio/speech")
+        .with_status(401)
+        .with_body(r#"{"error":"Invalid API key"}"#)
+        .create();
+    let options = SpeechOptions::new("Fritz-PlayAI").with_format(SpeechFormat::Wav);
+    let mut stream =
+        Box::pin(client.create_speech_stream(Provider::Groq, "playai-tts", "Hello there", options));
+    let err = stream.next().await.unwrap().unwrap_err();
+    assert!(matches!(err, GatewayError::Unauthorized(_)));
+    assert!(stream.next().await.is_none());
+
+    let options = SpeechOptions::new(Voice::Alloy).with_speed(5.0);
+    let mut stream =
+        Box::pin(client.create_speech_stream(Provider::Openai, "tts", "Hello there", options));
+    let err = stream.next().await.unwrap().unwrap_err();
+    assert!(matches!(err, GatewayError::UnsupportedSpeechOptions { .. }));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_create_moderation() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+    let mock = server
+        .mock("POST", "/v1/proxy/openai/moderations")
+        .match_body(Matcher::Json(json!({
+            "model": "omni-moderation-latest",
+            "input": ["Have a nice day", "I will hurt myself"]
+        })))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            json!({
+                "id": "modr-123",
+                "model": "omni-moderation-latest",
+                "results": [
+                    {
+                        "flagged": false,
+                        "categories": { "self-harm": false, "violence": false },
+                        "category_scores": { "self-harm": 0.0001, "violence": 0.0002 }
+                    },
+                    {
+                        "flagged": true,
+                        "categories": {
+                            "self-harm": true,
+                            "self-harm/intent": true,
+                            "violence": false
+                        },
+                        "category_scores": {
+                            "self-harm": 0.91,
+                            "self-harm/intent": 0.87,
+                            "violence": 0.12
+                        }
+                    }
+                ]
+            })
+            .to_string(),
+        )
+        .create();
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()));
+
+    let response = client
+        .create_moderation(
+            Provider::Openai,
+            "omni-moderation-latest",
+            ["Have a nice day", "I will hurt myself"].as_slice(),
+        )
+        .await?;
+    mock.assert();
+    assert!(response.flagged());
+    assert!(response.results[0].flagged_categories().is_empty());
+    let flagged = &response.results[1];
+    assert_eq!(
+        flagged.flagged_categories(),
+        ["self-harm", "self-harm/intent"]
+    );
+    assert_eq!(flagged.category_scores.self_harm_intent, 0.87);
+    assert!(!flagged.categories.illicit);
+    assert_eq!(flagged.category_scores.illicit, 0.0);
+
+    server
+        .mock("POST", "/v1/proxy/groq/moderations")
+        .match_body(Matcher::PartialJson(json!({ "input": "Hello" })))
+        .with_status(404)
+        .with_body(r#"{"error":"Route not found"}"#)
+        .create();
+    let err = client
+        .create_moderation(
+            Provider::Groq,
+            "llama-guard-4-12b",
+            ModerationInput::Text("Hello".to_string()),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, GatewayError::NotFound(_)));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_create_transcription() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+    let verbose = server
+        .mock("POST", "/v1/proxy/openai/audio/transcriptions")
+        .match_body(Matcher::AllOf(vec![
+            Matcher::Regex(r#"name="model"\r\n\r\nwhisper-1\r\n"#.to_string()),
+            Matcher::Regex(r#"name="response_format"\r\n\r\nverbose_json\r\n"#.to_string()),
+            Matcher::Regex(r#"name="language"\r\n\r\nen\r\n"#.to_string()),
+            Matcher::Regex(r#"name="timestamp_granularities\[\]"\r\n\r\nword\r\n"#.to_string()),
+            Matcher::Regex(r#"filename="call.wav"\r\n"#.to_string()),
+            Matcher::Regex("RIFF....WAVEfmt ".to_string()),
+        ]))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            json!({
+                "task": "transcribe",
+                "language": "english",
+                "duration": 1.5,
+                "text": "Hello there",
+                "segments": [
+                    {"id": 0, "seek": 0, "start": 0.0, "end": 1.5, "text": "Hello there",
+                     "avg_logprob": -0.2, "no_speech_prob": 0.01}
+                ],
+                "words": [
+                    {"word": "Hello", "start": 0.0, "end": 0.6},
+                    {"word": "there", "start": 0.7, "end": 1.5}
+                ]
+            })
+            .to_string(),
+        )
+        .create();
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()));
+
+    let transcription = client
+        .create_transcription(
+            Provider::Openai,
+            "whisper-1",
+            AudioFile::from_bytes("call.wav", &b"RIFF\0\0\0\0WAVEfmt "[..]),
+            TranscriptionOptions::new()
+                .with_language("en")
+                .with_timestamps([TimestampGranularity::Word]),
+        )
+        .await?;
+    assert_eq!(transcription.text, "Hello there");
+    assert_eq!(transcription.language.as_deref(), Some("english"));
+    assert_eq!(transcription.duration, Some(1.5));
+    assert_eq!(transcription.segments.len(), 1);
+    assert_eq!(transcription.segments[0].end, 1.5);
+    assert_eq!(transcription.segments[0].no_speech_prob, Some(0.01));
+    assert_eq!(
+        transcription.words,
+        [
+            TranscriptionWord {
+                word: "Hello".to_string(),
+                start: 0.0,
+                end: 0.6
+            },
+            TranscriptionWord {
+                word: "there".to_string(),
+                start: 0.7,
+                end: 1.5
+            },
+        ]
+    );
+    verbose.assert();
+
+    // Files are uploaded under their own name, and plain results parse.
+    let plain = server
+        .mock("POST", "/v1/proxy/groq/audio/transcriptions")
+        .match_body(Matcher::Regex(
+            r#"filename="memo.mp3"\r\n(.|\r|\n)*ID3"#.to_string(),
+        ))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"text": "Hi", "segments": null}"#)
+        .create();
+    let dir = std::env::temp_dir().join(format!("igw-audio-{}", rand::random::<u64>()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("memo.mp3");
+    std::fs::write(&path, b"ID3audio").unwrap();
+    let transcription = client
+        .create_transcription(
+            Provider::Groq,
+            "whisper-large-v3",
+            path.as_path(),
+            TranscriptionOptions::new(),
+        )
+        .await?;
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(transcription.text, "Hi");
+    assert!(transcription.segments.is_empty());
+    plain.assert();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_transcribe_stream() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+    let streamed = server
+        .mock("POST", "/v1/proxy/openai/audio/transcriptions")
+        .match_body(Matcher::AllOf(vec![
+            Matcher::Regex(r#"name="model"\r\n\r\ngpt-4o-transcribe\r\n"#.to_string()),
+            Matcher::Regex(r#"name="stream"\r\n\r\ntrue\r\n"#.to_string()),
+            Matcher::Regex(r#"filename="call.wav"\r\n"#.to_string()),
+            Matcher::Regex("RIFF....WAVEfmt ".to_string()),
+        ]))
+        .with_status(200)
+        .with_header("content-type", "text/event-stream")
+        .with_body(
+            "data: {\"type\":\"transcript.text.delta\",\"delta\":\"Hello\"}\n\n\
+             data: {\"type\":\"transcript.text.delta\",\"delta\":\" there\"}\n\n\
+             data: {\"type\":\"transcript.text.done\",\"text\":\"Hello there\"}\n\n",
+        )
+        .create();
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()));
+    let audio = || {
+        futures_util::stream::iter([
+            Ok(bytes::Bytes::from_static(b"RIFF\0\0\0\0")),
+            Ok(bytes::Bytes::from_static(b"WAVEfmt ")),
+        ])
+    };
+
+    let events: Vec<_> = client
+        .transcribe_stream(
+            Provider::Openai,
+            ModelRef::new(Provider::Openai, "gpt-4o-transcribe"),
+            "call.wav",
+            audio(),
+        )
+        .collect()
+        .await;
+    let events = events.into_iter().collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(
+        events,
+        [
+            TranscriptionEvent::Delta("Hello".to_string()),
+            TranscriptionEvent::Delta(" there".to_string()),
+            TranscriptionEvent::Done("Hello there".to_string()),
+        ]
+    );
+    streamed.assert();
+
+    let whole = server
+        .mock("POST", "/v1/proxy/groq/audio/transcriptions")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"text": "Hello there"}"#)
+        .create();
+    let events: Vec<_> = client
+        .transcribe_stream(Provider::Groq, "whisper-large-v3", "call.wav", audio())
+        .collect()
+        .await;
+    assert!(matches!(
+        events.as_slice(),
+        [Ok(TranscriptionEvent::Done(text))] if text == "Hello there"
+    ));
+    whole.assert();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_upload_file() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+    let mock = server
+        .mock("POST", "/v1/proxy/openai/files")
+        .match_header(
+            "content-type",
+            Matcher::Regex("^multipart/form-data; boundary=".to_string()),
+        )
+        .match_body(Matcher::AllOf(vec![
+            Matcher::Regex(r#"name="purpose"\r\n\r\nuser_data\r\n"#.to_string()),
+            Matcher::Regex(r#"filename="report.pdf"\r\n"#.to_string()),
+            Matcher::Regex("%PDF-1.7".to_string()),
+        ]))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"id": "file-abc123", "object": "file", "filename": "report.pdf"}"#)
+        .create();
+
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()));
+    let part = client
+        .upload_file(Provider::Openai, "report.pdf", b"%PDF-1.7", "user_data")
+        .await?;
+    mock.assert();
+
+    let content = MessageContent::Parts(vec![
+        part.into(),
+        FileContentPart::inline("notes.txt", "text/plain", b"hi").into(),
+    ]);
+    assert_eq!(
+        serde_json::to_value(&content).unwrap(),
+        json!([
+            {"type": "file", "file": {"file_id": "file-abc123"}},
+            {"type": "file", "file": {"file_data": "data:text/plain;base64,aGk=", "filename": "notes.txt"}}
+        ])
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_generate_content() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+
+    let raw_json_response = r#"{
+        "id": "chatcmpl-456",
+        "object": "chat.completion",
+        "created": 1630000001,
+        "model": "deepseek-v4-flash",
+        "choices": [
+            {
+                "index": 0,
+                "finish_reason": "stop",
+                "logprobs": null,
+                "message": {
+                    "role": "assistant",
+                    "content": "Hellloooo"
+                }
+            }
+        ]
+    }"#;
+
+    let mock = server
+        .mock("POST", "/v1/chat/completions?provider=deepseek")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(raw_json_response)
+        .create();
+
+    let base_url = format!("{}/v1", server.url());
+    let client = InferenceGatewayClient::new(&base_url);
+
+    let messages = vec![user_message("Hello")];
+    let response = client
+        .generate_content(Provider::Deepseek, "deepseek-v4-flash", messages)
+        .await?;
+
+    assert_eq!(response.choices[0].message.role, MessageRole::Assistant);
+    assert!(matches!(
+        response.choices[0].message.content,
+        MessageContent::Text(ref s) if s == "Hellloooo"
+    ));
+    mock.assert();
+    Ok(())
+}
+
+#[test]
+fn test_response_fingerprint_tier_and_usage() {
+    let response: CreateChatCompletionResponse = serde_json::from_value(json!({
+        "id": "chatcmpl-789",
+        "object": "chat.completion",
+        "created": 1630000002,
+        "model": "gpt-4o",
+        "system_fingerprint": "fp_44709d6fcb",
+        "service_tier": "default",
+        "choices": [{
+            "index": 0,
+            "finish_reason": "stop",
+            "message": {"role": "assistant", "content": "Hi"}
+        }],
+        "usage": {"prompt_tokens": 9, "completion_tokens": 1, "total_tokens": 10}
+    }))
+    .unwrap();
+
+    assert_eq!(
+        response.system_fingerprint.as_deref(),
+        Some("fp_44709d6fcb")
+    );
+    assert_eq!(response.service_tier.as_deref(), Some("default"));
+    assert_eq!(response.usage.map(|usage| usage.total_tokens), Some(10));
+}
+
+#[tokio::test]
+async fn test_instruction_roles_mapped_per_provider() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+    let body = json!({
+        "id": "chatcmpl-1",
+        "object": "chat.completion",
+        "created": 1,
+        "model": "m",
+        "choices": [{
+            "index": 0,
+            "finish_reason": "stop",
+            "message": {"role": "model", "content": "ok"}
+        }]
+    })
+    .to_string();
+    let groq = server
+        .mock("POST", "/v1/chat/completions?provider=groq")
+        .match_body(Matcher::PartialJson(json!({
+            "messages": [{"role": "system", "content": "Be brief."}, {"role": "user"}]
+        })))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(&body)
+        .create();
+    let openai = server
+        .mock("POST", "/v1/chat/completions?provider=openai")
+        .match_body(Matcher::PartialJson(json!({
+            "messages": [{"role": "developer", "content": "Be brief."}, {"role": "user"}]
+        })))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(&body)
+        .create();
+
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()))
+        .with_instruction_role(Provider::Openai, InstructionRole::Developer);
+    let response = client
+        .generate_content(
+            Provider::Groq,
+            "m",
+            vec![Message::developer("Be brief."), user_message("Hi")],
+        )
+        .await?;
+    assert_eq!(response.choices[0].message.role, MessageRole::Assistant);
+    client
+        .generate_content(
+            Provider::Openai,
+            "m",
+            vec![Message::system("Be brief."), user_message("Hi")],
+        )
+        .await?;
+
+    groq.assert();
+    openai.assert();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_ask() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+    let mock = server
+        .mock("POST", "/v1/chat/completions?provider=groq")
+        .match_body(Matcher::PartialJson(json!({
+            "model": "llama-3.3-70b-versatile",
+            "messages": [
+                {"role": "system", "content": "Answer in one word."},
+                {"role": "user", "content": "Capital of France?"}
+            ]
+        })))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            json!({
+                "id": "chatcmpl-1",
+                "object": "chat.completion",
+                "created": 1,
+                "model": "llama-3.3-70b-versatile",
+                "choices": [{
+                    "index": 0,
+                    "finish_reason": "stop",
+                    "message": {"role": "assistant", "content": "Paris"}
+                }]
+            })
+            .to_string(),
+        )
+        .create();
+
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()));
+    let answer = client
+        .ask_with_system(
+            Provider::Groq,
+            "llama-3.3-70b-versatile",
+            "Answer in one word.",
+            "Capital of France?",
+        )
+        .await?;
+    assert_eq!(answer, "Paris");
+    mock.assert();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_default_model_shorthands() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+    let mock = server
+        .mock("POST", "/v1/chat/completions?provider=deepseek")
+        .match_body(Matcher::PartialJson(json!({
+            "model": "deepseek-v4-flash",
+            "stream": true
+        })))
+        .with_status(200)
+        .with_header("content-type", "text/event-stream")
+        .with_body(concat!(
+            "data: {\"id\":\"chatcmpl-1\",\"object\":\"chat.completion.chunk\",\"created\":1,\"model\":\"deepseek-v4-flash\",\"choices\":[{\"index\":0,\"delta\":{\"role\":\"assistant\",\"content\":\"Hello\"}}]}\n\n",
+            "data: {\"id\":\"chatcmpl-1\",\"object\":\"chat.completion.chunk\",\"created\":1,\"model\":\"deepseek-v4-flash\",\"choices\":[{\"index\":0,\"delta\":{\"content\":\" World\"},\"finish_reason\":\"stop\"}]}\n\n",
+            "data: [DONE]\n\n"
+        ))
+        .create();
+
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()));
+    let error = client.generate("Hi").await.unwrap_err();
+    assert!(matches!(error, GatewayError::NoDefaultModel));
+    assert_eq!(error.kind(), GatewayErrorKind::Validation);
+
+    let client = client.with_default_model(Provider::Deepseek, "deepseek-v4-flash");
+    let deltas: Vec<String> = client.generate_stream("Hi").try_collect().await?;
+    assert_eq!(deltas, ["Hello", " World"]);
+    mock.assert();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_router() -> Result<(), GatewayError> {
+    let router = Router::new()
+        .with_rule(RouteRule::new(Provider::Openai, "gpt-4o").when_tools(true))
+        .with_rule(RouteRule::new(Provider::Mistral, "mistral-large-latest").when_language("fr"))
+        .with_rule(RouteRule::new(Provider::Anthropic, "claude-opus-4-1").when_tag("review"))
+        .with_rule(
+            RouteRule::new(Provider::Groq, "llama-3.1-8b-instant").when_prompt_tokens_below(50),
+        );
+    let short = [Message::user("Hi")];
+    let long = [Message::user("x".repeat(400))];
+    let tags = ["review".to_string()];
+    let route = |messages, has_tools, language, tags| {
+        router
+            .route(&RouteRequest {
+                messages,
+                has_tools,
+                language,
+                tags,
+            })
+            .map(|(provider, model)| (provider, model.model().to_string()))
+    };
+    assert_eq!(
+        route(&short, true, None, &[]),
+        Some((Provider::Openai, "gpt-4o".into()))
+    );
+    assert_eq!(
+        route(&long, false, Some("FR"), &[]),
+        Some((Provider::Mistral, "mistral-large-latest".into()))
+    );
+    assert_eq!(
+        route(&long, false, None, &tags),
+        Some((Provider::Anthropic, "claude-opus-4-1".into()))
+    );
+    assert_eq!(
+        route(&short, false, None, &[]),
+        Some((Provider::Groq, "llama-3.1-8b-instant".into()))
+    );
+    assert_eq!(route(&long, false, None, &[]), None);
+
+    let mut server = Server::new_async().await;
+    let completion = json!({
+        "id": "chatcmpl-1",
+        "object": "chat.completion",
+        "created": 1,
+        "model": "any",
+        "choices": [{
+            "index": 0,
+            "finish_reason": "stop",
+            "message": {"role": "assistant", "content": "Hi"}
+        }]
+    })
+    .to_string();
+    let anthropic = server
+        .mock("POST", "/v1/chat/completions?provider=anthropic")
+        .match_body(Matcher::PartialJson(json!({"model": "claude-opus-4-1"})))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(&completion)
+        .create();
+    let fallback = server
+        .mock("POST", "/v1/chat/completions?provider=deepseek")
+        .match_body(Matcher::PartialJson(json!({"model": "deepseek-v4-flash"})))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(&completion)
+        .create();
+
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()))
+        .with_router(router.clone())
+        .with_default_model(Provider::Deepseek, "deepseek-v4-flash");
+    client
+        .chat_with_options(long.to_vec(), RequestOptions::new().with_tag("review"))
+        .await?;
+    client.chat(long.to_vec()).await?;
+    anthropic.assert();
+    fallback.assert();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_shadow_traffic() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+    let completion = |model: &str, text: &str| {
+        json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion",
+            "created": 1,
+            "model": model,
+            "choices": [{
+                "index": 0,
+                "finish_reason": "stop",
+                "message": {"role": "assistant", "content": text}
+            }],
+            "usage": {"prompt_tokens": 3, "completion_tokens": 2, "total_tokens": 5}
+        })
+        .to_string()
+    };
+    let primary = server
+        .mock("POST", "/v1/chat/completions?provider=openai")
+        .match_header("idempotency-key", "req-1")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(completion("gpt-4o", "Primary"))
+        .create();
+    let shadow = server
+        .mock("POST", "/v1/chat/completions?provider=anthropic")
+        .match_header("idempotency-key", Matcher::Missing)
+        .with_status(500)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"error": "overloaded"}"#)
+        .create();
+
+    let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+    let events = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let counted = events.clone();
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()))
+        .with_event_sink(move |_| {
+            counted.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        })
+        .with_shadow(
+            ShadowConfig::new(Provider::Anthropic, "claude-sonnet-5", 1.0),
+            move |comparison| sender.send(comparison.clone()).unwrap(),
+        );
+    let response = client
+        .generate_content_with_options(
+            Provider::Openai,
+            "gpt-4o",
+            vec![user_message("Hi")],
+            RequestOptions::new().with_idempotency_key("req-1"),
+        )
+        .await?;
+    assert_eq!(
+        response.choices[0].message.content.as_text(),
+        Some("Primary")
+    );
+
+    let comparison = receiver.recv().await.unwrap();
+    assert_eq!(comparison.primary.provider, Provider::Openai);
+    assert_eq!(comparison.primary.text, "Primary");
+    assert_eq!(comparison.primary.finish_reason, Some(FinishReason::Stop));
+    assert_eq!(comparison.primary.usage.unwrap().total_tokens, 5);
+    assert_eq!(comparison.shadow.provider, Provider::Anthropic);
+    assert_eq!(comparison.shadow.model, "claude-sonnet-5");
+    assert!(comparison.shadow.error.is_some());
+    // Only the primary call reaches the client's own event sink.
+    assert_eq!(events.load(std::sync::atomic::Ordering::SeqCst), 1);
+    primary.assert();
+    shadow.assert();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_experiment_assigns_and_aggregates() -> Result<(), GatewayError> {
+    let experiment = Experiment::new("greeting")
+        .with_variant("control", Provider::Openai, "gpt-4o", 1)
+        .with_variant("candidate", Provider::Groq, "llama-3.3-70b-versatile", 1);
+    let keys: Vec<String> = (0..64).map(|i| format!("user-{i}")).collect();
+    let assigned = |name| {
+        keys.iter()
+            .find(|key| experiment.assign(key).unwrap().name() == name)
+            .unwrap()
+            .clone()
+    };
+    let (control_key, candidate_key) = (assigned("control"), assigned("candidate"));
+    // Assignment only depends on the experiment's name and the key.
+    let copy = Experiment::new("greeting")
+        .with_variant("control", Provider::Openai, "gpt-4o", 1)
+        .with_variant("candidate", Provider::Groq, "llama-3.3-70b-versatile", 1);
+    for key in &keys {
+        assert_eq!(
+            copy.assign(key).unwrap().name(),
+            experiment.assign(key).unwrap().name()
+        );
+    }
+
+    let mut server = Server::new_async().await;
+    let openai = server
+        .mock("POST", "/v1/chat/completions?provider=openai")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            json!({
+                "id": "chatcmpl-1",
+                "object": "chat.completion",
+                "created": 1,
+                "model": "gpt-4o",
+                "choices": [{
+                    "index": 0,
+                    "finish_reason": "stop",
+                    "message": {"role": "assistant", "content": "Hi"}
+                }],
+                "usage": {"prompt_tokens": 3, "completion_tokens": 2, "total_tokens": 5}
+            })
+            .to_string(),
+        )
+        .expect(2)
+        .create();
+    let groq = server
+        .mock("POST", "/v1/chat/completions?provider=groq")
+        .with_status(400)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"error": "bad request"}"#)
+        .create();
+
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()));
+    for _ in 0..2 {
+        let tagged = client
+            .generate_in_experiment(&experiment, &control_key, vec![user_message("Hi")])
+            .await?;
+        assert_eq!(tagged.variant, "control");
+    }
+    let failed = client
+        .generate_in_experiment(&experiment, &candidate_key, vec![user_message("Hi")])
+        .await;
+    assert!(matches!(failed, Err(GatewayError::BadRequest(_))));
+
+    let stats = experiment.stats();
+    let control = &stats["control"];
+    assert_eq!((control.requests, control.errors), (2, 0));
+    assert_eq!((control.prompt_tokens, control.completion_tokens), (6, 4));
+    assert_eq!(control.finish_reasons[&FinishReason::Stop], 2);
+    assert!(control.mean_latency().is_some());
+    let candidate = &stats["candidate"];
+    assert_eq!((candidate.requests, candidate.errors), (1, 1));
+    openai.assert();
+    groq.assert();
+
+    let empty = Experiment::new("empty").with_variant("off", Provider::Openai, "gpt-4o", 0);
+    let result = client
+        .generate_in_experiment(&empty, "user-1", vec![user_message("Hi")])
+        .await;
+    assert!(matches!(result, Err(GatewayError::NoVariant(name)) if name == "empty"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_replay_reproduces_recorded_run() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+    let completion = server
+        .mock("POST", "/v1/chat/completions?provider=openai")
+        .match_body(Matcher::PartialJson(json!({"seed": 42, "stream": false})))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            json!({
+                "id": "chatcmpl-1",
+                "object": "chat.completion",
+                "created": 1,
+                "model": "gpt-4o",
+                "choices": [{
+                    "index": 0,
+                    "finish_reason": "stop",
+                    "message": {"role": "assistant", "content": "Sunny"}
+                }]
+            })
+            .to_string(),
+        )
+        .create();
+    let stream = server
+        .mock("POST", "/v1/chat/completions?provider=deepseek")
+        .match_body(Matcher::PartialJson(json!({"seed": 42, "stream": true})))
+        .with_status(200)
+        .with_header("content-type", "text/event-stream")
+        .with_body(format!(
+            "data: {}\n\ndata: {}\n\ndata: [DONE]\n\n",
+            content_chunk(0, "Rain"),
+            content_chunk(0, "y")
+        ))
+        .create();
+
+    let base_url = format!("{}/v1", server.url());
+    let recorder = ReplayClient::record(InferenceGatewayClient::new(&base_url), 42);
+    let recorded = recorder
+        .generate_content(Provider::Openai, "gpt-4o", vec![user_message("Today?")])
+        .await?;
+    let recorded_stream: Vec<_> = recorder
+        .generate_content_stream(
+            Provider::Deepseek,
+            "deepseek-v4-flash",
+            vec![user_message("Tomorrow?")],
+        )
+        .map_ok(|event| event.data)
+        .try_collect()
+        .await?;
+    completion.assert();
+    stream.assert();
+    let cassette: Cassette =
+        serde_json::from_str(&serde_json::to_string(&recorder.cassette()).unwrap()).unwrap();
+    assert_eq!(cassette.exchanges.len(), 2);
+    drop(server);
+
+    let replayer = ReplayClient::replay(InferenceGatewayClient::new(&base_url), cassette);
+    assert_eq!(replayer.now(), recorder.now());
+    // Requests are matched on their content, not the order they were made.
+    let replayed_stream: Vec<_> = replayer
+        .generate_content_stream(
+            Provider::Deepseek,
+            "deepseek-v4-flash",
+            vec![user_message("Tomorrow?")],
+        )
+        .map_ok(|event| event.data)
+        .try_collect()
+        .await?;
+    assert_eq!(replayed_stream, recorded_stream);
+    let replayed = replayer
+        .generate_content(Provider::Openai, "gpt-4o", vec![user_message("Today?")])
+        .await?;
+    assert_eq!(
+        serde_json::to_string(&replayed).unwrap(),
+        serde_json::to_string(&recorded).unwrap()
+    );
+
+    // Each exchange is played once, and unrecorded requests fail.
+    for messages in [vec![user_message("Today?")], vec![user_message("Later?")]] {
+        let result = replayer
+            .generate_content(Provider::Openai, "gpt-4o", messages)
+            .await;
+        assert!(matches!(result, Err(GatewayError::ReplayMismatch { .. })));
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_generate_content_serialization() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+
+    let raw_json = r#"{
+        "id": "chatcmpl-456",
+        "object": "chat.completion",
+        "created": 1630000001,
+        "model": "deepseek-v4-flash",
+        "choices": [
+            {
+                "index": 0,
+                "finish_reason": "stop",
+                "logprobs": null,
+                "message": {
+                    "role": "assistant",
+                    "content": "Hello"
+                }
+            }
+        ]
+    }"#;
+
+    let mock = server
+        .mock("POST", "/v1/chat/completions?provider=deepseek")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(raw_json)
+        .create();
+
+    let base_url = format!("{}/v1", server.url());
+    let client = InferenceGatewayClient::new(&base_url);
+
+    let direct_parse: Result<CreateChatCompletionResponse, _> = serde_json::from_str(raw_json);
+    assert!(
+        direct_parse.is_ok(),
+        "direct parse: {:?}",
+        direct_parse.err()
+    );
+
+    let messages = vec![user_message("Hello")];
+    let response = client
+        .generate_content(Provider::Deepseek, "deepseek-v4-flash", messages)
+        .await?;
+
+    assert_eq!(response.choices[0].message.role, MessageRole::Assistant);
+    assert!(matches!(
+        response.choices[0].message.content,
+        MessageContent::Text(ref s) if s == "Hello"
+    ));
+    mock.assert();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_generate_content_error_response() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+
+    let mock = server
+        .mock("POST", "/v1/chat/completions?provider=deepseek")
+        .with_status(400)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{ "error": "Invalid request" }"#)
+        .create();
+
+    let base_url = format!("{}/v1", server.url());
+    let client = InferenceGatewayClient::new(&base_url);
+    let messages = vec![user_message("Hello")];
+    let error = client
+        .generate_content(Provider::Deepseek, "deepseek-v4-flash", messages)
+        .await
+        .unwrap_err();
+
+    assert!(matches!(error, GatewayError::BadRequest(_)));
+    if let GatewayError::BadRequest(msg) = error {
+        assert_eq!(msg, "Invalid request");
+    }
+    mock.assert();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_gateway_errors() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+
+    let unauthorized_mock = server
+        .mock("GET", "/v1/models")
+        .with_status(401)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"error":"Invalid token"}"#)
+        .create();
+
+    let base_url = format!("{}/v1", server.url());
+    let client = InferenceGatewayClient::new(&base_url);
+    match client.list_models().await {
+        Err(GatewayError::Unauthorized(msg)) => assert_eq!(msg, "Invalid token"),
+        _ => panic!("Expected Unauthorized error"),
+    }
+    unauthorized_mock.assert();
+
+    let bad_request_mock = server
+        .mock("GET", "/v1/models")
+        .with_status(400)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"error":"Invalid provider"}"#)
+        .create();
+
+    match client.list_models().await {
+        Err(GatewayError::BadRequest(msg)) => assert_eq!(msg, "Invalid provider"),
+        _ => panic!("Expected BadRequest error"),
+    }
+    bad_request_mock.assert();
+
+    let internal_error_mock = server
+        .mock("GET", "/v1/models")
+        .with_status(500)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"error":"Internal server error occurred"}"#)
+        .create();
+
+    match client.list_models().await {
+        Err(GatewayError::InternalError(msg)) => {
+            assert_eq!(msg, "Internal server error occurred")
+        }
+        _ => panic!("Expected InternalError error"),
+    }
+    internal_error_mock.assert();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_error_context() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+    let mock = server
+        .mock("POST", "/v1/chat/completions?provider=groq")
+        .with_status(401)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"error":"Invalid token"}"#)
+        .expect(2)
+        .create();
+
+    let base_url = format!("{}/v1", server.url());
+    let client = InferenceGatewayClient::new(&base_url);
+    let error = client
+        .generate_content(Provider::Groq, "llama-3.3-70b", vec![user_message("Hi")])
+        .await
+        .unwrap_err();
+    assert!(error.context().is_none());
+    assert!(matches!(error, GatewayError::Unauthorized(_)));
+
+    let client = InferenceGatewayClient::new(&base_url).with_error_context();
+    let error = client
+        .generate_content(Provider::Groq, "llama-3.3-70b", vec![user_message("Hi")])
+        .await
+        .unwrap_err();
+
+    let url = format!("{base_url}/chat/completions?provider=groq");
+    let context = error.context().expect("client errors carry context");
+    assert_eq!(context.method, reqwest::Method::POST);
+    assert_eq!(context.url, url);
+    assert_eq!(context.provider, Some(Provider::Groq));
+    assert_eq!(context.model.as_deref(), Some("llama-3.3-70b"));
+    assert_eq!(
+        error.to_string(),
+        format!("Unauthorized: Invalid token (POST {url}, provider groq, model llama-3.3-70b)")
+    );
+    assert_eq!(error.kind(), GatewayErrorKind::Auth);
+    assert!(matches!(error.root(), GatewayError::Unauthorized(_)));
+    assert!(error.into_root().context().is_none());
+    mock.assert();
+    Ok(())
+}
+
+#[test]
+fn test_privacy_summary() {
+    // Turning privacy mode on would affect every other test in the binary,
+    // so this checks the pieces it is made of.
+    use crate::privacy;
+
+    let summary = privacy::summary("Tell me a secret");
+    assert!(summary.starts_with("<16 chars #"), "{summary}");
+    assert!(!summary.contains("secret"));
+    assert_eq!(summary, privacy::summary("Tell me a secret"));
+    assert_ne!(summary, privacy::summary("Tell me a story!"));
+
+    let message = Message {
+        reasoning: Some("thinking".to_string()),
+        ..Message::user("Hello")
+    };
+    let debug = format!("{message:?}");
+    assert!(debug.contains(r#"Text("Hello")"#), "{debug}");
+    assert!(debug.contains(r#"reasoning: Some("thinking")"#), "{debug}");
+}
+
+#[tokio::test]
+async fn test_deserialization_error_snippet() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+    let body = json!({
+        "id": "chatcmpl-1",
+        "object": "chat.completion",
+        "created": 1,
+        "model": "deepseek-v4-flash",
+        "api_key": "sk-secret",
+        "choices": [{
+            "index": 0,
+            "finish_reason": 42,
+            "message": {"role": "assistant", "content": "x".repeat(1000)}
+        }]
+    });
+    let mock = server
+        .mock("POST", "/v1/chat/completions?provider=deepseek")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(body.to_string())
+        .create();
+
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()));
+    let error = client
+        .generate_content(
+            Provider::Deepseek,
+            "deepseek-v4-flash",
+            vec![user_message("Hi")],
+        )
+        .await
+        .unwrap_err();
+
+    match error {
+        GatewayError::DeserializationError { path, snippet, .. } => {
+            assert_eq!(path, "choices[0].finish_reason");
+            assert!(snippet.contains(r#""api_key":"[redacted]""#), "{snippet}");
+            assert!(!snippet.contains("sk-secret"));
+            assert!(snippet.ends_with("..."));
+            assert_eq!(snippet.chars().count(), 512 + 3);
+        }
+        other => panic!("expected DeserializationError, got {other:?}"),
+    }
+    mock.assert();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_deserialization_error_snippet_of_truncated_body() {
+    let mut server = Server::new_async().await;
+    let body = r#"{"id":"chatcmpl-1","api_key":"sk-secret","choices":[{"index":0,"mess"#;
+    let mock = server
+        .mock("POST", "/v1/chat/completions?provider=deepseek")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(body)
+        .create();
+
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()));
+    let error = client
+        .generate_content(
+            Provider::Deepseek,
+            "deepseek-v4-flash",
+            vec![user_message("Hi")],
+        )
+        .await
+        .unwrap_err();
+
+    match error {
+        GatewayError::DeserializationError { snippet, .. } => {
+            assert_eq!(
+                snippet,
+                format!("<{} bytes of truncated or malformed JSON>", body.len())
+            );
+        }
+        other => panic!("expected DeserializationError, got {other:?}"),
+    }
+    mock.assert();
+}
+
+#[tokio::test]
+async fn test_gateway_error_kinds() {
+    let json_error = || serde_json::from_str::<u8>("x").unwrap_err();
+    let cases = vec![
+        (
+            GatewayError::Unauthorized(String::new()),
+            GatewayErrorKind::Auth,
+        ),
+        (
+            GatewayError::Forbidden(String::new()),
+            GatewayErrorKind::Auth,
+        ),
+        (
+            GatewayError::NotFound(String::new()),
+            GatewayErrorKind::NotFound,
+        ),
+        (
+            GatewayError::BadRequest(String::new()),
+            GatewayErrorKind::Validation,
+        ),
+        (
+            GatewayError::InternalError(String::new()),
+            GatewayErrorKind::Provider,
+        ),
+        (
+            GatewayError::DecodingError(String::from_utf8(vec![0xff]).unwrap_err()),
+            GatewayErrorKind::Protocol,
+        ),
+        (
+            GatewayError::DeserializationError {
+                source: json_error(),
+                path: String::new(),
+                snippet: String::new(),
+            },
+            GatewayErrorKind::Protocol,
+        ),
+        (
+            GatewayError::SerializationError(json_error()),
+            GatewayErrorKind::Protocol,
+        ),
+        (
+            GatewayError::StructuredOutput {
+                type_name: "Forecast",
+                content: "{}".to_string(),
+                source: json_error(),
+            },
+            GatewayErrorKind::Protocol,
+        ),
+        (
+            GatewayError::FirstTokenTimeout(std::time::Duration::from_secs(1)),
+            GatewayErrorKind::Timeout,
+        ),
+        (
+            GatewayError::Timeout(std::time::Duration::from_secs(1)),
+            GatewayErrorKind::Timeout,
+        ),
+        (
+            GatewayError::FeatureDisabled {
+                feature: GatewayFeature::Mcp,
+                hint: String::new(),
+            },
+            GatewayErrorKind::Unsupported,
+        ),
+        (GatewayError::QueueFull, GatewayErrorKind::RateLimit),
+        (
+            GatewayError::ToolRoundsExceeded(1),
+            GatewayErrorKind::Provider,
+        ),
+        (
+            GatewayError::RateLimited {
+                retry_after: None,
+                message: String::new(),
+            },
+            GatewayErrorKind::RateLimit,
+        ),
+        (
+            GatewayError::AttachmentTooLarge {
+                provider: Provider::Anthropic,
+                kind: crate::attachment::AttachmentKind::Image,
+                size: 2,
+                limit: 1,
+            },
+            GatewayErrorKind::Validation,
+        ),
+        (
+            GatewayError::UnsupportedSpeechOptions {
+                provider: Provider::Groq,
+                reason: String::new(),
+            },
+            GatewayErrorKind::Validation,
+        ),
+        (
+            GatewayError::ContextWindowExceeded {
+                needed: 2,
+                available: 1,
+            },
+            GatewayErrorKind::Validation,
+        ),
+        (
+            GatewayError::TokenCountingUnsupported(Provider::Openai),
+            GatewayErrorKind::Unsupported,
+        ),
+        (GatewayError::Other("boom".into()), GatewayErrorKind::Other),
+    ];
+    for (error, kind) in cases {
+        assert_eq!(error.kind(), kind, "{error:?}");
+    }
+
+    // Nothing listens on port 1, so the connection is refused.
+    let client = InferenceGatewayClient::new("http://127.0.0.1:1/v1");
+    let error = client.list_models().await.unwrap_err();
+    assert!(matches!(error, GatewayError::RequestError(_)));
+    assert_eq!(error.kind(), GatewayErrorKind::Network);
+}
+
+#[tokio::test]
+async fn test_generate_content_with_query_params() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+
+    let mock = server
+        .mock("POST", "/v1/chat/completions")
+        .match_query(Matcher::AllOf(vec![
+            Matcher::UrlEncoded("provider".into(), "deepseek".into()),
+            Matcher::UrlEncoded("region".into(), "eu".into()),
+            Matcher::UrlEncoded("route".into(), "canary".into()),
+        ]))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            json!({
+                "id": "chatcmpl-456",
+                "object": "chat.completion",
+                "created": 1630000001,
+                "model": "deepseek-v4-flash",
+                "choices": [{
+                    "index": 0,
+                    "finish_reason": "stop",
+                    "message": {"role": "assistant", "content": "Hi"}
+                }]
+            })
+            .to_string(),
+        )
+        .create();
+
+    let base_url = format!("{}/v1", server.url());
+    let client = InferenceGatewayClient::new(&base_url).with_query_param("region", "eu");
+
+    client
+        .generate_content_with_options(
+            Provider::Deepseek,
+            "deepseek-v4-flash",
+            vec![user_message("Hi")],
+            RequestOptions::new().with_query_param("route", "canary"),
+        )
+        .await?;
+
+    mock.assert();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_scheduling_hints() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+    let completion = json!({
+        "id": "chatcmpl-1",
+        "object": "chat.completion",
+        "created": 1,
+        "model": "gpt-4o",
+        "choices": [{
+            "index": 0,
+            "finish_reason": "stop",
+            "message": {"role": "assistant", "content": "Hi"}
+        }]
+    })
+    .to_string();
+    let overridden = server
+        .mock("POST", "/v1/chat/completions?provider=openai")
+        .match_header("x-tenant-id", "globex")
+        .match_header("x-cost-center", "research")
+        .match_header("x-priority-class", "batch")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(&completion)
+        .create();
+    let models = server
+        .mock("GET", "/v1/models")
+        .match_header("x-tenant-id", "acme")
+        .match_header("x-cost-center", "research")
+        .match_header("x-priority-class", Matcher::Missing)
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(json!({"object": "list", "data": []}).to_string())
+        .create();
+
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()))
+        .with_scheduling_hints(
+            SchedulingHints::new()
+                .with_tenant_id("acme")
+                .with_cost_center("research"),
+        );
+    client
+        .generate_content_with_options(
+            Provider::Openai,
+            "gpt-4o",
+            vec![user_message("Hi")],
+            RequestOptions::new().with_scheduling_hints(
+                SchedulingHints::new()
+                    .with_tenant_id("globex")
+                    .with_priority_class("batch"),
+            ),
+        )
+        .await?;
+    client.list_models().await?;
+
+    overridden.assert();
+    models.assert();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_provider_quirks_normalized() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+    let anthropic = server
+        .mock("POST", "/v1/chat/completions?provider=anthropic")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            json!({
+                "id": "chatcmpl-1",
+                "object": "chat.completion",
+                "created": 1,
+                "model": "claude-sonnet-4-5",
+                "choices": [{
+                    "index": 0,
+                    "finish_reason": "end_turn",
+                    "message": {"role": "assistant", "content": "Hi", "thinking": "Greet back."}
+                }]
+            })
+            .to_string(),
+        )
+        .create();
+    let ollama = server
+        .mock("POST", "/v1/chat/completions?provider=ollama")
+        .with_status(200)
+        .with_header("content-type", "text/event-stream")
+        .with_body(format!(
+            "data: {}\n\ndata: {}\n\ndata: [DONE]\n\n",
+            json!({
+                "id": "chatcmpl-2",
+                "object": "chat.completion.chunk",
+                "created": 1,
+                "model": "llama3.2",
+                "choices": [{"index": 0, "delta": {"tool_calls": [{
+                    "index": 0,
+                    "type": "function",
+                    "function": {"name": "get_weather", "arguments": "{}"}
+                }]}}]
+            }),
+            json!({
+                "id": "chatcmpl-2",
+                "object": "chat.completion.chunk",
+                "created": 1,
+                "model": "llama3.2",
+                "choices": [{"index": 0, "delta": {}, "finish_reason": "tool_calls"}]
+            })
+        ))
+        .create();
+
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()));
+    let response = client
+        .generate_content(
+            Provider::Anthropic,
+            "claude-sonnet-4-5",
+            vec![user_message("Hi")],
+        )
+        .await?;
+    assert_eq!(response.choices[0].finish_reason, FinishReason::Stop);
+    assert_eq!(
+        response.choices[0].message.reasoning_content.as_deref(),
+        Some("Greet back.")
+    );
+
+    let folded = client
+        .generate_content_stream(Provider::Ollama, "llama3.2", vec![user_message("Hi")])
+        .chat_chunks()
+        .fold_stream()
+        .await?;
+    assert_eq!(folded.message.tool_calls[0].id, "call_0");
+
+    anthropic.assert();
+    ollama.assert();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_generate_content_stream() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+
+    let mock = server
+        .mock("POST", "/v1/chat/completions?provider=deepseek")
+        .with_status(200)
+        .with_header("content-type", "text/event-stream")
+        .with_chunked_body(move |writer| -> std::io::Result<()> {
+            let events = vec![
+                format!("data: {}\n\n", r#"{"id":"chatcmpl-123","object":"chat.completion.chunk","created":1694268190,"model":"deepseek-v4-flash","system_fingerprint":"fp_","choices":[{"index":0,"delta":{"role":"assistant","content":"Hello"},"finish_reason":null}]}"#),
+                format!("data: {}\n\n", r#"{"id":"chatcmpl-123","object":"chat.completion.chunk","created":1694268191,"model":"deepseek-v4-flash","system_fingerprint":"fp_","choices":[{"index":0,"delta":{"role":"assistant","content":" World"},"finish_reason":null}]}"#),
+                format!("data: {}\n\n", r#"{"id":"chatcmpl-123","object":"chat.completion.chunk","created":1694268192,"model":"deepseek-v4-flash","system_fingerprint":"fp_","choices":[{"index":0,"delta":{},"finish_reason":"stop"}],"usage":{"prompt_tokens":17,"completion_tokens":40,"total_tokens":57}}"#),
+                format!("data: [DONE]\n\n")
+            ];
+            for event in events {
+                writer.write_all(event.as_bytes())?;
+            }
+            Ok(())
+        })
+        .create();
+
+    let base_url = format!("{}/v1", server.url());
+    let client = InferenceGatewayClient::new(&base_url);
+
+    let messages = vec![user_message("Test message")];
+
+    let stream = client.generate_content_stream(Provider::Deepseek, "deepseek-v4-flash", messages);
+    pin_mut!(stream);
+    while let Some(result) = stream.next().await {
+        let result = result?;
+        if result.data == "[DONE]" {
+            break;
+        }
+        let generate_response: CreateChatCompletionStreamResponse =
+            serde_json::from_str(&result.data).expect("parse stream chunk");
+
+        if let Some(reason) = &generate_response.choices[0].finish_reason {
+            assert_eq!(reason, &FinishReason::Stop);
+            break;
+        }
+
+        let content = &generate_response.choices[0].delta.content;
+        if let Some(content) = content {
+            assert!(matches!(content.as_str(), "Hello" | " World"));
+        }
+        if let Some(role) = &generate_response.choices[0].delta.role {
+            assert_eq!(role, &MessageRole::Assistant);
+        }
+    }
+
+    mock.assert();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_metrics_callback_reports_streamed_call() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+
+    let mock = server
+        .mock("POST", "/v1/chat/completions?provider=deepseek")
+        .with_status(200)
+        .with_header("content-type", "text/event-stream")
+        .with_chunked_body(move |writer| -> std::io::Result<()> {
+            writer.write_all(format!("data: {}\n\n", content_chunk(0, "Hi")).as_bytes())?;
+            writer.write_all(format!("data: {}\n\n", r#"{"id":"chatcmpl-123","object":"chat.completion.chunk","created":1694268192,"model":"deepseek-v4-flash","choices":[{"index":0,"delta":{},"finish_reason":"stop"}],"usage":{"prompt_tokens":17,"completion_tokens":40,"total_tokens":57}}"#).as_bytes())?;
+            writer.write_all(b"data: [DONE]\n\n")?;
+            Ok(())
+        })
+        .create();
+
+    let recorded = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let sink = recorded.clone();
+    let base_url = format!("{}/v1", server.url());
+    let client = InferenceGatewayClient::new(&base_url)
+        .with_metrics_callback(move |metrics| sink.lock().unwrap().push(metrics.clone()));
+
+    let messages = vec![user_message("Test message")];
+    let events: Vec<_> = client
+        .generate_content_stream(Provider::Deepseek, "deepseek-v4-flash", messages)
+        .collect()
+        .await;
+    assert_eq!(events.len(), 3);
+
+    let recorded = recorded.lock().unwrap();
+    assert_eq!(recorded.len(), 1);
+    let metrics = &recorded[0];
+    assert_eq!(
+        metrics.endpoint,
+        format!("{base_url}/chat/completions?provider=deepseek")
+    );
+    assert!(metrics.time_to_first_token.unwrap() <= metrics.total_duration);
+    assert_eq!(metrics.prompt_tokens, Some(17));
+    assert_eq!(metrics.completion_tokens, Some(40));
+    assert!(metrics.output_tokens_per_second.is_some());
+    assert_eq!(metrics.retries, 0);
+
+    mock.assert();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_metrics_observer() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+    let streamed = server
+        .mock("POST", "/v1/chat/completions?provider=deepseek")
+        .with_status(200)
+        .with_header("content-type", "text/event-stream")
+        .with_chunked_body(move |writer| -> std::io::Result<()> {
+            writer.write_all(format!("data: {}\n\n", content_chunk(0, "Hi")).as_bytes())?;
+            writer.write_all(format!("data: {}\n\n", r#"{"id":"chatcmpl-123","object":"chat.completion.chunk","created":1694268192,"model":"deepseek-v4-flash","choices":[{"index":0,"delta":{},"finish_reason":"stop"}],"usage":{"prompt_tokens":17,"completion_tokens":40,"total_tokens":57}}"#).as_bytes())?;
+            writer.write_all(b"data: [DONE]\n\n")?;
+            Ok(())
+        })
+        .create();
+    let denied = server
+        .mock("POST", "/v1/chat/completions?provider=openai")
+        .with_status(401)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"error":"bad token"}"#)
+        .create();
+
+    let recorded = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let sink = recorded.clone();
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()))
+        .with_metrics_observer(move |call: &RequestMetrics| {
+            sink.lock().unwrap().push(call.clone())
+        });
+
+    let events: Vec<_> = client
+        .generate_content_stream(
+            Provider::Deepseek,
+            "deepseek-v4-flash",
+            vec![user_message("Hi")],
+        )
+        .collect()
+        .await;
+    assert_eq!(events.len(), 3);
+    assert!(
+        client
+            .generate_content(Provider::Openai, "gpt-4o", vec![user_message("Hi")])
+            .await
+            .is_err()
+    );
+
+    let recorded = recorded.lock().unwrap();
+    assert_eq!(recorded.len(), 2);
+    let stream = &recorded[0];
+    assert_eq!(stream.provider, Some(Provider::Deepseek));
+    assert_eq!(stream.model, "deepseek-v4-flash");
+    assert!(stream.streaming);
+    assert_eq!(stream.status, Some(StatusCode::OK));
+    assert_eq!(stream.error, None);
+    assert!(stream.time_to_first_token.unwrap() <= stream.total_duration);
+    assert_eq!(stream.prompt_tokens, Some(17));
+    assert_eq!(stream.completion_tokens, Some(40));
+
+    let failed = &recorded[1];
+    assert_eq!(failed.provider, Some(Provider::Openai));
+    assert!(!failed.streaming);
+    assert_eq!(failed.status, Some(StatusCode::UNAUTHORIZED));
+    assert_eq!(failed.error, Some(GatewayErrorKind::Auth));
+    assert_eq!(failed.time_to_first_token, None);
+    assert_eq!(failed.completion_tokens, None);
+
+    streamed.assert();
+    denied.assert();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_event_sink_reports_success_and_failure() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+
+    let ok = server
+        .mock("POST", "/v1/chat/completions?provider=deepseek")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            json!({
+                "id": "chatcmpl-456",
+                "object": "chat.completion",
+                "created": 1630000001,
+                "model": "deepseek-v4-flash",
+                "choices": [{
+                    "index": 0,
+                    "finish_reason": "length",
+                    "message": {"role": "assistant", "content": "Hi"}
+                }],
+                "usage": {"prompt_tokens": 5, "completion_tokens": 1, "total_tokens": 6}
+            })
+            .to_string(),
+        )
+        .create();
+    let failed = server
+        .mock("POST", "/v1/chat/completions?provider=groq")
+        .with_status(401)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"error":"Invalid token"}"#)
+        .create();
+
+    let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let sink = events.clone();
+    let base_url = format!("{}/v1", server.url());
+    let client = InferenceGatewayClient::new(&base_url)
+        .with_event_sink(move |event| sink.lock().unwrap().push(event.clone()));
+
+    client
+        .generate_content(
+            Provider::Deepseek,
+            "deepseek-v4-flash",
+            vec![user_message("Hi")],
+        )
+        .await?;
+    let error = client
+        .generate_content(Provider::Groq, "llama-3.3-70b", vec![user_message("Hi")])
+        .await;
+    assert!(matches!(error, Err(GatewayError::Unauthorized(_))));
+
+    let events = events.lock().unwrap();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].provider, Some(Provider::Deepseek));
+    assert_eq!(events[0].model, "deepseek-v4-flash");
+    assert_eq!(events[0].usage.as_ref().unwrap().total_tokens, 6);
+    assert_eq!(events[0].finish_reason, Some(FinishReason::Length));
+    assert!(events[0].error.is_none());
+    assert_eq!(events[1].provider, Some(Provider::Groq));
+    assert!(events[1].usage.is_none());
+    assert_eq!(
+        events[1].error.as_deref(),
+        Some("Unauthorized: Invalid token")
+    );
+
+    ok.assert();
+    failed.assert();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_event_sink_reads_normalized_stream() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+    let chunk = |finish_reason: serde_json::Value, usage: serde_json::Value| {
+        json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion.chunk",
+            "created": 1630000000,
+            "model": "mistral-large",
+            "choices": [{
+                "index": 0,
+                "delta": {"content": "Hi"},
+                "finish_reason": finish_reason
+            }],
+            "usage": usage
+        })
+    };
+    let body = format!(
+        "data: {}\n\ndata: {}\n\ndata: [DONE]\n\n",
+        chunk(json!(null), json!(null)),
+        chunk(
+            json!("model_length"),
+            json!({"prompt_tokens": 3, "completion_tokens": 1, "total_tokens": 4})
+        ),
+    );
+    let mock = server
+        .mock("POST", "/v1/chat/completions?provider=mistral")
+        .with_status(200)
+        .with_header("content-type", "text/event-stream")
+        .with_body(body)
+        .create();
+
+    let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let sink = events.clone();
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()))
+        .with_event_sink(move |event| sink.lock().unwrap().push(event.clone()));
+    let chunks: Vec<_> = client
+        .generate_content_stream(Provider::Mistral, "mistral-large", vec![user_message("Hi")])
+        .chat_chunks()
+        .try_collect()
+        .await?;
+    assert_eq!(
+        chunks[1].choices[0].finish_reason,
+        Some(FinishReason::Length)
+    );
+
+    let events = events.lock().unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].finish_reason, Some(FinishReason::Length));
+    assert_eq!(events[0].usage.as_ref().unwrap().total_tokens, 4);
+    mock.assert();
+    Ok(())
+}
+
+#[cfg(feature = "trace")]
+#[test]
+fn test_trace_context_round_trips_traceparent() {
+    let traceparent = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+    let context = TraceContext::from_headers(traceparent, Some("congo=t61rcWkgMzE")).unwrap();
+    assert_eq!(context.trace_id(), "4bf92f3577b34da6a3ce929d0e0e4736");
+    assert_eq!(context.traceparent(), traceparent);
+    assert_eq!(context.tracestate(), Some("congo=t61rcWkgMzE"));
+
+    assert!(
+        TraceContext::from_headers(
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+            None
+        )
+        .is_none()
+    );
+    assert!(
+        TraceContext::from_headers(
+            "00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01",
+            None
+        )
+        .is_none()
+    );
+    assert!(
+        TraceContext::from_headers(
+            "ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            None
+        )
+        .is_none()
+    );
+
+    let root = TraceContext::new_root();
+    assert_eq!(
+        TraceContext::from_headers(&root.traceparent(), None),
+        Some(root)
+    );
+}
+
+#[cfg(feature = "trace")]
+#[tokio::test]
+async fn test_requests_join_ambient_trace() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+
+    let mock = server
+        .mock("GET", "/v1/models")
+        .match_header(
+            "traceparent",
+            Matcher::Regex("^00-4bf92f3577b34da6a3ce929d0e0e4736-[0-9a-f]{16}-01$".to_string()),
+        )
+        .match_header("tracestate", "congo=t61rcWkgMzE")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"object":"list","data":[]}"#)
+        .create();
+
+    let base_url = format!("{}/v1", server.url());
+    let client = InferenceGatewayClient::new(&base_url);
+    let parent = TraceContext::from_headers(
+        "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+        Some("congo=t61rcWkgMzE"),
+    )
+    .unwrap();
+
+    parent.scope(client.list_models()).await?;
+
+    mock.assert();
+    Ok(())
+}
+
+#[cfg(feature = "opentelemetry")]
+#[tokio::test]
+async fn test_requests_join_opentelemetry_span() -> Result<(), GatewayError> {
+    use opentelemetry::context::FutureExt;
+    use opentelemetry::trace::{
+        SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState,
+    };
+
+    let mut server = Server::new_async().await;
+    let mock = server
+        .mock("GET", "/v1/models")
+        .match_header(
+            "traceparent",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+        )
+        .match_header("tracestate", "congo=t61rcWkgMzE")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_header(
+            "traceparent",
+            "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01",
+        )
+        .with_body(r#"{"object":"list","data":[]}"#)
+        .create();
+
+    let span = SpanContext::new(
+        TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").unwrap(),
+        SpanId::from_hex("00f067aa0ba902b7").unwrap(),
+        TraceFlags::SAMPLED,
+        true,
+        TraceState::from_key_value([("congo", "t61rcWkgMzE")]).unwrap(),
+    );
+    let context = opentelemetry::Context::new().with_remote_span_context(span);
+    assert_eq!(
+        TraceContext::from_opentelemetry(&context)
+            .unwrap()
+            .traceparent(),
+        "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"
+    );
+    assert!(TraceContext::from_opentelemetry(&opentelemetry::Context::new()).is_none());
+
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()));
+    client.list_models().with_context(context).await?;
+
+    mock.assert();
+    Ok(())
+}
+
+#[test]
+fn test_sse_decoder_handles_split_lines() {
+    let body = b"event: message_start\r\ndata: {\"a\":1}\r\n\r\n: keep-alive\n\ndata: first\ndata: second\nid: 7\n\n";
+    let mut decoder = SseDecoder::new();
+    let mut events = Vec::new();
+    for chunk in body.chunks(3) {
+        decoder.push(chunk);
+        while let Some(event) = decoder.next_event() {
+            events.push(event);
+        }
+    }
+
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].event.as_deref(), Some("message_start"));
+    assert_eq!(events[0].data, r#"{"a":1}"#);
+    assert_eq!(events[1].event, None);
+    assert_eq!(events[1].data, "first\nsecond");
+    assert_eq!(decoder.last_event_id(), Some("7"));
+}
+
+#[test]
+fn test_sse_decoder_holds_incomplete_event() {
+    let mut decoder = SseDecoder::new();
+    decoder.push(b"data: partial");
+    assert!(decoder.next_event().is_none());
+    decoder.push(b" value\n");
+    assert!(decoder.next_event().is_none());
+    decoder.push(b"\n");
+    assert_eq!(decoder.next_event().unwrap().data, "partial value");
+}
+
+fn sse_data(data: &str) -> Result<SSEvents, GatewayError> {
+    Ok(SSEvents {
+        data: data.to_string(),
+        event: None,
+        retry: None,
+    })
+}
+
+fn content_chunk(index: i64, content: &str) -> String {
+    json!({
+        "id": "chatcmpl-123",
+        "object": "chat.completion.chunk",
+        "created": 1694268190,
+        "model": "deepseek-v4-flash",
+        "choices": [{"index": index, "delta": {"content": content}}]
+    })
+    .to_string()
+}
+
+#[tokio::test]
+async fn test_chat_chunks_stops_at_done() -> Result<(), GatewayError> {
+    let events = futures_util::stream::iter(vec![
+        sse_data(&content_chunk(0, "Hello")),
+        sse_data("[DONE]"),
+        sse_data("not json"),
+    ]);
+    let chunks: Vec<_> = events.chat_chunks().collect().await;
+
+    assert_eq!(chunks.len(), 1);
+    assert_eq!(
+        chunks[0].as_ref().unwrap().choices[0]
+            .delta
+            .content
+            .as_deref(),
+        Some("Hello")
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_coalesce_merges_deltas_until_finish() -> Result<(), GatewayError> {
+    let finish = json!({
+        "id": "chatcmpl-123",
+        "object": "chat.completion.chunk",
+        "created": 1694268191,
+        "model": "deepseek-v4-flash",
+        "choices": [{"index": 0, "delta": {}, "finish_reason": "stop"}],
+        "usage": {"prompt_tokens": 3, "completion_tokens": 3, "total_tokens": 6}
+    })
+    .to_string();
+    let events = futures_util::stream::iter(vec![
+        sse_data(&content_chunk(0, "Hel")),
+        sse_data(&content_chunk(0, "lo")),
+        sse_data(&content_chunk(0, " World")),
+        sse_data(&finish),
+    ]);
+
+    let merged: Vec<_> = events
+        .chat_chunks()
+        .coalesce(std::time::Duration::from_secs(60))
+        .collect()
+        .await;
+
+    assert_eq!(merged.len(), 1);
+    let chunk = merged[0].as_ref().unwrap();
+    assert_eq!(
+        chunk.choices[0].delta.content.as_deref(),
+        Some("Hello World")
+    );
+    assert_eq!(chunk.choices[0].finish_reason, Some(FinishReason::Stop));
+    assert_eq!(chunk.usage.as_ref().unwrap().total_tokens, 6);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_coalesce_flushes_at_max_chars() -> Result<(), GatewayError> {
+    let events = futures_util::stream::iter(
+        ["ab", "cd", "ef", "gh", "ij"].map(|text| sse_data(&content_chunk(0, text))),
+    );
+
+    let policy = CoalescePolicy::new(std::time::Duration::from_secs(60)).with_max_chars(4);
+    let merged: Vec<String> = events
+        .chat_chunks()
+        .coalesce(policy)
+        .map_ok(|chunk| chunk.choices[0].delta.content.clone().unwrap_or_default())
+        .try_collect()
+        .await?;
+
+    assert_eq!(merged, ["abcd", "efgh", "ij"]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_pace_splits_content_into_timed_pieces() -> Result<(), GatewayError> {
+    let finish = json!({
+        "id": "chatcmpl-123",
+        "object": "chat.completion.chunk",
+        "created": 1694268191,
+        "model": "deepseek-v4-flash",
+        "choices": [{"index": 0, "delta": {"content": "ijkl"}, "finish_reason": "stop"}]
+    })
+    .to_string();
+    let events = futures_util::stream::iter(vec![
+        sse_data(&content_chunk(0, "abcdefgh")),
+        sse_data(&finish),
+    ]);
+
+    let started = std::time::Instant::now();
+    let paced: Vec<_> = events.chat_chunks().pace(200).try_collect().await?;
+
+    let pieces: Vec<_> = paced
+        .iter()
+        .map(|chunk| {
+            chunk.choices[0]
+                .delta
+                .content
+                .as_deref()
+                .unwrap_or_default()
+        })
+        .collect();
+    assert_eq!(pieces, ["abcd", "efgh", "ijkl"]);
+    assert_eq!(paced[0].choices[0].finish_reason, None);
+    assert_eq!(paced[2].choices[0].finish_reason, Some(FinishReason::Stop));
+    assert!(started.elapsed() >= std::time::Duration::from_millis(10));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_tap_observes_chunks() -> Result<(), GatewayError> {
+    let events = futures_util::stream::iter(vec![
+        sse_data(&content_chunk(0, "Hel")),
+        sse_data(&content_chunk(0, "lo")),
+    ]);
+
+    let mut seen = Vec::new();
+    let chunks: Vec<_> = events
+        .chat_chunks()
+        .tap(|chunk| seen.push(chunk.choices[0].delta.content.clone()))
+        .try_collect()
+        .await?;
+
+    assert_eq!(chunks.len(), 2);
+    assert_eq!(seen, [Some("Hel".to_string()), Some("lo".to_string())]);
+    Ok(())
+}
+
+#[test]
+fn test_unknown_enum_values_parse_as_other() {
+    let reason: FinishReason = serde_json::from_value(json!("overheated")).unwrap();
+    assert_eq!(reason, FinishReason::Other("overheated".to_string()));
+    let reason: FinishReason = serde_json::from_value(json!("length")).unwrap();
+    assert_eq!(reason, FinishReason::Length);
+
+    let kind: ChatCompletionToolType = serde_json::from_value(json!("web_search")).unwrap();
+    assert_eq!(
+        kind,
+        ChatCompletionToolType::Other("web_search".to_string())
+    );
+    let event: crate::MessagesStreamEventType =
+        serde_json::from_value(json!("content_block_citation")).unwrap();
+    assert_eq!(
+        event,
+        crate::MessagesStreamEventType::Other("content_block_citation".to_string())
+    );
+    let stop: crate::MessagesResponseStopReason =
+        serde_json::from_value(json!("model_context_window_exceeded")).unwrap();
+    assert_eq!(
+        stop,
+        crate::MessagesResponseStopReason::Other("model_context_window_exceeded".to_string())
+    );
+
+    // Unknown values are sent on as they came.
+    assert_eq!(
+        serde_json::to_value(FinishReason::Other("overheated".to_string())).unwrap(),
+        json!("overheated")
+    );
+    assert_eq!(stop.to_string(), "model_context_window_exceeded");
+    assert_eq!(
+        "overheated".parse::<FinishReason>().unwrap(),
+        FinishReason::Other("overheated".to_string())
+    );
+    assert_eq!(
+        serde_json::to_value(FinishReason::Stop).unwrap(),
+        json!("stop")
+    );
+}
+
+#[test]
+fn test_parse_partial_json() {
+    assert_eq!(parse_partial_json(""), None);
+    assert_eq!(parse_partial_json("{"), Some(json!({})));
+    assert_eq!(
+        parse_partial_json(r#"{"location": "San Fr"#),
+        Some(json!({"location": "San Fr"}))
+    );
+    assert_eq!(
+        parse_partial_json(r#"{"unit": "c", "loc"#),
+        Some(json!({"unit": "c"}))
+    );
+    assert_eq!(
+        parse_partial_json(r#"{"days": [1, 2, 3"#),
+        Some(json!({"days": [1, 2, 3]}))
+    );
+    assert_eq!(
+        parse_partial_json(r#"{"ok": tr"#),
+        Some(json!({"ok": true}))
+    );
+    assert_eq!(parse_partial_json(r#"{"n": -1."#), Some(json!({"n": -1})));
+    assert_eq!(
+        parse_partial_json(r#"{"s": "a\u00"#),
+        Some(json!({"s": "a"}))
+    );
+    assert_eq!(parse_partial_json(r#"{"a" 1}"#), None);
+    assert_eq!(
+        parse_partial_json(r#"{"a": {"b": null}}"#),
+        Some(json!({"a": {"b": null}}))
+    );
+}
+
+#[tokio::test]
+async fn test_coalesce_merges_tool_call_fragments() -> Result<(), GatewayError> {
+    let fragment = |created: i64, function: serde_json::Value| {
+        json!({
+            "id": "chatcmpl-123",
+            "object": "chat.completion.chunk",
+            "created": created,
+            "model": "deepseek-v4-flash",
+            "choices": [{
+                "index": 0,
+                "delta": {"tool_calls": [{"index": 0, "function": function}]}
+            }]
+        })
+        .to_string()
+    };
+    let events = futures_util::stream::iter(vec![
+        sse_data(&fragment(
+            1,
+            json!({"name": "get_weather", "arguments": ""}),
+        )),
+        sse_data(&fragment(2, json!({"arguments": "{\"location\": "}))),
+        sse_data(&fragment(3, json!({"arguments": "\"Par"}))),
+    ]);
+
+    let merged: Vec<_> = events
+        .chat_chunks()
+        .coalesce(std::time::Duration::from_secs(60))
+        .collect()
+        .await;
+
+    assert_eq!(merged.len(), 1);
+    let chunk = merged[0].as_ref().unwrap();
+    let function = chunk.choices[0].delta.tool_calls[0]
+        .function
+        .as_ref()
+        .unwrap();
+    assert_eq!(function.name.as_deref(), Some("get_weather"));
+    assert_eq!(
+        function.parse_partial_arguments(),
+        Some(json!({"location": "Par"}))
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fold_stream_assembles_message() -> Result<(), GatewayError> {
+    let tool_call = json!({
+        "id": "chatcmpl-123",
+        "object": "chat.completion.chunk",
+        "created": 1694268191,
+        "model": "deepseek-v4-flash",
+        "choices": [{
+            "index": 0,
+            "delta": {"tool_calls": [{
+                "index": 0,
+                "id": "call_1",
+                "type": "function",
+                "function": {"name": "get_weather", "arguments": "{}"}
+            }]},
+            "finish_reason": "tool_calls"
+        }],
+        "usage": {"prompt_tokens": 3, "completion_tokens": 4, "total_tokens": 7}
+    })
+    .to_string();
+    let events = futures_util::stream::iter(vec![
+        sse_data(&content_chunk(0, "Let me ")),
+        sse_data(&content_chunk(0, "check.")),
+        sse_data(&tool_call),
+    ]);
+
+    let folded = events.chat_chunks().fold_stream().await?;
+
+    assert_eq!(folded.message.role, MessageRole::Assistant);
+    assert!(
+        matches!(&folded.message.content, MessageContent::Text(text) if text == "Let me check.")
+    );
+    assert_eq!(folded.message.tool_calls[0].id, "call_1");
+    assert_eq!(folded.message.tool_calls[0].function.name, "get_weather");
+    assert_eq!(folded.finish_reason, Some(FinishReason::ToolCalls));
+    assert_eq!(folded.usage.unwrap().total_tokens, 7);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_collect_completion_merges_choices() -> Result<(), GatewayError> {
+    let reasoning = json!({
+        "id": "chatcmpl-123",
+        "object": "chat.completion.chunk",
+        "created": 1694268190,
+        "model": "deepseek-v4-flash",
+        "choices": [{"index": 1, "delta": {"role": "assistant", "reasoning_content": "Paris?"}}]
+    })
+    .to_string();
+    let tool_call = |arguments: &str, finish_reason: Option<&str>| {
+        json!({
+            "id": "chatcmpl-123",
+            "object": "chat.completion.chunk",
+            "created": 1694268191,
+            "model": "deepseek-v4-flash",
+            "choices": [{
+                "index": 1,
+                "delta": {"tool_calls": [{
+                    "index": 0,
+                    "id": "call_1",
+                    "type": "function",
+                    "function": {"name": "get_weather", "arguments": arguments}
+                }]},
+                "finish_reason": finish_reason
+            }]
+        })
+        .to_string()
+    };
+    let usage = json!({
+        "id": "chatcmpl-123",
+        "object": "chat.completion.chunk",
+        "created": 1694268191,
+        "model": "deepseek-v4-flash",
+        "choices": [],
+        "usage": {"prompt_tokens": 3, "completion_tokens": 4, "total_tokens": 7}
+    })
+    .to_string();
+    let events = futures_util::stream::iter(vec![
+        sse_data(&reasoning),
+        sse_data(&content_chunk(0, "Hello ")),
+        sse_data(&tool_call(r#"{"city":"#, None)),
+        sse_data(&content_chunk(0, "there")),
+        sse_data(&tool_call(r#""Paris"}"#, Some("tool_calls"))),
+        sse_data(&usage),
+        sse_data("[DONE]"),
+    ]);
+
+    let response = events.chat_chunks().collect_completion().await?;
+
+    assert_eq!(response.id, "chatcmpl-123");
+    assert_eq!(response.object, "chat.completion");
+    assert_eq!(response.model, "deepseek-v4-flash");
+    assert_eq!(response.created, 1694268191);
+    assert_eq!(response.usage.unwrap().total_tokens, 7);
+    assert_eq!(response.choices.len(), 2);
+    let first = &response.choices[0];
+    assert_eq!(first.index, 0);
+    assert_eq!(first.message.content.as_text(), Some("Hello there"));
+    // The stream never finished this choice.
+    assert_eq!(first.finish_reason, FinishReason::Other(String::new()));
+    let second = &response.choices[1];
+    assert_eq!(second.finish_reason, FinishReason::ToolCalls);
+    assert_eq!(second.message.reasoning_content.as_deref(), Some("Paris?"));
+    assert_eq!(second.message.tool_calls[0].id, "call_1");
+    assert_eq!(
+        second.message.tool_calls[0].function.arguments,
+        r#"{"city":"Paris"}"#
+    );
+
+    let empty = futures_util::stream::iter(vec![sse_data("[DONE]")]);
+    assert!(empty.chat_chunks().collect_completion().await.is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fold_stream_assembles_audio() -> Result<(), GatewayError> {
+    let audio_chunk = |audio: serde_json::Value| {
+        json!({
+            "id": "chatcmpl-123",
+            "object": "chat.completion.chunk",
+            "created": 1694268191,
+            "model": "gpt-4o-audio-preview",
+            "choices": [{"index": 0, "delta": {"audio": audio}}]
+        })
+        .to_string()
+    };
+    let events = futures_util::stream::iter(vec![
+        sse_data(&audio_chunk(
+            json!({"id": "audio_1", "expires_at": 1729234747, "data": "AAE=", "transcript": "Hel"}),
+        )),
+        sse_data(&audio_chunk(json!({"data": "Ag==", "transcript": "lo"}))),
+    ]);
+
+    let folded = events.chat_chunks().fold_stream().await?;
+
+    let audio = folded.message.audio.expect("audio");
+    assert_eq!(audio.id, "audio_1");
+    assert_eq!(audio.expires_at, 1729234747);
+    assert_eq!(audio.transcript, "Hello");
+    assert_eq!(audio.decode().unwrap(), [0, 1, 2]);
+
+    let message: Message = serde_json::from_value(json!({
+        "role": "assistant",
+        "content": null,
+        "audio": {"id": "audio_2", "expires_at": 1, "data": "AAEC", "transcript": "Hi"}
+    }))
+    .unwrap();
+    assert_eq!(message.audio.unwrap().decode().unwrap(), [0, 1, 2]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_citations_resolve_against_text() -> Result<(), GatewayError> {
+    let citation = |start: u64, end: u64, url: &str| {
+        json!({
+            "type": "url_citation",
+            "url_citation": {"start_index": start, "end_index": end, "url": url, "title": "Source"}
+        })
+    };
+    let finish = json!({
+        "id": "chatcmpl-123",
+        "object": "chat.completion.chunk",
+        "created": 1694268191,
+        "model": "gpt-4o-search-preview",
+        "choices": [{
+            "index": 0,
+            "delta": {"annotations": [
+                citation(0, 12, "https://example.com/café"),
+                citation(13, 99, "https://example.com/out-of-range"),
+                {"type": "file_citation", "file_citation": {"file_id": "file_1"}}
+            ]},
+            "finish_reason": "stop"
+        }]
+    })
+    .to_string();
+    let events = futures_util::stream::iter(vec![
+        sse_data(&content_chunk(0, "Café is open")),
+        sse_data(&content_chunk(0, " until 6.")),
+        sse_data(&finish),
+    ]);
+
+    let folded = events.chat_chunks().fold_stream().await?;
+
+    assert_eq!(folded.message.annotations.len(), 3);
+    assert_eq!(
+        folded.message.annotations[2].type_,
+        crate::ChatCompletionAnnotationType::Other("file_citation".to_string())
+    );
+    let citations: Vec<_> = folded
+        .message
+        .citations()
+        .map(|(citation, text)| (citation.url.as_str(), text))
+        .collect();
+    assert_eq!(citations, [("https://example.com/café", "Café is open")]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_split_choices_routes_by_index() -> Result<(), GatewayError> {
+    let events = futures_util::stream::iter(vec![
+        sse_data(&content_chunk(0, "A")),
+        sse_data(&content_chunk(1, "x")),
+        sse_data(&content_chunk(0, "B")),
+        sse_data(&content_chunk(2, "dropped")),
+        sse_data(&content_chunk(1, "y")),
+    ]);
+
+    let mut branches = events.chat_chunks().split_choices(2).into_iter();
+    let first: Vec<_> = branches.next().unwrap().collect().await;
+    let second: Vec<_> = branches.next().unwrap().collect().await;
+
+    let text = |choices: Vec<Result<ChatCompletionStreamChoice, GatewayError>>| {
+        choices
+            .into_iter()
+            .map(|choice| Ok(choice?.delta.content.unwrap_or_default()))
+            .collect::<Result<String, GatewayError>>()
+    };
+    assert_eq!(text(first)?, "AB");
+    assert_eq!(text(second)?, "xy");
+    Ok(())
+}
+
+#[test]
+fn test_split_choices_outside_runtime_fails_each_branch() {
+    let events = futures_util::stream::iter(vec![sse_data(&content_chunk(0, "A"))]);
+
+    for mut branch in events.chat_chunks().split_choices(2) {
+        let first = branch.next().now_or_never().flatten();
+        assert!(matches!(first, Some(Err(GatewayError::Other(_)))));
+        assert!(branch.next().now_or_never().flatten().is_none());
+    }
+}
+
+/// Serves one connection: reads the request, writes `response` verbatim, then
+/// keeps the socket open for `hold` before dropping it. mockito buffers
+/// chunked bodies, so this is used to control exactly what reaches the client.
+fn raw_http_server(
+    response: &'static [u8],
+    hold: std::time::Duration,
+) -> (std::net::SocketAddr, std::thread::JoinHandle<Vec<String>>) {
+    raw_http_server_sequence(vec![response], hold)
+}
+
+/// Like [`raw_http_server`], serving one connection per entry of `responses`
+/// in turn. The thread returns the request bodies it received.
+fn raw_http_server_sequence(
+    responses: Vec<&'static [u8]>,
+    hold: std::time::Duration,
+) -> (std::net::SocketAddr, std::thread::JoinHandle<Vec<String>>) {
+    use std::io::{BufRead, BufReader, Read, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = std::thread::spawn(move || {
+        let mut bodies = Vec::new();
+        for response in responses {
+            let (socket, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(socket);
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line == "\r\n" {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':')
+                    && name.eq_ignore_ascii_case("content-length")
+                {
+                    content_length = value.trim().parse().unwrap();
+                }
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+            bodies.push(String::from_utf8(body).unwrap());
+
+            let mut socket = reader.into_inner();
+            socket.write_all(response).unwrap();
+            std::thread::sleep(hold);
+        }
+        bodies
+    });
+    (addr, server)
+}
+
+#[tokio::test]
+async fn test_generate_content_stream_interrupted() -> Result<(), GatewayError> {
+    let (addr, server) = raw_http_server(
+        b"HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\ntransfer-encoding: chunked\r\n\r\n10\r\nid: 7\ndata: {}\n\n\r\n",
+        std::time::Duration::ZERO,
+    );
+    let client = InferenceGatewayClient::new(&format!("http://{addr}/v1"));
+
+    let messages = vec![user_message("Test message")];
+    let stream = client.generate_content_stream(Provider::Deepseek, "deepseek-v4-flash", messages);
+    let results: Vec<_> = stream.collect().await;
+
+    assert_eq!(results.len(), 2);
+    assert!(results[0].is_ok());
+    match &results[1] {
+        Err(GatewayError::StreamInterrupted {
+            bytes_received,
+            last_event_id,
+            ..
+        }) => {
+            assert_eq!(*bytes_received, 16);
+            assert_eq!(last_event_id.as_deref(), Some("7"));
+        }
+        other => panic!("expected StreamInterrupted, got {other:?}"),
+    }
+
+    server.join().unwrap();
+    Ok(())
+}
+
+/// A stream that drops after the first words of an answer.
+const PARTIAL_ANSWER: &[u8] = b"HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\ntransfer-encoding: chunked\r\n\r\n98\r\ndata: {\"id\":\"1\",\"object\":\"chat.completion.chunk\",\"created\":1,\"model\":\"m\",\"choices\":[{\"index\":0,\"delta\":{\"role\":\"assistant\",\"content\":\"The answer \"}}]}\n\n\r\n";
+
+#[tokio::test]
+async fn test_generate_content_resumable() -> Result<(), GatewayError> {
+    let (addr, server) = raw_http_server_sequence(
+        vec![
+            PARTIAL_ANSWER,
+            b"HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\nconnection: close\r\n\r\ndata: {\"id\":\"2\",\"object\":\"chat.completion.chunk\",\"created\":2,\"model\":\"m\",\"choices\":[{\"index\":0,\"delta\":{\"role\":\"assistant\",\"content\":\"is 42.\"},\"finish_reason\":\"stop\"}]}\n\ndata: {\"id\":\"2\",\"object\":\"chat.completion.chunk\",\"created\":2,\"model\":\"m\",\"choices\":[],\"usage\":{\"prompt_tokens\":20,\"completion_tokens\":3,\"total_tokens\":23}}\n\ndata: [DONE]\n\n",
+        ],
+        std::time::Duration::ZERO,
+    );
+    let client = InferenceGatewayClient::new(&format!("http://{addr}/v1"));
+
+    let messages = vec![user_message("What is the answer?")];
+    let folded = client
+        .generate_content_resumable(Provider::Deepseek, "deepseek-v4-flash", messages, 1)
+        .await?;
+
+    match &folded.message.content {
+        MessageContent::Text(text) => assert_eq!(text, "The answer is 42."),
+        other => panic!("expected text content, got {other:?}"),
+    }
+    assert_eq!(folded.finish_reason, Some(FinishReason::Stop));
+    assert_eq!(folded.usage.map(|usage| usage.total_tokens), Some(23));
+
+    let bodies = server.join().unwrap();
+    let resumed: serde_json::Value = serde_json::from_str(&bodies[1])?;
+    let resumed = resumed["messages"].as_array().unwrap();
+    assert_eq!(resumed.len(), 3);
+    assert_eq!(resumed[1]["role"], "assistant");
+    assert_eq!(resumed[1]["content"], "The answer ");
+    assert_eq!(resumed[2]["role"], "user");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_generate_content_resumable_gives_up() -> Result<(), GatewayError> {
+    let (addr, server) = raw_http_server(PARTIAL_ANSWER, std::time::Duration::ZERO);
+    let client = InferenceGatewayClient::new(&format!("http://{addr}/v1"));
+
+    let messages = vec![user_message("Test message")];
+    let result = client
+        .generate_content_resumable(Provider::Deepseek, "deepseek-v4-flash", messages, 0)
+        .await;
+    assert!(matches!(
+        result,
+        Err(GatewayError::StreamInterrupted { .. })
+    ));
+
+    server.join().unwrap();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_generate_content_stream_first_token_timeout() -> Result<(), GatewayError> {
+    // The role-only chunk keeps the connection busy without producing a token.
+    let (addr, server) = raw_http_server(
+        b"HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\ntransfer-encoding: chunked\r\n\r\n80\r\ndata: {\"id\":\"1\",\"object\":\"chat.completion.chunk\",\"created\":1,\"model\":\"m\",\"choices\":[{\"index\":0,\"delta\":{\"role\":\"assistant\"}}]}\n\n\r\n",
+        std::time::Duration::from_secs(1),
+    );
+    let client = InferenceGatewayClient::new(&format!("http://{addr}/v1"))
+        .with_first_token_timeout(std::time::Duration::from_millis(200));
+
+    let messages = vec![user_message("Test message")];
+    let stream = client.generate_content_stream(Provider::Deepseek, "deepseek-v4-flash", messages);
+    let results: Vec<_> = stream.collect().await;
+
+    assert_eq!(results.len(), 2);
+    assert!(results[0].is_ok());
+    assert!(matches!(
+        results[1],
+        Err(GatewayError::FirstTokenTimeout(timeout)) if timeout == std::time::Duration::from_millis(200)
+    ));
+
+    server.join().unwrap();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_generate_content_request_timeout() -> Result<(), GatewayError> {
+    // The body never completes, so only the per-call timeout ends the call.
+    let (addr, server) = raw_http_server(
+        b"HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: 100\r\n\r\n{\"id\":",
+        std::time::Duration::from_secs(1),
+    );
+    let client = InferenceGatewayClient::new(&format!("http://{addr}/v1"));
+    let timeout = std::time::Duration::from_millis(200);
+
+    let started = std::time::Instant::now();
+    let error = client
+        .generate_content_with_options(
+            Provider::Deepseek,
+            "deepseek-v4-flash",
+            vec![user_message("Test message")],
+            ChatCompletionOptions::new().with_timeout(timeout),
+        )
+        .await
+        .unwrap_err();
+
+    assert!(started.elapsed() < std::time::Duration::from_secs(1));
+    assert_eq!(error.kind(), GatewayErrorKind::Timeout);
+    assert!(matches!(&error, GatewayError::Timeout(t) if *t == timeout));
+
+    server.join().unwrap();
+    Ok(())
+}
+
+#[test]
+fn test_timeout_setters_leave_tcp_keepalive_unset() {
+    let timeout = std::time::Duration::from_secs(5);
+    let client = InferenceGatewayClient::new("http://localhost:8080/v1")
+        .with_connect_timeout(timeout)
+        .with_request_timeout(timeout)
+        .with_read_timeout(timeout);
+    assert_eq!(client.http.tcp_keepalive, None);
+
+    let client = client.with_tcp_keepalive(std::time::Duration::from_secs(30));
+    assert_eq!(
+        client.http.tcp_keepalive,
+        Some(std::time::Duration::from_secs(30))
+    );
+}
+
+#[tokio::test]
+async fn test_client_http_timeouts() -> Result<(), GatewayError> {
+    const STALLED: &[u8] =
+        b"HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: 100\r\n\r\n{\"id\":";
+    let hold = std::time::Duration::from_secs(1);
+    let timeout = std::time::Duration::from_millis(200);
+
+    let (addr, server) = raw_http_server(STALLED, hold);
+    let client = InferenceGatewayClient::new(&format!("http://{addr}/v1"))
+        .with_connect_timeout(std::time::Duration::from_secs(5))
+        .with_tcp_keepalive(std::time::Duration::from_secs(30))
+        .with_request_timeout(timeout);
+    let started = std::time::Instant::now();
+    let error = client
+        .generate_content(
+            Provider::Deepseek,
+            "deepseek-v4-flash",
+            vec![user_message("Test message")],
+        )
+        .await
+        .unwrap_err();
+    assert!(started.elapsed() < hold);
+    assert_eq!(error.kind(), GatewayErrorKind::Timeout);
+    server.join().unwrap();
+
+    let (addr, server) = raw_http_server(STALLED, hold);
+    let client =
+        InferenceGatewayClient::new(&format!("http://{addr}/v1")).with_read_timeout(timeout);
+    let started = std::time::Instant::now();
+    let error = client
+        .generate_content(
+            Provider::Deepseek,
+            "deepseek-v4-flash",
+            vec![user_message("Test message")],
+        )
+        .await
+        .unwrap_err();
+    assert!(started.elapsed() < hold);
+    assert_eq!(error.kind(), GatewayErrorKind::Timeout);
+    server.join().unwrap();
     Ok(())
 }
 
 #[tokio::test]
-async fn test_generate_content_stream() -> Result<(), GatewayError> {
+async fn test_generate_content_stream_error() -> Result<(), GatewayError> {
     let mut server = Server::new_async().await;
 
     let mock = server
         .mock("POST", "/v1/chat/completions?provider=deepseek")
-        .with_status(200)
-        .with_header("content-type", "text/event-stream")
+        .with_status(400)
+        .with_header("content-type", "application/json")
         .with_chunked_body(move |writer| -> std::io::Result<()> {
-            let events = vec![
-                format!("data: {}\n\n", r#"{"id":"chatcmpl-123","object":"chat.completion.chunk","created":1694268190,"model":"deepseek-v4-flash","system_fingerprint":"fp_","choices":[{"index":0,"delta":{"role":"assistant","content":"Hello"},"finish_reason":null}]}"#),
-                format!("data: {}\n\n", r#"{"id":"chatcmpl-123","object":"chat.completion.chunk","created":1694268191,"model":"deepseek-v4-flash","system_fingerprint":"fp_","choices":[{"index":0,"delta":{"role":"assistant","content":" World"},"finish_reason":null}]}"#),
-                format!("data: {}\n\n", r#"{"id":"chatcmpl-123","object":"chat.completion.chunk","created":1694268192,"model":"deepseek-v4-flash","system_fingerprint":"fp_","choices":[{"index":0,"delta":{},"finish_reason":"stop"}],"usage":{"prompt_tokens":17,"completion_tokens":40,"total_tokens":57}}"#),
-                format!("data: [DONE]\n\n")
-            ];
-            for event in events {
-                writer.write_all(event.as_bytes())?;
-            }
+            let event = "event: error\ndata: {\"error\":\"Invalid request\"}\nretry: 1000\n\n";
+            writer.write_all(event.as_bytes())?;
             Ok(())
         })
+        .expect_at_least(1)
         .create();
 
     let base_url = format!("{}/v1", server.url());
     let client = InferenceGatewayClient::new(&base_url);
 
     let messages = vec![user_message("Test message")];
-
     let stream = client.generate_content_stream(Provider::Deepseek, "deepseek-v4-flash", messages);
-    pin_mut!(stream);
-    while let Some(result) = stream.next().await {
-        let result = result?;
-        if result.data == "[DONE]" {
-            break;
-        }
-        let generate_response: CreateChatCompletionStreamResponse =
-            serde_json::from_str(&result.data).expect("parse stream chunk");
 
-        if let Some(reason) = &generate_response.choices[0].finish_reason {
-            assert_eq!(reason, &FinishReason::Stop);
-            break;
-        }
+    let results: Vec<_> = stream.collect().await;
 
-        let content = &generate_response.choices[0].delta.content;
-        if let Some(content) = content {
-            assert!(matches!(content.as_str(), "Hello" | " World"));
-        }
-        if let Some(role) = &generate_response.choices[0].delta.role {
-            assert_eq!(role, &MessageRole::Assistant);
-        }
+    assert_eq!(results.len(), 1);
+    match &results[0] {
+        Err(GatewayError::BadRequest(message)) => assert_eq!(message, "Invalid request"),
+        other => panic!("expected BadRequest, got {other:?}"),
     }
 
     mock.assert();
@@ -826,33 +6155,88 @@ async fn test_generate_content_stream() -> Result<(), GatewayError> {
 }
 
 #[tokio::test]
-async fn test_generate_content_stream_error() -> Result<(), GatewayError> {
+async fn test_generate_content_stream_rate_limited() -> Result<(), GatewayError> {
     let mut server = Server::new_async().await;
+    let mock = server
+        .mock("POST", "/v1/chat/completions?provider=groq")
+        .with_status(429)
+        .with_header("retry-after", "7")
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"error":"Slow down"}"#)
+        .create();
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()));
+
+    let results: Vec<_> = client
+        .generate_content_stream(Provider::Groq, "llama-3.3-70b", vec![user_message("Hi")])
+        .collect()
+        .await;
+
+    assert_eq!(results.len(), 1);
+    assert!(matches!(
+        results[0].as_ref().map_err(GatewayError::root),
+        Err(GatewayError::RateLimited { retry_after: Some(wait), message })
+            if *wait == std::time::Duration::from_secs(7) && message == "Slow down"
+    ));
+    mock.assert();
+    Ok(())
+}
 
+#[tokio::test]
+async fn test_create_message_stream_rate_limited() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
     let mock = server
-        .mock("POST", "/v1/chat/completions?provider=deepseek")
-        .with_status(400)
+        .mock("POST", "/v1/messages")
+        .with_status(429)
+        .with_header("retry-after", "3")
         .with_header("content-type", "application/json")
+        .with_body(
+            r#"{"type":"error","error":{"type":"rate_limit_error","message":"Too many requests"}}"#,
+        )
+        .create();
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()));
+
+    let request = messages_request("claude-sonnet-4-5", "Hello");
+    let results: Vec<_> = client.create_message_stream(None, request).collect().await;
+
+    assert_eq!(results.len(), 1);
+    assert!(matches!(
+        results[0].as_ref().map_err(GatewayError::root),
+        Err(GatewayError::RateLimited { retry_after: Some(wait), message })
+            if *wait == std::time::Duration::from_secs(3) && message == "Too many requests"
+    ));
+    mock.assert();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_create_message_stream_error_event() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+
+    let mock = server
+        .mock("POST", "/v1/messages")
+        .with_status(200)
+        .with_header("content-type", "text/event-stream")
         .with_chunked_body(move |writer| -> std::io::Result<()> {
-            let event = "event: error\ndata: {\"error\":\"Invalid request\"}\nretry: 1000\n\n";
-            writer.write_all(event.as_bytes())?;
+            writer.write_all(b"event: ping\ndata: {\"type\":\"ping\"}\n\n")?;
+            writer.write_all(
+                b"event: error\ndata: {\"type\":\"error\",\"error\":{\"type\":\"overloaded_error\",\"message\":\"Overloaded\"}}\n\n",
+            )?;
+            writer.write_all(b"event: ping\ndata: {\"type\":\"ping\"}\n\n")?;
             Ok(())
         })
-        .expect_at_least(1)
         .create();
 
     let base_url = format!("{}/v1", server.url());
     let client = InferenceGatewayClient::new(&base_url);
 
-    let messages = vec![user_message("Test message")];
-    let stream = client.generate_content_stream(Provider::Deepseek, "deepseek-v4-flash", messages);
+    let request = messages_request("claude-sonnet-4-5", "Hello");
+    let results: Vec<_> = client.create_message_stream(None, request).collect().await;
 
-    pin_mut!(stream);
-    while let Some(result) = stream.next().await {
-        let result = result?;
-        assert_eq!(result.event.as_deref(), Some("error"));
-        assert!(result.data.contains("Invalid request"));
-        assert!(result.retry.is_none());
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].as_ref().unwrap().event.as_deref(), Some("ping"));
+    match &results[1] {
+        Err(GatewayError::InternalError(message)) => assert_eq!(message, "Overloaded"),
+        other => panic!("expected InternalError, got {other:?}"),
     }
 
     mock.assert();
@@ -929,7 +6313,7 @@ async fn test_generate_content_with_tools() -> Result<(), GatewayError> {
     assert_eq!(response.choices[0].message.role, MessageRole::Assistant);
     assert!(matches!(
         response.choices[0].message.content,
-        MessageContent::String(ref s) if s == "Let me check the weather for you."
+        MessageContent::Text(ref s) if s == "Let me check the weather for you."
     ));
 
     let tool_calls = &response.choices[0].message.tool_calls;
@@ -987,7 +6371,7 @@ async fn test_generate_content_without_tools() -> Result<(), GatewayError> {
     assert_eq!(response.model, "deepseek-v4-flash");
     assert!(matches!(
         response.choices[0].message.content,
-        MessageContent::String(ref s) if s == "Hello!"
+        MessageContent::Text(ref s) if s == "Hello!"
     ));
     assert_eq!(response.choices[0].message.role, MessageRole::Assistant);
     assert!(response.choices[0].message.tool_calls.is_empty());
@@ -1009,6 +6393,406 @@ async fn test_health_check() -> Result<(), GatewayError> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_middleware() -> Result<(), GatewayError> {
+    struct Audit {
+        name: &'static str,
+        log: std::sync::Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    impl Middleware for Audit {
+        fn before_request(&self, request: &mut reqwest::Request) -> Result<(), GatewayError> {
+            if request.url().path().starts_with("/blocked") {
+                return Err(GatewayError::Other("blocked by policy".into()));
+            }
+            request.headers_mut().append(
+                "x-audit",
+                reqwest::header::HeaderValue::from_static(self.name),
+            );
+            self.log.lock().unwrap().push(format!(
+                "{} {} {}",
+                self.name,
+                request.method(),
+                request.url().path()
+            ));
+            Ok(())
+        }
+
+        fn after_response(&self, response: &mut reqwest::Response) {
+            self.log
+                .lock()
+                .unwrap()
+                .push(format!("{} {}", self.name, response.status().as_u16()));
+        }
+    }
+
+    let mut server = Server::new_async().await;
+    let health = server
+        .mock("GET", "/health")
+        .match_header("x-audit", Matcher::Any)
+        .with_status(200)
+        .create();
+    let models = server
+        .mock("GET", "/v1/models")
+        .match_header("x-audit", Matcher::Any)
+        .with_status(401)
+        .with_body("denied")
+        .create();
+
+    let log = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let layer = |name| {
+        Box::new(Audit {
+            name,
+            log: log.clone(),
+        })
+    };
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()))
+        .with_middleware(layer("outer"))
+        .with_middleware(layer("inner"));
+
+    assert!(client.health_check().await?);
+    assert!(client.list_models().await.is_err());
+    assert_eq!(
+        *log.lock().unwrap(),
+        [
+            "outer GET /health",
+            "inner GET /health",
+            "inner 200",
+            "outer 200",
+            "outer GET /v1/models",
+            "inner GET /v1/models",
+            "inner 401",
+            "outer 401",
+        ]
+    );
+    health.assert();
+    models.assert();
+
+    let blocked = InferenceGatewayClient::new(&format!("{}/blocked", server.url()))
+        .with_middleware(layer("outer"));
+    let error = blocked.health_check().await.unwrap_err();
+    assert!(error.to_string().contains("blocked by policy"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_ready_check() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+    let _health = server.mock("GET", "/health").with_status(200).create();
+    let models = server
+        .mock("GET", "/v1/models")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"object":"list","data":[]}"#)
+        .create();
+
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()));
+    assert_eq!(client.ready_check().await, HealthStatus::Healthy);
+    models.remove();
+
+    let _failing = server
+        .mock("GET", "/v1/models")
+        .with_status(500)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"error":"no providers configured"}"#)
+        .create();
+    assert_eq!(
+        client.ready_check().await,
+        HealthStatus::Degraded("Internal server error: no providers configured".to_string())
+    );
+
+    let unreachable = InferenceGatewayClient::new("http://127.0.0.1:1/v1");
+    assert_eq!(unreachable.ready_check().await, HealthStatus::Unreachable);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_provider_health() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+    let model = |id: &str, provider: &str| {
+        format!(
+            r#"{{"id":"{id}","object":"model","created":1,"owned_by":"{provider}","served_by":"{provider}"}}"#
+        )
+    };
+    let _all = server
+        .mock("GET", "/v1/models")
+        .match_query(Matcher::Missing)
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(format!(
+            r#"{{"object":"list","data":[{},{},{}]}}"#,
+            model("gpt-4o", "openai"),
+            model("llama-3.3-70b", "groq"),
+            model("gpt-4o-mini", "openai"),
+        ))
+        .create();
+    let groq = server
+        .mock("GET", "/v1/models")
+        .match_query(Matcher::UrlEncoded("provider".into(), "groq".into()))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(format!(
+            r#"{{"object":"list","provider":"groq","data":[{}]}}"#,
+            model("llama-3.3-70b", "groq")
+        ))
+        .expect(1)
+        .create();
+    let openai = server
+        .mock("GET", "/v1/models")
+        .match_query(Matcher::UrlEncoded("provider".into(), "openai".into()))
+        .with_status(502)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"error":"upstream unavailable"}"#)
+        .expect(1)
+        .create();
+
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()));
+    let health = client.provider_health().await?;
+
+    let summary: Vec<_> = health
+        .iter()
+        .map(|h| (h.provider, h.status.clone()))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (Provider::Groq, HealthStatus::Healthy),
+            (
+                Provider::Openai,
+                HealthStatus::Degraded(
+                    "Other error: Unexpected status code: 502 Bad Gateway".to_string()
+                )
+            ),
+        ]
+    );
+    groq.assert();
+    openai.assert();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_rate_limit_budget_shared_across_clones() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+    let mock = server
+        .mock("GET", "/v1/models")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_header("x-ratelimit-remaining", "0")
+        .with_header("x-ratelimit-reset", "1")
+        .with_body(r#"{"object":"list","data":[]}"#)
+        .expect(2)
+        .create();
+
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()));
+    let clone = client.clone();
+
+    client.list_models().await?;
+    let started = std::time::Instant::now();
+    clone.list_models().await?;
+    assert!(
+        started.elapsed() >= std::time::Duration::from_millis(900),
+        "second request was not delayed: {:?}",
+        started.elapsed()
+    );
+    mock.assert();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_provider_token_limit() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+    let body = r#"{
+        "id": "chatcmpl-1",
+        "object": "chat.completion",
+        "created": 1630000001,
+        "model": "m",
+        "choices": [{"index": 0, "finish_reason": "stop", "message": {"role": "assistant", "content": "hi"}}],
+        "usage": {"prompt_tokens": 600, "completion_tokens": 5, "total_tokens": 605}
+    }"#;
+    for provider in ["groq", "openai"] {
+        server
+            .mock(
+                "POST",
+                format!("/v1/chat/completions?provider={provider}").as_str(),
+            )
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(body)
+            .create();
+    }
+
+    // 600 tokens per minute refill at 10 per second; a 605-token response
+    // overdraws the bucket by 5, so the next call waits about 0.6 seconds.
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url())).with_provider_limits(
+        Provider::Groq,
+        ProviderLimits::new().with_tokens_per_minute(600),
+    );
+
+    client
+        .generate_content(Provider::Groq, "m", vec![user_message("Hi")])
+        .await?;
+    client
+        .generate_content(Provider::Openai, "m", vec![user_message("Hi")])
+        .await?;
+    assert!(client.throttle(Some(Provider::Openai)).is_none());
+
+    tokio::time::pause();
+    let throttle = client.throttle(Some(Provider::Groq)).unwrap();
+    let next = throttle.acquire(Priority::Normal, None);
+    pin_mut!(next);
+    assert!(futures_util::poll!(next.as_mut()).is_pending());
+    tokio::time::advance(std::time::Duration::from_millis(500)).await;
+    assert!(futures_util::poll!(next.as_mut()).is_pending());
+    tokio::time::advance(std::time::Duration::from_millis(200)).await;
+    assert!(futures_util::poll!(next.as_mut()).is_ready());
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_priority_orders_throttled_requests() {
+    use crate::rate_limit::ProviderThrottle;
+
+    // 600 requests per minute: one slot every 100ms once the burst is spent.
+    let throttle = ProviderThrottle::new(ProviderLimits::new().with_requests_per_minute(600));
+    for _ in 0..600 {
+        throttle.acquire(Priority::Normal, None).await.unwrap();
+    }
+    let slot = std::time::Duration::from_millis(110);
+
+    // Polled in arrival order, so only priority can put `high` first.
+    let low = throttle.acquire(Priority::Low, None);
+    let high = throttle.acquire(Priority::High, None);
+    pin_mut!(low, high);
+    assert!(futures_util::poll!(low.as_mut()).is_pending());
+    assert!(futures_util::poll!(high.as_mut()).is_pending());
+
+    tokio::time::advance(slot).await;
+    assert!(futures_util::poll!(low.as_mut()).is_pending());
+    assert!(futures_util::poll!(high.as_mut()).is_ready());
+
+    tokio::time::advance(slot).await;
+    assert!(futures_util::poll!(low.as_mut()).is_ready());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_throttled_requests_of_equal_priority_go_in_arrival_order() {
+    use crate::rate_limit::ProviderThrottle;
+
+    let throttle = ProviderThrottle::new(ProviderLimits::new().with_requests_per_minute(600));
+    for _ in 0..600 {
+        throttle.acquire(Priority::Normal, None).await.unwrap();
+    }
+    let slot = std::time::Duration::from_millis(110);
+
+    let first = throttle.acquire(Priority::Normal, None);
+    let second = throttle.acquire(Priority::Normal, None);
+    pin_mut!(first, second);
+    assert!(futures_util::poll!(first.as_mut()).is_pending());
+    assert!(futures_util::poll!(second.as_mut()).is_pending());
+
+    // The slot goes to `first` even though `second` asks for it first.
+    tokio::time::advance(slot).await;
+    assert!(futures_util::poll!(second.as_mut()).is_pending());
+    assert!(futures_util::poll!(first.as_mut()).is_ready());
+
+    // A newcomer doesn't jump the queue either.
+    tokio::time::advance(slot).await;
+    let newcomer = throttle.acquire(Priority::Normal, None);
+    pin_mut!(newcomer);
+    assert!(futures_util::poll!(newcomer.as_mut()).is_pending());
+    assert!(futures_util::poll!(second.as_mut()).is_ready());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_queue_overflow_policies() {
+    use crate::rate_limit::{ProviderThrottle, QueueLimit};
+
+    async fn saturated() -> ProviderThrottle {
+        let throttle = ProviderThrottle::new(ProviderLimits::new().with_requests_per_minute(600));
+        for _ in 0..600 {
+            throttle.acquire(Priority::Normal, None).await.unwrap();
+        }
+        throttle
+    }
+    let limit = |policy| Some(QueueLimit { depth: 1, policy });
+    let slot = std::time::Duration::from_millis(110);
+
+    let throttle = saturated().await;
+    let reject = limit(OverflowPolicy::RejectWithError);
+    let queued = throttle.acquire(Priority::Normal, reject);
+    pin_mut!(queued);
+    assert!(futures_util::poll!(queued.as_mut()).is_pending());
+    let rejected = throttle.acquire(Priority::Normal, reject).await;
+    assert!(matches!(rejected, Err(GatewayError::QueueFull)));
+    tokio::time::advance(slot).await;
+    assert!(matches!(
+        futures_util::poll!(queued.as_mut()),
+        std::task::Poll::Ready(Ok(()))
+    ));
+
+    let throttle = saturated().await;
+    let drop_oldest = limit(OverflowPolicy::DropOldest);
+    let oldest = throttle.acquire(Priority::Normal, drop_oldest);
+    let newest = throttle.acquire(Priority::Normal, drop_oldest);
+    pin_mut!(oldest, newest);
+    assert!(futures_util::poll!(oldest.as_mut()).is_pending());
+    assert!(futures_util::poll!(newest.as_mut()).is_pending());
+    assert!(matches!(
+        futures_util::poll!(oldest.as_mut()),
+        std::task::Poll::Ready(Err(GatewayError::QueueFull))
+    ));
+    tokio::time::advance(slot).await;
+    assert!(matches!(
+        futures_util::poll!(newest.as_mut()),
+        std::task::Poll::Ready(Ok(()))
+    ));
+}
+
+#[tokio::test]
+async fn test_watch_models() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+    let models = |ids: &[&str]| {
+        let data: Vec<_> = ids
+            .iter()
+            .map(|id| json!({"id": id, "object": "model", "created": 1, "owned_by": "groq", "served_by": "groq"}))
+            .collect();
+        json!({"object": "list", "data": data}).to_string()
+    };
+    let initial = server
+        .mock("GET", "/v1/models")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(models(&["llama-3.1-8b"]))
+        .create();
+
+    let client = InferenceGatewayClient::new(&format!("{}/v1", server.url()));
+    let mut receiver = client
+        .watch_models(std::time::Duration::from_millis(50))
+        .await?;
+    assert_eq!(receiver.borrow_and_update().data.len(), 1);
+
+    initial.remove();
+    let _updated = server
+        .mock("GET", "/v1/models")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(models(&["llama-3.1-8b", "llama-3.3-70b"]))
+        .create();
+
+    tokio::time::timeout(std::time::Duration::from_secs(2), receiver.changed())
+        .await
+        .expect("model list was not refreshed")
+        .unwrap();
+    let ids: Vec<_> = receiver
+        .borrow()
+        .data
+        .iter()
+        .map(|m| m.id.clone())
+        .collect();
+    assert_eq!(ids, vec!["llama-3.1-8b", "llama-3.3-70b"]);
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_health_check_strips_versioned_prefix() -> Result<(), GatewayError> {
     let mut server = Server::new_async().await;
@@ -1029,6 +6813,31 @@ async fn test_health_check_strips_versioned_prefix() -> Result<(), GatewayError>
     Ok(())
 }
 
+#[tokio::test]
+async fn test_api_version_and_trailing_slashes() -> Result<(), GatewayError> {
+    let mut server = Server::new_async().await;
+    let models = server
+        .mock("GET", "/v1/models")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"object":"list","data":[]}"#)
+        .expect(2)
+        .create();
+    let health = server.mock("GET", "/health").with_status(200).create();
+
+    let slashed = InferenceGatewayClient::new(&format!("{}/v1/", server.url()));
+    slashed.list_models().await?;
+
+    let versioned =
+        InferenceGatewayClient::new(&format!("{}/", server.url())).with_api_version("/v1/");
+    versioned.list_models().await?;
+    assert!(versioned.health_check().await?);
+
+    models.assert();
+    health.assert();
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_client_base_url_configuration() -> Result<(), GatewayError> {
     let mut custom_url_server = Server::new_async().await;
@@ -1142,22 +6951,45 @@ async fn test_list_tools_mcp_not_exposed() -> Result<(), GatewayError> {
         .with_body(
             r#"{"error":"MCP tools endpoint is not exposed. Set EXPOSE_MCP=true to enable."}"#,
         )
+        .expect(1)
         .create();
 
     let base_url = format!("{}/v1", server.url());
     let client = InferenceGatewayClient::new(&base_url);
 
-    match client.list_tools().await {
-        Err(GatewayError::Forbidden(msg)) => {
-            assert_eq!(
-                msg,
-                "MCP tools endpoint is not exposed. Set EXPOSE_MCP=true to enable."
-            );
+    // The second call is answered from the first one's result.
+    for _ in 0..2 {
+        match client.list_tools().await {
+            Err(GatewayError::FeatureDisabled { feature, hint }) => {
+                assert_eq!(feature, GatewayFeature::Mcp);
+                assert_eq!(
+                    hint,
+                    "MCP tools endpoint is not exposed. Set EXPOSE_MCP=true to enable."
+                );
+            }
+            _ => panic!("Expected FeatureDisabled error for MCP not exposed"),
         }
-        _ => panic!("Expected Forbidden error for MCP not exposed"),
     }
-
     mock.assert();
+    mock.remove();
+
+    // A new token, or a minute later, the gateway is asked again.
+    let exposed = server
+        .mock("GET", "/v1/mcp/tools")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"object": "list", "data": []}"#)
+        .expect(2)
+        .create();
+    client
+        .clone()
+        .with_token("admin-token")
+        .list_tools()
+        .await?;
+    tokio::time::pause();
+    tokio::time::advance(std::time::Duration::from_secs(61)).await;
+    client.list_tools().await?;
+    exposed.assert();
     Ok(())
 }
 
@@ -0,0 +1,115 @@
+//! Prompt token counting.
+
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{ContentPart, Message, MessageContent, MessageRole, Provider};
+
+/// Rough characters per token of English text, for estimates.
+pub(crate) const CHARS_PER_TOKEN: usize = 4;
+
+/// Tokens a chat format spends framing each message.
+const TOKENS_PER_MESSAGE: u64 = 4;
+
+/// The response of a provider's token counting endpoint.
+#[derive(Deserialize)]
+pub(crate) struct CountTokensResponse {
+    pub(crate) input_tokens: u64,
+}
+
+/// The proxy path and body that count the tokens of `messages` for `model`,
+/// or `None` if `provider` has no counting endpoint.
+pub(crate) fn count_request(
+    provider: Provider,
+    model: &str,
+    messages: &[Message],
+) -> Option<(&'static str, serde_json::Value)> {
+    match provider {
+        Provider::Anthropic => {
+            let system: Vec<String> = messages
+                .iter()
+                .filter(|message| is_instruction(message))
+                .map(|message| text(&message.content))
+                .collect();
+            let turns: Vec<_> = messages
+                .iter()
+                .filter(|message| !is_instruction(message))
+                .map(anthropic_turn)
+                .collect();
+            let mut body = json!({"model": model, "messages": turns});
+            if !system.is_empty() {
+                body["system"] = system.join("\n\n").into();
+            }
+            Some(("messages/count_tokens", body))
+        }
+        _ => None,
+    }
+}
+
+/// `message` as an Anthropic turn, with its tool calls as `tool_use` blocks
+/// and a tool result as a `tool_result` block, so they are counted too.
+fn anthropic_turn(message: &Message) -> serde_json::Value {
+    let text = text(&message.content);
+    match (message.role, &message.tool_call_id) {
+        (MessageRole::Tool, Some(id)) => json!({
+            "role": "user",
+            "content": [{"type": "tool_result", "tool_use_id": id, "content": text}],
+        }),
+        (MessageRole::Assistant, _) if !message.tool_calls.is_empty() => {
+            let text_block = (!text.is_empty()).then(|| json!({"type": "text", "text": text}));
+            let tool_uses = message.tool_calls.iter().map(|call| {
+                // Arguments that aren't a JSON object are still counted.
+                let input = call
+                    .function
+                    .parse_arguments::<serde_json::Map<_, _>>()
+                    .map_or_else(
+                        |_| json!({"arguments": call.function.arguments}),
+                        Into::into,
+                    );
+                json!({
+                    "type": "tool_use",
+                    "id": call.id,
+                    "name": call.function.name,
+                    "input": input,
+                })
+            });
+            let content: Vec<_> = text_block.into_iter().chain(tool_uses).collect();
+            json!({"role": "assistant", "content": content})
+        }
+        (MessageRole::Assistant, _) => json!({"role": "assistant", "content": text}),
+        _ => json!({"role": "user", "content": text}),
+    }
+}
+
+/// Whether `message` is a system or developer message, which Anthropic
+/// takes separately from the turns.
+fn is_instruction(message: &Message) -> bool {
+    matches!(message.role, MessageRole::System | MessageRole::Developer)
+}
+
+/// The text of `content`, with text parts joined by newlines.
+pub(crate) fn text(content: &MessageContent) -> String {
+    match content {
+        MessageContent::Text(text) => text.clone(),
+        MessageContent::Parts(parts) => parts
+            .iter()
+            .filter_map(|part| match part {
+                ContentPart::TextContentPart(part) => Some(part.text.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+    }
+}
+
+/// A rough count of the prompt tokens `messages` take, from the length of
+/// their text. Non-text parts are not counted.
+pub(crate) fn estimate(messages: &[Message]) -> u64 {
+    messages
+        .iter()
+        .map(|message| {
+            let chars = text(&message.content).chars().count();
+            chars.div_ceil(CHARS_PER_TOKEN) as u64 + TOKENS_PER_MESSAGE
+        })
+        .sum()
+}
@@ -0,0 +1,263 @@
+//! Local tools the model can call, and the loop that runs them.
+//!
+//! A [`ToolRegistry`] pairs each [`ChatCompletionTool`] definition with an
+//! async handler.
+//! [`InferenceGatewayClient::run_with_tools`](crate::InferenceGatewayClient::run_with_tools)
+//! offers the definitions to the model, runs the handlers for the tool
+//! calls it makes, sends the results back and repeats until the model
+//! answers without calling a tool.
+//!
+//! A tool can also be a type implementing [`Tool`], which the `#[tool]`
+//! macro of the `macros` feature generates from a function.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+
+use futures_util::FutureExt;
+use futures_util::future::{BoxFuture, join_all};
+use serde_json::Value;
+
+use crate::{
+    ChatCompletionMessageToolCall, ChatCompletionTool, CreateChatCompletionResponse, Message,
+};
+
+type Handler = Arc<dyn Fn(Value) -> BoxFuture<'static, Result<String, String>> + Send + Sync>;
+
+/// A tool definition together with the code that runs it.
+///
+/// With the `macros` feature, `#[tool]` implements it for a function.
+pub trait Tool: Send + Sync + 'static {
+    /// The definition offered to the model.
+    fn definition(&self) -> ChatCompletionTool;
+
+    /// Runs the tool with the call's `arguments`, returning the text sent
+    /// back to the model, or the error to report to it.
+    fn call(&self, arguments: Value) -> BoxFuture<'static, Result<String, String>>;
+}
+
+/// Tool definitions and the handlers that run them.
+///
+/// A handler gets the call's arguments as JSON and returns the text sent
+/// back to the model. An error is sent back too, prefixed with `Error:`,
+/// so the model can correct its call; so are calls to unknown tools and
+/// arguments that aren't JSON.
+///
+/// ```
+/// use inference_gateway_sdk::{
+///     ChatCompletionTool, ChatCompletionToolType, FunctionObject, ToolRegistry,
+/// };
+///
+/// let weather = ChatCompletionTool {
+///     type_: ChatCompletionToolType::Function,
+///     function: FunctionObject {
+///         name: "get_weather".to_string(),
+///         description: Some("Get the weather in a city".to_string()),
+///         parameters: None,
+///         strict: false,
+///     },
+/// };
+/// let registry = ToolRegistry::new().with_tool(weather, |args| async move {
+///     let city = args["city"].as_str().ok_or("city is required")?;
+///     Ok::<_, &str>(format!("Sunny in {city}"))
+/// });
+/// ```
+#[derive(Clone)]
+pub struct ToolRegistry {
+    tools: Vec<ChatCompletionTool>,
+    handlers: HashMap<String, Handler>,
+    max_rounds: u32,
+}
+
+impl Default for ToolRegistry {
+    fn default() -> Self {
+        Self {
+            tools: Vec::new(),
+            handlers: HashMap::new(),
+            max_rounds: 10,
+        }
+    }
+}
+
+impl ToolRegistry {
+    /// A registry without tools, allowing 10 rounds of tool calls.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `tool`, run by `handler`. A tool registered under the same
+    /// name before is replaced.
+    pub fn with_tool<F, Fut, E>(mut self, tool: ChatCompletionTool, handler: F) -> Self
+    where
+        F: Fn(Value) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<String, E>> + Send + 'static,
+        E: fmt::Display,
+    {
+        let name = tool.function.name.clone();
+        self.tools.retain(|existing| existing.function.name != name);
+        self.tools.push(tool);
+        let handler: Handler = Arc::new(move |args| {
+            handler(args)
+                .map(|result| result.map_err(|e| e.to_string()))
+                .boxed()
+        });
+        self.handlers.insert(name, handler);
+        self
+    }
+
+    /// Registers `tool`. A tool registered under the same name before is
+    /// replaced.
+    pub fn with_tool_impl(self, tool: impl Tool) -> Self {
+        let tool = Arc::new(tool);
+        self.with_tool(tool.definition(), move |arguments| tool.call(arguments))
+    }
+
+    /// Fails [`run_with_tools`](crate::InferenceGatewayClient::run_with_tools)
+    /// with [`GatewayError::ToolRoundsExceeded`](crate::GatewayError::ToolRoundsExceeded)
+    /// if the model still calls tools after `rounds` rounds.
+    pub fn with_max_rounds(mut self, rounds: u32) -> Self {
+        self.max_rounds = rounds;
+        self
+    }
+
+    /// The registered tool definitions, in registration order.
+    pub fn tools(&self) -> &[ChatCompletionTool] {
+        &self.tools
+    }
+
+    pub(crate) fn max_rounds(&self) -> u32 {
+        self.max_rounds
+    }
+
+    /// Runs `calls` concurrently, returning their results as tool messages
+    /// in the same order.
+    pub(crate) async fn call_all(&self, calls: &[ChatCompletionMessageToolCall]) -> Vec<Message> {
+        join_all(calls.iter().map(|call| async move {
+            let content = match self.call(call).await {
+                Ok(content) => content,
+                Err(e) => format!("Error: {e}"),
+            };
+            Message::tool(call.id.clone(), content)
+        }))
+        .await
+    }
+
+    async fn call(&self, call: &ChatCompletionMessageToolCall) -> Result<String, String> {
+        let name = &call.function.name;
+        let handler = self
+            .handlers
+            .get(name)
+            .ok_or_else(|| format!("unknown tool `{name}`"))?;
+        let args = if call.function.arguments.trim().is_empty() {
+            Value::Object(Default::default())
+        } else {
+            call.function
+                .parse_arguments()
+                .map_err(|e| format!("invalid arguments for `{name}`: {e}"))?
+        };
+        handler(args).await
+    }
+}
+
+impl fmt::Debug for ToolRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ToolRegistry")
+            .field("tools", &self.tools)
+            .field("max_rounds", &self.max_rounds)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The result of [`run_with_tools`](crate::InferenceGatewayClient::run_with_tools).
+#[derive(Clone, Debug)]
+pub struct ToolRun {
+    /// The model's final response, which called no tools.
+    pub response: CreateChatCompletionResponse,
+    /// The whole conversation: the messages sent, each assistant turn and
+    /// tool result, and the final answer.
+    pub messages: Vec<Message>,
+    /// How many rounds of tool calls were run.
+    pub rounds: u32,
+}
+
+/// Support code for the `#[tool]` macro. Not public API.
+#[doc(hidden)]
+pub mod __private {
+    use std::fmt;
+
+    pub use futures_util::future::BoxFuture;
+    use serde::de::DeserializeOwned;
+    pub use serde_json::Value;
+
+    use crate::{ChatCompletionTool, ChatCompletionToolType, FunctionObject, FunctionParameters};
+
+    /// A function tool taking `parameters`, each a name, a JSON schema and
+    /// whether it is required.
+    pub fn definition(
+        name: &str,
+        description: Option<&str>,
+        parameters: &[(&str, &str, bool)],
+    ) -> ChatCompletionTool {
+        let properties: serde_json::Map<String, Value> = parameters
+            .iter()
+            .map(|(name, schema, _)| {
+                let schema = serde_json::from_str(schema).expect("#[tool] emits valid schemas");
+                (name.to_string(), schema)
+            })
+            .collect();
+        let required: Vec<&str> = parameters
+            .iter()
+            .filter(|(_, _, required)| *required)
+            .map(|(name, _, _)| *name)
+            .collect();
+        let mut schema = serde_json::Map::new();
+        schema.insert("type".to_string(), "object".into());
+        schema.insert("properties".to_string(), properties.into());
+        schema.insert("required".to_string(), required.into());
+        ChatCompletionTool {
+            type_: ChatCompletionToolType::Function,
+            function: FunctionObject {
+                name: name.to_string(),
+                description: description.map(str::to_string),
+                parameters: Some(FunctionParameters(schema)),
+                strict: false,
+            },
+        }
+    }
+
+    /// The argument `name` of a call, missing ones read as `null`.
+    pub fn argument<T: DeserializeOwned>(arguments: &Value, name: &str) -> Result<T, String> {
+        let value = arguments.get(name).cloned().unwrap_or(Value::Null);
+        serde_json::from_value(value).map_err(|e| format!("invalid argument `{name}`: {e}"))
+    }
+
+    /// What a `#[tool]` function may return.
+    pub trait ToolOutput {
+        fn into_tool_output(self) -> Result<String, String>;
+    }
+
+    impl ToolOutput for String {
+        fn into_tool_output(self) -> Result<String, String> {
+            Ok(self)
+        }
+    }
+
+    impl ToolOutput for &str {
+        fn into_tool_output(self) -> Result<String, String> {
+            Ok(self.to_string())
+        }
+    }
+
+    impl ToolOutput for Value {
+        fn into_tool_output(self) -> Result<String, String> {
+            Ok(self.to_string())
+        }
+    }
+
+    impl<T: ToolOutput, E: fmt::Display> ToolOutput for Result<T, E> {
+        fn into_tool_output(self) -> Result<String, String> {
+            self.map_err(|e| e.to_string())?.into_tool_output()
+        }
+    }
+}
@@ -0,0 +1,188 @@
+//! W3C Trace Context propagation, behind the `trace` feature.
+//!
+//! Every request carries a `traceparent` header (and `tracestate`, when one
+//! is known) so gateway and provider logs can be correlated with the calling
+//! application's traces without a full OpenTelemetry setup. Requests made
+//! inside [`TraceContext::scope`] join that trace as children; all others
+//! start a new trace.
+//!
+//! With the `opentelemetry` feature, requests made outside a scope are
+//! children of the current OpenTelemetry span instead, when there is one,
+//! and a trace id the gateway returns is recorded on that span.
+
+use std::fmt::Write as _;
+use std::future::Future;
+
+tokio::task_local! {
+    static CURRENT: TraceContext;
+}
+
+/// A W3C trace context: the `traceparent` identifiers plus an optional
+/// vendor-specific `tracestate`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceContext {
+    trace_id: [u8; 16],
+    parent_id: [u8; 8],
+    sampled: bool,
+    tracestate: Option<String>,
+}
+
+impl TraceContext {
+    /// Starts a new, sampled trace with random identifiers.
+    pub fn new_root() -> Self {
+        Self {
+            trace_id: random_nonzero(),
+            parent_id: random_nonzero(),
+            sampled: true,
+            tracestate: None,
+        }
+    }
+
+    /// Parses a `traceparent` header value, keeping `tracestate` as given.
+    ///
+    /// Returns `None` for malformed values and for the all-zero trace or
+    /// parent ids the specification forbids.
+    pub fn from_headers(traceparent: &str, tracestate: Option<&str>) -> Option<Self> {
+        let mut parts = traceparent.trim().split('-');
+        let version = parts.next()?;
+        let trace_id = decode_hex::<16>(parts.next()?)?;
+        let parent_id = decode_hex::<8>(parts.next()?)?;
+        let flags = decode_hex::<1>(parts.next()?)?[0];
+        if version.len() != 2 || version == "ff" || (version == "00" && parts.next().is_some()) {
+            return None;
+        }
+        if trace_id == [0; 16] || parent_id == [0; 8] {
+            return None;
+        }
+        Some(Self {
+            trace_id,
+            parent_id,
+            sampled: flags & 1 == 1,
+            tracestate: tracestate.map(str::to_string),
+        })
+    }
+
+    /// The 32-character hex trace id.
+    pub fn trace_id(&self) -> String {
+        encode_hex(&self.trace_id)
+    }
+
+    /// The `traceparent` header value.
+    pub fn traceparent(&self) -> String {
+        format!(
+            "00-{}-{}-{:02x}",
+            encode_hex(&self.trace_id),
+            encode_hex(&self.parent_id),
+            u8::from(self.sampled)
+        )
+    }
+
+    /// The `tracestate` header value, if any.
+    pub fn tracestate(&self) -> Option<&str> {
+        self.tracestate.as_deref()
+    }
+
+    /// Runs `future` with this context as the parent of every request the
+    /// client makes inside it.
+    pub async fn scope<F: Future>(self, future: F) -> F::Output {
+        CURRENT.scope(self, future).await
+    }
+
+    /// The context set by the innermost enclosing [`TraceContext::scope`].
+    pub fn current() -> Option<Self> {
+        CURRENT.try_with(Clone::clone).ok()
+    }
+
+    /// The context of the span current in `context`, if it is valid.
+    #[cfg(feature = "opentelemetry")]
+    pub fn from_opentelemetry(context: &opentelemetry::Context) -> Option<Self> {
+        use opentelemetry::trace::TraceContextExt;
+
+        let span = context.span();
+        let span = span.span_context();
+        if !span.is_valid() {
+            return None;
+        }
+        let tracestate = span.trace_state().header();
+        Some(Self {
+            trace_id: span.trace_id().to_bytes(),
+            parent_id: span.span_id().to_bytes(),
+            sampled: span.is_sampled(),
+            tracestate: (!tracestate.is_empty()).then_some(tracestate),
+        })
+    }
+
+    /// The context for an outgoing request: a new span in the ambient trace,
+    /// a child of the current OpenTelemetry span, or a new trace.
+    pub(crate) fn for_request() -> Self {
+        match Self::current() {
+            Some(parent) => Self {
+                parent_id: random_nonzero(),
+                ..parent
+            },
+            None => Self::from_current_span().unwrap_or_else(Self::new_root),
+        }
+    }
+
+    #[cfg(feature = "opentelemetry")]
+    fn from_current_span() -> Option<Self> {
+        Self::from_opentelemetry(&opentelemetry::Context::current())
+    }
+
+    #[cfg(not(feature = "opentelemetry"))]
+    fn from_current_span() -> Option<Self> {
+        None
+    }
+}
+
+/// Records the trace id of the gateway's `traceparent` response header, if
+/// it sends one, on the current OpenTelemetry span as `gateway.trace_id`.
+#[cfg(feature = "opentelemetry")]
+pub(crate) fn record_response(headers: &reqwest::header::HeaderMap) {
+    use opentelemetry::trace::TraceContextExt;
+
+    let Some(traceparent) = headers
+        .get("traceparent")
+        .and_then(|value| value.to_str().ok())
+    else {
+        return;
+    };
+    if let Some(trace) = TraceContext::from_headers(traceparent, None) {
+        opentelemetry::Context::current()
+            .span()
+            .set_attribute(opentelemetry::KeyValue::new(
+                "gateway.trace_id",
+                trace.trace_id(),
+            ));
+    }
+}
+
+#[cfg(not(feature = "opentelemetry"))]
+pub(crate) fn record_response(_headers: &reqwest::header::HeaderMap) {}
+
+fn random_nonzero<const N: usize>() -> [u8; N] {
+    loop {
+        let bytes: [u8; N] = rand::random();
+        if bytes != [0; N] {
+            return bytes;
+        }
+    }
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut out, b| {
+        let _ = write!(out, "{b:02x}");
+        out
+    })
+}
+
+fn decode_hex<const N: usize>(text: &str) -> Option<[u8; N]> {
+    if text.len() != N * 2 || !text.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f')) {
+        return None;
+    }
+    let mut bytes = [0; N];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&text[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(bytes)
+}
@@ -0,0 +1,47 @@
+//! Background refresh of the model list.
+
+use std::time::Duration;
+
+use tokio::sync::watch;
+
+use crate::{InferenceGatewayAPI, InferenceGatewayClient, ListModelsResponse};
+
+/// Refreshes the model list every `interval` into `sender`, until every
+/// receiver is dropped. Failed refreshes keep the last list; receivers are
+/// only notified when the set of models changes.
+pub(crate) fn spawn_model_watch(
+    client: InferenceGatewayClient,
+    interval: Duration,
+    sender: watch::Sender<ListModelsResponse>,
+) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        // The first tick completes immediately; the caller already fetched.
+        ticker.tick().await;
+        loop {
+            tokio::select! {
+                _ = sender.closed() => return,
+                _ = ticker.tick() => {}
+            }
+            let Ok(models) = client.list_models().await else {
+                continue;
+            };
+            sender.send_if_modified(|current| {
+                if same_models(current, &models) {
+                    return false;
+                }
+                *current = models;
+                true
+            });
+        }
+    });
+}
+
+fn same_models(a: &ListModelsResponse, b: &ListModelsResponse) -> bool {
+    a.data.len() == b.data.len()
+        && a.data
+            .iter()
+            .zip(&b.data)
+            .all(|(a, b)| a.id == b.id && a.served_by == b.served_by)
+}
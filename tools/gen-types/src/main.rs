@@ -1,8 +1,10 @@
-//! Generates `src/generated/schemas.rs` from `openapi.yaml`.
+//! Generates `src/generated/schemas.rs` from `openapi.yaml`, and
+//! `src/generated/models.rs` from `models.yaml`.
 //!
 //! Reads the OpenAPI 3.1 spec, extracts `components.schemas`, rewrites refs
 //! to typify's `#/definitions/...` convention, runs typify, and writes
-//! the rendered Rust file with a `// @generated` header.
+//! the rendered Rust file with a `// @generated` header. The known-model
+//! list is rendered as one module of string constants per provider.
 //!
 //! Run via `task generate-types` (or `cargo run -p gen-types --release`).
 
@@ -12,7 +14,8 @@ use std::path::PathBuf;
 use anyhow::{Context, Result, anyhow};
 use schemars::schema::{RootSchema, Schema};
 use serde_json::Value;
-use typify::{TypeSpace, TypeSpaceSettings};
+use syn::punctuated::Punctuated;
+use typify::{TypeSpace, TypeSpaceImpl, TypeSpaceSettings};
 
 const HEADER: &str = "\
 // @generated - DO NOT EDIT.
@@ -27,6 +30,47 @@ const HEADER: &str = "\
 
 ";
 
+/// Types whose text may hold prompts, completions or attachment data. Their
+/// `Debug` is written by hand in `src/ext/debug.rs` so privacy mode can
+/// elide it, so the derive is dropped here.
+const PRIVATE_DEBUG: &[&str] = &[
+    "ChatCompletionMessageAudio",
+    "ChatCompletionMessageToolCallChunkFunction",
+    "ChatCompletionMessageToolCallFunction",
+    "ChatCompletionStreamResponseDelta",
+    "ChatCompletionStreamResponseDeltaAudio",
+    "ImageUrl",
+    "InputAudio",
+    "InputFile",
+    "Message",
+    "MessagesDocumentSource",
+    "MessagesImageSource",
+    "MessagesMessageContent",
+    "MessagesStreamEventDelta",
+    "MessagesTextBlock",
+    "MessagesThinkingBlock",
+    "MessagesToolResultBlockContent",
+    "MessagesToolUseBlock",
+    "TextContentPart",
+];
+
+/// Enums newer gateways may extend. Each gets an `Other(String)` variant that
+/// unknown values deserialize to and serialize back from unchanged, so a new
+/// finish reason or event kind neither fails the whole response nor gets lost
+/// when the response is sent on.
+const OPEN_ENUMS: &[&str] = &[
+    "ChatCompletionAnnotationType",
+    "ChatCompletionToolType",
+    "FinishReason",
+    "MessagesResponseStopReason",
+    "MessagesStreamEventType",
+];
+
+/// Extra names accepted when deserializing an enum variant, as
+/// `(enum, variant, alias)`: other APIs' spellings of the same value, for
+/// histories stored from them.
+const SERDE_ALIASES: &[(&str, &str, &str)] = &[("MessageRole", "Assistant", "model")];
+
 fn main() -> Result<()> {
     let workspace_root = workspace_root()?;
     let spec_path = workspace_root.join("openapi.yaml");
@@ -71,6 +115,26 @@ fn main() -> Result<()> {
     let mut settings = TypeSpaceSettings::default();
     settings.with_struct_builder(false);
     settings.with_derive("Clone".to_string());
+    // The spec's untagged string-or-parts content would come out with the
+    // variant names `String`/`Array`. The SDK's own `MessageContent` names
+    // them `Text`/`Parts` and serializes text-only parts as a plain string.
+    settings.with_replacement(
+        "MessageContent",
+        "crate::MessageContent",
+        std::iter::empty::<TypeSpaceImpl>(),
+    );
+    // IDs patched in as named schemas by `apply_known_patches`, replaced by
+    // the SDK's newtypes so completion and tool-call IDs can't be mixed up.
+    settings.with_replacement(
+        "CompletionId",
+        "crate::CompletionId",
+        std::iter::empty::<TypeSpaceImpl>(),
+    );
+    settings.with_replacement(
+        "ToolCallId",
+        "crate::ToolCallId",
+        std::iter::empty::<TypeSpaceImpl>(),
+    );
 
     let mut type_space = TypeSpace::new(&settings);
     type_space
@@ -78,7 +142,10 @@ fn main() -> Result<()> {
         .map_err(|e| anyhow!("typify add_root_schema: {e}"))?;
 
     let body = type_space.to_stream();
-    let parsed: syn::File = syn::parse2(body).context("parsing typify output as Rust")?;
+    let mut parsed: syn::File = syn::parse2(body).context("parsing typify output as Rust")?;
+    drop_debug_derives(&mut parsed, PRIVATE_DEBUG)?;
+    add_catch_all_variants(&mut parsed, OPEN_ENUMS)?;
+    add_serde_aliases(&mut parsed, SERDE_ALIASES);
     let formatted = prettyplease::unparse(&parsed);
 
     let pre_fmt = format!("{HEADER}{formatted}");
@@ -95,10 +162,83 @@ fn main() -> Result<()> {
     // the file we commit is identical to what `cargo fmt --check` expects.
     cargo_fmt_file(&workspace_root, &out_path)?;
 
+    println!("Wrote {}", out_path.display());
+
+    generate_models(&workspace_root)
+}
+
+const MODELS_HEADER: &str = "\
+// @generated - DO NOT EDIT.
+// Regenerate with `task generate-types` (or `cargo run -p gen-types --release`).
+// Source: models.yaml.
+
+//! Commonly available model IDs, grouped by provider.
+//!
+//! These are plain `&str` constants, so any other model ID can be used
+//! wherever one of them can.
+
+";
+
+/// Renders `models.yaml` (provider -> model IDs) as one module of constants
+/// per provider.
+fn generate_models(workspace_root: &std::path::Path) -> Result<()> {
+    let data_path = workspace_root.join("models.yaml");
+    let out_path = workspace_root.join("src/generated/models.rs");
+
+    let text = std::fs::read_to_string(&data_path)
+        .with_context(|| format!("reading {}", data_path.display()))?;
+    let providers: BTreeMap<String, Vec<String>> =
+        serde_yaml::from_str(&text).with_context(|| format!("parsing {}", data_path.display()))?;
+
+    let modules: Vec<String> = providers
+        .iter()
+        .map(|(provider, ids)| {
+            let variant: String = provider
+                .split('_')
+                .map(|part| {
+                    let mut chars = part.chars();
+                    chars.next().map_or_else(String::new, |first| {
+                        first.to_ascii_uppercase().to_string() + chars.as_str()
+                    })
+                })
+                .collect();
+            let mut module = format!(
+                "/// Models served by [`Provider::{variant}`](crate::Provider::{variant}).\n\
+                 pub mod {provider} {{\n"
+            );
+            for id in ids {
+                module += &format!("    pub const {}: &str = \"{id}\";\n", const_name(id));
+            }
+            let all: Vec<String> = ids.iter().map(|id| const_name(id)).collect();
+            module += &format!(
+                "\n    /// Every model listed above.\n    pub const ALL: &[&str] = &[{}];\n}}\n",
+                all.join(", ")
+            );
+            module
+        })
+        .collect();
+
+    std::fs::write(&out_path, format!("{MODELS_HEADER}{}", modules.join("\n")))
+        .with_context(|| format!("writing {}", out_path.display()))?;
+    cargo_fmt_file(workspace_root, &out_path)?;
+
     println!("Wrote {}", out_path.display());
     Ok(())
 }
 
+/// `gpt-4.1-mini` -> `GPT_4_1_MINI`.
+fn const_name(id: &str) -> String {
+    id.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
 fn cargo_fmt_file(workspace_root: &std::path::Path, file: &std::path::Path) -> Result<()> {
     use std::process::Command;
 
@@ -129,6 +269,126 @@ fn cargo_fmt_file(workspace_root: &std::path::Path, file: &std::path::Path) -> R
     ))
 }
 
+/// Removes `Debug` from the derives of the types named in `names`.
+fn drop_debug_derives(file: &mut syn::File, names: &[&str]) -> Result<()> {
+    for item in &mut file.items {
+        let (ident, attrs) = match item {
+            syn::Item::Struct(item) => (&item.ident, &mut item.attrs),
+            syn::Item::Enum(item) => (&item.ident, &mut item.attrs),
+            _ => continue,
+        };
+        if !names.iter().any(|name| ident == name) {
+            continue;
+        }
+        for attr in attrs
+            .iter_mut()
+            .filter(|attr| attr.path().is_ident("derive"))
+        {
+            let derives = attr
+                .parse_args_with(Punctuated::<syn::Path, syn::Token![,]>::parse_terminated)
+                .with_context(|| format!("parsing the derives of {ident}"))?;
+            let kept: Punctuated<syn::Path, syn::Token![,]> = derives
+                .into_iter()
+                .filter(|path| !path.is_ident("Debug"))
+                .collect();
+            *attr = syn::parse_quote!(#[derive(#kept)]);
+        }
+    }
+    Ok(())
+}
+
+/// Adds an untagged `Other(String)` variant to each enum in `names`, and the
+/// matching arms to its `Display` and `FromStr`. `Copy` is dropped from its
+/// derives, as the variant holds a `String`.
+fn add_catch_all_variants(file: &mut syn::File, names: &[&str]) -> Result<()> {
+    for item in &mut file.items {
+        match item {
+            syn::Item::Enum(item) if names.iter().any(|name| item.ident == name) => {
+                for attr in item
+                    .attrs
+                    .iter_mut()
+                    .filter(|attr| attr.path().is_ident("derive"))
+                {
+                    let derives = attr
+                        .parse_args_with(Punctuated::<syn::Path, syn::Token![,]>::parse_terminated)
+                        .with_context(|| format!("parsing the derives of {}", item.ident))?;
+                    let kept: Punctuated<syn::Path, syn::Token![,]> = derives
+                        .into_iter()
+                        .filter(|path| !path.is_ident("Copy"))
+                        .collect();
+                    *attr = syn::parse_quote!(#[derive(#kept)]);
+                }
+                item.variants.push(syn::parse_quote! {
+                    /// A value this SDK doesn't know yet, as it was sent.
+                    #[serde(untagged)]
+                    Other(::std::string::String)
+                });
+            }
+            syn::Item::Impl(item) => {
+                let syn::Type::Path(self_ty) = &*item.self_ty else {
+                    continue;
+                };
+                let Some(name) = names.iter().find(|name| self_ty.path.is_ident(name)) else {
+                    continue;
+                };
+                let Some((trait_path, _)) = &item.trait_ else {
+                    continue;
+                };
+                let trait_name = trait_path
+                    .segments
+                    .last()
+                    .map(|segment| segment.ident.to_string());
+                let arm: syn::Arm = match trait_name.as_deref() {
+                    Some("Display") => {
+                        syn::parse_quote!(Self::Other(ref value) => f.write_str(value),)
+                    }
+                    Some("FromStr") => syn::parse_quote!(_ => Ok(Self::Other(value.to_string())),),
+                    _ => continue,
+                };
+                let expr = item
+                    .items
+                    .iter_mut()
+                    .find_map(|item| match item {
+                        syn::ImplItem::Fn(f) => f.block.stmts.last_mut(),
+                        _ => None,
+                    })
+                    .and_then(|stmt| match stmt {
+                        syn::Stmt::Expr(syn::Expr::Match(expr), None) => Some(expr),
+                        _ => None,
+                    })
+                    .ok_or_else(|| anyhow!("unexpected {trait_name:?} impl for {name}"))?;
+                // `FromStr` ends with a wildcard arm rejecting unknown values,
+                // which the catch-all replaces.
+                expr.arms
+                    .retain(|arm| !matches!(arm.pat, syn::Pat::Wild(_)));
+                expr.arms.push(arm);
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Adds `#[serde(alias = ...)]` to the variants listed in `aliases`.
+fn add_serde_aliases(file: &mut syn::File, aliases: &[(&str, &str, &str)]) {
+    for item in &mut file.items {
+        let syn::Item::Enum(item) = item else {
+            continue;
+        };
+        for (_, variant_name, alias) in aliases.iter().filter(|(name, ..)| item.ident == name) {
+            for variant in item
+                .variants
+                .iter_mut()
+                .filter(|variant| variant.ident == variant_name)
+            {
+                variant
+                    .attrs
+                    .push(syn::parse_quote!(#[serde(alias = #alias)]));
+            }
+        }
+    }
+}
+
 fn workspace_root() -> Result<PathBuf> {
     // CARGO_MANIFEST_DIR for this binary is tools/gen-types/. Go up two levels.
     let manifest_dir =
@@ -201,6 +461,16 @@ fn apply_known_patches(value: &mut Value) -> Result<()> {
         .as_object_mut()
         .ok_or_else(|| anyhow!("schemas not an object"))?;
 
+    // OpenAI's newer models take instructions in a `developer` message in
+    // place of `system`. The gateway forwards the role as-is, but the spec
+    // does not list it yet.
+    if let Some(Value::Array(values)) = schemas
+        .get_mut("MessageRole")
+        .and_then(|s| s.get_mut("enum"))
+    {
+        values.push("developer".into());
+    }
+
     if let Some(Value::Object(s)) = schemas.get_mut("ChatCompletionStreamChoice") {
         if let Some(Value::Array(req)) = s.get_mut("required") {
             req.retain(|v| v.as_str() != Some("finish_reason"));
@@ -216,6 +486,317 @@ fn apply_known_patches(value: &mut Value) -> Result<()> {
         }
     }
 
+    // Streamed tool calls send `name` once and then only `arguments`
+    // fragments. The spec reuses the non-streaming function object, where both
+    // are required, so every fragment after the first would fail to parse.
+    if let Some(Value::Object(s)) = schemas.get_mut("ChatCompletionMessageToolCallChunk") {
+        if let Some(Value::Object(props)) = s.get_mut("properties") {
+            props.insert(
+                "function".into(),
+                serde_json::json!({
+                    "type": "object",
+                    "description": "A fragment of the function that the model called.",
+                    "properties": {
+                        "name": {
+                            "type": "string",
+                            "description": "The name of the function to call. Only sent with the first fragment."
+                        },
+                        "arguments": {
+                            "type": "string",
+                            "description": "A slice of the JSON-encoded arguments. Concatenate the fragments that share an `index` to rebuild the full string."
+                        }
+                    }
+                }),
+            );
+        }
+    }
+
+    // Audio-capable chat models take `input_audio` parts in the
+    // OpenAI-compatible schema the gateway forwards, but the spec only lists
+    // text and image parts. Add the part until the spec catches up.
+    schemas.insert(
+        "InputAudio".into(),
+        serde_json::json!({
+            "type": "object",
+            "description": "Base64-encoded audio input",
+            "required": ["data", "format"],
+            "properties": {
+                "data": {
+                    "type": "string",
+                    "description": "Base64-encoded audio data"
+                },
+                "format": {
+                    "type": "string",
+                    "description": "Encoding of the audio data",
+                    "enum": ["wav", "mp3"]
+                }
+            }
+        }),
+    );
+    schemas.insert(
+        "InputAudioContentPart".into(),
+        serde_json::json!({
+            "type": "object",
+            "description": "Audio content part",
+            "required": ["type", "input_audio"],
+            "properties": {
+                "type": {
+                    "type": "string",
+                    "description": "Content type identifier",
+                    "enum": ["input_audio"]
+                },
+                "input_audio": { "$ref": "#/definitions/InputAudio" }
+            }
+        }),
+    );
+    // Likewise for `file` parts, which document-capable providers accept
+    // either by uploaded file id or inline as a data URL.
+    schemas.insert(
+        "InputFile".into(),
+        serde_json::json!({
+            "type": "object",
+            "description": "A file attached by id or inline as a data URL",
+            "properties": {
+                "file_id": {
+                    "type": "string",
+                    "description": "ID of a file previously uploaded to the provider"
+                },
+                "file_data": {
+                    "type": "string",
+                    "description": "Base64 data URL of the file contents"
+                },
+                "filename": {
+                    "type": "string",
+                    "description": "Name of the file, used with inline data"
+                }
+            }
+        }),
+    );
+    schemas.insert(
+        "FileContentPart".into(),
+        serde_json::json!({
+            "type": "object",
+            "description": "File content part, for providers that accept document attachments",
+            "required": ["type", "file"],
+            "properties": {
+                "type": {
+                    "type": "string",
+                    "description": "Content type identifier",
+                    "enum": ["file"]
+                },
+                "file": { "$ref": "#/definitions/InputFile" }
+            }
+        }),
+    );
+    if let Some(Value::Array(parts)) = schemas
+        .get_mut("ContentPart")
+        .and_then(|s| s.get_mut("oneOf"))
+    {
+        parts.push(serde_json::json!({ "$ref": "#/definitions/InputAudioContentPart" }));
+        parts.push(serde_json::json!({ "$ref": "#/definitions/FileContentPart" }));
+    }
+
+    // The gateway forwards `system_fingerprint` and `service_tier` on
+    // non-streaming responses as OpenAI sends them, but the spec only lists
+    // the fingerprint on stream chunks.
+    if let Some(Value::Object(props)) = schemas
+        .get_mut("CreateChatCompletionResponse")
+        .and_then(|s| s.get_mut("properties"))
+    {
+        let fingerprint = serde_json::json!({
+            "type": "string",
+            "description": "This fingerprint represents the backend configuration that the model runs with.\nCan be used in conjunction with the `seed` request parameter to understand when backend changes have been made that might impact determinism.\n"
+        });
+        props.insert("system_fingerprint".into(), fingerprint);
+        props.insert(
+            "service_tier".into(),
+            serde_json::json!({
+                "type": "string",
+                "description": "The service tier the provider processed the request with, when it reports one."
+            }),
+        );
+    }
+
+    // OpenAI-compatible providers take a participant `name` on messages, which
+    // the gateway forwards, but the spec's `Message` does not list it.
+    if let Some(Value::Object(props)) = schemas
+        .get_mut("Message")
+        .and_then(|s| s.get_mut("properties"))
+    {
+        props.insert(
+            "name".into(),
+            serde_json::json!({
+                "type": "string",
+                "description": "An optional name for the participant, to tell apart several users or name the tool a message comes from."
+            }),
+        );
+    }
+
+    // Audio-output-capable models return the spoken reply on the assistant
+    // message, and stream it in fragments on the delta. The gateway forwards
+    // both, but the spec has neither.
+    schemas.insert(
+        "ChatCompletionMessageAudio".into(),
+        serde_json::json!({
+            "description": "Audio generated by an audio-output-capable model.",
+            "type": "object",
+            "required": ["data", "expires_at", "id", "transcript"],
+            "properties": {
+                "data": {
+                    "description": "Base64-encoded audio bytes, in the format the request asked for.",
+                    "type": "string"
+                },
+                "expires_at": {
+                    "description": "The Unix timestamp (in seconds) after which the audio can no longer be referenced in a follow-up request.",
+                    "type": "integer"
+                },
+                "id": {
+                    "description": "Identifier to refer back to this audio in a follow-up request.",
+                    "type": "string"
+                },
+                "transcript": {
+                    "description": "Transcript of the audio.",
+                    "type": "string"
+                }
+            }
+        }),
+    );
+    schemas.insert(
+        "ChatCompletionStreamResponseDeltaAudio".into(),
+        serde_json::json!({
+            "description": "A fragment of the audio generated by an audio-output-capable model.",
+            "type": "object",
+            "properties": {
+                "data": {
+                    "description": "A slice of the base64-encoded audio bytes.",
+                    "type": "string"
+                },
+                "expires_at": {
+                    "description": "The Unix timestamp (in seconds) after which the audio can no longer be referenced in a follow-up request.",
+                    "type": "integer"
+                },
+                "id": {
+                    "description": "Identifier to refer back to this audio in a follow-up request.",
+                    "type": "string"
+                },
+                "transcript": {
+                    "description": "A slice of the transcript.",
+                    "type": "string"
+                }
+            }
+        }),
+    );
+    for (name, audio) in [
+        ("Message", "ChatCompletionMessageAudio"),
+        (
+            "ChatCompletionStreamResponseDelta",
+            "ChatCompletionStreamResponseDeltaAudio",
+        ),
+    ] {
+        if let Some(Value::Object(props)) =
+            schemas.get_mut(name).and_then(|s| s.get_mut("properties"))
+        {
+            props.insert(
+                "audio".into(),
+                serde_json::json!({ "$ref": format!("#/definitions/{audio}") }),
+            );
+        }
+    }
+
+    // Web-search-enabled models cite their sources as `annotations` on the
+    // message, sent in the final delta when streaming. The gateway forwards
+    // them, but the spec has no such field.
+    schemas.insert(
+        "UrlCitation".into(),
+        serde_json::json!({
+            "description": "A web page cited by a web-search-enabled model.",
+            "type": "object",
+            "required": ["end_index", "start_index", "title", "url"],
+            "properties": {
+                "end_index": {
+                    "description": "Index one past the last character of the cited span in the message text.",
+                    "type": "integer",
+                    "minimum": 0
+                },
+                "start_index": {
+                    "description": "Index of the first character of the cited span in the message text.",
+                    "type": "integer",
+                    "minimum": 0
+                },
+                "title": {
+                    "description": "Title of the cited page.",
+                    "type": "string"
+                },
+                "url": {
+                    "description": "URL of the cited page.",
+                    "type": "string"
+                }
+            }
+        }),
+    );
+    schemas.insert(
+        "ChatCompletionAnnotationType".into(),
+        serde_json::json!({
+            "description": "The kind of annotation.",
+            "type": "string",
+            "enum": ["url_citation"]
+        }),
+    );
+    schemas.insert(
+        "ChatCompletionAnnotation".into(),
+        serde_json::json!({
+            "description": "A source the model cited, with the span of the message text it supports.",
+            "type": "object",
+            "required": ["type"],
+            "properties": {
+                "type": { "$ref": "#/definitions/ChatCompletionAnnotationType" },
+                "url_citation": { "$ref": "#/definitions/UrlCitation" }
+            }
+        }),
+    );
+    for name in ["Message", "ChatCompletionStreamResponseDelta"] {
+        if let Some(Value::Object(props)) =
+            schemas.get_mut(name).and_then(|s| s.get_mut("properties"))
+        {
+            props.insert(
+                "annotations".into(),
+                serde_json::json!({
+                    "description": "Sources the model cited, for web-search-enabled models.",
+                    "type": "array",
+                    "items": { "$ref": "#/definitions/ChatCompletionAnnotation" }
+                }),
+            );
+        }
+    }
+
+    // Completion and tool-call IDs are plain strings in the spec. Name them so
+    // `main` can swap in the SDK's ID newtypes.
+    schemas.insert(
+        "CompletionId".into(),
+        serde_json::json!({ "type": "string", "description": "ID of a chat completion" }),
+    );
+    schemas.insert(
+        "ToolCallId".into(),
+        serde_json::json!({ "type": "string", "description": "ID of a tool call" }),
+    );
+    for (schema, property, id) in [
+        ("CreateChatCompletionResponse", "id", "CompletionId"),
+        ("CreateChatCompletionStreamResponse", "id", "CompletionId"),
+        ("ChatCompletionMessageToolCall", "id", "ToolCallId"),
+        ("ChatCompletionMessageToolCallChunk", "id", "ToolCallId"),
+        ("Message", "tool_call_id", "ToolCallId"),
+    ] {
+        let property = schemas
+            .get_mut(schema)
+            .and_then(|s| s.pointer_mut(&format!("/properties/{property}")))
+            .ok_or_else(|| anyhow!("{schema} has no {property} property"))?;
+        let mut replacement = serde_json::json!({ "$ref": format!("#/definitions/{id}") });
+        if let Some(description) = property.get("description") {
+            replacement["description"] = description.clone();
+        }
+        *property = replacement;
+    }
+
     Ok(())
 }
 